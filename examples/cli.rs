@@ -1,18 +1,29 @@
 extern crate keyring;
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, ValueEnum};
 use std::collections::HashMap;
 
-use keyring::{Entry, Error, Result};
+use keyring::{CredentialSummary, Entry, Error, Result};
 
 fn main() {
     let mut args: Cli = Cli::parse();
     if args.user.eq_ignore_ascii_case("<logged-in username>") {
         args.user = whoami::username()
     }
+    if let Command::List { json, show_secrets } = args.command {
+        args.list_entries(json || args.output == Output::Json, show_secrets);
+        return;
+    }
+    if let Command::Migrate { from, to, dry_run } = &args.command {
+        args.migrate_entries(from, to, *dry_run);
+        return;
+    }
     let entry = match args.entry_for() {
         Ok(entry) => entry,
         Err(err) => {
+            if args.output == Output::Json {
+                println!("{}", error_json(&err));
+            }
             if args.verbose {
                 let description = args.description();
                 eprintln!("Couldn't create entry for '{description}': {err}")
@@ -45,15 +56,37 @@ fn main() {
                 _ => panic!("Can't set without a value"),
             }
         }
-        Command::Password => match entry.get_password() {
-            Ok(password) => {
-                println!("{password}");
-                args.success_message_for(&Value::Password(password));
+        Command::Password { copy, clear_after } => {
+            match args.read_chosen(&entry, |entry| args.read_password(entry)) {
+                Ok((entry, (password, summary))) => {
+                    match copy.then(|| copy_to_clipboard(&password)).flatten() {
+                        // a copied password is never written, even in JSON or verbose mode
+                        Some(clipboard) => {
+                            if args.output == Output::Json {
+                                println!("{}", args.get_json(&entry, &Value::None));
+                            }
+                            clear_clipboard_after(clipboard, &password, *clear_after);
+                        }
+                        None if args.output == Output::Json => {
+                            println!("{}", args.get_json(&entry, &Value::Password(password)));
+                        }
+                        None => {
+                            println!("{password}");
+                            args.success_message_for(&Value::Password(password));
+                            if let Some(summary) = summary {
+                                eprint_summary(&summary);
+                            }
+                        }
+                    }
+                }
+                Err(err) => args.error_message_for(err),
+            }
+        }
+        Command::Secret => match args.read_chosen(&entry, Entry::get_secret) {
+            Ok((entry, secret)) if args.output == Output::Json => {
+                println!("{}", args.get_json(&entry, &Value::Secret(secret)));
             }
-            Err(err) => args.error_message_for(err),
-        },
-        Command::Secret => match entry.get_secret() {
-            Ok(secret) => {
+            Ok((_, secret)) => {
                 println!("{}", secret_string(&secret));
                 args.success_message_for(&Value::Secret(secret));
             }
@@ -70,6 +103,8 @@ fn main() {
             Ok(()) => args.success_message_for(&Value::None),
             Err(err) => args.error_message_for(err),
         },
+        Command::List { .. } => unreachable!("List is handled without an entry"),
+        Command::Migrate { .. } => unreachable!("Migrate is handled without an entry"),
     }
 }
 
@@ -78,10 +113,25 @@ fn main() {
 /// Keyring CLI: A command-line interface to platform secure storage
 pub struct Cli {
     #[clap(short, long, action, verbatim_doc_comment)]
-    /// Write debugging info to stderr, including retrieved passwords and secrets.
+    /// Write debugging info to stderr, including retrieved passwords and secrets
+    /// (but not passwords copied to the clipboard).
     /// If an operation fails, detailed error information is provided.
     pub verbose: bool,
 
+    #[clap(short, long, value_enum, default_value_t = Output::Text, verbatim_doc_comment)]
+    /// The format of the output.  In JSON mode, the password and secret
+    /// commands write an object with the service, username, and password
+    /// (or secret), and errors are written to the standard output as an
+    /// object with the error's name and stable code.  A password copied
+    /// to the clipboard is left out of its object.  With --verbose,
+    /// the password and secret objects also describe the credential.
+    pub output: Output,
+
+    #[clap(short, long, action, verbatim_doc_comment)]
+    /// If more than one credential matches, use the first one
+    /// rather than asking which one to use.
+    pub first: bool,
+
     #[clap(short, long, value_parser)]
     /// The (optional) target for the entry.
     pub target: Option<String>,
@@ -98,6 +148,14 @@ pub struct Cli {
     pub command: Command,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// Human-readable text
+    Text,
+    /// JSON objects
+    Json,
+}
+
 #[derive(Debug, Parser)]
 pub enum Command {
     /// Set the password or update the attributes in the secure store
@@ -105,15 +163,41 @@ pub enum Command {
         #[command(flatten)]
         what: What,
 
-        #[clap(value_parser)]
+        #[clap(value_parser, conflicts_with_all = ["file", "stdin"])]
         /// The input to parse. If not specified, it will be
         /// read interactively from the terminal. Password/secret
         /// input will not be echoed.
         input: Option<String>,
+
+        #[clap(long, value_parser, conflicts_with = "stdin", verbatim_doc_comment)]
+        /// Read the input from this file, so it isn't in the command line.
+        /// A secret is the file's bytes (not base64-encoded), and a
+        /// password that isn't UTF-8 is stored as a secret.  A single
+        /// newline at the end of a password or attributes is ignored.
+        file: Option<std::path::PathBuf>,
+
+        #[clap(long, action, verbatim_doc_comment)]
+        /// Read the input from the standard input, just as from a --file.
+        /// If the standard input is a terminal, one line is read without
+        /// being echoed.
+        stdin: bool,
     },
     /// Retrieve the (string) password from the secure store
     /// and write it to the standard output.
-    Password,
+    Password {
+        #[clap(short, long, action, verbatim_doc_comment)]
+        /// Copy the password to the clipboard instead of writing it.
+        /// If there's no clipboard (or the CLI wasn't built with the
+        /// `clipboard` feature), the password is written after a warning.
+        copy: bool,
+
+        #[clap(long, value_parser, default_value_t = 30, verbatim_doc_comment)]
+        /// Clear the clipboard after this many seconds (0 leaves it alone),
+        /// unless something else has been copied since.  Only used with --copy.
+        /// On Linux, the password may only stay on the clipboard while
+        /// the CLI is running, unless there's a clipboard manager.
+        clear_after: u64,
+    },
     /// Retrieve the (binary) secret from the secure store
     /// and write it in base64 encoding to the standard output.
     Secret,
@@ -121,6 +205,35 @@ pub enum Command {
     Attributes,
     /// Delete the credential from the secure store.
     Delete,
+    /// List the service/user pairs of all the credentials
+    /// in the secure store for the service.
+    List {
+        #[clap(long, action)]
+        /// Write the list as JSON.
+        json: bool,
+
+        #[clap(long, action)]
+        /// Also write each credential's password (or, if it's
+        /// not a string, its secret in base64 encoding).
+        show_secrets: bool,
+    },
+    /// Copy all the credentials from one secure store
+    /// to another, reporting on each one.
+    /// (The CLI must be built with the `export` feature.)
+    Migrate {
+        #[clap(long, value_parser, verbatim_doc_comment)]
+        /// The store to copy from, such as secret-service.
+        /// (Run with an unknown store name to see those available.)
+        from: String,
+
+        #[clap(long, value_parser)]
+        /// The store to copy to, such as keyutils.
+        to: String,
+
+        #[clap(long, action)]
+        /// List the credentials that would be copied, without copying them.
+        dry_run: bool,
+    },
 }
 
 #[derive(Debug, Args)]
@@ -145,6 +258,7 @@ enum Value {
     Secret(Vec<u8>),
     Password(String),
     Attributes(HashMap<String, String>),
+    Count(usize),
     None,
 }
 
@@ -165,7 +279,89 @@ impl Cli {
         }
     }
 
+    /// Choose one of the credentials in an ambiguous match.
+    ///
+    /// Unless --first was specified, the user picks from a numbered list
+    /// if there's a terminal to ask them on; otherwise the error is returned.
+    /// Read a value from the entry, or if it's ambiguous, from the entry the user chooses.
+    ///
+    /// The entry that was read is returned with the value, so it can be described.
+    /// Read an entry's password, and in verbose mode the description
+    /// of its credential (from the same read of the store).
+    fn read_password(&self, entry: &Entry) -> Result<(String, Option<CredentialSummary>)> {
+        if self.verbose {
+            let (password, summary) = entry.get_password_and_credential()?;
+            Ok((password, Some(summary)))
+        } else {
+            Ok((entry.get_password()?, None))
+        }
+    }
+
+    fn read_chosen<T>(
+        &self,
+        entry: &Entry,
+        read: impl Fn(&Entry) -> Result<T>,
+    ) -> Result<(Entry, T)> {
+        match read(entry) {
+            Ok(value) => Ok((entry.clone(), value)),
+            Err(err) => {
+                let chosen = self.choose_entry(err)?;
+                let value = read(&chosen)?;
+                Ok((chosen, value))
+            }
+        }
+    }
+
+    fn choose_entry(&self, err: Error) -> Result<Entry> {
+        use std::io::IsTerminal;
+
+        let mut credentials = match err {
+            Error::Ambiguous(credentials) if !credentials.is_empty() => credentials,
+            err => return Err(err),
+        };
+        if self.first {
+            return Ok(Entry::new_with_credential(credentials.remove(0)));
+        }
+        // JSON output is for scripts, so they get the ambiguity as an error
+        if self.output == Output::Json || !std::io::stdin().is_terminal() {
+            return Err(Error::Ambiguous(credentials));
+        }
+        let entries: Vec<Entry> = credentials
+            .into_iter()
+            .map(Entry::new_with_credential)
+            .collect();
+        eprintln!("More than one credential matches '{}':", self.description());
+        for (i, entry) in entries.iter().enumerate() {
+            let (service, user) = entry
+                .get_specifiers()
+                .unwrap_or_else(|| (self.service.clone(), "<unknown>".to_string()));
+            let created = match entry.created() {
+                Ok(Some(time)) => time_string(time),
+                _ => "unknown".to_string(),
+            };
+            eprintln!(
+                "  {}) service: {service}, user: {user}, created: {created}",
+                i + 1
+            );
+        }
+        loop {
+            eprint!("Which one (1-{})? ", entries.len());
+            let reply = rprompt::read_reply().unwrap_or_default();
+            match reply.trim().parse::<usize>() {
+                Ok(n) if (1..=entries.len()).contains(&n) => return Ok(entries[n - 1].clone()),
+                _ if reply.is_empty() => {
+                    eprintln!("No credential was chosen, so nothing was done");
+                    std::process::exit(1)
+                }
+                _ => eprintln!("Please enter a number from 1 to {}", entries.len()),
+            }
+        }
+    }
+
     fn error_message_for(&self, err: Error) {
+        if self.output == Output::Json {
+            println!("{}", error_json(&err));
+        }
         if self.verbose {
             let description = self.description();
             match err {
@@ -179,7 +375,7 @@ impl Cli {
                     Command::Set { .. } => {
                         eprintln!("Couldn't set credential data for '{description}': {err}");
                     }
-                    Command::Password => {
+                    Command::Password { .. } => {
                         eprintln!("Couldn't get password for '{description}': {err}");
                     }
                     Command::Secret => {
@@ -191,6 +387,13 @@ impl Cli {
                     Command::Delete => {
                         eprintln!("Couldn't delete credential for '{description}': {err}");
                     }
+                    Command::List { .. } => {
+                        let service = &self.service;
+                        eprintln!("Couldn't list credentials for service '{service}': {err}");
+                    }
+                    Command::Migrate { ref from, .. } => {
+                        eprintln!("Couldn't list the {from} credentials: {err}");
+                    }
                 },
             }
         }
@@ -217,7 +420,7 @@ impl Cli {
                 }
                 _ => panic!("Can't set without a value"),
             },
-            Command::Password => {
+            Command::Password { .. } => {
                 match value {
                     Value::Password(password) => {
                         eprintln!("Password for '{description}' is '{password}'");
@@ -246,11 +449,182 @@ impl Cli {
             Command::Delete => {
                 eprintln!("Successfully deleted credential for '{description}'");
             }
+            Command::List { .. } => match value {
+                Value::Count(count) => {
+                    let service = &self.service;
+                    eprintln!("Found {count} credential(s) for service '{service}'");
+                }
+                _ => panic!("Wrong value type for command"),
+            },
+            Command::Migrate {
+                ref from,
+                ref to,
+                dry_run,
+            } => match value {
+                Value::Count(count) if dry_run => {
+                    eprintln!("Would migrate {count} credential(s) from {from} to {to}");
+                }
+                Value::Count(count) => {
+                    eprintln!("Migrated {count} credential(s) from {from} to {to}");
+                }
+                _ => panic!("Wrong value type for command"),
+            },
+        }
+    }
+
+    fn get_json(&self, entry: &Entry, value: &Value) -> String {
+        let mut fields = vec![
+            format!("\"service\":{}", json_string(&self.service)),
+            format!("\"username\":{}", json_string(&self.user)),
+        ];
+        match value {
+            Value::Password(password) => {
+                fields.push(format!("\"password\":{}", json_string(password)))
+            }
+            Value::Secret(secret) => fields.push(format!(
+                "\"secret\":{}",
+                json_string(&secret_string(secret))
+            )),
+            Value::None => fields.push("\"copied\":true".to_string()),
+            _ => panic!("Wrong value type for command"),
+        }
+        if self.verbose {
+            let mut credential = Vec::new();
+            if let Some(target) = &self.target {
+                credential.push(format!("\"target\":{}", json_string(target)));
+            }
+            if let Ok(attributes) = entry.get_attributes() {
+                credential.push(format!("\"attributes\":{}", attributes_json(&attributes)));
+            }
+            // an entry's debug format shows where its credential is, but not its secret
+            credential.push(format!(
+                "\"debug\":{}",
+                json_string(&format!("{:?}", entry))
+            ));
+            fields.push(format!("\"credential\":{{{}}}", credential.join(",")));
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+
+    fn list_entries(&self, json: bool, show_secrets: bool) {
+        let entries = match Entry::search(&self.service) {
+            Ok(entries) => entries,
+            Err(Error::NotSupportedByStore(_)) => {
+                eprintln!("Sorry, the secure store on this platform can't list its credentials");
+                std::process::exit(1)
+            }
+            Err(err) => return self.error_message_for(err),
+        };
+        let mut listed = Vec::new();
+        for entry in &entries {
+            let (service, user) = entry
+                .get_specifiers()
+                .unwrap_or_else(|| (self.service.clone(), "<unknown>".to_string()));
+            let value = if show_secrets {
+                match entry.get_password() {
+                    Ok(password) => Value::Password(password),
+                    Err(Error::BadEncoding(secret)) => Value::Secret(secret),
+                    Err(err) => {
+                        if self.verbose {
+                            eprintln!("Couldn't get secret for '{user}@{service}': {err}");
+                        }
+                        Value::None
+                    }
+                }
+            } else {
+                Value::None
+            };
+            listed.push((service, user, value));
+        }
+        if json {
+            println!("{}", list_json(&listed));
+        } else {
+            for (service, user, value) in &listed {
+                match value {
+                    Value::Password(password) => println!("{service}\t{user}\t{password}"),
+                    Value::Secret(secret) => {
+                        println!("{service}\t{user}\t{}", secret_string(secret))
+                    }
+                    _ => println!("{service}\t{user}"),
+                }
+            }
+        }
+        self.success_message_for(&Value::Count(entries.len()));
+    }
+
+    /// Copy all the credentials in one store to another,
+    /// writing a line for each with its service, user, and outcome.
+    ///
+    /// Each credential is copied by exporting it and importing it into
+    /// the other store.  Credentials whose service and user aren't known
+    /// can't be copied, so they count as failures.
+    ///
+    /// Exits with a failure status if any credential couldn't be copied.
+    #[cfg(feature = "export")]
+    fn migrate_entries(&self, from: &str, to: &str, dry_run: bool) {
+        let source = store_named(from);
+        let destination = store_named(to);
+        if from == to {
+            eprintln!("Can't migrate credentials from a store to itself");
+            std::process::exit(1)
+        }
+        let credentials = match source.query(&HashMap::new()) {
+            Ok(credentials) => credentials,
+            Err(Error::NotSupportedByStore(_)) => {
+                eprintln!("Sorry, the {from} store can't list its credentials");
+                std::process::exit(1)
+            }
+            Err(err) => return self.error_message_for(err),
+        };
+        let (mut count, mut failures) = (0, 0);
+        for credential in credentials {
+            let entry = Entry::new_with_credential(credential);
+            let Some((service, user)) = entry.get_specifiers() else {
+                println!("<unknown>\t<unknown>\tfailed: its service and user are unknown");
+                failures += 1;
+                continue;
+            };
+            if dry_run {
+                println!("{service}\t{user}\twould migrate");
+                count += 1;
+                continue;
+            }
+            let migrated = keyring::export(std::slice::from_ref(&entry))
+                .and_then(|exported| keyring::import(destination.as_ref(), &exported).remove(0));
+            match migrated {
+                Ok(_) => {
+                    println!("{service}\t{user}\tmigrated");
+                    count += 1;
+                }
+                Err(err) => {
+                    println!("{service}\t{user}\tfailed: {err}");
+                    failures += 1;
+                }
+            }
+        }
+        self.success_message_for(&Value::Count(count));
+        if failures > 0 {
+            std::process::exit(1)
         }
     }
 
+    #[cfg(not(feature = "export"))]
+    fn migrate_entries(&self, _from: &str, _to: &str, _dry_run: bool) {
+        eprintln!("Sorry, this CLI was built without the `export` feature, so it can't migrate");
+        std::process::exit(1)
+    }
+
     fn get_password_and_attributes(&self) -> Value {
-        if let Command::Set { what, input } = &self.command {
+        if let Command::Set {
+            what,
+            input,
+            file,
+            stdin,
+        } = &self.command
+        {
+            if let Some(bytes) = read_input_source(file.as_deref(), *stdin) {
+                return value_from_bytes(what, bytes);
+            }
             if what.password {
                 Value::Password(read_password(input))
             } else if what.secret {
@@ -264,15 +638,212 @@ impl Cli {
     }
 }
 
+#[cfg(feature = "clipboard")]
+type Clipboard = arboard::Clipboard;
+
+#[cfg(not(feature = "clipboard"))]
+type Clipboard = std::convert::Infallible;
+
+/// Copy the password to the clipboard.
+///
+/// If that fails, this warns that the password will be written instead and returns `None`.
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(password: &str) -> Option<Clipboard> {
+    let copied = Clipboard::new().and_then(|mut clipboard| {
+        clipboard.set_text(password)?;
+        Ok(clipboard)
+    });
+    match copied {
+        Ok(clipboard) => Some(clipboard),
+        Err(err) => {
+            eprintln!(
+                "Warning: couldn't copy the password to the clipboard ({err}), so here it is:"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_password: &str) -> Option<Clipboard> {
+    eprintln!(
+        "Warning: this CLI was built without the `clipboard` feature, so here's the password:"
+    );
+    None
+}
+
+/// Wait, and then clear the clipboard if it still holds the password.
+///
+/// The clipboard is held open while waiting, since on Linux its
+/// contents are served by the process that put them there.
+#[cfg(feature = "clipboard")]
+fn clear_clipboard_after(mut clipboard: Clipboard, password: &str, clear_after: u64) {
+    if clear_after == 0 {
+        eprintln!("Copied the password to the clipboard");
+        return;
+    }
+    eprintln!("Copied the password to the clipboard; it will be cleared in {clear_after} seconds");
+    std::thread::sleep(std::time::Duration::from_secs(clear_after));
+    match clipboard.get_text().map(keyring::Zeroizing::new) {
+        // something else has been copied since, so leave it be
+        Ok(text) if *text != password => {}
+        Ok(_) => {
+            if let Err(err) = clipboard.clear() {
+                eprintln!("Warning: couldn't clear the clipboard: {err}");
+            }
+        }
+        Err(arboard::Error::ContentNotAvailable) => {}
+        Err(err) => eprintln!("Warning: couldn't check the clipboard before clearing it: {err}"),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn clear_clipboard_after(clipboard: Clipboard, _password: &str, _clear_after: u64) {
+    match clipboard {}
+}
+
+/// The credential builder for the store with the given name.
+///
+/// The names are those given by the stores' `backend_name`; if there's no
+/// store with the given name on this platform, this exits after listing those there are.
+#[cfg(feature = "export")]
+fn store_named(name: &str) -> Box<keyring::CredentialBuilder> {
+    #[allow(unused_mut)]
+    let mut builders: Vec<Box<keyring::CredentialBuilder>> = Vec::new();
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    ))]
+    builders.push(keyring::secret_service::default_credential_builder());
+    #[cfg(all(target_os = "linux", feature = "keyutils"))]
+    builders.push(keyring::keyutils::default_credential_builder());
+    #[cfg(all(target_os = "macos", feature = "apple-native"))]
+    builders.push(keyring::macos::default_credential_builder());
+    #[cfg(all(target_os = "windows", feature = "windows-native"))]
+    builders.push(keyring::windows::default_credential_builder());
+    let names: Vec<&str> = builders.iter().map(|b| b.backend_name()).collect();
+    match names.iter().position(|n| *n == name) {
+        Some(index) => builders.swap_remove(index),
+        None => {
+            eprintln!(
+                "There's no {name} store; the stores are: {}",
+                names.join(", ")
+            );
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Format a time as a UTC date and time, such as `2024-03-01 12:34:56 UTC`.
+fn time_string(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // convert days since the epoch to a civil date (see Howard Hinnant's `civil_from_days`)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
 fn secret_string(secret: &[u8]) -> String {
     use base64::prelude::*;
 
     BASE64_STANDARD.encode(secret)
 }
 
+fn list_json(listed: &[(String, String, Value)]) -> String {
+    let objects = listed
+        .iter()
+        .map(|(service, user, value)| {
+            let mut fields = vec![
+                format!("\"service\":{}", json_string(service)),
+                format!("\"user\":{}", json_string(user)),
+            ];
+            match value {
+                Value::Password(password) => {
+                    fields.push(format!("\"password\":{}", json_string(password)))
+                }
+                Value::Secret(secret) => fields.push(format!(
+                    "\"secret\":{}",
+                    json_string(&secret_string(secret))
+                )),
+                _ => {}
+            }
+            format!("{{{}}}", fields.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", objects.join(","))
+}
+
+fn attributes_json(attributes: &HashMap<String, String>) -> String {
+    let mut names: Vec<&String> = attributes.keys().collect();
+    names.sort();
+    let fields = names
+        .into_iter()
+        .map(|name| format!("{}:{}", json_string(name), json_string(&attributes[name])))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn error_json(err: &Error) -> String {
+    let code = err.code();
+    format!(
+        "{{\"error\":{},\"code\":{}}}",
+        json_string(&format!("{code:?}")),
+        code as i32
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Describe an entry's credential on stderr.
+fn eprint_summary(summary: &CredentialSummary) {
+    let time =
+        |time: Option<std::time::SystemTime>| time.map_or("unknown".to_string(), time_string);
+    eprintln!(
+        "The credential is in the {} store, with service '{}' and user '{}'.",
+        summary.backend, summary.service, summary.username
+    );
+    eprintln!(
+        "It was created {} and modified {}.",
+        time(summary.created),
+        time(summary.modified)
+    );
+    if !summary.attributes.is_empty() {
+        eprintln!("Its attributes are:");
+        eprint_attributes(&summary.attributes);
+    }
+}
+
 fn eprint_attributes(attributes: &HashMap<String, String>) {
     for (key, value) in attributes {
-        println!("    {key}: {value}");
+        eprintln!("    {key}: {value}");
     }
 }
 
@@ -296,6 +867,51 @@ fn decode_secret(input: &Option<String>) -> Vec<u8> {
     }
 }
 
+/// Read the raw input from the given file or the standard input, if either was given.
+fn read_input_source(file: Option<&std::path::Path>, stdin: bool) -> Option<Vec<u8>> {
+    use std::io::{IsTerminal, Read};
+
+    let result = if let Some(path) = file {
+        std::fs::read(path)
+    } else if !stdin {
+        return None;
+    } else if std::io::stdin().is_terminal() {
+        rpassword::read_password().map(String::into_bytes)
+    } else {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    };
+    match result {
+        Ok(bytes) => Some(bytes),
+        Err(err) => {
+            eprintln!("Sorry, the input couldn't be read: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Interpret input read from a file or the standard input.
+fn value_from_bytes(what: &What, mut bytes: Vec<u8>) -> Value {
+    if what.secret {
+        return Value::Secret(bytes);
+    }
+    if bytes.ends_with(b"\n") {
+        bytes.pop();
+        if bytes.ends_with(b"\r") {
+            bytes.pop();
+        }
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) if what.password => Value::Password(text),
+        Ok(text) => Value::Attributes(parse_attributes(&Some(text))),
+        Err(err) if what.password => Value::Secret(err.into_bytes()),
+        Err(_) => {
+            eprintln!("Sorry, the provided attributes are not UTF-8");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn read_password(input: &Option<String>) -> String {
     if let Some(input) = input {
         input.clone()
@@ -0,0 +1,311 @@
+/*!
+
+# Asynchronous entries
+
+The credential stores used by this crate are all accessed via blocking calls.
+Some of those calls (notably the RPCs made to the Secret Service) can take
+tens or even hundreds of milliseconds, which is a problem if they are made
+from inside a task running on an async executor.
+
+This module provides an [AsyncEntry] type that mirrors the API of [Entry],
+but whose methods return futures rather than blocking the caller.  Each
+operation is run to completion on a dedicated worker thread, and the returned
+future is woken when that operation finishes.  Because there is no dependency
+on any particular async runtime, these futures can be awaited from
+any executor (Tokio, async-std, smol, or a hand-rolled one).
+
+This module is only included if the `async` feature is specified;
+the synchronous [Entry] API is unaffected by that feature.
+ */
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use super::{Entry, Result, Zeroizing};
+
+/// An entry whose operations are performed asynchronously.
+///
+/// An async entry wraps a (synchronous) [Entry], so it can be created
+/// from any existing entry via the [From] trait.
+#[derive(Debug, Clone)]
+pub struct AsyncEntry {
+    inner: Arc<Entry>,
+}
+
+impl From<Entry> for AsyncEntry {
+    fn from(entry: Entry) -> Self {
+        Self {
+            inner: Arc::new(entry),
+        }
+    }
+}
+
+impl AsyncEntry {
+    /// Create an async entry for the given service and user.
+    ///
+    /// This has the same behavior (and errors) as [Entry::new].
+    /// Creating an entry does not access the credential store,
+    /// so this call is not itself asynchronous.
+    pub fn new(service: &str, user: &str) -> Result<AsyncEntry> {
+        Ok(Entry::new(service, user)?.into())
+    }
+
+    /// Create an async entry for the given target, service, and user.
+    ///
+    /// This has the same behavior (and errors) as [Entry::new_with_target].
+    pub fn new_with_target(target: &str, service: &str, user: &str) -> Result<AsyncEntry> {
+        Ok(Entry::new_with_target(target, service, user)?.into())
+    }
+
+    /// Return a reference to the synchronous entry wrapped by this one.
+    pub fn entry(&self) -> &Entry {
+        &self.inner
+    }
+
+    /// Set the password for this entry.
+    ///
+    /// See [Entry::set_password] for details.
+    pub async fn set_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |entry| entry.set_password(&password)).await
+    }
+
+    /// Set the secret for this entry.
+    ///
+    /// See [Entry::set_secret] for details.
+    pub async fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |entry| entry.set_secret(&secret)).await
+    }
+
+    /// Update the password on this entry's existing credential.
+    ///
+    /// See [Entry::update_password] for details.
+    pub async fn update_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |entry| entry.update_password(&password))
+            .await
+    }
+
+    /// Update the secret on this entry's existing credential.
+    ///
+    /// See [Entry::update_secret] for details.
+    pub async fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |entry| entry.update_secret(&secret)).await
+    }
+
+    /// Create a credential for this entry with the given password.
+    ///
+    /// See [Entry::create_password] for details.
+    pub async fn create_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |entry| entry.create_password(&password))
+            .await
+    }
+
+    /// Create a credential for this entry with the given secret.
+    ///
+    /// See [Entry::create_secret] for details.
+    pub async fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |entry| entry.create_secret(&secret)).await
+    }
+
+    /// Retrieve the password saved for this entry.
+    ///
+    /// See [Entry::get_password] for details.
+    pub async fn get_password(&self) -> Result<String> {
+        self.run(|entry| entry.get_password()).await
+    }
+
+    /// Retrieve the secret saved for this entry.
+    ///
+    /// See [Entry::get_secret] for details.
+    pub async fn get_secret(&self) -> Result<Vec<u8>> {
+        self.run(|entry| entry.get_secret()).await
+    }
+
+    /// Get the attributes on the underlying credential for this entry.
+    ///
+    /// See [Entry::get_attributes] for details.
+    pub async fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        self.run(|entry| entry.get_attributes()).await
+    }
+
+    /// Update the attributes on the underlying credential for this entry.
+    ///
+    /// See [Entry::update_attributes] for details.
+    pub async fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        let attributes: HashMap<String, String> = attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self.run(move |entry| {
+            let attributes: HashMap<&str, &str> = attributes
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            entry.update_attributes(&attributes)
+        })
+        .await
+    }
+
+    /// Move the underlying credential for this entry to a new service and user.
+    ///
+    /// See [Entry::rename] for details.
+    pub async fn rename(&self, new_service: &str, new_user: &str) -> Result<()> {
+        let (new_service, new_user) = (new_service.to_string(), new_user.to_string());
+        self.run(move |entry| entry.rename(&new_service, &new_user))
+            .await
+    }
+
+    /// Delete the underlying credential for this entry.
+    ///
+    /// See [Entry::delete_credential] for details.
+    pub async fn delete_credential(&self) -> Result<()> {
+        self.run(|entry| entry.delete_credential()).await
+    }
+
+    /// Run a blocking operation on this entry in a worker thread.
+    fn run<F, T>(&self, f: F) -> Blocking<T>
+    where
+        F: FnOnce(&Entry) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let entry = self.inner.clone();
+        Blocking::spawn(move || f(&entry))
+    }
+}
+
+/// The state shared between a [Blocking] future and its worker thread.
+struct Shared<T> {
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// A future that resolves when a blocking operation on a worker thread completes.
+struct Blocking<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T: Send + 'static> Blocking<T> {
+    fn spawn<F>(f: F) -> Self
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || {
+            let result = f();
+            let mut guard = thread_shared
+                .lock()
+                .expect("Poisoned async state in keyring-rs: please report a bug!");
+            guard.result = Some(result);
+            if let Some(waker) = guard.waker.take() {
+                waker.wake()
+            }
+        });
+        Self { shared }
+    }
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut guard = self
+            .shared
+            .lock()
+            .expect("Poisoned async state in keyring-rs: please report a bug!");
+        match guard.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                guard.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    use super::AsyncEntry;
+    use crate::{Entry, Error, mock::MockCredential};
+
+    /// A minimal executor, so the tests don't depend on any async runtime.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct ThreadWaker(std::thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark()
+            }
+        }
+
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    fn entry_new() -> AsyncEntry {
+        Entry::new_with_credential(Box::new(MockCredential::default())).into()
+    }
+
+    #[test]
+    fn test_round_trip_password() {
+        let entry = entry_new();
+        block_on(async {
+            assert!(matches!(entry.get_password().await, Err(Error::NoEntry)));
+            entry
+                .set_password("async password")
+                .await
+                .expect("Can't set password");
+            let password = entry.get_password().await.expect("Can't get password");
+            assert_eq!(password, "async password");
+            entry
+                .delete_credential()
+                .await
+                .expect("Can't delete password");
+            assert!(matches!(entry.get_password().await, Err(Error::NoEntry)));
+        })
+    }
+
+    #[test]
+    fn test_round_trip_secret_and_attributes() {
+        let entry = entry_new();
+        let clone = entry.clone();
+        block_on(async {
+            entry
+                .set_secret(b"\x00\x01\x02")
+                .await
+                .expect("Can't set secret");
+            // clones share the same underlying credential
+            let secret = clone.get_secret().await.expect("Can't get secret");
+            assert_eq!(secret, b"\x00\x01\x02");
+            let attributes = HashMap::from([("name", "value")]);
+            clone
+                .update_attributes(&attributes)
+                .await
+                .expect("Can't update attributes");
+            let attributes = entry.get_attributes().await.expect("Can't get attributes");
+            assert_eq!(attributes.get("name").map(String::as_str), Some("value"));
+        })
+    }
+}
@@ -0,0 +1,377 @@
+/*!
+
+# Caching
+
+Reading a credential's secret goes to the credential store every time,
+which can be slow (the macOS keychain and the secret-service are each a
+round trip to another process).  [Entry::with_cache] gives an entry that keeps
+the password or secret it last read in memory, and returns it again without
+going to the store until the given time has passed.
+
+Caching is opt-in because it keeps secrets in memory for longer than
+they would otherwise be: a cached value is held in a
+[Zeroizing] buffer, which is cleared when the value
+expires (the next time the entry is read), when the entry changes its
+credential, and when the entry is dropped.  A cached value can also be
+out of date, because changes made to the credential by other entries
+or other processes aren't seen until it expires, and neither is the expiry
+of a credential set with [set_password_with_expiry](Entry::set_password_with_expiry).
+
+Setting, updating, creating, renaming, or deleting the credential through
+the entry clears the cache, so the entry's own changes are always seen.
+
+```rust
+# use std::time::Duration;
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?.with_cache(Duration::from_secs(30));
+entry.set_password("my password")?;
+assert_eq!(entry.get_password()?, "my password"); // read from the store
+assert_eq!(entry.get_password()?, "my password"); // read from the cache
+# Ok::<(), keyring::Error>(())
+```
+ */
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::credential::{Capabilities, Credential, CredentialApi};
+use super::{Entry, Result, Zeroizing};
+
+/// A value read from the store, and when it stops being used
+/// (`None` if the TTL is too long for that to be represented, so never).
+struct Cached<T: zeroize::Zeroize> {
+    value: Zeroizing<T>,
+    expires: Option<Instant>,
+}
+
+/// What's been read from the store.
+#[derive(Default)]
+struct Cache {
+    password: Option<Cached<String>>,
+    secret: Option<Cached<Vec<u8>>>,
+    /// How many times the cache has been cleared, so that a read which
+    /// overlaps a change doesn't cache the value it read.
+    generation: u64,
+}
+
+/// A credential that caches the password and secret it reads.
+///
+/// See the [module docs](crate::cache) for details.
+///
+/// The wrapped credential's [as_any](CredentialApi::as_any) is returned as this
+/// credential's, so [Entry::get_credential] still gives the concrete store credential.
+pub struct CachedCredential {
+    inner: Arc<Credential>,
+    ttl: Duration,
+    cache: Mutex<Cache>,
+}
+
+impl CachedCredential {
+    /// Wrap the given credential so the values it reads are cached for the given time.
+    pub fn new(inner: Arc<Credential>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// How long a value read from the store is used.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Forget (and zeroize) the cached values, so the next read goes to the store.
+    pub fn clear(&self) {
+        let mut cache = self.lock();
+        *cache = Cache {
+            generation: cache.generation + 1,
+            ..Cache::default()
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache
+            .lock()
+            .expect("Poisoned Mutex in keyring-rs: please report a bug!")
+    }
+
+    /// Run an operation that changes the credential, clearing the cache afterwards
+    /// (whether or not it succeeds, since a failure may have changed it too).
+    fn change<T>(&self, f: impl FnOnce(&Credential) -> Result<T>) -> Result<T> {
+        let result = f(self.inner.as_ref());
+        self.clear();
+        result
+    }
+
+    /// Return the value in the given slot if it's fresh, or else read and cache it.
+    ///
+    /// The store is read without the cache locked, so the value read is only
+    /// cached if the cache wasn't cleared (by a change) in the meantime.
+    fn read<T: zeroize::Zeroize + Clone>(
+        &self,
+        slot: impl Fn(&mut Cache) -> &mut Option<Cached<T>>,
+        f: impl FnOnce(&Credential) -> Result<T>,
+    ) -> Result<T> {
+        let now = Instant::now();
+        let generation = {
+            let mut cache = self.lock();
+            if let Some(cached) = slot(&mut cache) {
+                if cached.expires.is_none_or(|expires| expires > now) {
+                    return Ok((*cached.value).clone());
+                }
+            }
+            // dropping an expired value zeroizes it
+            *slot(&mut cache) = None;
+            cache.generation
+        };
+        let value = f(self.inner.as_ref())?;
+        let mut cache = self.lock();
+        if cache.generation == generation {
+            *slot(&mut cache) = Some(Cached {
+                value: Zeroizing::new(value.clone()),
+                expires: now.checked_add(self.ttl),
+            });
+        }
+        Ok(value)
+    }
+}
+
+impl CredentialApi for CachedCredential {
+    fn set_password(&self, password: &str) -> Result<()> {
+        self.change(|inner| inner.set_password(password))
+    }
+
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        self.change(|inner| inner.set_secret(secret))
+    }
+
+    fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        self.change(|inner| inner.set_password_with_expiry(password, ttl))
+    }
+
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        self.change(|inner| inner.set_secret_with_expiry(secret, ttl))
+    }
+
+    fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        self.change(|inner| inner.set_password_replacing_attributes(password))
+    }
+
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        self.change(|inner| inner.set_secret_replacing_attributes(secret))
+    }
+
+    /// Return the cached password if it hasn't expired, and otherwise read it from the store.
+    fn get_password(&self) -> Result<String> {
+        self.read(|cache| &mut cache.password, |inner| inner.get_password())
+    }
+
+    /// Return the cached secret if it hasn't expired, and otherwise read it from the store.
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        self.read(|cache| &mut cache.secret, |inner| inner.get_secret())
+    }
+
+    fn exists(&self) -> Result<bool> {
+        self.inner.exists()
+    }
+
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.change(|inner| inner.update_password(password))
+    }
+
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        self.change(|inner| inner.update_secret(secret))
+    }
+
+    fn create_password(&self, password: &str) -> Result<()> {
+        self.change(|inner| inner.create_password(password))
+    }
+
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        self.change(|inner| inner.create_secret(secret))
+    }
+
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        self.inner.get_attributes()
+    }
+
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        self.inner.get_secret_with_attributes()
+    }
+
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        self.inner.update_attributes(attributes)
+    }
+
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        self.inner.get_secret_with_times()
+    }
+
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        self.inner.last_modified()
+    }
+
+    fn created(&self) -> Result<Option<SystemTime>> {
+        self.inner.created()
+    }
+
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        self.inner.get_specifiers()
+    }
+
+    fn get_target(&self) -> Option<String> {
+        self.inner.get_target()
+    }
+
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        self.change(|inner| inner.rename(service, user))
+    }
+
+    /// Make a new wrapper with an empty cache and the same TTL.
+    fn rewrap(&self, f: &dyn Fn(&Arc<Credential>) -> Arc<Credential>) -> Option<Box<Credential>> {
+        Some(Box::new(Self::new(f(&self.inner), self.ttl)))
+    }
+
+    /// Make a sibling of the wrapped credential with an empty cache and the same TTL.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let sibling = self.inner.sibling(user)?;
+        Ok(Box::new(Self::new(sibling.into(), self.ttl)))
+    }
+
+    fn delete_credential(&self) -> Result<()> {
+        self.change(|inner| inner.delete_credential())
+    }
+
+    /// Return the wrapped credential as an [Any](std::any::Any) reference.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedCredential")
+            .field("inner", &self.inner)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Entry {
+    /// Make an entry for the same credential that caches the password or
+    /// secret it reads for the given time.
+    ///
+    /// Reads of the returned entry within the time return the cached value
+    /// rather than going to the store.  See the [cache](crate::cache) module
+    /// for how this works and what it costs.  With a time too long to
+    /// represent, such as [Duration::MAX], values are cached until the entry
+    /// changes its credential.
+    pub fn with_cache(self, ttl: Duration) -> Entry {
+        Entry::new_with_credential(Box::new(CachedCredential::new(self.inner, ttl)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::CachedCredential;
+    use crate::credential::{Credential, CredentialApi};
+    use crate::{Entry, Result};
+
+    /// A credential that counts its reads.
+    struct CountingCredential(AtomicUsize);
+
+    impl CredentialApi for CountingCredential {
+        fn set_secret(&self, _: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_secret(&self) -> Result<Vec<u8>> {
+            let reads = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(reads.to_string().into_bytes())
+        }
+
+        fn delete_credential(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_cache() {
+        let entry = Entry::new_with_credential(Box::new(CountingCredential(AtomicUsize::new(0))))
+            .with_cache(Duration::from_millis(200));
+        assert_eq!(entry.get_password().unwrap(), "1");
+        assert_eq!(entry.get_password().unwrap(), "1", "Password wasn't cached");
+        entry.set_password("new").unwrap();
+        assert_eq!(
+            entry.get_password().unwrap(),
+            "2",
+            "Set didn't clear the cache"
+        );
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(
+            entry.get_password().unwrap(),
+            "3",
+            "Cached password didn't expire"
+        );
+        entry.delete_credential().unwrap();
+        assert_eq!(entry.get_secret().unwrap(), b"4");
+    }
+
+    #[test]
+    fn test_cache_forever() {
+        let entry = Entry::new_with_credential(Box::new(CountingCredential(AtomicUsize::new(0))))
+            .with_cache(Duration::MAX);
+        assert_eq!(entry.get_password().unwrap(), "1");
+        assert_eq!(entry.get_password().unwrap(), "1", "Password wasn't cached");
+    }
+
+    #[test]
+    fn test_change_during_read() {
+        let inner: Arc<Credential> = Arc::new(CountingCredential(AtomicUsize::new(0)));
+        let cached = CachedCredential::new(inner, Duration::from_secs(5));
+        let value = cached
+            .read(
+                |cache| &mut cache.password,
+                |inner| {
+                    let value = inner.get_password();
+                    // as if another thread changed the credential during the read
+                    cached.set_password("new").unwrap();
+                    value
+                },
+            )
+            .unwrap();
+        assert_eq!(value, "1");
+        assert_eq!(
+            cached.get_password().unwrap(),
+            "2",
+            "Cached a value read before a change"
+        );
+        assert_eq!(
+            cached.get_password().unwrap(),
+            "2",
+            "Password wasn't cached"
+        );
+    }
+
+    #[test]
+    fn test_cache_passes_through() {
+        crate::tests::test_wrapper_passes_through(|entry| entry.with_cache(Duration::from_secs(5)));
+    }
+}
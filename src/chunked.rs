@@ -0,0 +1,683 @@
+/*!
+
+# Chunked secrets
+
+Some credential stores limit the size of the secrets they hold: Windows
+won't store a secret of more than 2560 bytes, and keyutils limits the
+size of its keys.  The chunked operations on [Entry] get around these
+limits by splitting a large secret into chunks, each stored in a
+credential of its own, and reassembling them on read.
+
+A secret that's small enough to fit in a single chunk is stored in the
+entry's credential just as [Entry::set_secret] would store it (and a small
+password just as [Entry::set_password] would, where small means that
+even its UTF-16 encoding, which is how Windows stores passwords, fits
+in a chunk).  A larger secret is split into chunks of at most [CHUNK_SIZE] bytes, each
+stored in a credential with the same service as the entry and a user
+made from the entry's user with an index suffix.  The entry's own
+credential then holds a short manifest that says how many chunks there
+are and how to find them.  Because chunks are found through the manifest,
+reading a chunked secret with [Entry::get_secret] returns the
+manifest rather than the secret: always use [Entry::get_secret_chunked]
+(or [Entry::get_password_chunked]) to read values written by the chunked
+operations, and [Entry::delete_chunked] to delete them.
+A value that happens to start like a manifest is always stored in chunks,
+so the chunked operations never mistake it for one.
+
+Chunks are written before the manifest, and each write uses a fresh set
+of chunk credentials, so if a write fails part way through the entry
+still holds its previous value.  The chunks written by a failed write are
+deleted before its error is returned, and the chunks of a replaced value
+are deleted once the new manifest is stored.
+
+```rust
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?;
+let token = "x".repeat(10_000);
+entry.set_password_chunked(&token)?;
+assert_eq!(entry.get_password_chunked()?, token);
+entry.delete_chunked()?;
+# Ok::<(), keyring::Error>(())
+```
+
+## Streaming
+
+[Entry::get_secret_reader] and [Entry::set_secret_writer] read and write
+secrets in the same form as the chunked operations, but a chunk at a time,
+so a secret of many megabytes never has to be held in memory all at once.
+A [SecretWriter] stores each chunk as soon as it's full, and stores the
+manifest when it's [finished](SecretWriter::finish); until then, the entry
+keeps its previous value.  A writer that's dropped without being finished
+deletes the chunks it stored.
+
+```rust
+# use std::io::{Read, Write};
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my config")?;
+let mut writer = entry.set_secret_writer()?;
+for _ in 0..1000 {
+    writer.write_all(&[7; 1024])?;
+}
+writer.finish()?;
+let mut total = 0;
+let mut reader = entry.get_secret_reader()?;
+let mut buffer = [0; 4096];
+loop {
+    match reader.read(&mut buffer)? {
+        0 => break,
+        n => total += n,
+    }
+}
+assert_eq!(total, 1000 * 1024);
+# entry.delete_chunked()?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+ */
+use std::io::{Read, Write};
+use std::time::SystemTime;
+
+use super::error::decode_password;
+use super::logging::{debug, warn};
+use super::{Credential, Entry, Error, Result, Zeroizing};
+
+/// The largest number of bytes stored in a single chunk.
+///
+/// This fits within the secret size limits of all the stores in this crate.
+pub const CHUNK_SIZE: usize = 2048;
+
+/// The prefix that marks a credential's secret as a chunk manifest.
+const MANIFEST_PREFIX: &str = "keyring-chunked-v1:";
+
+/// What an entry's credential holds when its secret is chunked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Manifest {
+    /// Distinguishes the chunks of one write from those of another.
+    id: String,
+    count: usize,
+    len: usize,
+}
+
+impl Manifest {
+    /// A manifest id that's different from those of other writes.
+    fn new_id() -> String {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        format!("{}-{nanos}", std::process::id())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        format!("{MANIFEST_PREFIX}{}:{}:{}", self.id, self.count, self.len).into_bytes()
+    }
+
+    /// Parse a manifest, returning `None` if the bytes aren't one.
+    fn parse(bytes: &[u8]) -> Option<Manifest> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let mut fields = text.strip_prefix(MANIFEST_PREFIX)?.split(':');
+        let id = fields.next()?.to_string();
+        let count = fields.next()?.parse().ok()?;
+        let len = fields.next()?.parse().ok()?;
+        if fields.next().is_some() || id.is_empty() {
+            return None;
+        }
+        Some(Manifest { id, count, len })
+    }
+}
+
+/// Whether a value can't be stored whole, because it would be read as a manifest.
+fn looks_like_manifest(value: &[u8]) -> bool {
+    value.starts_with(MANIFEST_PREFIX.as_bytes())
+}
+
+impl Entry {
+    /// Set the password for this entry, splitting it across
+    /// several credentials if it's too long for one.
+    ///
+    /// See the [chunked](crate::chunked) module for how the password is stored.
+    pub fn set_password_chunked(&self, password: &str) -> Result<()> {
+        debug!("set chunked password for entry {:?}", self.inner);
+        // stores that keep passwords as UTF-16 need two bytes for each unit
+        let stored_len = password.len().max(2 * password.encode_utf16().count());
+        self.set_chunked(password.as_bytes(), stored_len, || {
+            self.inner.set_password(password)
+        })
+    }
+
+    /// Set the secret for this entry, splitting it across
+    /// several credentials if it's too long for one.
+    ///
+    /// See the [chunked](crate::chunked) module for how the secret is stored.
+    pub fn set_secret_chunked(&self, secret: &[u8]) -> Result<()> {
+        debug!("set chunked secret for entry {:?}", self.inner);
+        self.set_chunked(secret, secret.len(), || self.inner.set_secret(secret))
+    }
+
+    /// Retrieve the password written by [set_password_chunked](Entry::set_password_chunked).
+    ///
+    /// A password that wasn't chunked is read just as [get_password](Entry::get_password)
+    /// reads it.  Returns an [Invalid](Error::Invalid) error if any of the chunks
+    /// are missing or have been changed.
+    pub fn get_password_chunked(&self) -> Result<String> {
+        debug!("get chunked password from entry {:?}", self.inner);
+        match self.read_manifest()? {
+            Some(manifest) => decode_password(self.read_chunks(&manifest)?.to_vec()),
+            None => self.inner.get_password(),
+        }
+    }
+
+    /// Retrieve the secret written by [set_secret_chunked](Entry::set_secret_chunked).
+    ///
+    /// A secret that wasn't chunked is read just as [get_secret](Entry::get_secret)
+    /// reads it.  Returns an [Invalid](Error::Invalid) error if any of the chunks
+    /// are missing or have been changed.
+    pub fn get_secret_chunked(&self) -> Result<Vec<u8>> {
+        debug!("get chunked secret from entry {:?}", self.inner);
+        let secret = Zeroizing::new(self.inner.get_secret()?);
+        match Manifest::parse(&secret) {
+            Some(manifest) => Ok(self.read_chunks(&manifest)?.to_vec()),
+            None => Ok(secret.to_vec()),
+        }
+    }
+
+    /// Delete this entry's credential along with any chunks it refers to.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential
+    /// for this entry.
+    pub fn delete_chunked(&self) -> Result<()> {
+        debug!("delete chunked credential for entry {:?}", self.inner);
+        let manifest = self.read_manifest()?;
+        self.inner.delete_credential()?;
+        if let Some(manifest) = manifest {
+            self.delete_chunks(&manifest, manifest.count);
+        }
+        Ok(())
+    }
+
+    /// Read this entry's secret a chunk at a time.
+    ///
+    /// This reads secrets written by [set_secret_writer](Entry::set_secret_writer)
+    /// or [set_secret_chunked](Entry::set_secret_chunked), and also secrets that
+    /// weren't chunked.  Only one chunk is held in memory at a time.  Returns a
+    /// [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
+    /// The reader's errors are I/O errors that wrap an [Error]; in particular,
+    /// a missing or changed chunk gives an [Invalid](Error::Invalid) error.
+    pub fn get_secret_reader(&self) -> Result<SecretReader> {
+        debug!("get secret reader for entry {:?}", self.inner);
+        let secret = Zeroizing::new(self.inner.get_secret()?);
+        let (manifest, buffer) = match Manifest::parse(&secret) {
+            Some(manifest) => (Some(manifest), Zeroizing::new(Vec::new())),
+            None => (None, secret),
+        };
+        Ok(SecretReader {
+            entry: self.clone(),
+            manifest,
+            next: 0,
+            read: 0,
+            buffer,
+            pos: 0,
+        })
+    }
+
+    /// Write this entry's secret a chunk at a time.
+    ///
+    /// The secret is stored just as [set_secret_chunked](Entry::set_secret_chunked)
+    /// would store it, once the writer is [finished](SecretWriter::finish).
+    /// Only one chunk is held in memory at a time.
+    pub fn set_secret_writer(&self) -> Result<SecretWriter> {
+        debug!("set secret writer for entry {:?}", self.inner);
+        Ok(SecretWriter {
+            entry: self.clone(),
+            old: self.old_manifest()?,
+            manifest: Manifest {
+                id: Manifest::new_id(),
+                count: 0,
+                len: 0,
+            },
+            buffer: Zeroizing::new(Vec::with_capacity(CHUNK_SIZE + 1)),
+            finished: false,
+        })
+    }
+
+    /// The manifest of the value being replaced, if it's chunked.
+    fn old_manifest(&self) -> Result<Option<Manifest>> {
+        match self.read_manifest() {
+            Ok(manifest) => Ok(manifest),
+            Err(Error::NoEntry) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Store a value whose whole form takes `stored_len` bytes in the store,
+    /// using `set_whole` if that fits in a chunk.
+    fn set_chunked(
+        &self,
+        value: &[u8],
+        stored_len: usize,
+        set_whole: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        let old = self.old_manifest()?;
+        if stored_len <= CHUNK_SIZE && !looks_like_manifest(value) {
+            set_whole()?;
+        } else {
+            let manifest = Manifest {
+                id: Manifest::new_id(),
+                count: value.len().div_ceil(CHUNK_SIZE),
+                len: value.len(),
+            };
+            for (index, chunk) in value.chunks(CHUNK_SIZE).enumerate() {
+                if let Err(err) = self.chunk(&manifest, index)?.set_secret(chunk) {
+                    self.delete_chunks(&manifest, index);
+                    return Err(err);
+                }
+            }
+            if let Err(err) = self.inner.set_secret(&manifest.to_bytes()) {
+                self.delete_chunks(&manifest, manifest.count);
+                return Err(err);
+            }
+        }
+        if let Some(old) = old {
+            self.delete_chunks(&old, old.count);
+        }
+        Ok(())
+    }
+
+    /// Read the manifest in this entry's credential, if it holds one.
+    fn read_manifest(&self) -> Result<Option<Manifest>> {
+        let secret = Zeroizing::new(self.inner.get_secret()?);
+        Ok(Manifest::parse(&secret))
+    }
+
+    /// The credential that holds the chunk with the given index.
+    fn chunk(&self, manifest: &Manifest, index: usize) -> Result<Box<Credential>> {
+        let user = match self.inner.get_specifiers() {
+            Some((_, user)) => user,
+            None => String::new(),
+        };
+        self.inner
+            .sibling(&format!("{user}.keyring-chunk-{}-{index}", manifest.id))
+    }
+
+    fn read_chunks(&self, manifest: &Manifest) -> Result<Zeroizing<Vec<u8>>> {
+        let mut secret = Zeroizing::new(Vec::with_capacity(manifest.len));
+        for index in 0..manifest.count {
+            match self.chunk(manifest, index)?.get_secret() {
+                Ok(chunk) => secret.extend_from_slice(&Zeroizing::new(chunk)),
+                Err(Error::NoEntry) => {
+                    return Err(Error::Invalid(
+                        "secret".to_string(),
+                        format!("chunk {index} of {} is missing", manifest.count),
+                    ));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if secret.len() != manifest.len {
+            return Err(Error::Invalid(
+                "secret".to_string(),
+                format!("chunks hold {} bytes, not {}", secret.len(), manifest.len),
+            ));
+        }
+        Ok(secret)
+    }
+
+    /// Delete the first `count` chunks of the given manifest.
+    ///
+    /// Failures are logged rather than returned, since by the time chunks
+    /// are deleted the entry's value has already been decided.
+    fn delete_chunks(&self, manifest: &Manifest, count: usize) {
+        for index in 0..count {
+            let result = self
+                .chunk(manifest, index)
+                .and_then(|chunk| chunk.delete_credential());
+            match result {
+                Ok(()) | Err(Error::NoEntry) => {}
+                Err(err) => warn!(
+                    "couldn't delete chunk {index} of entry {:?}: {err}",
+                    self.inner
+                ),
+            }
+        }
+    }
+}
+
+/// A reader of an entry's secret, made by [Entry::get_secret_reader].
+pub struct SecretReader {
+    entry: Entry,
+    manifest: Option<Manifest>,
+    /// The index of the next chunk to load
+    next: usize,
+    /// The number of bytes loaded from chunks so far
+    read: usize,
+    buffer: Zeroizing<Vec<u8>>,
+    pos: usize,
+}
+
+impl SecretReader {
+    /// Load the next chunk into the buffer, returning false if there are no more.
+    fn load_chunk(&mut self) -> Result<bool> {
+        let Some(manifest) = self.manifest.as_ref() else {
+            return Ok(false);
+        };
+        if self.next == manifest.count {
+            if self.read != manifest.len {
+                return Err(Error::Invalid(
+                    "secret".to_string(),
+                    format!("chunks hold {} bytes, not {}", self.read, manifest.len),
+                ));
+            }
+            return Ok(false);
+        }
+        let chunk = match self.entry.chunk(manifest, self.next)?.get_secret() {
+            Ok(chunk) => chunk,
+            Err(Error::NoEntry) => {
+                return Err(Error::Invalid(
+                    "secret".to_string(),
+                    format!("chunk {} of {} is missing", self.next, manifest.count),
+                ));
+            }
+            Err(err) => return Err(err),
+        };
+        self.next += 1;
+        self.read += chunk.len();
+        self.buffer = Zeroizing::new(chunk);
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl Read for SecretReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.buffer.len() {
+            if !self.load_chunk().map_err(std::io::Error::other)? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.buffer.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buffer[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl std::fmt::Debug for SecretReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretReader")
+            .field("entry", &self.entry)
+            .field("manifest", &self.manifest)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A writer of an entry's secret, made by [Entry::set_secret_writer].
+///
+/// Nothing is stored in the entry's credential until the writer is
+/// [finished](SecretWriter::finish); flushing it does nothing.
+pub struct SecretWriter {
+    entry: Entry,
+    old: Option<Manifest>,
+    /// The chunks stored so far, and the bytes they hold
+    manifest: Manifest,
+    buffer: Zeroizing<Vec<u8>>,
+    finished: bool,
+}
+
+impl SecretWriter {
+    /// Store what's been written as the entry's secret.
+    ///
+    /// A secret of at most [CHUNK_SIZE] bytes is stored in the entry's credential,
+    /// and a larger one (or one that starts like a manifest) is stored in chunks.
+    /// If this fails, the entry keeps its previous value.
+    pub fn finish(mut self) -> Result<()> {
+        self.finished = true;
+        if self.manifest.count == 0 && !looks_like_manifest(&self.buffer) {
+            self.entry.inner.set_secret(&self.buffer)?;
+        } else {
+            if !self.buffer.is_empty() {
+                let buffer = std::mem::take(&mut *self.buffer);
+                self.store_chunk(&Zeroizing::new(buffer))?;
+            }
+            if let Err(err) = self.entry.inner.set_secret(&self.manifest.to_bytes()) {
+                self.entry
+                    .delete_chunks(&self.manifest, self.manifest.count);
+                return Err(err);
+            }
+        }
+        if let Some(old) = self.old.as_ref() {
+            self.entry.delete_chunks(old, old.count);
+        }
+        Ok(())
+    }
+
+    /// Store the given bytes as the next chunk, deleting
+    /// the chunks stored before it if that fails.
+    fn store_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        let result = self
+            .entry
+            .chunk(&self.manifest, self.manifest.count)
+            .and_then(|credential| credential.set_secret(chunk));
+        if let Err(err) = result {
+            self.finished = true;
+            self.entry
+                .delete_chunks(&self.manifest, self.manifest.count);
+            return Err(err);
+        }
+        self.manifest.count += 1;
+        self.manifest.len += chunk.len();
+        Ok(())
+    }
+}
+
+impl Write for SecretWriter {
+    /// Buffer the bytes, storing a chunk once more than a chunk's worth is buffered.
+    ///
+    /// If storing a chunk fails, the chunks stored so far are deleted,
+    /// and the writer can't be used again.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.finished {
+            return Err(std::io::Error::other(Error::Invalid(
+                "secret writer".to_string(),
+                "failed to store a chunk".to_string(),
+            )));
+        }
+        let n = buf.len().min(CHUNK_SIZE + 1 - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        if self.buffer.len() > CHUNK_SIZE {
+            let chunk = Zeroizing::new(self.buffer.drain(..CHUNK_SIZE).collect::<Vec<u8>>());
+            self.store_chunk(&chunk).map_err(std::io::Error::other)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SecretWriter {
+    /// Delete the stored chunks of a writer that wasn't finished.
+    fn drop(&mut self) {
+        if !self.finished {
+            self.entry
+                .delete_chunks(&self.manifest, self.manifest.count);
+        }
+    }
+}
+
+impl std::fmt::Debug for SecretWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretWriter")
+            .field("entry", &self.entry)
+            .field("manifest", &self.manifest)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::{CHUNK_SIZE, Manifest};
+    use crate::{Entry, Error, mock, tests::generate_random_string};
+
+    fn entry_new(service: &str, user: &str) -> Entry {
+        let credential = mock::default_credential_builder()
+            .build(None, service, user)
+            .unwrap();
+        Entry::new_with_credential(credential)
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = Manifest {
+            id: "12-345".to_string(),
+            count: 3,
+            len: 5000,
+        };
+        assert_eq!(Manifest::parse(&manifest.to_bytes()), Some(manifest));
+        assert_eq!(Manifest::parse(b"keyring-chunked-v1:12:3"), None);
+        assert_eq!(Manifest::parse(b"my password"), None);
+        assert_eq!(Manifest::parse(&[0xff, 0xfe]), None);
+    }
+
+    #[test]
+    fn test_chunks_cleaned_up() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let secret: Vec<u8> = (0..3 * CHUNK_SIZE + 1).map(|i| i as u8).collect();
+        entry.set_secret_chunked(&secret).unwrap();
+        let manifest = Manifest::parse(&entry.get_secret().unwrap()).expect("Not chunked");
+        assert_eq!(manifest.count, 4);
+        assert_eq!(entry.get_secret_chunked().unwrap(), secret);
+        entry.set_secret_chunked(b"small").unwrap();
+        assert_eq!(entry.get_secret().unwrap(), b"small");
+        for index in 0..manifest.count {
+            let chunk = entry.chunk(&manifest, index).unwrap();
+            assert!(matches!(chunk.get_secret(), Err(Error::NoEntry)));
+        }
+        entry.delete_chunked().unwrap();
+        assert!(matches!(entry.get_secret_chunked(), Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_missing_chunk() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password_chunked(&"y".repeat(CHUNK_SIZE * 2))
+            .unwrap();
+        let manifest = Manifest::parse(&entry.get_secret().unwrap()).expect("Not chunked");
+        entry
+            .chunk(&manifest, 1)
+            .unwrap()
+            .delete_credential()
+            .unwrap();
+        assert!(matches!(
+            entry.get_password_chunked(),
+            Err(Error::Invalid(_, _))
+        ));
+        entry.delete_chunked().unwrap();
+    }
+
+    #[test]
+    fn test_chunk_boundaries() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry.set_secret_chunked(&[3; CHUNK_SIZE]).unwrap();
+        assert_eq!(entry.get_secret().unwrap(), [3; CHUNK_SIZE]);
+        entry.set_secret_chunked(&[3; CHUNK_SIZE + 1]).unwrap();
+        let manifest = Manifest::parse(&entry.get_secret().unwrap()).expect("Not chunked");
+        assert_eq!(manifest.count, 2);
+        assert_eq!(entry.get_secret_chunked().unwrap(), [3; CHUNK_SIZE + 1]);
+        // passwords are sized as UTF-16, so they fit on Windows
+        let password = "p".repeat(CHUNK_SIZE / 2);
+        entry.set_password_chunked(&password).unwrap();
+        assert_eq!(entry.get_password().unwrap(), password);
+        let password = "p".repeat(CHUNK_SIZE / 2 + 1);
+        entry.set_password_chunked(&password).unwrap();
+        let manifest = Manifest::parse(&entry.get_secret().unwrap()).expect("Not chunked");
+        assert_eq!(manifest.count, 1);
+        assert_eq!(entry.get_password_chunked().unwrap(), password);
+        entry.delete_chunked().unwrap();
+    }
+
+    #[test]
+    fn test_value_like_manifest() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let password = "keyring-chunked-v1:12-345:1:5";
+        entry.set_password_chunked(password).unwrap();
+        assert_ne!(entry.get_password().unwrap(), password);
+        assert_eq!(entry.get_password_chunked().unwrap(), password);
+        let mut writer = entry.set_secret_writer().unwrap();
+        writer.write_all(password.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        assert_ne!(entry.get_password().unwrap(), password);
+        let mut read = Vec::new();
+        entry
+            .get_secret_reader()
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, password.as_bytes());
+        entry.delete_chunked().unwrap();
+    }
+
+    #[test]
+    fn test_streaming() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let secret: Vec<u8> = (0..5 * CHUNK_SIZE + 17).map(|i| (i % 251) as u8).collect();
+        let mut writer = entry.set_secret_writer().unwrap();
+        for piece in secret.chunks(1000) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap();
+        let manifest = Manifest::parse(&entry.get_secret().unwrap()).expect("Not chunked");
+        assert_eq!(manifest.count, 6);
+        let mut read = Vec::new();
+        entry
+            .get_secret_reader()
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, secret);
+        assert_eq!(entry.get_secret_chunked().unwrap(), secret);
+        // a small secret is stored whole, and replaces the chunks
+        let mut writer = entry.set_secret_writer().unwrap();
+        writer.write_all(b"small").unwrap();
+        writer.finish().unwrap();
+        assert_eq!(entry.get_secret().unwrap(), b"small");
+        let chunk = entry.chunk(&manifest, 0).unwrap();
+        assert!(matches!(chunk.get_secret(), Err(Error::NoEntry)));
+        let mut read = Vec::new();
+        entry
+            .get_secret_reader()
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, b"small");
+        entry.delete_chunked().unwrap();
+        assert!(matches!(entry.get_secret_reader(), Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_unfinished_writer() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry.set_password("previous").unwrap();
+        let mut writer = entry.set_secret_writer().unwrap();
+        writer.write_all(&[1; 3 * CHUNK_SIZE]).unwrap();
+        let manifest = writer.manifest.clone();
+        assert_eq!(manifest.count, 2);
+        drop(writer);
+        assert_eq!(entry.get_password().unwrap(), "previous");
+        for index in 0..manifest.count {
+            let chunk = entry.chunk(&manifest, index).unwrap();
+            assert!(matches!(chunk.get_secret(), Err(Error::NoEntry)));
+        }
+        entry.delete_credential().unwrap();
+    }
+}
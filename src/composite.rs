@@ -0,0 +1,283 @@
+/*!
+
+# Falling back across credential stores
+
+Which credential store is available can depend on where a program runs:
+on a Linux desktop the secret-service is usually running, but on a
+headless server it usually isn't, and keyutils is the store to use there.
+A [CompositeCredentialBuilder] holds a chain of credential builders, in
+order of preference, and uses the first one whose store is available.
+
+Each time the composite builder is used, it
+[probes](CredentialBuilderApi::probe) the stores of its builders in order,
+and uses the first one that's available.  Once the most preferred store has
+been found available, it's used from then on without probing, so the
+entries built after that all use the same store.  But a failed probe isn't
+kept: while the most preferred store is unavailable, each use probes it
+again (and then the others), so a store that was only briefly unavailable
+(say, a secret-service that hadn't yet started when the program did) is
+used as soon as it's back.  Entries built while a less preferred store was
+in use keep using that store, and what they wrote isn't seen by entries
+built once the preferred store is back.  The composite builder's
+[backend_name](CredentialBuilderApi::backend_name) is that of the store in use.
+
+```rust
+# use keyring::{composite::CompositeCredentialBuilder, mock};
+let builder = CompositeCredentialBuilder::new(vec![mock::default_credential_builder()]);
+keyring::set_default_credential_builder(Box::new(builder));
+assert_eq!(keyring::default_backend_name(), "mock");
+```
+
+On Linux, [default_credential_builder] gives a composite builder that
+prefers the secret-service and falls back to keyutils (for whichever
+of those stores are included in the build).
+ */
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::credential::{
+    Credential, CredentialBuilder, CredentialBuilderApi, CredentialPersistence, Scope,
+};
+use super::error::{Error, Result};
+use super::logging::debug;
+
+/// A credential builder that uses the first available store in a chain.
+///
+/// See the [module docs](crate::composite) for how the store is chosen.
+pub struct CompositeCredentialBuilder {
+    builders: Vec<Box<CredentialBuilder>>,
+    chosen: OnceLock<usize>,
+}
+
+impl CompositeCredentialBuilder {
+    /// Create a composite builder from the given builders, in order of preference.
+    pub fn new(builders: Vec<Box<CredentialBuilder>>) -> Self {
+        Self {
+            builders,
+            chosen: OnceLock::new(),
+        }
+    }
+
+    /// The builder of the first available store.
+    ///
+    /// Only the choice of the most preferred store is kept, so the others are
+    /// chosen afresh each time.  If none of the stores is available,
+    /// this returns the error from the most preferred one.  If there are no builders in the chain, it returns a
+    /// [NoDefaultCredentialBuilder](Error::NoDefaultCredentialBuilder) error.
+    pub fn chosen(&self) -> Result<&CredentialBuilder> {
+        if let Some(index) = self.chosen.get() {
+            return Ok(self.builders[*index].as_ref());
+        }
+        let mut first_error = None;
+        for (index, builder) in self.builders.iter().enumerate() {
+            match builder.probe() {
+                Ok(()) => {
+                    debug!("chose the {} store", builder.backend_name());
+                    if index == 0 {
+                        self.chosen.get_or_init(|| index);
+                    }
+                    return Ok(builder.as_ref());
+                }
+                Err(err) => {
+                    debug!("the {} store is unavailable: {err}", builder.backend_name());
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_error.unwrap_or(Error::NoDefaultCredentialBuilder))
+    }
+}
+
+impl CredentialBuilderApi for CompositeCredentialBuilder {
+    /// The name of the chosen store, or `"none"` if none of the stores is available.
+    fn backend_name(&self) -> &'static str {
+        match self.chosen() {
+            Ok(builder) => builder.backend_name(),
+            Err(_) => "none",
+        }
+    }
+
+    fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
+        self.chosen()?.build(target, service, user)
+    }
+
+    fn build_in_scope(&self, scope: Scope, service: &str, user: &str) -> Result<Box<Credential>> {
+        self.chosen()?.build_in_scope(scope, service, user)
+    }
+
+    fn build_with_label(&self, label: &str, service: &str, user: &str) -> Result<Box<Credential>> {
+        self.chosen()?.build_with_label(label, service, user)
+    }
+
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        self.chosen()?.search(service)
+    }
+
+    fn count(&self, service: &str) -> Result<usize> {
+        self.chosen()?.count(service)
+    }
+
+    fn query(&self, attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        self.chosen()?.query(attributes)
+    }
+
+    /// Succeeds if any of the stores is available.
+    fn probe(&self) -> Result<()> {
+        self.chosen()?;
+        Ok(())
+    }
+
+    /// Return the underlying builder object with an `Any` type so that it can
+    /// be downgraded to a [CompositeCredentialBuilder] for platform-specific processing.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// The persistence of the chosen store's credentials.
+    ///
+    /// If none of the stores is available, this is the persistence
+    /// of the most preferred store's credentials.
+    fn persistence(&self) -> CredentialPersistence {
+        match self.chosen() {
+            Ok(builder) => builder.persistence(),
+            Err(_) => self
+                .builders
+                .first()
+                .map_or(CredentialPersistence::UntilDelete, |b| b.persistence()),
+        }
+    }
+}
+
+/// Returns a composite builder for the platform.
+///
+/// On Linux, the chain is the secret-service followed by keyutils (leaving
+/// out any store not included in the build).  On other platforms, it has
+/// only the platform's [default builder](crate::default_credential_builder).
+pub fn default_credential_builder() -> Box<CredentialBuilder> {
+    #[allow(unused_mut)]
+    let mut builders: Vec<Box<CredentialBuilder>> = Vec::new();
+    #[cfg(all(target_os = "linux", feature = "secret-service"))]
+    builders.push(crate::secret_service::default_credential_builder());
+    #[cfg(all(target_os = "linux", feature = "keyutils"))]
+    builders.push(crate::keyutils::default_credential_builder());
+    if builders.is_empty() {
+        builders.push(crate::default_credential_builder());
+    }
+    Box::new(CompositeCredentialBuilder::new(builders))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::CompositeCredentialBuilder;
+    use crate::credential::{CredentialBuilderApi, CredentialPersistence};
+    use crate::{Credential, Entry, Error, Result, mock, tests::generate_random_string};
+
+    /// A builder whose store is never available.
+    struct UnavailableBuilder;
+
+    impl CredentialBuilderApi for UnavailableBuilder {
+        fn backend_name(&self) -> &'static str {
+            "unavailable"
+        }
+
+        fn build(&self, _: Option<&str>, _: &str, _: &str) -> Result<Box<Credential>> {
+            panic!("Built a credential in an unavailable store")
+        }
+
+        fn probe(&self) -> Result<()> {
+            Err(Error::NoStorageAccess("no such store".into()))
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn persistence(&self) -> CredentialPersistence {
+            CredentialPersistence::EntryOnly
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        let builder = CompositeCredentialBuilder::new(vec![
+            Box::new(UnavailableBuilder),
+            mock::default_credential_builder(),
+        ]);
+        assert_eq!(builder.backend_name(), "mock");
+        builder.probe().expect("Composite store is unavailable");
+        let name = generate_random_string();
+        let entry = Entry::new_with_credential(builder.build(None, &name, &name).unwrap());
+        assert_eq!(entry.backend_name(), "mock");
+        entry.set_password("test fallback").unwrap();
+        let found = builder.search(&name).unwrap();
+        assert_eq!(found.len(), 1, "Didn't find the entry in the chosen store");
+        entry.delete_credential().unwrap();
+    }
+
+    /// A mock builder whose store is unavailable the first time it's probed.
+    struct LateBuilder(AtomicBool);
+
+    impl CredentialBuilderApi for LateBuilder {
+        fn backend_name(&self) -> &'static str {
+            "late"
+        }
+
+        fn build(
+            &self,
+            target: Option<&str>,
+            service: &str,
+            user: &str,
+        ) -> Result<Box<Credential>> {
+            mock::default_credential_builder().build(target, service, user)
+        }
+
+        fn probe(&self) -> Result<()> {
+            if self.0.swap(true, Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(Error::NoStorageAccess("not started yet".into()))
+            }
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_failed_probe_not_kept() {
+        let builder = CompositeCredentialBuilder::new(vec![
+            Box::new(LateBuilder(AtomicBool::new(false))),
+            mock::default_credential_builder(),
+        ]);
+        assert_eq!(builder.backend_name(), "mock");
+        assert_eq!(
+            builder.backend_name(),
+            "late",
+            "Preferred store wasn't probed again"
+        );
+        assert_eq!(builder.backend_name(), "late");
+    }
+
+    #[test]
+    fn test_nothing_available() {
+        let builder = CompositeCredentialBuilder::new(vec![Box::new(UnavailableBuilder)]);
+        assert_eq!(builder.backend_name(), "none");
+        assert!(matches!(builder.probe(), Err(Error::NoStorageAccess(_))));
+        assert!(matches!(
+            builder.build(None, "service", "user"),
+            Err(Error::NoStorageAccess(_))
+        ));
+        assert!(matches!(
+            builder.persistence(),
+            CredentialPersistence::EntryOnly
+        ));
+        let empty = CompositeCredentialBuilder::new(Vec::new());
+        assert!(matches!(
+            empty.probe(),
+            Err(Error::NoDefaultCredentialBuilder)
+        ));
+    }
+}
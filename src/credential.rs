@@ -7,13 +7,85 @@ The model comprises two traits: [CredentialBuilderApi] for the underlying store
 and [CredentialApi] for the entries in the store.  These traits must be implemented
 in a thread-safe way, a requirement captured in the [CredentialBuilder] and
 [Credential] types that wrap them.
+
+To use your own credential store with this crate, implement these
+two traits and register your builder with
+[set_default_credential_builder](crate::set_default_credential_builder).
+Here is a minimal (and not very useful) store that keeps
+each entry's secret in the entry itself:
+
+```
+use std::any::Any;
+use std::sync::Mutex;
+
+use keyring::credential::{CredentialApi, CredentialBuilderApi, CredentialPersistence};
+use keyring::{Credential, Entry, Error, Result};
+
+#[derive(Debug, Default)]
+struct MyCredential {
+    secret: Mutex<Option<Vec<u8>>>,
+}
+
+impl CredentialApi for MyCredential {
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        *self.secret.lock().unwrap() = Some(secret.to_vec());
+        Ok(())
+    }
+
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        self.secret.lock().unwrap().clone().ok_or(Error::NoEntry)
+    }
+
+    fn delete_credential(&self) -> Result<()> {
+        self.secret.lock().unwrap().take().map(|_| ()).ok_or(Error::NoEntry)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct MyCredentialBuilder;
+
+impl CredentialBuilderApi for MyCredentialBuilder {
+    fn build(&self, _: Option<&str>, _: &str, _: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(MyCredential::default()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn persistence(&self) -> CredentialPersistence {
+        CredentialPersistence::EntryOnly
+    }
+}
+
+keyring::set_default_credential_builder(Box::new(MyCredentialBuilder));
+let entry = Entry::new("my-service", "my-user")?;
+entry.set_password("my password")?;
+assert_eq!(entry.get_password()?, "my password");
+entry.delete_credential()?;
+# Ok::<(), Error>(())
+```
  */
 use std::any::Any;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::Result;
 
+/// The name of the attribute that holds a credential's expiration time,
+/// in seconds since the Unix epoch, in stores that don't support
+/// expiration natively.
+pub const EXPIRY_ATTRIBUTE: &str = "keyring-expires";
+
 /// The API that [credentials](Credential) implement.
+///
+/// This trait is object-safe, and is always used through the
+/// [Credential] trait object, so credentials from different stores
+/// can be kept together (for example, in a `Vec<Box<Credential>>`).
 pub trait CredentialApi {
     /// Set the credential's password (a string).
     ///
@@ -27,6 +99,56 @@ pub trait CredentialApi {
     /// This will persist the secret in the underlying store.
     fn set_secret(&self, password: &[u8]) -> Result<()>;
 
+    /// Set the credential's password (a string), which expires after the given time.
+    ///
+    /// Once the credential has expired, reading it returns a
+    /// [NoEntry](crate::Error::NoEntry) error.  Setting the credential's password
+    /// or secret without an expiration removes any existing expiration.
+    /// Expiration times are rounded up to the next whole second, and a zero
+    /// duration is rejected with an [Invalid](crate::Error::Invalid) error.
+    fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        self.set_secret_with_expiry(password.as_bytes(), ttl)
+    }
+
+    /// Set the credential's secret (a byte array), which expires after the given time.
+    ///
+    /// See [set_password_with_expiry](CredentialApi::set_password_with_expiry)
+    /// for details.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns a [NotSupportedByStore](crate::Error::NotSupportedByStore) error.
+    fn set_secret_with_expiry(&self, _secret: &[u8], _ttl: Duration) -> Result<()> {
+        Err(super::Error::NotSupportedByStore("expiry".to_string()))
+    }
+
+    /// Set the credential's password (a string), replacing any existing
+    /// credential so that the new one has only the attributes that the store
+    /// gives every new credential.
+    fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        self.set_secret_replacing_attributes(password.as_bytes())
+    }
+
+    /// Set the credential's secret (a byte array), replacing any existing
+    /// credential so that the new one has only the attributes that the store
+    /// gives every new credential.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// If the store has no [attributes](Capabilities::attributes), it just sets
+    /// the secret.  Otherwise it deletes any existing credential and then sets
+    /// the secret, so if setting the secret fails the old credential is gone
+    /// nonetheless.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        if self.capabilities().attributes {
+            match self.delete_credential() {
+                Ok(()) | Err(super::Error::NoEntry) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.set_secret(secret)
+    }
+
     /// Retrieve the password (a string) from the underlying credential.
     ///
     /// This has no effect on the underlying store. If there is no credential
@@ -42,6 +164,88 @@ pub trait CredentialApi {
     /// for this entry, a [NoEntry](crate::Error::NoEntry) error is returned.
     fn get_secret(&self) -> Result<Vec<u8>>;
 
+    /// Check whether the underlying credential exists, without retrieving its secret.
+    ///
+    /// An expired credential doesn't exist.  If more than one platform
+    /// credential matches this one, it exists.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It retrieves the secret (and wipes it from memory).
+    fn exists(&self) -> Result<bool> {
+        match self.get_secret() {
+            Ok(secret) => {
+                drop(zeroize::Zeroizing::new(secret));
+                Ok(true)
+            }
+            Err(super::Error::NoEntry) => Ok(false),
+            Err(super::Error::Ambiguous(_)) => Ok(true),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Update the password (a string) on the existing underlying credential.
+    ///
+    /// Unlike [set_password](CredentialApi::set_password), this never creates
+    /// a credential: if there isn't one, a [NoEntry](crate::Error::NoEntry)
+    /// error is returned.
+    ///
+    /// We provide a default implementation that checks for an existing
+    /// credential before setting its password.  Stores that can update
+    /// credentials atomically should override it.
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.get_secret()?;
+        self.set_password(password)
+    }
+
+    /// Update the secret (a byte array) on the existing underlying credential.
+    ///
+    /// Unlike [set_secret](CredentialApi::set_secret), this never creates
+    /// a credential: if there isn't one, a [NoEntry](crate::Error::NoEntry)
+    /// error is returned.
+    ///
+    /// We provide a default implementation that checks for an existing
+    /// credential before setting its secret.  Stores that can update
+    /// credentials atomically should override it.
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        self.get_secret()?;
+        self.set_secret(secret)
+    }
+
+    /// Create the underlying credential with the given password (a string).
+    ///
+    /// Unlike [set_password](CredentialApi::set_password), this never replaces
+    /// an existing credential: if there is one, an
+    /// [AlreadyExists](crate::Error::AlreadyExists) error is returned.
+    ///
+    /// We provide a default implementation that checks for an existing
+    /// credential before setting the password.  Stores that can create
+    /// credentials atomically should override it.
+    fn create_password(&self, password: &str) -> Result<()> {
+        match self.get_secret() {
+            Ok(_) => Err(super::Error::AlreadyExists),
+            Err(super::Error::NoEntry) => self.set_password(password),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Create the underlying credential with the given secret (a byte array).
+    ///
+    /// Unlike [set_secret](CredentialApi::set_secret), this never replaces
+    /// an existing credential: if there is one, an
+    /// [AlreadyExists](crate::Error::AlreadyExists) error is returned.
+    ///
+    /// We provide a default implementation that checks for an existing
+    /// credential before setting the secret.  Stores that can create
+    /// credentials atomically should override it.
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        match self.get_secret() {
+            Ok(_) => Err(super::Error::AlreadyExists),
+            Err(super::Error::NoEntry) => self.set_secret(secret),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Get the secure store attributes on this entry's credential.
     ///
     /// Each credential store may support reading and updating different
@@ -59,6 +263,21 @@ pub trait CredentialApi {
         Ok(HashMap::new())
     }
 
+    /// Retrieve both the secret and the attributes of the underlying credential.
+    ///
+    /// Stores that can read a credential's secret and attributes in a
+    /// single operation do so, which saves a round trip to the store
+    /// compared with calling [get_secret](CredentialApi::get_secret) and
+    /// [get_attributes](CredentialApi::get_attributes) separately.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It calls those two methods in turn.
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        let secret = self.get_secret()?;
+        Ok((secret, self.get_attributes()?))
+    }
+
     /// Update the secure store attributes on this entry's credential.
     ///
     /// Each credential store may support reading and updating different
@@ -77,6 +296,120 @@ pub trait CredentialApi {
         Ok(())
     }
 
+    /// Retrieve the secret of the underlying credential along with the times at which
+    /// it was created and last written (as [created](CredentialApi::created) and
+    /// [last_modified](CredentialApi::last_modified) return them), in that order.
+    ///
+    /// Stores that can read all three in a single operation do so, which
+    /// saves two round trips to the store compared with calling those methods
+    /// and [get_secret](CredentialApi::get_secret) separately.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It calls those three methods in turn.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        let secret = self.get_secret()?;
+        Ok((secret, self.created()?, self.last_modified()?))
+    }
+
+    /// Get the time at which the underlying credential was last written, if the store records it.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it
+    /// (or that don't record modification times).
+    /// It returns `None` if the credential exists.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        // this should err in the same cases as get_secret, so first call that for effect
+        self.get_secret()?;
+        Ok(None)
+    }
+
+    /// Get the time at which the underlying credential was created, if the store records it.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it
+    /// (or that don't record creation times).
+    /// It returns `None` if the credential exists.
+    fn created(&self) -> Result<Option<SystemTime>> {
+        // this should err in the same cases as get_secret, so first call that for effect
+        self.get_secret()?;
+        Ok(None)
+    }
+
+    /// Return the service and user that identify this credential, if known.
+    ///
+    /// This has no effect on the underlying store.  It's mostly useful
+    /// for credentials returned by [search](CredentialBuilderApi::search),
+    /// since their service and user come from the store.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns `None`.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        None
+    }
+
+    /// Return the target of this credential, if known.
+    ///
+    /// What a target means is store-specific (see the documentation of each store).
+    /// Like [get_specifiers](CredentialApi::get_specifiers), this has no effect
+    /// on the underlying store.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns `None`.
+    fn get_target(&self) -> Option<String> {
+        None
+    }
+
+    /// Move the underlying credential to the given service and user,
+    /// keeping its target (if any).
+    ///
+    /// The secret and attributes of the credential are kept,
+    /// and afterward there is no credential for the old service and user.
+    /// Returns a [NoEntry](crate::Error::NoEntry) error if there is no
+    /// credential to move, and an [AlreadyExists](crate::Error::AlreadyExists)
+    /// error if there is already a credential for the new service and user.
+    /// Renaming a credential to its own service and user has no effect.
+    ///
+    /// This credential still refers to the old service and user afterward.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns a [NotSupportedByStore](crate::Error::NotSupportedByStore) error.
+    fn rename(&self, _service: &str, _user: &str) -> Result<()> {
+        Err(super::Error::NotSupportedByStore("rename".to_string()))
+    }
+
+    /// Make a credential for the given user in the same store as this one,
+    /// and in the same part of it (keychain, collection, and so on),
+    /// with the same service.
+    ///
+    /// This has no effect on the underlying store.  It's used by
+    /// [can_set](crate::Entry::can_set) to try a write
+    /// without touching this credential.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns a [NotSupportedByStore](crate::Error::NotSupportedByStore) error.
+    fn sibling(&self, _user: &str) -> Result<Box<Credential>> {
+        Err(super::Error::NotSupportedByStore("sibling".to_string()))
+    }
+
+    /// If this credential wraps another (as those made by
+    /// [with_timeout](crate::Entry::with_timeout) do), make a wrapper
+    /// like this one around the credential that `f` makes from the wrapped one.
+    ///
+    /// This has no effect on the underlying store.  It's used by store-specific
+    /// options (such as [with_no_prompt](crate::Entry::with_no_prompt)) to change
+    /// the store credential of an entry without losing the wrappers around it.
+    ///
+    /// We provide a default implementation of this method for credentials
+    /// that don't wrap another.  It returns `None`.
+    fn rewrap(&self, _f: &dyn Fn(&Arc<Credential>) -> Arc<Credential>) -> Option<Box<Credential>> {
+        None
+    }
+
     /// Delete the underlying credential, if there is one.
     ///
     /// This is not idempotent if the credential existed!
@@ -92,12 +425,37 @@ pub trait CredentialApi {
     /// query its attributes in the underlying store).
     fn as_any(&self) -> &dyn Any;
 
+    /// The name of the store this credential is in, such as `"secret-service"`.
+    ///
+    /// This is read-only metadata for diagnostics; see
+    /// [Entry::backend_name](crate::Entry::backend_name) for the names
+    /// used by this crate's stores.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns `"unknown"`.
+    fn backend_name(&self) -> &'static str {
+        "unknown"
+    }
+
+    /// The optional features that this credential's store supports.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns [Capabilities::default], which claims only binary secrets.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
     /// The Debug trait call for the object.
     ///
     /// This is used to implement the Debug trait on this type; it
     /// allows generic code to provide debug printing as provided by
     /// the underlying concrete object.
     ///
+    /// Since entries are logged with this format, it should show the
+    /// fields that locate the credential, but never its secret.
+    ///
     /// We provide a (useless) default implementation for backward
     /// compatibility with existing implementors who may have not
     /// implemented the Debug trait for their credential objects
@@ -115,6 +473,62 @@ impl std::fmt::Debug for Credential {
     }
 }
 
+/// Credentials display as `service/user` when their
+/// [specifiers](CredentialApi::get_specifiers) are known,
+/// and otherwise as their [target](CredentialApi::get_target) (or `<unknown>`).
+impl std::fmt::Display for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.get_specifiers(), self.get_target()) {
+            (Some((service, user)), _) => write!(f, "{service}/{user}"),
+            (None, Some(target)) => f.write_str(&target),
+            (None, None) => f.write_str("<unknown>"),
+        }
+    }
+}
+
+/// The optional features a credential store supports, returned from
+/// a credential's [capabilities](CredentialApi::capabilities) call.
+///
+/// Stores that don't support a feature return a
+/// [NotSupportedByStore](crate::Error::NotSupportedByStore) error
+/// from its operations (or, for labels, ignore them).
+/// Stores outside this crate can start from the default, which claims
+/// only binary secrets, and set the fields for the features they support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Credentials have attributes that can be read and updated
+    /// (see [update_attributes](CredentialApi::update_attributes))
+    pub attributes: bool,
+    /// The store's credentials can be found by service
+    /// (see [search](CredentialBuilderApi::search))
+    pub enumeration: bool,
+    /// Credentials can be given an expiration time
+    /// (see [set_secret_with_expiry](CredentialApi::set_secret_with_expiry))
+    pub ttl: bool,
+    /// Secrets can hold arbitrary bytes, not just UTF-8 strings
+    pub binary: bool,
+    /// Credentials can be given a label when they're built
+    /// (see [build_with_label](CredentialBuilderApi::build_with_label))
+    pub labels: bool,
+    /// Credentials can be built in the [Machine](Scope::Machine) scope
+    /// (see [build_in_scope](CredentialBuilderApi::build_in_scope))
+    pub machine_scope: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            attributes: false,
+            enumeration: false,
+            ttl: false,
+            binary: true,
+            labels: false,
+            machine_scope: false,
+        }
+    }
+}
+
 /// A descriptor for the lifetime of stored credentials, returned from
 /// a credential store's [persistence](CredentialBuilderApi::persistence) call.
 #[non_exhaustive]
@@ -129,6 +543,33 @@ pub enum CredentialPersistence {
     UntilDelete,
 }
 
+/// Whose credentials an entry refers to, given when
+/// building a credential with [build_in_scope](CredentialBuilderApi::build_in_scope).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Scope {
+    /// Credentials belong to the current user (this is the default)
+    #[default]
+    User,
+    /// Credentials belong to the machine, for use by system services
+    /// (writing them usually requires elevated privileges)
+    Machine,
+}
+
+/// How long the credentials of an entry persist, on stores that let you
+/// choose (see [Entry::with_persistence](crate::Entry::with_persistence)).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Persistence {
+    /// Credentials vanish when the user logs off
+    Session,
+    /// Credentials persist on this machine, but don't roam with the user's profile
+    LocalMachine,
+    /// Credentials persist, and roam with the user's profile (this is the default)
+    #[default]
+    Enterprise,
+}
+
 /// The API that [credential builders](CredentialBuilder) implement.
 pub trait CredentialBuilderApi {
     /// Create a credential identified by the given target, service, and user.
@@ -137,6 +578,87 @@ pub trait CredentialBuilderApi {
     /// A credential need not be persisted until its password is set.
     fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>>;
 
+    /// Create a credential in the given scope, identified by the given service and user.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It builds credentials in the [User](Scope::User) scope with
+    /// [build](CredentialBuilderApi::build), and returns
+    /// a [NotSupportedByStore](crate::Error::NotSupportedByStore) error for any other scope.
+    fn build_in_scope(&self, scope: Scope, service: &str, user: &str) -> Result<Box<Credential>> {
+        match scope {
+            Scope::User => self.build(None, service, user),
+            Scope::Machine => Err(super::Error::NotSupportedByStore(
+                "machine scope".to_string(),
+            )),
+        }
+    }
+
+    /// Create a credential identified by the given service and user, whose
+    /// underlying credential is given the human-readable label
+    /// when it's created (so it's easy to recognize in the store's UI).
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it
+    /// (or that have no notion of a label).
+    /// It ignores the label and calls [build](CredentialBuilderApi::build).
+    fn build_with_label(&self, _label: &str, service: &str, user: &str) -> Result<Box<Credential>> {
+        self.build(None, service, user)
+    }
+
+    /// Find all the existing credentials in the store for the given service.
+    ///
+    /// The returned credentials can be used just like those returned by
+    /// [build](CredentialBuilderApi::build). If there are no matching
+    /// credentials in the store, an empty vector is returned.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns a [NotSupportedByStore](crate::Error::NotSupportedByStore) error.
+    fn search(&self, _service: &str) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NotSupportedByStore("search".to_string()))
+    }
+
+    /// Count the existing credentials in the store for the given service.
+    ///
+    /// This counts the credentials that [search](CredentialBuilderApi::search)
+    /// would find, and stores that can count them without reading each one
+    /// should do so.  Secrets are never read.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It calls [search](CredentialBuilderApi::search) and discards the results.
+    fn count(&self, service: &str) -> Result<usize> {
+        Ok(self.search(service)?.len())
+    }
+
+    /// Find all the existing credentials in the store that have all the given attributes.
+    ///
+    /// A credential matches if, for each of the given attributes, it has an
+    /// attribute of that name with the given value.  Which attributes can be
+    /// queried depends on the store; see [get_attributes](CredentialApi::get_attributes).
+    /// If there are no matching credentials in the store, an empty vector is returned.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns a [NotSupportedByStore](crate::Error::NotSupportedByStore) error.
+    fn query(&self, _attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NotSupportedByStore("query".to_string()))
+    }
+
+    /// Check that the underlying store is available, without touching any credentials.
+    ///
+    /// This does whatever lightweight operation is needed to confirm
+    /// that the store can be reached (e.g., connecting to a service), and
+    /// returns the same errors that real operations on the store would.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It assumes that the store is always available.
+    fn probe(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Return the underlying concrete object cast to [Any].
     ///
     /// Because credential builders need not have any internal structure,
@@ -152,6 +674,16 @@ pub trait CredentialBuilderApi {
     fn persistence(&self) -> CredentialPersistence {
         CredentialPersistence::UntilDelete
     }
+
+    /// The name of the store this builder builds credentials in,
+    /// such as `"secret-service"`.
+    ///
+    /// We provide a default implementation of this method
+    /// for backward compatibility with stores that don't implement it.
+    /// It returns `"unknown"`.
+    fn backend_name(&self) -> &'static str {
+        "unknown"
+    }
 }
 
 impl std::fmt::Debug for CredentialBuilder {
@@ -166,10 +698,26 @@ pub type CredentialBuilder = dyn CredentialBuilderApi + Send + Sync;
 struct NopCredentialBuilder;
 
 impl CredentialBuilderApi for NopCredentialBuilder {
+    fn backend_name(&self) -> &'static str {
+        "none"
+    }
+
     fn build(&self, _: Option<&str>, _: &str, _: &str) -> Result<Box<Credential>> {
         Err(super::Error::NoDefaultCredentialBuilder)
     }
 
+    fn search(&self, _: &str) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NoDefaultCredentialBuilder)
+    }
+
+    fn query(&self, _: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NoDefaultCredentialBuilder)
+    }
+
+    fn probe(&self) -> Result<()> {
+        Err(super::Error::NoDefaultCredentialBuilder)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -179,8 +727,97 @@ impl CredentialBuilderApi for NopCredentialBuilder {
     }
 }
 
+/// The builder used on targets that have no credential store in this crate,
+/// such as WebAssembly.
+struct UnsupportedCredentialBuilder;
+
+impl CredentialBuilderApi for UnsupportedCredentialBuilder {
+    fn backend_name(&self) -> &'static str {
+        "unsupported"
+    }
+
+    fn build(&self, _: Option<&str>, _: &str, _: &str) -> Result<Box<Credential>> {
+        Err(super::Error::NotSupportedByStore("build".to_string()))
+    }
+
+    fn search(&self, _: &str) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NotSupportedByStore("search".to_string()))
+    }
+
+    fn query(&self, _: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        Err(super::Error::NotSupportedByStore("query".to_string()))
+    }
+
+    fn probe(&self) -> Result<()> {
+        Err(super::Error::NotSupportedByStore("probe".to_string()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn persistence(&self) -> CredentialPersistence {
+        CredentialPersistence::EntryOnly
+    }
+}
+
+/// Convert a time-to-live into a whole number of seconds, rounding up.
+///
+/// Zero durations are rejected, because stores can't
+/// create a credential that has already expired, and so are
+/// durations too long to count in seconds.
+pub(crate) fn ttl_seconds(ttl: Duration) -> Result<u64> {
+    if ttl.is_zero() {
+        return Err(super::Error::Invalid(
+            "ttl".to_string(),
+            "cannot be zero".to_string(),
+        ));
+    }
+    round_up_seconds(ttl).ok_or_else(ttl_too_long)
+}
+
+/// The expiration time (in seconds since the Unix epoch) of a credential
+/// that expires after the given time-to-live.
+///
+/// Returns an [Invalid](super::Error::Invalid) error if the expiration
+/// time is too far in the future to represent.
+pub(crate) fn expiry_after(ttl: Duration) -> Result<u64> {
+    let ttl = Duration::from_secs(ttl_seconds(ttl)?);
+    let deadline = SystemTime::now()
+        .checked_add(ttl)
+        .ok_or_else(ttl_too_long)?;
+    let since_epoch = deadline.duration_since(UNIX_EPOCH).unwrap_or_default();
+    round_up_seconds(since_epoch).ok_or_else(ttl_too_long)
+}
+
+/// The whole number of seconds in the given duration, rounded up,
+/// or `None` if that overflows.
+fn round_up_seconds(duration: Duration) -> Option<u64> {
+    duration
+        .as_secs()
+        .checked_add(u64::from(duration.subsec_nanos() > 0))
+}
+
+fn ttl_too_long() -> super::Error {
+    super::Error::Invalid("ttl".to_string(), "is too long".to_string())
+}
+
+/// Whether the given expiration time (in seconds since the Unix epoch) has passed.
+pub(crate) fn has_expired(expiry: u64) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now >= Duration::from_secs(expiry)
+}
+
 // Return a credential builder that always fails. This is the builder
 // used if none of the crate-supplied keystores were included in the build.
 pub fn nop_credential_builder() -> Box<CredentialBuilder> {
     Box::new(NopCredentialBuilder)
 }
+
+// Return a credential builder whose operations aren't supported. This is the
+// builder used on targets (such as WebAssembly) that have no keystore in this crate.
+pub fn unsupported_credential_builder() -> Box<CredentialBuilder> {
+    Box::new(UnsupportedCredentialBuilder)
+}
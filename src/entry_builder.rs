@@ -0,0 +1,483 @@
+/*!
+
+# Building entries with options
+
+The `Entry::new_*` constructors each take one option (a target, a scope,
+a label, and so on).  An [EntryBuilder] collects options with chainable
+methods instead, and makes an entry with them when
+[build](EntryBuilder::build) is called:
+
+```rust
+# use keyring::{Entry, EntryBuilder, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = EntryBuilder::new().label("My App token").build("my service", "my user")?;
+entry.set_password("my password")?;
+# Ok::<(), keyring::Error>(())
+```
+
+The entry's credential is made just as it would be by the constructor
+for the option:
+
+- With no options, the entry is the same as one made by [Entry::new].
+- A [target](EntryBuilder::target), [scope](EntryBuilder::scope), or
+  [label](EntryBuilder::label) is given to the default credential builder,
+  as it is by [Entry::new_with_target], [Entry::new_in_scope], and
+  [Entry::new_with_label].  Since credential builders take only one of these,
+  at most one of them can be given.
+- Store-specific options can only be given when the default credential
+  builder is for their store.  With a macOS or iOS keychain builder,
+  an [access group](EntryBuilder::access_group), an
+  [access control](EntryBuilder::access_control), and
+  [synchronization](EntryBuilder::synchronizable) can be combined with
+  each other; with a macOS keychain builder, a [keychain](EntryBuilder::keychain)
+  path can be combined with a label; and with a secret-service builder,
+  a [collection](EntryBuilder::collection) can be combined with a label.
+  None of these can be combined with a target or scope, and none of the
+  keychain options can be combined with a collection.
+
+Options that can't be combined make [build](EntryBuilder::build) fail with
+an [Invalid](Error::Invalid) error whose attribute is the name of the option,
+and a store-specific option given with some other default credential builder
+makes it fail with a [NotSupportedByStore](Error::NotSupportedByStore) error
+that names the option.
+ */
+
+use super::credential::{Credential, CredentialBuilder, Scope};
+use super::logging::debug;
+use super::{Entry, Error, Result, namespaced_service, with_default_builder};
+
+/// A control on access to an entry's credential, which the store
+/// enforces when the credential is read.
+///
+/// Only the macOS and iOS keychains support access controls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessControl {
+    /// Require the user to be present (via Touch ID, Face ID, or their
+    /// passcode), prompting them with the given reason.
+    ///
+    /// See `IosCredential::new_with_user_presence` in the `ios` module.
+    UserPresence(String),
+}
+
+/// Options for making an entry, given with chainable methods.
+///
+/// See the [module docs](crate::entry_builder) for how the options combine.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryBuilder {
+    target: Option<String>,
+    scope: Option<Scope>,
+    label: Option<String>,
+    collection: Option<String>,
+    access_group: Option<String>,
+    access_control: Option<AccessControl>,
+    synchronizable: bool,
+    keychain: Option<std::path::PathBuf>,
+}
+
+impl EntryBuilder {
+    /// Start building an entry with no options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give the entry a target, as [Entry::new_with_target] does.
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    /// Put the entry in a scope, as [Entry::new_in_scope] does.
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Give the entry's credential a label, as [Entry::new_with_label] does.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Keep the entry's item in the secret-service collection labeled with the given name.
+    ///
+    /// The collection is never created (see `SsCredential::new_with_collection`
+    /// in the `secret_service` module).  Only the secret-service supports this.
+    pub fn collection(mut self, collection: &str) -> Self {
+        self.collection = Some(collection.to_string());
+        self
+    }
+
+    /// Keep the entry's credential in the given keychain access group,
+    /// as [Entry::new_with_access_group] does.
+    ///
+    /// Only the macOS and iOS keychains support this.
+    pub fn access_group(mut self, group: &str) -> Self {
+        self.access_group = Some(group.to_string());
+        self
+    }
+
+    /// Put the given access control on the entry's credential.
+    ///
+    /// Only the macOS and iOS keychains support this.
+    pub fn access_control(mut self, control: AccessControl) -> Self {
+        self.access_control = Some(control);
+        self
+    }
+
+    /// Require the user to be present to read the entry's secret, prompting
+    /// them with the given reason: this is the same as an
+    /// [access control](EntryBuilder::access_control) of [AccessControl::UserPresence].
+    pub fn user_presence(self, prompt: &str) -> Self {
+        self.access_control(AccessControl::UserPresence(prompt.to_string()))
+    }
+
+    /// Have iCloud Keychain synchronize the entry's credential
+    /// across the user's devices, as [Entry::new_synchronizable] does.
+    ///
+    /// Only the macOS and iOS keychains support this.
+    pub fn synchronizable(mut self) -> Self {
+        self.synchronizable = true;
+        self
+    }
+
+    /// Keep the entry's credential in the macOS keychain in the file
+    /// at the given path, as `Entry::new_with_keychain_path` does.
+    ///
+    /// Only the macOS keychain supports this.
+    pub fn keychain(mut self, path: &std::path::Path) -> Self {
+        self.keychain = Some(path.to_path_buf());
+        self
+    }
+
+    /// Make an entry for the given service and user with these options.
+    ///
+    /// Returns an [Invalid](Error::Invalid) error if options are given
+    /// that can't be combined, a [NotSupportedByStore](Error::NotSupportedByStore)
+    /// error if a store-specific option is given that the default credential
+    /// builder's store doesn't have, and otherwise any error that the constructor
+    /// for the options would return.
+    pub fn build(&self, service: &str, user: &str) -> Result<Entry> {
+        debug!("building entry with service {service}, user {user}, and options {self:?}");
+        let service = namespaced_service(service);
+        let credential =
+            with_default_builder(|builder| self.build_credential(builder, &service, user))?;
+        let entry = Entry::new_with_credential(credential);
+        debug!("built entry {:?}", entry.inner);
+        Ok(entry)
+    }
+
+    /// Build the credential with the given builder's store.
+    fn build_credential(
+        &self,
+        builder: &CredentialBuilder,
+        service: &str,
+        user: &str,
+    ) -> Result<Box<Credential>> {
+        match self.build_in_store(builder, service, user)? {
+            Some(credential) => Ok(credential),
+            None => self.build_with(builder, service, user),
+        }
+    }
+
+    /// Build the credential with the store-specific options, if any are given,
+    /// provided that the given builder is for their store.
+    #[allow(unused_variables)]
+    fn build_in_store(
+        &self,
+        builder: &CredentialBuilder,
+        service: &str,
+        user: &str,
+    ) -> Result<Option<Box<Credential>>> {
+        if let Some(option) = self.keychain_item_option() {
+            self.check_exclusive(&[
+                (option, true),
+                ("keychain", self.keychain.is_some()),
+                ("label", self.label.is_some()),
+                ("collection", self.collection.is_some()),
+            ])?;
+            self.check_no_location(option)?;
+            #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+            if super::is_keychain_builder(builder) {
+                let mut credential = match &self.access_control {
+                    Some(AccessControl::UserPresence(prompt)) => {
+                        super::ios::IosCredential::new_with_user_presence(service, user, prompt)?
+                    }
+                    None => super::ios::IosCredential::new_with_target(None, service, user)?,
+                };
+                if let Some(group) = self.access_group.as_deref() {
+                    super::ios::validate_access_group(group)?;
+                    credential.access_group = Some(group.to_string());
+                }
+                credential.synchronizable = self.synchronizable;
+                return Ok(Some(Box::new(credential)));
+            }
+            return Err(unsupported(option));
+        }
+        if let Some(path) = self.keychain.as_deref() {
+            self.check_exclusive(&[
+                ("keychain", true),
+                ("collection", self.collection.is_some()),
+            ])?;
+            self.check_no_location("keychain")?;
+            #[cfg(all(target_os = "macos", feature = "apple-native"))]
+            if builder.as_any().is::<super::macos::MacCredentialBuilder>() {
+                let mut credential =
+                    super::macos::MacCredential::new_with_keychain_path(path, service, user)?;
+                credential.label = self.label.clone();
+                return Ok(Some(Box::new(credential)));
+            }
+            return Err(unsupported("keychain"));
+        }
+        if let Some(collection) = self.collection.as_deref() {
+            self.check_no_location("collection")?;
+            #[cfg(all(
+                any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+                feature = "secret-service"
+            ))]
+            if builder
+                .as_any()
+                .is::<super::secret_service::SsCredentialBuilder>()
+            {
+                let mut credential = super::secret_service::SsCredential::new_with_collection(
+                    collection, service, user,
+                )?;
+                if let Some(label) = self.label.as_deref() {
+                    credential.label = label.to_string();
+                }
+                return Ok(Some(Box::new(credential)));
+            }
+            return Err(unsupported("collection"));
+        }
+        Ok(None)
+    }
+
+    /// Build the credential with the given builder, using
+    /// whichever of the target, scope, and label was given.
+    fn build_with(
+        &self,
+        builder: &CredentialBuilder,
+        service: &str,
+        user: &str,
+    ) -> Result<Box<Credential>> {
+        self.check_exclusive(&[
+            ("target", self.target.is_some()),
+            ("scope", self.scope.is_some()),
+            ("label", self.label.is_some()),
+        ])?;
+        if let Some(scope) = self.scope {
+            builder.build_in_scope(scope, service, user)
+        } else if let Some(label) = self.label.as_deref() {
+            builder.build_with_label(label, service, user)
+        } else {
+            builder.build(self.target.as_deref(), service, user)
+        }
+    }
+
+    /// Fail if a target or scope is given along with the named store-specific option.
+    fn check_no_location(&self, option: &str) -> Result<()> {
+        self.check_exclusive(&[
+            (option, true),
+            ("target", self.target.is_some()),
+            ("scope", self.scope.is_some()),
+        ])
+    }
+
+    /// Fail if more than one of the named options is given.
+    fn check_exclusive(&self, options: &[(&str, bool)]) -> Result<()> {
+        let mut given = options.iter().filter(|(_, given)| *given);
+        match (given.next(), given.next()) {
+            (Some((first, _)), Some((second, _))) => Err(Error::Invalid(
+                second.to_string(),
+                format!("can't be combined with a {first}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The name of the first of the keychain item options that's given, if any.
+    fn keychain_item_option(&self) -> Option<&'static str> {
+        if self.access_group.is_some() {
+            Some("access group")
+        } else if self.access_control.is_some() {
+            Some("access control")
+        } else if self.synchronizable {
+            Some("synchronizable")
+        } else {
+            None
+        }
+    }
+}
+
+/// The error for a store-specific option that the default store doesn't have.
+fn unsupported(option: &str) -> Error {
+    Error::NotSupportedByStore(option.to_string())
+}
+
+impl Entry {
+    /// Start building an entry with options; see [EntryBuilder].
+    pub fn builder() -> EntryBuilder {
+        EntryBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessControl, EntryBuilder};
+    use crate::{Entry, Error, Scope, mock, tests::generate_random_string};
+
+    #[test]
+    fn test_build_with_builder() {
+        let builder = mock::default_credential_builder();
+        let name = generate_random_string();
+        for options in [
+            EntryBuilder::new(),
+            Entry::builder().target("target"),
+            Entry::builder().scope(Scope::User),
+            Entry::builder().label("label"),
+        ] {
+            let credential = options.build_with(builder.as_ref(), &name, &name).unwrap();
+            let entry = Entry::new_with_credential(credential);
+            assert_eq!(entry.get_specifiers(), Some((name.clone(), name.clone())));
+            entry.set_password("test builder").unwrap();
+            assert_eq!(entry.get_password().unwrap(), "test builder");
+            entry.delete_credential().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_conflicting_options() {
+        let builder = mock::default_credential_builder();
+        for (options, attr) in [
+            (
+                Entry::builder().target("target").scope(Scope::User),
+                "scope",
+            ),
+            (Entry::builder().target("target").label("label"), "label"),
+            (
+                Entry::builder().scope(Scope::Machine).label("label"),
+                "label",
+            ),
+        ] {
+            assert!(
+                matches!(
+                    options.build_with(builder.as_ref(), "service", "user"),
+                    Err(Error::Invalid(invalid, _)) if invalid == attr
+                ),
+                "Accepted {options:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_store_options_need_their_store() {
+        let builder = mock::default_credential_builder();
+        for (options, option) in [
+            (Entry::builder().collection("collection"), "collection"),
+            (
+                Entry::builder().access_group("group.example"),
+                "access group",
+            ),
+            (
+                Entry::builder().access_control(AccessControl::UserPresence("prompt".into())),
+                "access control",
+            ),
+            (Entry::builder().user_presence("prompt"), "access control"),
+            (Entry::builder().synchronizable(), "synchronizable"),
+            (
+                Entry::builder().keychain(std::path::Path::new("test.keychain")),
+                "keychain",
+            ),
+        ] {
+            assert!(
+                matches!(
+                    options.build_credential(builder.as_ref(), "service", "user"),
+                    Err(Error::NotSupportedByStore(name)) if name == option
+                ),
+                "Mock store accepted {options:?}"
+            );
+        }
+        assert!(matches!(
+            Entry::builder()
+                .collection("collection")
+                .target("target")
+                .build_credential(builder.as_ref(), "service", "user"),
+            Err(Error::Invalid(invalid, _)) if invalid == "target"
+        ));
+        assert!(matches!(
+            Entry::builder()
+                .synchronizable()
+                .label("label")
+                .build_credential(builder.as_ref(), "service", "user"),
+            Err(Error::Invalid(invalid, _)) if invalid == "label"
+        ));
+        for options in [
+            Entry::builder().synchronizable(),
+            Entry::builder().keychain(std::path::Path::new("test.keychain")),
+        ] {
+            let options = options.collection("collection");
+            assert!(
+                matches!(
+                    options.build_credential(builder.as_ref(), "service", "user"),
+                    Err(Error::Invalid(invalid, _)) if invalid == "collection"
+                ),
+                "Ignored the collection of {options:?}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    ))]
+    fn test_collection() {
+        let builder = crate::secret_service::default_credential_builder();
+        let credential = Entry::builder()
+            .collection("collection")
+            .label("label")
+            .build_credential(builder.as_ref(), "service", "user")
+            .unwrap();
+        let entry = Entry::new_with_credential(credential);
+        assert_eq!(entry.collection().as_deref(), Some("collection"));
+        let credential: &crate::secret_service::SsCredential =
+            entry.get_credential().downcast_ref().unwrap();
+        assert_eq!(credential.label, "label");
+    }
+
+    #[test]
+    #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+    fn test_keychain_item_options() {
+        let builder = crate::ios::default_credential_builder();
+        let credential = Entry::builder()
+            .access_group("group.example")
+            .user_presence("prompt")
+            .synchronizable()
+            .build_credential(builder.as_ref(), "service", "user")
+            .unwrap();
+        let credential: &crate::ios::IosCredential = credential.as_any().downcast_ref().unwrap();
+        assert_eq!(credential.access_group.as_deref(), Some("group.example"));
+        assert_eq!(credential.presence_prompt.as_deref(), Some("prompt"));
+        assert!(credential.synchronizable);
+        assert!(matches!(
+            Entry::builder()
+                .access_group("not a group")
+                .build_credential(builder.as_ref(), "service", "user"),
+            Err(Error::Invalid(attr, _)) if attr == "access group"
+        ));
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "apple-native"))]
+    fn test_keychain_path() {
+        let builder = crate::macos::default_credential_builder();
+        let path = std::path::Path::new("test.keychain");
+        let credential = Entry::builder()
+            .keychain(path)
+            .label("label")
+            .build_credential(builder.as_ref(), "service", "user")
+            .unwrap();
+        let credential: &crate::macos::MacCredential = credential.as_any().downcast_ref().unwrap();
+        assert_eq!(credential.label.as_deref(), Some("label"));
+    }
+}
@@ -0,0 +1,172 @@
+/*!
+
+# Entry identities
+
+An [EntryId] holds the values that locate an entry's credential in
+the default credential store: its (optional) target and collection,
+its service, and its user.
+It never holds the credential's secret, so it's safe to save in
+configuration files or send to other processes.  Making an entry
+from an identity with [Entry::from_id] gives an entry that reads
+and writes the same credential as the entry the identity came from.
+
+On the secret-service, the target of an entry names the collection that
+holds its credential, so an identity also captures the entry's collection.
+Entries made with a collection (see `SsCredential::new_with_collection` in
+the `secret_service` module) only look in that collection, so their
+identities hold the collection instead of a target.
+
+If the `serde` feature is specified, [EntryId] implements the `serde`
+`Serialize` and `Deserialize` traits.  The serialized form is a
+struct with `target`, `collection`, `service`, and `user` fields,
+of which `target` and `collection` may be left out.
+
+```rust
+# use keyring::{Entry, EntryId, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?;
+entry.set_password("my password")?;
+let id = entry.id().unwrap();
+assert_eq!(id, EntryId::new("my service", "my user"));
+let same = Entry::from_id(&id)?;
+assert_eq!(same.get_password()?, "my password");
+# Ok::<(), keyring::Error>(())
+```
+ */
+use super::{Entry, EntryBuilder, Result};
+
+/// The identity of an entry: the values that locate its credential,
+/// but never its secret.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryId {
+    pub target: Option<String>,
+    /// The secret-service collection that holds the credential, if the
+    /// entry only looks in that collection.
+    pub collection: Option<String>,
+    pub service: String,
+    pub user: String,
+}
+
+impl EntryId {
+    /// Create an identity with the given service and user and no target.
+    pub fn new(service: &str, user: &str) -> EntryId {
+        EntryId {
+            target: None,
+            collection: None,
+            service: service.to_string(),
+            user: user.to_string(),
+        }
+    }
+
+    /// Create an identity with the given target, service, and user.
+    pub fn new_with_target(target: &str, service: &str, user: &str) -> EntryId {
+        EntryId {
+            target: Some(target.to_string()),
+            ..EntryId::new(service, user)
+        }
+    }
+
+    /// Create an identity with the given secret-service collection, service, and user.
+    pub fn new_with_collection(collection: &str, service: &str, user: &str) -> EntryId {
+        EntryId {
+            collection: Some(collection.to_string()),
+            ..EntryId::new(service, user)
+        }
+    }
+}
+
+impl Entry {
+    /// Create an entry with the given identity.
+    ///
+    /// The default credential builder is used, just as it
+    /// is by [Entry::new] and [Entry::new_with_target].
+    /// An identity with a collection is built as it is by
+    /// [EntryBuilder::collection].
+    pub fn from_id(id: &EntryId) -> Result<Entry> {
+        if let Some(collection) = id.collection.as_deref() {
+            let mut builder = EntryBuilder::new().collection(collection);
+            if let Some(target) = id.target.as_deref() {
+                builder = builder.target(target);
+            }
+            return builder.build(&id.service, &id.user);
+        }
+        match &id.target {
+            Some(target) => Entry::new_with_target(target, &id.service, &id.user),
+            None => Entry::new(&id.service, &id.user),
+        }
+    }
+
+    /// Get the identity of this entry, if its store knows the entry's
+    /// service and user (see [get_specifiers](Entry::get_specifiers)).
+    ///
+    /// Making an entry from the identity with [Entry::from_id]
+    /// gives an entry for the same credential.
+    pub fn id(&self) -> Option<EntryId> {
+        let (service, user) = self.get_specifiers()?;
+        let collection = self.collection();
+        // a collection is also its credential's target
+        let target = match collection {
+            Some(_) => None,
+            None => self.target(),
+        };
+        Some(EntryId {
+            target,
+            collection,
+            service,
+            user,
+        })
+    }
+
+    /// The secret-service collection this entry only looks in, if any.
+    pub(crate) fn collection(&self) -> Option<String> {
+        #[cfg(all(
+            any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+            feature = "secret-service"
+        ))]
+        if let Some(credential) = self
+            .inner
+            .as_any()
+            .downcast_ref::<super::secret_service::SsCredential>()
+        {
+            return credential.collection().map(str::to_string);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntryId;
+    use crate::{Entry, mock, tests::generate_random_string};
+
+    #[test]
+    fn test_id() {
+        let name = generate_random_string();
+        let builder = mock::default_credential_builder();
+        let entry = Entry::new_with_credential(builder.build(None, &name, &name).unwrap());
+        assert_eq!(entry.id(), Some(EntryId::new(&name, &name)));
+        let entry =
+            Entry::new_with_credential(builder.build(Some("target"), &name, &name).unwrap());
+        assert_eq!(
+            entry.id(),
+            Some(EntryId::new_with_target("target", &name, &name))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let ids = [
+            EntryId::new("service", "user"),
+            EntryId::new_with_target("target", "service", "user"),
+            EntryId::new_with_collection("collection", "service", "user"),
+        ];
+        for id in ids {
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(serde_json::from_str::<EntryId>(&json).unwrap(), id);
+        }
+        let id: EntryId = serde_json::from_str(r#"{"service":"service","user":"user"}"#).unwrap();
+        assert_eq!(id, EntryId::new("service", "user"));
+    }
+}
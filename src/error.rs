@@ -8,11 +8,17 @@ but the concrete objects returned must be `Send` so they can be
 moved from one thread to another. (Since most platform errors
 are integer error codes, this requirement
 is not much of a burden on the platform-specific store providers.)
+
+The errors of each platform store convert into an [Error] with `From`,
+so every store on a platform reports a given platform error the same way:
+for example, a Keychain Services `errSecItemNotFound` is always a
+[NoEntry](Error::NoEntry) error, and `errSecInteractionNotAllowed` is always a
+[Locked](Error::Locked) error.  The converted error keeps the platform
+error (when there's one to keep) so it can be examined.
  */
 
 use crate::Credential;
 
-#[derive(Debug)]
 /// Each variant of the `Error` enum provides a summary of the error.
 /// More details, if relevant, are contained in the associated value,
 /// which may be platform-specific.
@@ -28,14 +34,23 @@ pub enum Error {
     PlatformFailure(Box<dyn std::error::Error + Send + Sync>),
     /// This indicates that the underlying secure storage
     /// holding saved items could not be accessed.  Typically, this
-    /// is because of access rules in the platform; for example, it
-    /// might be that the credential store is locked.  The underlying
+    /// is because of access rules in the platform or because
+    /// the storage can't be reached.  The underlying
     /// platform error will typically give the reason.
     NoStorageAccess(Box<dyn std::error::Error + Send + Sync>),
+    /// This indicates that the underlying secure storage is locked,
+    /// and either it couldn't be unlocked without user interaction
+    /// or the user declined to unlock it.  Clients may want to ask
+    /// the user to unlock their keychain (or collection) and retry.
+    /// The underlying platform error is attached.
+    Locked(Box<dyn std::error::Error + Send + Sync>),
     /// This indicates that there is no underlying credential
     /// entry in the platform for this entry.  Either one was
     /// never set, or it was deleted.
     NoEntry,
+    /// This indicates that there is already an underlying credential
+    /// in the platform for this entry, so a new one couldn't be created.
+    AlreadyExists,
     /// This indicates that the retrieved password blob was not
     /// a UTF-8 string.  The underlying bytes are available
     /// for examination in the attached value.
@@ -45,6 +60,9 @@ pub enum Error {
     /// length limit in the underlying platform.  The
     /// attached values give the name of the attribute and
     /// the platform length limit that was exceeded.
+    /// Stores with a limit on the size of secrets (such as Windows
+    /// and keyutils) check it before writing, and report it with
+    /// an attribute name of `secret`.
     TooLong(String, u32),
     /// This indicates that one of the entry's required credential
     /// attributes was invalid.  The
@@ -57,10 +75,86 @@ pub enum Error {
     /// This indicates that there was no default credential builder to use;
     /// the client must set one before creating entries.
     NoDefaultCredentialBuilder,
+    /// This indicates that the underlying credential store doesn't
+    /// support the requested operation.  The attached value
+    /// names the operation.
+    NotSupportedByStore(String),
+    /// This indicates that an operation on an entry made with
+    /// [with_timeout](crate::Entry::with_timeout) didn't finish
+    /// within its timeout, or that the user didn't answer a secret-service
+    /// unlock prompt within `secret_service::PROMPT_TIMEOUT`.
+    /// The attached value is the timeout.
+    Timeout(std::time::Duration),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+// The bytes of a badly encoded password are left out of the debug format,
+// since they are the secret that couldn't be decoded.
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::PlatformFailure(err) => f.debug_tuple("PlatformFailure").field(err).finish(),
+            Error::NoStorageAccess(err) => f.debug_tuple("NoStorageAccess").field(err).finish(),
+            Error::Locked(err) => f.debug_tuple("Locked").field(err).finish(),
+            Error::NoEntry => f.write_str("NoEntry"),
+            Error::AlreadyExists => f.write_str("AlreadyExists"),
+            Error::BadEncoding(bytes) => f
+                .debug_tuple("BadEncoding")
+                .field(&format_args!("<redacted {} bytes>", bytes.len()))
+                .finish(),
+            Error::TooLong(name, len) => f.debug_tuple("TooLong").field(name).field(len).finish(),
+            Error::Invalid(attr, reason) => {
+                f.debug_tuple("Invalid").field(attr).field(reason).finish()
+            }
+            Error::Ambiguous(items) => f.debug_tuple("Ambiguous").field(items).finish(),
+            Error::NoDefaultCredentialBuilder => f.write_str("NoDefaultCredentialBuilder"),
+            Error::NotSupportedByStore(operation) => f
+                .debug_tuple("NotSupportedByStore")
+                .field(operation)
+                .finish(),
+            Error::Timeout(timeout) => f.debug_tuple("Timeout").field(timeout).finish(),
+        }
+    }
+}
+
+/// A platform-independent code for each kind of [Error], returned
+/// by [Error::code].
+///
+/// The numeric value of each code (given by `code as i32`) is part of this
+/// crate's stable API: it will not change across releases, and new codes
+/// will only be added with new values.  That makes these codes suitable for
+/// passing across an FFI boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [Error::PlatformFailure]
+    PlatformFailure = 1,
+    /// See [Error::NoStorageAccess]
+    NoStorageAccess = 2,
+    /// See [Error::Locked]
+    Locked = 3,
+    /// See [Error::NoEntry]
+    NoEntry = 4,
+    /// See [Error::AlreadyExists]
+    AlreadyExists = 5,
+    /// See [Error::BadEncoding]
+    BadEncoding = 6,
+    /// See [Error::TooLong]
+    TooLong = 7,
+    /// See [Error::Invalid]
+    Invalid = 8,
+    /// See [Error::Ambiguous]
+    Ambiguous = 9,
+    /// See [Error::NoDefaultCredentialBuilder]
+    NoDefaultCredentialBuilder = 10,
+    /// See [Error::NotSupportedByStore]
+    NotSupported = 11,
+    /// See [Error::Timeout]
+    Timeout = 12,
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -68,7 +162,9 @@ impl std::fmt::Display for Error {
             Error::NoStorageAccess(err) => {
                 write!(f, "Couldn't access platform secure storage: {err}")
             }
+            Error::Locked(err) => write!(f, "Platform secure storage is locked: {err}"),
             Error::NoEntry => write!(f, "No matching entry found in secure storage"),
+            Error::AlreadyExists => write!(f, "A matching entry already exists in secure storage"),
             Error::BadEncoding(_) => write!(f, "Data is not UTF-8 encoded"),
             Error::TooLong(name, len) => write!(
                 f,
@@ -90,6 +186,12 @@ impl std::fmt::Display for Error {
                     "No default credential builder is available; set one before creating entries"
                 )
             }
+            Error::NotSupportedByStore(operation) => {
+                write!(f, "The credential store does not support {operation}")
+            }
+            Error::Timeout(timeout) => {
+                write!(f, "The credential store did not respond within {timeout:?}")
+            }
         }
     }
 }
@@ -99,11 +201,284 @@ impl std::error::Error for Error {
         match self {
             Error::PlatformFailure(err) => Some(err.as_ref()),
             Error::NoStorageAccess(err) => Some(err.as_ref()),
+            Error::Locked(err) => Some(err.as_ref()),
             _ => None,
         }
     }
 }
 
+impl Error {
+    /// The platform-independent code for this error.
+    ///
+    /// Unlike the error's [Display](std::fmt::Display) string, this code
+    /// is stable across releases; see [ErrorKind] for the numeric values.
+    pub fn code(&self) -> ErrorKind {
+        match self {
+            Error::PlatformFailure(_) => ErrorKind::PlatformFailure,
+            Error::NoStorageAccess(_) => ErrorKind::NoStorageAccess,
+            Error::Locked(_) => ErrorKind::Locked,
+            Error::NoEntry => ErrorKind::NoEntry,
+            Error::AlreadyExists => ErrorKind::AlreadyExists,
+            Error::BadEncoding(_) => ErrorKind::BadEncoding,
+            Error::TooLong(_, _) => ErrorKind::TooLong,
+            Error::Invalid(_, _) => ErrorKind::Invalid,
+            Error::Ambiguous(_) => ErrorKind::Ambiguous,
+            Error::NoDefaultCredentialBuilder => ErrorKind::NoDefaultCredentialBuilder,
+            Error::NotSupportedByStore(_) => ErrorKind::NotSupported,
+            Error::Timeout(_) => ErrorKind::Timeout,
+        }
+    }
+
+    /// The platform-specific code of the failure underlying this error, if any.
+    ///
+    /// This is only available for [PlatformFailure](Error::PlatformFailure),
+    /// [NoStorageAccess](Error::NoStorageAccess), and [Locked](Error::Locked) errors. It is the
+    /// `GetLastError` code on Windows, the `OSStatus` on macOS and iOS,
+    /// and the `errno` of failures that the keyutils store doesn't otherwise
+    /// classify.  Secret-service failures are identified by name rather than
+    /// by code; see [platform_error_name](Error::platform_error_name).
+    pub fn platform_code(&self) -> Option<i64> {
+        #[allow(unused_variables)]
+        let err = self.platform_error()?;
+        #[cfg(all(target_os = "windows", feature = "windows-native"))]
+        if let Some(err) = err.downcast_ref::<crate::windows::Error>() {
+            return Some(err.0 as i64);
+        }
+        #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+        if let Some(err) = err.downcast_ref::<security_framework::base::Error>() {
+            return Some(err.code() as i64);
+        }
+        #[cfg(all(target_os = "linux", feature = "keyutils"))]
+        if let Some(linux_keyutils::KeyError::Unknown(errno)) =
+            err.downcast_ref::<linux_keyutils::KeyError>()
+        {
+            return Some(*errno as i64);
+        }
+        None
+    }
+
+    /// The operating system's description of the failure underlying this error, if any.
+    ///
+    /// This is only available for [PlatformFailure](Error::PlatformFailure),
+    /// [NoStorageAccess](Error::NoStorageAccess), and [Locked](Error::Locked) errors
+    /// on macOS and iOS, where it's the localized message that
+    /// `SecCopyErrorMessageString` gives for the error's `OSStatus`
+    /// (see [platform_code](Error::platform_code)), such as
+    /// "The specified item could not be found in the keychain."
+    pub fn platform_message(&self) -> Option<String> {
+        #[allow(unused_variables)]
+        let err = self.platform_error()?;
+        #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+        if let Some(err) = err.downcast_ref::<security_framework::base::Error>() {
+            return err.message();
+        }
+        None
+    }
+
+    /// The D-Bus name of the secret-service failure underlying this error, if any.
+    ///
+    /// This is only available for [PlatformFailure](Error::PlatformFailure),
+    /// [NoStorageAccess](Error::NoStorageAccess), and [Locked](Error::Locked) errors that came from
+    /// a D-Bus call in the secret-service store
+    /// (e.g., `org.freedesktop.DBus.Error.ServiceUnknown`).
+    pub fn platform_error_name(&self) -> Option<String> {
+        #[allow(unused_variables)]
+        let err = self.platform_error()?;
+        #[cfg(all(
+            any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+            feature = "secret-service"
+        ))]
+        if let Some(dbus_secret_service::Error::Dbus(err)) =
+            err.downcast_ref::<dbus_secret_service::Error>()
+        {
+            return err.name().map(str::to_string);
+        }
+        None
+    }
+
+    /// The platform error attached to this error, if any.
+    fn platform_error(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        match self {
+            Error::PlatformFailure(err) => Some(err.as_ref()),
+            Error::NoStorageAccess(err) => Some(err.as_ref()),
+            Error::Locked(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The error returned by [delete_service](crate::delete_service)
+/// when it can't delete one of the service's credentials.
+///
+/// The credentials found before the failing one were deleted,
+/// and those after it were left alone.
+#[derive(Debug)]
+pub struct DeleteServiceError {
+    /// How many credentials were deleted before the failure
+    pub deleted: usize,
+    /// The service and user of the credential that couldn't be deleted, if known
+    pub failed: Option<(String, String)>,
+    /// Why the credential couldn't be deleted (or the service's credentials found)
+    pub error: Error,
+}
+
+impl std::fmt::Display for DeleteServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.failed {
+            Some((service, user)) => write!(
+                f,
+                "Deleted {} credential(s), then couldn't delete the one for user '{user}' of service '{service}': {}",
+                self.deleted, self.error
+            ),
+            None => write!(
+                f,
+                "Deleted {} credential(s), then failed: {}",
+                self.deleted, self.error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeleteServiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<DeleteServiceError> for Error {
+    fn from(err: DeleteServiceError) -> Self {
+        err.error
+    }
+}
+
+//
+// Platform error conversions
+//
+// Each store maps its platform's errors to crate errors with these,
+// so that a given platform error is reported the same way by every
+// operation (and by every store on that platform).
+//
+
+/// Map a Keychain Services status to a crate error.
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+impl From<security_framework::base::Error> for Error {
+    fn from(err: security_framework::base::Error) -> Self {
+        match err.code() {
+            -61 => Error::NoStorageAccess(Box::new(err)), // errSecWrPerm
+            -25291 => Error::NoStorageAccess(Box::new(err)), // errSecNotAvailable
+            -25292 => Error::NoStorageAccess(Box::new(err)), // errSecReadOnly
+            -25293 => Error::Locked(Box::new(err)),       // errSecAuthFailed
+            -25294 => Error::NoStorageAccess(Box::new(err)), // errSecNoSuchKeychain
+            -25295 => Error::NoStorageAccess(Box::new(err)), // errSecInvalidKeychain
+            -25299 => Error::AlreadyExists,               // errSecDuplicateItem
+            -25300 => Error::NoEntry,                     // errSecItemNotFound
+            -25308 => Error::Locked(Box::new(err)),       // errSecInteractionNotAllowed
+            _ => Error::PlatformFailure(Box::new(err)),
+        }
+    }
+}
+
+/// Map a Windows error code to a crate error.
+#[cfg(all(target_os = "windows", feature = "windows-native"))]
+impl From<crate::windows::Error> for Error {
+    fn from(err: crate::windows::Error) -> Self {
+        use windows_sys::Win32::Foundation::{
+            ERROR_ACCESS_DENIED, ERROR_NO_SUCH_LOGON_SESSION, ERROR_NOT_FOUND,
+        };
+        match err.0 {
+            ERROR_NOT_FOUND => Error::NoEntry,
+            ERROR_NO_SUCH_LOGON_SESSION | ERROR_ACCESS_DENIED => {
+                Error::NoStorageAccess(Box::new(err))
+            }
+            _ => Error::PlatformFailure(Box::new(err)),
+        }
+    }
+}
+
+/// Map a secret-service error to a crate error.
+#[cfg(all(
+    any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+    feature = "secret-service"
+))]
+impl From<dbus_secret_service::Error> for Error {
+    fn from(err: dbus_secret_service::Error) -> Self {
+        match err {
+            dbus_secret_service::Error::Locked | dbus_secret_service::Error::Prompt => {
+                Error::Locked(Box::new(err))
+            }
+            dbus_secret_service::Error::NoResult => Error::NoStorageAccess(Box::new(err)),
+            _ => Error::PlatformFailure(Box::new(err)),
+        }
+    }
+}
+
+/// Map a keyutils error to a crate error.
+#[cfg(all(target_os = "linux", feature = "keyutils"))]
+impl From<linux_keyutils::KeyError> for Error {
+    fn from(err: linux_keyutils::KeyError) -> Self {
+        use linux_keyutils::KeyError;
+        match err {
+            KeyError::KeyDoesNotExist | KeyError::KeyExpired => Error::NoEntry,
+            KeyError::AccessDenied | KeyError::PermissionDenied => {
+                Error::NoStorageAccess(Box::new(err))
+            }
+            _ => Error::PlatformFailure(Box::new(err)),
+        }
+    }
+}
+
+/// The error returned by [delete_user](crate::delete_user)
+/// when it can't delete some of the user's credentials.
+///
+/// Unlike [delete_service](crate::delete_service), `delete_user` keeps going
+/// after a failure, so every credential that could be deleted was deleted.
+#[derive(Debug)]
+pub struct DeleteUserError {
+    /// How many credentials were deleted
+    pub deleted: usize,
+    /// The service and user (if known) of each credential that couldn't be
+    /// deleted, with the reason.  If the user's credentials couldn't be found,
+    /// this holds just the reason, with no service and user.
+    pub failures: Vec<(Option<(String, String)>, Error)>,
+}
+
+impl std::fmt::Display for DeleteUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Deleted {} credential(s), but {} failure(s) occurred",
+            self.deleted,
+            self.failures.len()
+        )?;
+        for (failed, error) in &self.failures {
+            match failed {
+                Some((service, user)) => {
+                    write!(f, "; user '{user}' of service '{service}': {error}")?
+                }
+                None => write!(f, "; {error}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DeleteUserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        let (_, error) = self.failures.first()?;
+        Some(error)
+    }
+}
+
+/// The first failure's error.
+impl From<DeleteUserError> for Error {
+    fn from(err: DeleteUserError) -> Self {
+        match err.failures.into_iter().next() {
+            Some((_, error)) => error,
+            None => Error::PlatformFailure("no credentials failed to delete".into()),
+        }
+    }
+}
+
 /// Try to interpret a byte vector as a password string
 pub fn decode_password(bytes: Vec<u8>) -> Result<String> {
     String::from_utf8(bytes).map_err(|err| Error::BadEncoding(err.into_bytes()))
@@ -125,4 +500,41 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_error_codes() {
+        let cases = [
+            (Error::PlatformFailure(Box::new(std::fmt::Error)), 1),
+            (Error::NoStorageAccess(Box::new(std::fmt::Error)), 2),
+            (Error::Locked(Box::new(std::fmt::Error)), 3),
+            (Error::NoEntry, 4),
+            (Error::AlreadyExists, 5),
+            (Error::BadEncoding(vec![0xff]), 6),
+            (Error::TooLong("user".to_string(), 10), 7),
+            (Error::Invalid("user".to_string(), "reason".to_string()), 8),
+            (Error::Ambiguous(vec![]), 9),
+            (Error::NoDefaultCredentialBuilder, 10),
+            (Error::NotSupportedByStore("search".to_string()), 11),
+            (Error::Timeout(std::time::Duration::from_secs(1)), 12),
+        ];
+        for (err, value) in cases {
+            assert_eq!(err.code() as i32, value, "Wrong code for {err:?}");
+        }
+    }
+
+    #[test]
+    fn test_debug_hides_bad_encoding() {
+        let err = Error::BadEncoding(b"secret\xff".to_vec());
+        assert_eq!(format!("{err:?}"), "BadEncoding(<redacted 7 bytes>)");
+        assert_eq!(format!("{:?}", Error::NoEntry), "NoEntry");
+    }
+
+    #[test]
+    fn test_no_platform_code() {
+        let err = Error::PlatformFailure(Box::new(std::fmt::Error));
+        assert_eq!(err.platform_code(), None);
+        assert_eq!(err.platform_error_name(), None);
+        assert_eq!(Error::NoEntry.platform_code(), None);
+        assert_eq!(Error::NoEntry.platform_error_name(), None);
+    }
 }
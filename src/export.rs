@@ -0,0 +1,287 @@
+/*!
+
+# Exporting and importing credentials
+
+This module lets you copy credentials from one credential store to another,
+for example when moving from the secret-service to keyutils, or when
+making a backup before reinstalling a machine.  [export] reads the
+service, user, secret, and attributes of each of a list of entries into an
+[ExportedCredential] (along with its target, if it has one), and [import]
+writes exported credentials into the store of a given credential builder.
+
+**Exported secrets are in plaintext.**  Once a credential is exported,
+its secret is no longer protected by a secure store: it's only as safe
+as the memory, file, or network connection you put it in.  That's why
+this module is only included if the `export` feature is specified.
+The secrets of exported credentials are wiped from memory when they are
+dropped, but any copies you make (such as a serialized backup file) are not.
+
+If the `serde` feature is also specified, [ExportedCredential] implements
+the `serde` `Serialize` and `Deserialize` traits.  The serialized form is a
+struct with `service`, `user`, `target` (which may be missing or null),
+`secret` (a sequence of bytes), and `attributes` (a map from names to values) fields.
+
+```rust
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?;
+entry.set_password("my password")?;
+let exported = keyring::export(&[entry.clone()])?;
+entry.delete_credential()?;
+let builder = mock::default_credential_builder();
+for result in keyring::import(builder.as_ref(), &exported) {
+    assert_eq!(result?.get_password()?, "my password");
+}
+# Ok::<(), keyring::Error>(())
+```
+ */
+use std::collections::HashMap;
+
+use super::logging::debug;
+use super::{CredentialBuilder, Entry, Error, Result, Zeroizing};
+
+/// The attributes that identify a credential rather than describe it.
+///
+/// These aren't written by [import], since the service and user
+/// of the imported credential determine them.
+const IDENTIFYING_ATTRIBUTES: &[&str] = &["target", "service", "username"];
+
+/// A credential that has been read out of its credential store.
+///
+/// The secret is wiped from memory when this is dropped.
+/// Its `Debug` output doesn't show the secret.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportedCredential {
+    pub service: String,
+    pub user: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub target: Option<String>,
+    pub secret: Zeroizing<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: HashMap<String, String>,
+}
+
+impl std::fmt::Debug for ExportedCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExportedCredential")
+            .field("service", &self.service)
+            .field("user", &self.user)
+            .field("target", &self.target)
+            .field("secret", &"<redacted>")
+            .field("attributes", &self.attributes)
+            .finish()
+    }
+}
+
+/// Read the credentials of several entries.
+///
+/// The credentials are returned in the same order as their entries.
+/// If any of the credentials can't be read, this returns the first error,
+/// so a successful export never silently leaves out a credential.
+/// Returns an [Invalid](Error::Invalid) error whose attribute is `entry`
+/// if the store doesn't know the service and user of an entry
+/// (see [Entry::get_specifiers]).
+///
+/// **The secrets in the returned credentials are in plaintext**;
+/// see the [module documentation](self) for what that means.
+pub fn export(entries: &[Entry]) -> Result<Vec<ExportedCredential>> {
+    debug!("export {} entries", entries.len());
+    super::in_batch(|| entries.iter().map(export_entry).collect())
+}
+
+fn export_entry(entry: &Entry) -> Result<ExportedCredential> {
+    let (service, user) = entry.get_specifiers().ok_or_else(|| {
+        Error::Invalid(
+            "entry".to_string(),
+            "its service and user are unknown".to_string(),
+        )
+    })?;
+    Ok(ExportedCredential {
+        service,
+        user,
+        target: entry.target(),
+        secret: Zeroizing::new(entry.get_secret()?),
+        attributes: entry.get_attributes()?,
+    })
+}
+
+/// Write exported credentials into the store of the given credential builder.
+///
+/// Each credential is built with its exported target, service, and user,
+/// and given its exported secret and then its exported
+/// attributes, except for those (such as `service` and `username`)
+/// that identify rather than describe it.  If its attributes can't be
+/// written, the credential is deleted again, so a failed import doesn't
+/// leave a credential without them.  As with
+/// [update_attributes](Entry::update_attributes), attributes
+/// that the store doesn't support are ignored.  Existing credentials
+/// with the same service and user are overwritten.  If an
+/// [app namespace](crate::set_app_namespace) is set, it's added to the services
+/// of the imported credentials, just as it's removed from those of exported ones.
+///
+/// The result of each import, which is an entry for the imported
+/// credential if it succeeds, is returned in the same order as the
+/// credentials; a failure to import one credential doesn't stop the others.
+pub fn import(builder: &CredentialBuilder, data: &[ExportedCredential]) -> Vec<Result<Entry>> {
+    debug!("import {} credentials into {builder:?}", data.len());
+    super::in_batch(|| {
+        data.iter()
+            .map(|exported| import_credential(builder, exported))
+            .collect()
+    })
+}
+
+fn import_credential(builder: &CredentialBuilder, exported: &ExportedCredential) -> Result<Entry> {
+    let service = super::namespaced_service(&exported.service);
+    let credential = builder.build(exported.target.as_deref(), &service, &exported.user)?;
+    let entry = Entry::new_with_credential(credential);
+    entry.set_secret(&exported.secret)?;
+    let attributes: HashMap<&str, &str> = exported
+        .attributes
+        .iter()
+        .filter(|(name, _)| !IDENTIFYING_ATTRIBUTES.contains(&name.as_str()))
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    if !attributes.is_empty() {
+        if let Err(err) = entry.update_attributes(&attributes) {
+            if let Err(err) = entry.delete_credential() {
+                debug!("couldn't delete partly imported credential: {err}");
+            }
+            return Err(err);
+        }
+    }
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ExportedCredential, export, import};
+    use crate::{
+        Entry, Error, Zeroizing, mock, null, tests::generate_random_string, tests::mock_entry,
+    };
+
+    #[test]
+    fn test_export_import() {
+        let builder = mock::default_credential_builder();
+        let service = generate_random_string();
+        let entries: Vec<Entry> = ["first", "second"]
+            .into_iter()
+            .map(|user| {
+                let credential = builder.build(None, &service, user).unwrap();
+                let entry = Entry::new_with_credential(credential);
+                entry.set_password(&format!("{user} password")).unwrap();
+                entry
+            })
+            .collect();
+        let exported = export(&entries).expect("Can't export entries");
+        assert_eq!(exported.len(), 2);
+        assert_eq!(exported[1].user, "second");
+        assert_eq!(exported[1].secret.as_slice(), b"second password");
+        for entry in &entries {
+            entry.delete_credential().unwrap();
+        }
+        let imported = import(builder.as_ref(), &exported);
+        for (result, user) in imported.into_iter().zip(["first", "second"]) {
+            let entry = result.expect("Can't import credential");
+            assert_eq!(entry.get_password().unwrap(), format!("{user} password"));
+            entry.delete_credential().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_export_missing_entry() {
+        assert!(matches!(export(&[mock_entry()]), Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_export_without_specifiers() {
+        let entry = Entry::new_with_credential(Box::new(null::NullCredential {}));
+        assert!(matches!(export(&[entry]), Err(Error::Invalid(attr, _)) if attr == "entry"));
+    }
+
+    #[test]
+    fn test_round_trip_keeps_target() {
+        let name = generate_random_string();
+        let builder = mock::default_credential_builder();
+        let entry =
+            Entry::new_with_credential(builder.build(Some("custom target"), &name, &name).unwrap());
+        entry.set_password("exported password").unwrap();
+        entry
+            .update_attributes(&HashMap::from([("comment", "a comment")]))
+            .unwrap();
+        let exported = export(std::slice::from_ref(&entry)).expect("Can't export");
+        assert_eq!(exported[0].target.as_deref(), Some("custom target"));
+        entry.delete_credential().unwrap();
+        let imported = import(builder.as_ref(), &exported)
+            .remove(0)
+            .expect("Can't import");
+        assert_eq!(imported, entry, "Imported under a different identity");
+        assert_eq!(imported.get_password().unwrap(), "exported password");
+        assert_eq!(
+            imported
+                .get_attributes()
+                .unwrap()
+                .get("comment")
+                .map(String::as_str),
+            Some("a comment")
+        );
+        imported.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_import_failure_is_per_credential() {
+        // the null store discards secrets, so it can't update the attributes
+        // of the first credential, but the second has none to update
+        let exported = ExportedCredential {
+            service: "service".to_string(),
+            user: "user".to_string(),
+            target: None,
+            secret: Zeroizing::new(b"secret".to_vec()),
+            attributes: HashMap::from([("comment".to_string(), "a comment".to_string())]),
+        };
+        let mut plain = exported.clone();
+        plain.attributes.clear();
+        let results = import(
+            null::default_credential_builder().as_ref(),
+            &[exported, plain],
+        );
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], Err(Error::NoEntry)));
+        assert!(results[1].is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let exported = ExportedCredential {
+            service: "service".to_string(),
+            user: "user".to_string(),
+            target: None,
+            secret: Zeroizing::new(b"secret".to_vec()),
+            attributes: HashMap::from([("label".to_string(), "label".to_string())]),
+        };
+        let json = serde_json::to_string(&exported).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ExportedCredential>(&json).unwrap(),
+            exported
+        );
+        let untargeted = r#"{"service":"service","user":"user","secret":[1]}"#;
+        let untargeted: ExportedCredential = serde_json::from_str(untargeted).unwrap();
+        assert_eq!(untargeted.target, None);
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let exported = ExportedCredential {
+            service: "service".to_string(),
+            user: "user".to_string(),
+            target: None,
+            secret: Zeroizing::new(b"top secret".to_vec()),
+            attributes: HashMap::new(),
+        };
+        assert!(!format!("{exported:?}").contains("top secret"));
+    }
+}
@@ -0,0 +1,1151 @@
+/*!
+
+# Encrypted file credential store
+
+Some applications need to behave identically everywhere, including in
+containers and CI runners that have no platform secure storage at all.
+This store keeps all its credentials in a single encrypted file at a path
+you choose, unlocked by a master passphrase you supply.  It's only
+included if the `file-backend` feature is specified, and it's never the
+default store; to use it, make this call during application startup
+_before_ creating any entries:
+```no_run
+# use keyring::file::FileCredentialBuilder;
+let builder = FileCredentialBuilder::new("/path/to/credentials.keyring", "master passphrase");
+keyring::set_default_credential_builder(Box::new(builder));
+```
+
+The credentials are identified by their target, service, and user, and
+each keeps its secret and the time it was last set.  This store doesn't
+support attributes or expiring credentials.  If the file doesn't exist,
+the store is empty; the file is created when the first credential is set.
+
+## File permissions
+
+The file is only ever written so that its owner alone can access it:
+on Unix it has mode `0600`, and on Windows its access control list is
+replaced by one that only grants access to the user who wrote it.
+The file's permissions are also checked whenever it's read, and if
+other users can read or write it (on Unix, if any of its group or other
+permission bits are set, and on Windows, if its access control list grants
+access to anyone but its owner), every operation on the store fails with a
+[NoStorageAccess](crate::Error::NoStorageAccess) error rather than use it.
+
+## Encryption
+
+The file's contents are encrypted and authenticated with XChaCha20-Poly1305.
+The key is derived from the passphrase with PBKDF2-HMAC-SHA256,
+using a random salt and (by default) 600,000 iterations; the salt and
+iteration count are kept in the file's header, which is also authenticated.
+Every write uses a fresh random nonce.  If the passphrase
+is wrong, or the file has been altered, every operation on the store fails
+with a [NoStorageAccess](crate::Error::NoStorageAccess) error.
+
+Deriving the key is deliberately slow, so each builder derives it only
+once per file.  Since the header can only be authenticated once the key is
+derived, a file whose iteration count is more than [MAX_ITERATIONS] is
+rejected without deriving it, so an altered file can't make the store hang.
+The store is only as strong as its passphrase: the file
+can be attacked offline by anyone who can read it.
+
+## Caveats
+
+Each write replaces the whole file (atomically, via a newly created
+temporary file with a unique name in the same directory).  Operations are serialized within a process, but not across
+processes: if two processes write the same file at the same time,
+one of their changes may be lost.
+ */
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilderApi, CredentialPersistence,
+};
+use super::error::{Error, Result};
+
+/// The first bytes of every credential file.
+const MAGIC: &[u8; 8] = b"KEYRINGF";
+/// The version of the file format.
+const VERSION: u8 = 1;
+/// The length of the header: the magic, version, iteration count, salt, and nonce.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_LEN;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+/// The default number of PBKDF2 iterations for new files.
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+/// The most PBKDF2 iterations a file can use.
+///
+/// Files whose header asks for more are rejected rather than read.
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+/// The error reported when a credential file can't be read.
+#[derive(Debug)]
+pub struct FileError(String);
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for FileError {}
+
+fn file_error(message: &str) -> Box<FileError> {
+    Box::new(FileError(message.to_string()))
+}
+
+/// The identifying information for a file credential
+type FileKey = (Option<String>, String, String);
+
+/// The data kept for a file credential.
+struct FileData {
+    secret: Zeroizing<Vec<u8>>,
+    /// When the secret was last set, in seconds since the Unix epoch
+    modified: u64,
+}
+
+/// The key derived from the passphrase for a file's salt and iteration count.
+struct Keys {
+    salt: [u8; SALT_LEN],
+    iterations: u32,
+    key: Zeroizing<[u8; 32]>,
+}
+
+/// The state shared by a builder and all the credentials it builds.
+struct FileStore {
+    path: PathBuf,
+    passphrase: Zeroizing<String>,
+    iterations: u32,
+    /// The keys of the file, once they have been derived.
+    ///
+    /// This lock is held for the duration of every operation on the file.
+    keys: Mutex<Option<Keys>>,
+}
+
+impl std::fmt::Debug for FileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStore")
+            .field("path", &self.path)
+            .field("iterations", &self.iterations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileStore {
+    /// Read the file, change its credentials with the given function,
+    /// and write the file back if the function says it changed them.
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut BTreeMap<FileKey, FileData>) -> Result<(T, bool)>,
+    ) -> Result<T> {
+        let mut keys = self
+            .keys
+            .lock()
+            .expect("Poisoned Mutex in keyring-rs: please report a bug!");
+        let mut credentials = self.read(&mut keys)?;
+        let (result, changed) = f(&mut credentials)?;
+        if changed {
+            self.write(&mut keys, &credentials)?;
+        }
+        Ok(result)
+    }
+
+    /// Read the credentials of the file (none if there is no file).
+    fn read(&self, keys: &mut Option<Keys>) -> Result<BTreeMap<FileKey, FileData>> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(io_error(err)),
+        };
+        check_private(&file)?;
+        let mut contents = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut contents).map_err(io_error)?;
+        if contents.len() < HEADER_LEN + TAG_LEN || !contents.starts_with(MAGIC) {
+            return Err(Error::PlatformFailure(file_error(
+                "the file is not a keyring credential file",
+            )));
+        }
+        if contents[MAGIC.len()] != VERSION {
+            return Err(Error::PlatformFailure(file_error(
+                "the file has an unknown format version",
+            )));
+        }
+        let (header, ciphertext) = contents.split_at(HEADER_LEN);
+        let mut iterations = [0u8; 4];
+        iterations.copy_from_slice(&header[MAGIC.len() + 1..MAGIC.len() + 5]);
+        let iterations = u32::from_be_bytes(iterations);
+        // the header isn't authenticated until the key is derived, so don't trust its count
+        if !(1..=MAX_ITERATIONS).contains(&iterations) {
+            return Err(Error::PlatformFailure(file_error(
+                "the file's iteration count is out of range",
+            )));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&header[MAGIC.len() + 5..MAGIC.len() + 5 + SALT_LEN]);
+        let nonce = &header[HEADER_LEN - NONCE_LEN..];
+        let keys = match keys {
+            Some(keys) if keys.salt == salt && keys.iterations == iterations => keys,
+            _ => keys.insert(derive_keys(&self.passphrase, salt, iterations)),
+        };
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        let plaintext =
+            Zeroizing::new(cipher(keys).decrypt(nonce.into(), payload).map_err(|_| {
+                Error::NoStorageAccess(file_error(
+                    "the passphrase is wrong, or the file has been altered",
+                ))
+            })?);
+        decode_credentials(&plaintext)
+            .ok_or_else(|| Error::PlatformFailure(file_error("the file is corrupt")))
+    }
+
+    /// Replace the file with one holding the given credentials.
+    fn write(
+        &self,
+        keys: &mut Option<Keys>,
+        credentials: &BTreeMap<FileKey, FileData>,
+    ) -> Result<()> {
+        let keys = match keys {
+            Some(keys) => keys,
+            None => {
+                let mut salt = [0u8; SALT_LEN];
+                random_bytes(&mut salt)?;
+                keys.insert(derive_keys(&self.passphrase, salt, self.iterations))
+            }
+        };
+        let mut nonce = [0u8; NONCE_LEN];
+        random_bytes(&mut nonce)?;
+        let mut contents = Vec::with_capacity(HEADER_LEN);
+        contents.extend_from_slice(MAGIC);
+        contents.push(VERSION);
+        contents.extend_from_slice(&keys.iterations.to_be_bytes());
+        contents.extend_from_slice(&keys.salt);
+        contents.extend_from_slice(&nonce);
+        let plaintext = encode_credentials(credentials);
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &contents,
+        };
+        let ciphertext = cipher(keys)
+            .encrypt(&nonce.into(), payload)
+            .map_err(|_| Error::PlatformFailure(file_error("can't encrypt the credentials")))?;
+        contents.extend_from_slice(&ciphertext);
+        let temp = write_private_temp(&self.path, &contents).map_err(io_error)?;
+        // if this fails, dropping the temporary file deletes it
+        temp.persist(&self.path)
+            .map_err(|err| io_error(err.error))?;
+        Ok(())
+    }
+}
+
+/// Write a new temporary file, in the same directory as the given path,
+/// that only its owner can access.
+///
+/// The file is given a unique name and created exclusively, so it can't
+/// collide with another process's temporary file or follow a link
+/// planted where it's created.
+fn write_private_temp(path: &Path, contents: &[u8]) -> std::io::Result<tempfile::NamedTempFile> {
+    use std::io::Write;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut prefix = std::ffi::OsString::from(".");
+    prefix.push(path.file_name().unwrap_or_default());
+    prefix.push(".");
+    // on Unix, temporary files are created with mode 0600
+    let mut file = tempfile::Builder::new()
+        .prefix(&prefix)
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+    #[cfg(target_os = "windows")]
+    restrict_to_owner(file.path())?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    Ok(file)
+}
+
+/// Fail if users other than the file's owner can access it.
+fn check_private(file: &fs::File) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = file.metadata().map_err(io_error)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(Error::NoStorageAccess(Box::new(FileError(format!(
+                "the file has mode {:04o}, so users other than its owner can access it; \
+                 restrict it to its owner (with `chmod 600`)",
+                mode & 0o7777
+            )))));
+        }
+    }
+    #[cfg(target_os = "windows")]
+    if !owner_only_access(file).map_err(io_error)? {
+        return Err(Error::NoStorageAccess(Box::new(FileError(
+            "the file's access control list grants access to users other than its owner; \
+             restrict it to its owner (with `icacls <file> /inheritance:r /grant:r %USERNAME%:F`)"
+                .to_string(),
+        ))));
+    }
+    #[cfg(not(any(unix, target_os = "windows")))]
+    let _ = file;
+    Ok(())
+}
+
+/// Whether the access control list of the file grants access only to its owner.
+///
+/// Entries that deny access, and those that are only inherited by
+/// a directory's children, are ignored.  Any other kind of entry
+/// that may allow access is taken as granting it.
+#[cfg(target_os = "windows")]
+fn owner_only_access(file: &fs::File) -> std::io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{ERROR_SUCCESS, LocalFree};
+    use windows_sys::Win32::Security::Authorization::{GetSecurityInfo, SE_FILE_OBJECT};
+    use windows_sys::Win32::Security::{
+        ACCESS_ALLOWED_ACE, ACE_HEADER, ACL, DACL_SECURITY_INFORMATION, EqualSid, GetAce,
+        INHERIT_ONLY_ACE, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID,
+    };
+    use windows_sys::Win32::System::SystemServices::{
+        ACCESS_ALLOWED_ACE_TYPE, ACCESS_DENIED_ACE_TYPE,
+    };
+
+    let mut owner: PSID = std::ptr::null_mut();
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+    // SAFETY: the handle is the open file's; the owner and DACL point into
+    // the descriptor, which is freed below once they've been used.
+    let result = unsafe {
+        GetSecurityInfo(
+            file.as_raw_handle(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+            &mut owner,
+            std::ptr::null_mut(),
+            &mut dacl,
+            std::ptr::null_mut(),
+            &mut descriptor,
+        )
+    };
+    if result != ERROR_SUCCESS {
+        return Err(std::io::Error::from_raw_os_error(result as i32));
+    }
+    let private = || -> std::io::Result<bool> {
+        // a missing DACL grants everyone access
+        if dacl.is_null() {
+            return Ok(false);
+        }
+        // SAFETY: the DACL is valid while the descriptor is.
+        let count = unsafe { (*dacl).AceCount };
+        for index in 0..u32::from(count) {
+            let mut ace = std::ptr::null_mut();
+            // SAFETY: the index is less than the DACL's count of entries.
+            if unsafe { GetAce(dacl, index, &mut ace) } == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // SAFETY: every entry starts with a header.
+            let header = unsafe { *ace.cast::<ACE_HEADER>() };
+            let kind = u32::from(header.AceType);
+            if u32::from(header.AceFlags) & INHERIT_ONLY_ACE != 0 || kind == ACCESS_DENIED_ACE_TYPE
+            {
+                continue;
+            }
+            if kind != ACCESS_ALLOWED_ACE_TYPE {
+                return Ok(false);
+            }
+            // SAFETY: an access-allowed entry's SID starts at its SidStart field,
+            // and the owner's SID is valid while the descriptor is.
+            let sid: PSID =
+                unsafe { std::ptr::addr_of_mut!((*ace.cast::<ACCESS_ALLOWED_ACE>()).SidStart) }
+                    .cast();
+            if unsafe { EqualSid(sid, owner) } == 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }();
+    // SAFETY: the descriptor was allocated by GetSecurityInfo and isn't used after this.
+    unsafe { LocalFree(descriptor) };
+    private
+}
+
+/// Replace the access control list of the file with one that
+/// grants access only to the user running this process.
+#[cfg(target_os = "windows")]
+fn restrict_to_owner(path: &Path) -> std::io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, GENERIC_ALL, HANDLE};
+    use windows_sys::Win32::Security::Authorization::{SE_FILE_OBJECT, SetNamedSecurityInfoW};
+    use windows_sys::Win32::Security::{
+        ACCESS_ALLOWED_ACE, ACL, ACL_REVISION, AddAccessAllowedAce, DACL_SECURITY_INFORMATION,
+        GetLengthSid, GetTokenInformation, InitializeAcl, PROTECTED_DACL_SECURITY_INFORMATION,
+        TOKEN_QUERY, TOKEN_USER, TokenUser,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let mut token: HANDLE = std::ptr::null_mut();
+    // SAFETY: the pseudo-handle of the current process is always valid.
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) } == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // the first call only gets the size of the token's user information
+    let mut len = 0;
+    // SAFETY: a null buffer of length zero asks for the needed length.
+    unsafe { GetTokenInformation(token, TokenUser, std::ptr::null_mut(), 0, &mut len) };
+    // the user information holds pointers, so its buffer is 8-byte aligned
+    let mut user = vec![0u64; (len as usize).div_ceil(8)];
+    // SAFETY: the buffer is at least the length the first call asked for.
+    let ok =
+        unsafe { GetTokenInformation(token, TokenUser, user.as_mut_ptr().cast(), len, &mut len) };
+    let err = std::io::Error::last_os_error();
+    // SAFETY: the token was opened above and isn't used after this.
+    unsafe { CloseHandle(token) };
+    if ok == 0 {
+        return Err(err);
+    }
+    // SAFETY: the buffer was filled with a TOKEN_USER, whose SID is
+    // stored in the buffer after it.
+    let sid = unsafe { (*user.as_ptr().cast::<TOKEN_USER>()).User.Sid };
+    // SAFETY: the SID is valid while the buffer is.
+    let sid_len = unsafe { GetLengthSid(sid) } as usize;
+    let acl_len = size_of::<ACL>() + size_of::<ACCESS_ALLOWED_ACE>() - size_of::<u32>() + sid_len;
+    // ACLs must be 4-byte aligned
+    let mut acl = vec![0u32; acl_len.div_ceil(4)];
+    let acl_ptr = acl.as_mut_ptr().cast::<ACL>();
+    // SAFETY: the ACL buffer is big enough for the header and one ACE with the SID.
+    let ok = unsafe {
+        InitializeAcl(acl_ptr, acl_len as u32, ACL_REVISION) != 0
+            && AddAccessAllowedAce(acl_ptr, ACL_REVISION, GENERIC_ALL, sid) != 0
+    };
+    if !ok {
+        return Err(std::io::Error::last_os_error());
+    }
+    let name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    // SAFETY: the name is null-terminated, and the ACL was built above; the
+    // protected flag stops the file inheriting entries from its directory.
+    let result = unsafe {
+        SetNamedSecurityInfoW(
+            name.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            acl_ptr,
+            std::ptr::null(),
+        )
+    };
+    if result != ERROR_SUCCESS {
+        return Err(std::io::Error::from_raw_os_error(result as i32));
+    }
+    Ok(())
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    match err.kind() {
+        std::io::ErrorKind::PermissionDenied => Error::NoStorageAccess(Box::new(err)),
+        _ => Error::PlatformFailure(Box::new(err)),
+    }
+}
+
+/// Derive the key from the passphrase.
+fn derive_keys(passphrase: &str, salt: [u8; SALT_LEN], iterations: u32) -> Keys {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, iterations, key.as_mut_slice());
+    Keys {
+        salt,
+        iterations,
+        key,
+    }
+}
+
+/// The cipher for the derived key.
+fn cipher(keys: &Keys) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(keys.key.as_slice().into())
+}
+
+/// Fill the buffer with bytes from the operating system's secure random number generator.
+fn random_bytes(buffer: &mut [u8]) -> Result<()> {
+    getrandom::fill(buffer).map_err(|err| Error::PlatformFailure(Box::new(err)))
+}
+
+/// Encode the credentials as a sequence of length-prefixed fields.
+fn encode_credentials(credentials: &BTreeMap<FileKey, FileData>) -> Zeroizing<Vec<u8>> {
+    fn push_field(buffer: &mut Vec<u8>, field: &[u8]) {
+        buffer.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        buffer.extend_from_slice(field);
+    }
+
+    let mut buffer = Zeroizing::new(Vec::new());
+    buffer.extend_from_slice(&(credentials.len() as u32).to_be_bytes());
+    for ((target, service, user), data) in credentials {
+        match target {
+            Some(target) => {
+                buffer.push(1);
+                push_field(&mut buffer, target.as_bytes());
+            }
+            None => buffer.push(0),
+        }
+        push_field(&mut buffer, service.as_bytes());
+        push_field(&mut buffer, user.as_bytes());
+        push_field(&mut buffer, &data.secret);
+        buffer.extend_from_slice(&data.modified.to_be_bytes());
+    }
+    buffer
+}
+
+/// Decode credentials encoded by [encode_credentials].
+fn decode_credentials(mut buffer: &[u8]) -> Option<BTreeMap<FileKey, FileData>> {
+    fn take<'a>(buffer: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        if buffer.len() < len {
+            return None;
+        }
+        let (taken, rest) = buffer.split_at(len);
+        *buffer = rest;
+        Some(taken)
+    }
+    fn take_u32(buffer: &mut &[u8]) -> Option<u32> {
+        Some(u32::from_be_bytes(take(buffer, 4)?.try_into().ok()?))
+    }
+    fn take_field<'a>(buffer: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let len = take_u32(buffer)? as usize;
+        take(buffer, len)
+    }
+    fn take_string(buffer: &mut &[u8]) -> Option<String> {
+        String::from_utf8(take_field(buffer)?.to_vec()).ok()
+    }
+
+    let count = take_u32(&mut buffer)?;
+    let mut credentials = BTreeMap::new();
+    for _ in 0..count {
+        let target = match take(&mut buffer, 1)? {
+            [0] => None,
+            [1] => Some(take_string(&mut buffer)?),
+            _ => return None,
+        };
+        let service = take_string(&mut buffer)?;
+        let user = take_string(&mut buffer)?;
+        let secret = Zeroizing::new(take_field(&mut buffer)?.to_vec());
+        let modified = u64::from_be_bytes(take(&mut buffer, 8)?.try_into().ok()?);
+        credentials.insert((target, service, user), FileData { secret, modified });
+    }
+    buffer.is_empty().then_some(credentials)
+}
+
+/// A credential kept in an encrypted file.
+///
+/// It's identified by its target, service, and user.
+#[derive(Debug, Clone)]
+pub struct FileCredential {
+    pub target: Option<String>,
+    pub service: String,
+    pub user: String,
+    store: Arc<FileStore>,
+}
+
+impl FileCredential {
+    fn key(&self) -> FileKey {
+        (self.target.clone(), self.service.clone(), self.user.clone())
+    }
+
+    /// The path of the file that holds this credential.
+    pub fn path(&self) -> &Path {
+        &self.store.path
+    }
+}
+
+impl CredentialApi for FileCredential {
+    /// The name of this store: `"file"`.
+    fn backend_name(&self) -> &'static str {
+        "file"
+    }
+
+    /// Set the secret of this credential, creating the file if necessary.
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        let modified = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let data = FileData {
+            secret: Zeroizing::new(secret.to_vec()),
+            modified,
+        };
+        self.store.update(|credentials| {
+            credentials.insert(self.key(), data);
+            Ok(((), true))
+        })
+    }
+
+    /// Get the secret of this credential.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if the file has no credential
+    /// for this credential's target, service, and user.
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        self.store
+            .update(|credentials| match credentials.get(&self.key()) {
+                Some(data) => Ok((data.secret.to_vec(), false)),
+                None => Err(Error::NoEntry),
+            })
+    }
+
+    /// Check whether the file has a credential for this credential's
+    /// target, service, and user.
+    fn exists(&self) -> Result<bool> {
+        self.store
+            .update(|credentials| Ok((credentials.contains_key(&self.key()), false)))
+    }
+
+    /// Get the secret of this credential and the time at which it was last set.
+    /// The file doesn't record when credentials are created.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        self.store
+            .update(|credentials| match credentials.get(&self.key()) {
+                Some(data) => {
+                    let modified = UNIX_EPOCH + Duration::from_secs(data.modified);
+                    Ok(((data.secret.to_vec(), None, Some(modified)), false))
+                }
+                None => Err(Error::NoEntry),
+            })
+    }
+
+    /// Get the time at which the secret of this credential was last set.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        self.store
+            .update(|credentials| match credentials.get(&self.key()) {
+                Some(data) => Ok((Some(UNIX_EPOCH + Duration::from_secs(data.modified)), false)),
+                None => Err(Error::NoEntry),
+            })
+    }
+
+    /// Return the service and user of this credential.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        Some((self.service.clone(), self.user.clone()))
+    }
+
+    /// Return the target of this credential, if it has one.
+    fn get_target(&self) -> Option<String> {
+        self.target.clone()
+    }
+
+    /// Move the secret of this credential to the credential
+    /// with the same target and the given service and user.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let renamed = (self.target.clone(), service.to_string(), user.to_string());
+        self.store.update(|credentials| {
+            if renamed == self.key() {
+                return match credentials.contains_key(&renamed) {
+                    true => Ok(((), false)),
+                    false => Err(Error::NoEntry),
+                };
+            }
+            if credentials.contains_key(&renamed) {
+                return Err(Error::AlreadyExists);
+            }
+            let data = credentials.remove(&self.key()).ok_or(Error::NoEntry)?;
+            credentials.insert(renamed, data);
+            Ok(((), true))
+        })
+    }
+
+    /// Make a credential for the given user in the same file,
+    /// with this credential's target and service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(Self {
+            target: self.target.clone(),
+            service: self.service.clone(),
+            user: user.to_string(),
+            store: self.store.clone(),
+        }))
+    }
+
+    /// Remove this credential from the file.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if the file has no credential
+    /// for this credential's target, service, and user.
+    fn delete_credential(&self) -> Result<()> {
+        self.store
+            .update(|credentials| match credentials.remove(&self.key()) {
+                Some(_) => Ok(((), true)),
+                None => Err(Error::NoEntry),
+            })
+    }
+
+    /// File credentials can be found by service.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: false,
+            enumeration: true,
+            ttl: false,
+            binary: true,
+            labels: false,
+            machine_scope: false,
+        }
+    }
+
+    /// Return this file credential concrete object
+    /// wrapped in the [Any](std::any::Any) trait,
+    /// so it can be downcast.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// Expose the concrete debug formatter for use via the [Credential] trait
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The builder for file credentials.
+///
+/// All the credentials built by a builder share its file, and its keys
+/// once they have been derived, so build all your entries with one builder.
+#[derive(Debug, Clone)]
+pub struct FileCredentialBuilder {
+    store: Arc<FileStore>,
+}
+
+impl FileCredentialBuilder {
+    /// Create a builder for credentials kept in the file at the given path,
+    /// which is encrypted with keys derived from the given passphrase.
+    ///
+    /// Neither the file nor the passphrase is checked until the first
+    /// credential operation.
+    pub fn new(path: impl AsRef<Path>, passphrase: &str) -> FileCredentialBuilder {
+        FileCredentialBuilder {
+            store: Arc::new(FileStore {
+                path: path.as_ref().to_path_buf(),
+                passphrase: Zeroizing::new(passphrase.to_string()),
+                iterations: DEFAULT_ITERATIONS,
+                keys: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Use the given number of PBKDF2 iterations if this builder creates the file.
+    ///
+    /// The count is limited to [MAX_ITERATIONS].
+    /// Existing files keep the iteration count they were created with.
+    /// Fewer iterations make the keys quicker to derive, but also make the
+    /// passphrase quicker to guess, so only use fewer than
+    /// [DEFAULT_ITERATIONS] in tests.
+    pub fn with_iterations(self, iterations: u32) -> FileCredentialBuilder {
+        let store = FileStore {
+            path: self.store.path.clone(),
+            passphrase: self.store.passphrase.clone(),
+            iterations: iterations.clamp(1, MAX_ITERATIONS),
+            keys: Mutex::new(None),
+        };
+        FileCredentialBuilder {
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl CredentialBuilderApi for FileCredentialBuilder {
+    /// The name of this store: `"file"`.
+    fn backend_name(&self) -> &'static str {
+        "file"
+    }
+
+    /// Build a file credential for the given target, service, and user.
+    fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(FileCredential {
+            target: target.map(str::to_string),
+            service: service.to_string(),
+            user: user.to_string(),
+            store: self.store.clone(),
+        }))
+    }
+
+    /// Find all the credentials in the file with the given service.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        self.store.update(|credentials| {
+            let found = credentials
+                .keys()
+                .filter(|(_, found_service, _)| found_service == service)
+                .map(|(target, service, user)| {
+                    Box::new(FileCredential {
+                        target: target.clone(),
+                        service: service.clone(),
+                        user: user.clone(),
+                        store: self.store.clone(),
+                    }) as Box<Credential>
+                })
+                .collect();
+            Ok((found, false))
+        })
+    }
+
+    /// Check that the file (if it exists) can be read with the passphrase.
+    fn probe(&self) -> Result<()> {
+        self.store.update(|_| Ok(((), false)))
+    }
+
+    /// Return the underlying builder object with an `Any` type so that it can
+    /// be downgraded to a [FileCredentialBuilder] for platform-specific processing.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// File credentials persist until they are deleted.
+    fn persistence(&self) -> CredentialPersistence {
+        CredentialPersistence::UntilDelete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::LazyLock;
+
+    use super::{FileCredential, FileCredentialBuilder, MAGIC, MAX_ITERATIONS};
+    use crate::credential::{CredentialBuilderApi, CredentialPersistence};
+    use crate::{Entry, Error, tests::generate_random_string};
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("keyring-test-{}.keyring", generate_random_string()))
+    }
+
+    /// The builder shared by the generic tests.
+    static BUILDER: LazyLock<FileCredentialBuilder> = LazyLock::new(|| {
+        FileCredentialBuilder::new(temp_path(), "test passphrase").with_iterations(1000)
+    });
+
+    fn entry_new(service: &str, user: &str) -> Entry {
+        Entry::new_with_credential(BUILDER.build(None, service, user).unwrap())
+    }
+
+    #[test]
+    fn test_persistence() {
+        assert!(matches!(
+            BUILDER.persistence(),
+            CredentialPersistence::UntilDelete
+        ))
+    }
+
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(BUILDER.backend_name(), "file");
+        assert_eq!(entry_new("service", "user").backend_name(), "file");
+    }
+
+    #[test]
+    fn test_entry_equality() {
+        let other = FileCredentialBuilder::new(temp_path(), "test passphrase");
+        let entry = entry_new("service", "user");
+        assert_eq!(entry, entry_new("service", "user"));
+        assert_ne!(
+            entry,
+            Entry::new_with_credential(other.build(None, "service", "user").unwrap()),
+            "Entries in different files are equal"
+        );
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        crate::tests::test_missing_entry(entry_new);
+    }
+
+    #[test]
+    fn test_empty_password() {
+        crate::tests::test_empty_password(entry_new);
+    }
+
+    #[test]
+    fn test_round_trip_ascii_password() {
+        crate::tests::test_round_trip_ascii_password(entry_new);
+    }
+
+    #[test]
+    fn test_round_trip_non_ascii_password() {
+        crate::tests::test_round_trip_non_ascii_password(entry_new);
+    }
+
+    #[test]
+    fn test_round_trip_random_secret() {
+        crate::tests::test_round_trip_random_secret(entry_new);
+    }
+
+    #[test]
+    fn test_update() {
+        crate::tests::test_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_search() {
+        let service = generate_random_string();
+        for user in ["first", "second"] {
+            entry_new(&service, user).set_password("password").unwrap();
+        }
+        let mut users: Vec<String> = BUILDER
+            .search(&service)
+            .unwrap()
+            .iter()
+            .map(|credential| credential.get_specifiers().unwrap().1)
+            .collect();
+        users.sort();
+        assert_eq!(users, ["first", "second"]);
+        for user in users {
+            entry_new(&service, &user).delete_credential().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_persists_across_builders() {
+        let path = temp_path();
+        let entry = Entry::new_with_credential(
+            FileCredentialBuilder::new(&path, "passphrase")
+                .with_iterations(1000)
+                .build(Some("target"), "service", "user")
+                .unwrap(),
+        );
+        entry.set_password("persisted password").unwrap();
+        let credential: &FileCredential = entry.get_credential().downcast_ref().unwrap();
+        assert_eq!(credential.path(), path);
+        let reopened = Entry::new_with_credential(
+            FileCredentialBuilder::new(&path, "passphrase")
+                .build(Some("target"), "service", "user")
+                .unwrap(),
+        );
+        assert_eq!(reopened.get_password().unwrap(), "persisted password");
+        let untargeted = Entry::new_with_credential(
+            FileCredentialBuilder::new(&path, "passphrase")
+                .build(None, "service", "user")
+                .unwrap(),
+        );
+        assert!(matches!(untargeted.get_password(), Err(Error::NoEntry)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "right").with_iterations(1000);
+        builder
+            .probe()
+            .expect("Missing file should be an empty store");
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        let wrong = FileCredentialBuilder::new(&path, "wrong");
+        assert!(matches!(wrong.probe(), Err(Error::NoStorageAccess(_))));
+        let entry = Entry::new_with_credential(wrong.build(None, "service", "user").unwrap());
+        assert!(matches!(
+            entry.get_password(),
+            Err(Error::NoStorageAccess(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_file() {
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "passphrase").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        let mut contents = std::fs::read(&path).unwrap();
+        let middle = contents.len() / 2;
+        contents[middle] ^= 1;
+        std::fs::write(&path, &contents).unwrap();
+        assert!(matches!(
+            entry.get_password(),
+            Err(Error::NoStorageAccess(_))
+        ));
+        std::fs::write(&path, b"not a credential file").unwrap();
+        assert!(matches!(
+            entry.get_password(),
+            Err(Error::PlatformFailure(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_iterations_out_of_range() {
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "passphrase").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        let mut contents = std::fs::read(&path).unwrap();
+        let count = MAGIC.len() + 1..MAGIC.len() + 5;
+        for iterations in [0, MAX_ITERATIONS + 1, u32::MAX] {
+            contents[count.clone()].copy_from_slice(&iterations.to_be_bytes());
+            std::fs::write(&path, &contents).unwrap();
+            // this fails at once, rather than deriving a key with the altered count
+            let fresh = FileCredentialBuilder::new(&path, "passphrase");
+            assert!(matches!(fresh.probe(), Err(Error::PlatformFailure(_))));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_no_temporary_files_left() {
+        let dir = std::env::temp_dir().join(format!("keyring-test-{}", generate_random_string()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("credentials.keyring");
+        // a file at the name a fixed temporary name would have must not be touched
+        let planted = dir.join("credentials.keyring.tmp");
+        std::fs::write(&planted, b"planted").unwrap();
+        let builder = FileCredentialBuilder::new(&path, "passphrase").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        entry.set_password("other password").unwrap();
+        let mut names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["credentials.keyring", "credentials.keyring.tmp"]);
+        assert_eq!(std::fs::read(&planted).unwrap(), b"planted");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_secret_not_in_file() {
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "unguessable").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("a very recognizable password").unwrap();
+        let contents = std::fs::read(&path).unwrap();
+        assert!(
+            !contents
+                .windows(b"recognizable".len())
+                .any(|window| window == b"recognizable")
+        );
+        assert!(!format!("{builder:?}").contains("unguessable"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_permissions() {
+        use std::fs::{Permissions, metadata, set_permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "passphrase").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        assert_eq!(
+            metadata(&path).unwrap().permissions().mode() & 0o7777,
+            0o600
+        );
+        set_permissions(&path, Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(
+            entry.get_password(),
+            Err(Error::NoStorageAccess(_))
+        ));
+        assert!(matches!(
+            entry.set_password("other password"),
+            Err(Error::NoStorageAccess(_))
+        ));
+        set_permissions(&path, Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(entry.get_password().unwrap(), "password");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_file_permissions() {
+        let path = temp_path();
+        let builder = FileCredentialBuilder::new(&path, "passphrase").with_iterations(1000);
+        let entry = Entry::new_with_credential(builder.build(None, "service", "user").unwrap());
+        entry.set_password("password").unwrap();
+        assert_eq!(entry.get_password().unwrap(), "password");
+        // a file written without restricting it inherits its directory's entries,
+        // which grant access to administrators and the system too
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        assert!(matches!(
+            entry.get_password(),
+            Err(Error::NoStorageAccess(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -16,31 +16,101 @@ Because of a quirk in the iOS keychain services API, neither the _account_
 nor the _name_ may be the empty string. (Empty strings are treated as
 wildcards when looking up credentials by attribute value.)
 
-Credentials on iOS can have a large number of _key/value_ attributes,
-but this module controls the _account_ and _name_ attributes and
-ignores all the others. so clients can't use it to access or update any attributes.
+Credentials on iOS can have a large number of _key/value_ attributes.
+This module controls the _account_ and _name_ attributes, but
+the [get_attributes](crate::Entry::get_attributes) and
+[update_attributes](crate::Entry::update_attributes) calls give access to
+the `label`, `comment`, and `description` attributes (using those strings
+as the attribute names).  Any other attribute names are ignored.
+
+## Requiring user presence
+
+Credentials made with [new_with_user_presence](IosCredential::new_with_user_presence)
+are stored with an access control that requires the user to be present
+(via Touch ID, Face ID, or their device passcode) before their secret is released,
+so reading their password will prompt the user with the reason given
+when the credential was made.  Since the access control of an existing keychain item
+can't be changed, setting the password on such a credential replaces any existing item.
+To use these credentials, wrap them in an entry with
+[Entry::new_with_credential](crate::Entry::new_with_credential).
+
+Processes that must never show a dialog can use
+[with_no_prompt](IosCredential::with_no_prompt) (or [Entry::with_no_prompt](crate::Entry::with_no_prompt)):
+the credential's reads and writes are then made with a `kSecUseAuthenticationUI`
+of `kSecUseAuthenticationUIFail`, so those that need the user to
+authenticate fail with a [Locked](crate::Error::Locked) error instead.
+
+## Access groups
+
+Apps in the same app group can share credentials by keeping them in a
+keychain _access group_ that they all have the entitlement for.  Credentials
+made with [new_with_access_group](IosCredential::new_with_access_group)
+(or with [Entry::new_with_access_group](crate::Entry::new_with_access_group))
+are created in, and searched for in, the given group.  Credentials made
+without a group are created in the app's default group, and are searched
+for in all the groups the app has access to.
+
+## iCloud Keychain
+
+Credentials made with [new_synchronizable](IosCredential::new_synchronizable)
+(or with [Entry::new_synchronizable](crate::Entry::new_synchronizable))
+are synchronized across the user's devices by iCloud Keychain.
+Synchronizable items are always in the data protection keychain, so on
+macOS they can only be used by signed apps with a keychain entitlement.
  */
 
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use core_foundation::base::{CFType, TCFType};
+use core_foundation::data::CFData;
+use core_foundation::date::CFDate;
+use core_foundation::string::{CFString, CFStringRef};
 use security_framework::base::Error;
+use security_framework::item::{
+    CloudSync, ItemAddOptions, ItemAddValue, ItemClass, ItemSearchOptions, ItemUpdateOptions,
+    ItemUpdateValue, Limit, SearchResult, update_item,
+};
 use security_framework::passwords::{
-    delete_generic_password, get_generic_password, set_generic_password,
+    AccessControlOptions, PasswordOptions, delete_generic_password_options, generic_password,
+    set_generic_password_options,
 };
 
-use super::credential::{Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi};
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi,
+};
 use super::error::{Error as ErrorCode, Result, decode_password};
+use super::prompt_hook::{PromptKind, before_prompt};
 
 /// The representation of a generic Keychain credential.
 ///
 /// The actual credentials can have lots of attributes
-/// not represented here.  There's no way to use this
-/// module to get at those attributes.
+/// not represented here.  See the module header for
+/// the ones that can be read and updated.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IosCredential {
     pub service: String,
     pub account: String,
+    /// If present, the credential requires user presence, and this is
+    /// the reason given to the user when they are prompted.
+    pub presence_prompt: Option<String>,
+    /// If present, the keychain access group (`kSecAttrAccessGroup`)
+    /// the credential is created and searched for in.
+    pub access_group: Option<String>,
+    /// Whether the credential is synchronized across the user's
+    /// devices by iCloud Keychain (`kSecAttrSynchronizable`).
+    pub synchronizable: bool,
+    /// Whether operations that need the user to authenticate fail
+    /// rather than prompt (see [with_no_prompt](IosCredential::with_no_prompt)).
+    pub no_prompt: bool,
 }
 
 impl CredentialApi for IosCredential {
+    /// The name of this store: `"ios-keychain"`.
+    fn backend_name(&self) -> &'static str {
+        "ios-keychain"
+    }
+
     /// Create and write a credential with password for this entry.
     ///
     /// The new credential replaces any existing one in the store.
@@ -57,10 +127,114 @@ impl CredentialApi for IosCredential {
     /// Since there is only one credential with a given _account_ and _user_
     /// in any given keychain, there is no chance of ambiguity.
     fn set_secret(&self, secret: &[u8]) -> Result<()> {
-        set_generic_password(&self.service, &self.account, secret).map_err(decode_error)?;
+        if self.presence_prompt.is_none() {
+            set_generic_password_options(secret, self.password_options()).map_err(decode_error)?;
+            return Ok(());
+        }
+        // the access control on an existing item can't be updated, so replace the item
+        match delete_generic_password_options(self.password_options()).map_err(decode_error) {
+            Ok(()) | Err(ErrorCode::NoEntry) => {}
+            Err(err) => return Err(err),
+        }
+        let mut options = self.password_options();
+        options.set_access_control_options(AccessControlOptions::USER_PRESENCE);
+        set_generic_password_options(secret, options).map_err(decode_error)?;
         Ok(())
     }
 
+    /// Replace the secret of the credential for this entry and empty its
+    /// label, comment, and description, or create the credential if there isn't one.
+    ///
+    /// An existing credential is replaced in place, with a single update.
+    /// A credential that [requires user presence](IosCredential::new_with_user_presence)
+    /// is always replaced by a new item, as [set_secret](CredentialApi::set_secret)
+    /// does.  A credential [without prompts](IosCredential::with_no_prompt) has its
+    /// secret set first and then its attributes emptied, since the keychain's
+    /// update call can't be told not to prompt for the secret.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        if self.presence_prompt.is_some() {
+            return self.set_secret(secret);
+        }
+        if self.no_prompt {
+            self.set_secret(secret)?;
+            let attributes = HashMap::from([("label", ""), ("comment", ""), ("description", "")]);
+            return self.update_attributes(&attributes);
+        }
+        let search = self.search_options();
+        match update_item(&search, &replace_options(secret, "")).map_err(decode_error) {
+            Err(ErrorCode::NoEntry) => self.set_secret(secret),
+            result => result,
+        }
+    }
+
+    /// Update the password on the existing credential for this entry.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.update_secret(password.as_bytes())
+    }
+
+    /// Update the secret on the existing credential for this entry.
+    ///
+    /// The credential is updated in place, so its other attributes
+    /// (including any requirement for user presence) are preserved.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    ///
+    /// A credential [without prompts](IosCredential::with_no_prompt) is checked
+    /// for and then written with options that refuse to prompt, since the
+    /// keychain's update call can't be told not to.
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        if self.no_prompt {
+            if !self.exists()? {
+                return Err(ErrorCode::NoEntry);
+            }
+            return set_generic_password_options(secret, self.password_options())
+                .map_err(decode_error);
+        }
+        let search = self.search_options();
+        let mut update = ItemUpdateOptions::new();
+        update.set_value(ItemUpdateValue::Data(CFData::from_buffer(secret)));
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Create a credential with password for this entry.
+    ///
+    /// Returns an [AlreadyExists](ErrorCode::AlreadyExists) error if there
+    /// is already a credential in the store.
+    fn create_password(&self, password: &str) -> Result<()> {
+        self.create_secret(password.as_bytes())
+    }
+
+    /// Create a credential with secret for this entry.
+    ///
+    /// Returns an [AlreadyExists](ErrorCode::AlreadyExists) error if there
+    /// is already a credential in the store.
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        if self.presence_prompt.is_some() || self.synchronizable {
+            // items that require user presence or are synchronizable can't be
+            // added atomically, so check for an existing one (without reading
+            // its secret) first
+            match self.get_attributes() {
+                Ok(_) => return Err(ErrorCode::AlreadyExists),
+                Err(ErrorCode::NoEntry) => return self.set_secret(secret),
+                Err(err) => return Err(err),
+            }
+        }
+        let mut options = ItemAddOptions::new(ItemAddValue::Data {
+            class: ItemClass::generic_password(),
+            data: CFData::from_buffer(secret),
+        });
+        options
+            .set_service(&self.service)
+            .set_account_name(&self.account);
+        if let Some(group) = self.access_group.as_deref() {
+            options.set_access_group(group);
+        }
+        options.add().map_err(decode_error)
+    }
+
     /// Look up the password for this entry, if any.
     ///
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
@@ -75,7 +249,158 @@ impl CredentialApi for IosCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_secret(&self) -> Result<Vec<u8>> {
-        get_generic_password(&self.service, &self.account).map_err(decode_error)
+        let mut options = self.password_options();
+        if let Some(prompt) = self.presence_prompt.as_deref() {
+            set_operation_prompt(&mut options, prompt);
+            if !self.no_prompt {
+                before_prompt(PromptKind::UserPresence, "ios-keychain", Some(self));
+            }
+        }
+        generic_password(options).map_err(decode_error)
+    }
+
+    /// Check whether there's a generic credential for this entry,
+    /// by searching for its attributes (but not its data).
+    ///
+    /// This never prompts for user presence.
+    fn exists(&self) -> Result<bool> {
+        let result = self
+            .search_options()
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error);
+        match result {
+            Ok(_) => Ok(true),
+            Err(ErrorCode::NoEntry) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the label, comment, and description of the credential for this entry, if any.
+    ///
+    /// Only the attributes that are present (and not empty) on the credential are returned.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        let results = self
+            .search_options()
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_attributes(&results))
+    }
+
+    /// Get the secret and the label, comment, and description of the credential
+    /// for this entry, if any.
+    ///
+    /// Both are read with a single keychain search, except for credentials that
+    /// require user presence, whose secret is read on its own (with their prompt).
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        if self.presence_prompt.is_some() {
+            let secret = self.get_secret()?;
+            return Ok((secret, self.get_attributes()?));
+        }
+        let results = self
+            .search_options()
+            .load_data(true)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok((extract_data(&results)?, extract_attributes(&results)))
+    }
+
+    /// Get the secret and the creation and modification dates of the credential
+    /// for this entry, if any.
+    ///
+    /// All three are read with a single keychain search, except for credentials that
+    /// require user presence, whose secret is read on its own (with their prompt).
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        let search = |load_data| {
+            self.search_options()
+                .load_data(load_data)
+                .load_attributes(true)
+                .limit(1)
+                .search()
+                .map_err(decode_error)
+        };
+        let (secret, results) = if self.presence_prompt.is_some() {
+            (self.get_secret()?, search(false)?)
+        } else {
+            let results = search(true)?;
+            (extract_data(&results)?, results)
+        };
+        Ok((
+            secret,
+            extract_created(&results),
+            extract_modified(&results),
+        ))
+    }
+
+    /// Get the modification date of the credential for this entry, if it exists.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        let results = self
+            .search_options()
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_modified(&results))
+    }
+
+    /// Get the creation date of the credential for this entry, if it exists.
+    fn created(&self) -> Result<Option<SystemTime>> {
+        let results = self
+            .search_options()
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_created(&results))
+    }
+
+    /// Update the label, comment, and description of the credential for this entry, if any.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        let Some(update) = update_options(attributes) else {
+            // nothing to update, but this should fail if there's no credential
+            return self.get_secret().map(|_| ());
+        };
+        let search = self.search_options();
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Return the service and account of this credential.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        Some((self.service.clone(), self.account.clone()))
+    }
+
+    /// Change the service and account of the underlying generic credential for this entry.
+    ///
+    /// The credential is changed in place (with `SecItemUpdate`), so it keeps
+    /// its secret and other attributes.  Returns an [AlreadyExists](ErrorCode::AlreadyExists)
+    /// error if there is already a credential with the new service and account.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let search = self.search_options();
+        let mut update = ItemUpdateOptions::new();
+        update.set_service(service).set_account_name(user);
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Make a credential for the given user with this credential's service.
+    ///
+    /// The sibling is in the same access group as this credential,
+    /// and is synchronizable (or doesn't prompt) if this credential is.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let mut sibling = Self::new_with_target(None, &self.service, user)?;
+        sibling.access_group = self.access_group.clone();
+        sibling.synchronizable = self.synchronizable;
+        sibling.no_prompt = self.no_prompt;
+        Ok(Box::new(sibling))
     }
 
     /// Delete the underlying generic credential for this entry, if any.
@@ -83,10 +408,22 @@ impl CredentialApi for IosCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn delete_credential(&self) -> Result<()> {
-        delete_generic_password(&self.service, &self.account).map_err(decode_error)?;
+        delete_generic_password_options(self.password_options()).map_err(decode_error)?;
         Ok(())
     }
 
+    /// Keychain items have attributes and can be found by service.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: true,
+            enumeration: true,
+            ttl: false,
+            binary: true,
+            labels: false,
+            machine_scope: false,
+        }
+    }
+
     /// Return the underlying concrete object with an `Any` type so that it can
     /// be downgraded to an [IosCredential] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -106,7 +443,7 @@ impl IosCredential {
     /// other than the ones we use to find the generic credential.
     /// But at least this checks whether the underlying credential exists.
     pub fn get_credential(&self) -> Result<Self> {
-        get_generic_password(&self.service, &self.account).map_err(decode_error)?;
+        generic_password(self.password_options()).map_err(decode_error)?;
         Ok(self.clone())
     }
 
@@ -146,8 +483,265 @@ impl IosCredential {
         Ok(Self {
             service: service.to_string(),
             account: user.to_string(),
+            presence_prompt: None,
+            access_group: None,
+            synchronizable: false,
+            no_prompt: false,
+        })
+    }
+
+    /// Create a credential representing an iOS keychain entry
+    /// that requires user presence to read.
+    ///
+    /// The prompt is the reason shown to the user when they
+    /// are asked to authenticate; it cannot be empty.
+    /// Otherwise, this behaves like [new_with_target](IosCredential::new_with_target)
+    /// with no target.
+    pub fn new_with_user_presence(service: &str, user: &str, prompt: &str) -> Result<Self> {
+        if prompt.is_empty() {
+            return Err(ErrorCode::Invalid(
+                "prompt".to_string(),
+                "cannot be empty".to_string(),
+            ));
+        }
+        let mut credential = Self::new_with_target(None, service, user)?;
+        credential.presence_prompt = Some(prompt.to_string());
+        Ok(credential)
+    }
+
+    /// Create a credential representing an iOS keychain entry
+    /// in the given keychain access group.
+    ///
+    /// The group must be in the form used by the `keychain-access-groups`
+    /// entitlement: an app ID prefix followed by a bundle ID, such as
+    /// `ABCDE12345.com.example.shared`, or an app group such as
+    /// `group.com.example.shared`.  The app must have the entitlement
+    /// for its operations on the credential to succeed.
+    /// Otherwise, this behaves like [new_with_target](IosCredential::new_with_target)
+    /// with no target.
+    pub fn new_with_access_group(group: &str, service: &str, user: &str) -> Result<Self> {
+        validate_access_group(group)?;
+        let mut credential = Self::new_with_target(None, service, user)?;
+        credential.access_group = Some(group.to_string());
+        Ok(credential)
+    }
+
+    /// Create a credential representing an iOS keychain entry that
+    /// iCloud Keychain synchronizes across the user's devices.
+    ///
+    /// The item is written with a `kSecAttrSynchronizable` of true.
+    /// The keychain keeps synchronizable and local items apart, and searches
+    /// that don't mention synchronization only find local ones, so this
+    /// credential's reads, updates, and deletes ask for the synchronizable
+    /// item too.  An existing local item for the same service and user
+    /// is left alone: it's neither read nor changed.
+    /// Otherwise, this behaves like [new_with_target](IosCredential::new_with_target)
+    /// with no target.
+    pub fn new_synchronizable(service: &str, user: &str) -> Result<Self> {
+        let mut credential = Self::new_with_target(None, service, user)?;
+        credential.synchronizable = true;
+        Ok(credential)
+    }
+
+    /// Choose that this credential's operations fail, rather than
+    /// asking the user, if they require the user to authenticate.
+    ///
+    /// Reads and writes are made with a `kSecUseAuthenticationUI` of
+    /// `kSecUseAuthenticationUIFail`, so where the keychain would ask for
+    /// the user's presence the operation fails with a [Locked](ErrorCode::Locked)
+    /// error instead.  This applies to credentials that
+    /// [require user presence](IosCredential::new_with_user_presence) as well.
+    pub fn with_no_prompt(mut self) -> Self {
+        self.no_prompt = true;
+        self
+    }
+
+    /// The password options that identify (and write) this credential's item.
+    ///
+    /// For a synchronizable credential, these only match the synchronizable item.
+    fn password_options(&self) -> PasswordOptions {
+        let mut options = PasswordOptions::new_generic_password(&self.service, &self.account);
+        if let Some(group) = self.access_group.as_deref() {
+            options.set_access_group(group);
+        }
+        if self.synchronizable {
+            options.set_access_synchronized(Some(true));
+        }
+        if self.no_prompt {
+            set_no_prompt(&mut options);
+        }
+        options
+    }
+
+    /// The search options that identify this credential.
+    ///
+    /// For a synchronizable credential, these only match the synchronizable item.
+    fn search_options(&self) -> ItemSearchOptions {
+        let mut options = ItemSearchOptions::new();
+        options
+            .class(ItemClass::generic_password())
+            .service(&self.service)
+            .account(&self.account);
+        if let Some(group) = self.access_group.as_deref() {
+            options.access_group(group);
+        }
+        if self.synchronizable {
+            options.cloud_sync(CloudSync::MatchSyncYes);
+        }
+        options
+    }
+}
+
+/// Check that a keychain access group is in entitlement form:
+/// at least two dot-separated components, each made of
+/// ASCII letters, digits, hyphens, and underscores.
+pub(crate) fn validate_access_group(group: &str) -> Result<()> {
+    let valid = group.split('.').count() >= 2
+        && group.split('.').all(|component| {
+            !component.is_empty()
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(ErrorCode::Invalid(
+            "access group".to_string(),
+            format!("'{group}' is not of the form TEAMID.bundle.id or group.bundle.id"),
+        ))
+    }
+}
+
+#[link(name = "Security", kind = "framework")]
+unsafe extern "C" {
+    static kSecUseOperationPrompt: CFStringRef;
+    static kSecUseAuthenticationUI: CFStringRef;
+    static kSecUseAuthenticationUIFail: CFStringRef;
+    static kSecValueData: CFStringRef;
+}
+
+/// Make the operation with the given options fail, rather than prompt the user,
+/// if it needs their authorization.
+pub(crate) fn set_no_prompt(options: &mut PasswordOptions) {
+    let (key, value) = unsafe {
+        (
+            CFString::wrap_under_get_rule(kSecUseAuthenticationUI),
+            CFString::wrap_under_get_rule(kSecUseAuthenticationUIFail),
+        )
+    };
+    #[allow(deprecated)]
+    options.query.push((key, value.into_CFType()));
+}
+
+/// Add the reason to show the user, if they are prompted, to the given options.
+pub(crate) fn set_operation_prompt(options: &mut PasswordOptions, prompt: &str) {
+    let key = unsafe { CFString::wrap_under_get_rule(kSecUseOperationPrompt) };
+    #[allow(deprecated)]
+    options
+        .query
+        .push((key, CFString::from(prompt).into_CFType()));
+}
+
+/// The attributes that can be read and updated, each paired with
+/// the key used for it in the dictionary of a keychain search result.
+const ATTRIBUTE_KEYS: [(&str, &str); 3] = [
+    ("label", "labl"),
+    ("comment", "icmt"),
+    ("description", "desc"),
+];
+
+/// Extract the data (that is, the secret) from the first of the given search results.
+pub(crate) fn extract_data(results: &[SearchResult]) -> Result<Vec<u8>> {
+    let key = unsafe { CFString::wrap_under_get_rule(kSecValueData) };
+    let data = match results.first() {
+        Some(SearchResult::Dict(dict)) => dict
+            .find(key.as_concrete_TypeRef().cast())
+            .and_then(|value| unsafe { CFType::wrap_under_get_rule(*value) }.downcast::<CFData>()),
+        _ => None,
+    };
+    match data {
+        Some(data) => Ok(data.bytes().to_vec()),
+        None => Err(ErrorCode::PlatformFailure(
+            "the keychain search returned no data".into(),
+        )),
+    }
+}
+
+/// Extract the readable attributes from the first of the given search results.
+///
+/// Empty attributes are left out, since an attribute can't be removed
+/// from an item, only [emptied](replace_options).
+pub(crate) fn extract_attributes(results: &[SearchResult]) -> HashMap<String, String> {
+    let mut dict = results
+        .first()
+        .and_then(|result| result.simplify_dict())
+        .unwrap_or_default();
+    ATTRIBUTE_KEYS
+        .iter()
+        .filter_map(|(name, key)| {
+            let value = dict.remove(*key).filter(|value| !value.is_empty())?;
+            Some((name.to_string(), value))
         })
+        .collect()
+}
+
+/// Extract the modification date from the first of the given search results.
+pub(crate) fn extract_modified(results: &[SearchResult]) -> Option<SystemTime> {
+    extract_date(results, "mdat") // kSecAttrModificationDate
+}
+
+/// Extract the creation date from the first of the given search results.
+pub(crate) fn extract_created(results: &[SearchResult]) -> Option<SystemTime> {
+    extract_date(results, "cdat") // kSecAttrCreationDate
+}
+
+/// Extract the date attribute with the given key from the first of the given search results.
+fn extract_date(results: &[SearchResult], key: &'static str) -> Option<SystemTime> {
+    // Core Foundation times are seconds since 2001-01-01, which is this Unix time
+    const CF_EPOCH_AS_UNIX_TIME: f64 = 978_307_200.0;
+    let Some(SearchResult::Dict(dict)) = results.first() else {
+        return None;
+    };
+    let key = CFString::from_static_string(key);
+    let value = dict.find(key.as_CFTypeRef())?;
+    let date = unsafe { CFType::wrap_under_get_rule(*value) }.downcast::<CFDate>()?;
+    let seconds = date.abs_time() + CF_EPOCH_AS_UNIX_TIME;
+    (seconds >= 0.0).then(|| UNIX_EPOCH + Duration::from_secs_f64(seconds))
+}
+
+/// Build the options for updating the updatable attributes among those given.
+///
+/// Returns `None` if none of the given attributes is updatable.
+pub(crate) fn update_options(attributes: &HashMap<&str, &str>) -> Option<ItemUpdateOptions> {
+    let mut options = ItemUpdateOptions::new();
+    let mut updated = false;
+    if let Some(label) = attributes.get("label") {
+        options.set_label(label);
+        updated = true;
     }
+    if let Some(comment) = attributes.get("comment") {
+        options.set_comment(comment);
+        updated = true;
+    }
+    if let Some(description) = attributes.get("description") {
+        options.set_description(description);
+        updated = true;
+    }
+    updated.then_some(options)
+}
+
+/// Build the options for replacing the secret of an item in place and
+/// resetting its readable attributes to the given label and an empty
+/// comment and description (since they can't be removed).
+pub(crate) fn replace_options(secret: &[u8], label: &str) -> ItemUpdateOptions {
+    let mut options = ItemUpdateOptions::new();
+    options
+        .set_value(ItemUpdateValue::Data(CFData::from_buffer(secret)))
+        .set_label(label)
+        .set_comment("")
+        .set_description("");
+    options
 }
 
 /// The builder for iOS keychain credentials
@@ -162,6 +756,11 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
 }
 
 impl CredentialBuilderApi for IosCredentialBuilder {
+    /// The name of this store: `"ios-keychain"`.
+    fn backend_name(&self) -> &'static str {
+        "ios-keychain"
+    }
+
     /// Build an [IosCredential] for the given target, service, and user.
     fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
         Ok(Box::new(IosCredential::new_with_target(
@@ -169,6 +768,35 @@ impl CredentialBuilderApi for IosCredentialBuilder {
         )?))
     }
 
+    /// Find all the generic credentials for the given service.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .service(service)
+            .load_attributes(true)
+            .limit(Limit::All)
+            .search();
+        let results = match results.map_err(decode_error) {
+            Ok(results) => results,
+            Err(ErrorCode::NoEntry) => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        Ok(results
+            .iter()
+            .filter_map(|result| result.simplify_dict()?.remove("acct"))
+            .map(|account| {
+                Box::new(IosCredential {
+                    service: service.to_string(),
+                    account,
+                    presence_prompt: None,
+                    access_group: None,
+                    synchronizable: false,
+                    no_prompt: false,
+                }) as Box<Credential>
+            })
+            .collect())
+    }
+
     /// Return the underlying builder object with an `Any` type so that it can
     /// be downgraded to an [IosCredentialBuilder] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -181,18 +809,13 @@ impl CredentialBuilderApi for IosCredentialBuilder {
 /// The iOS error code values used here are from
 /// [this reference](https://opensource.apple.com/source/libsecurity_keychain/libsecurity_keychain-78/lib/SecBase.h.auto.html)
 fn decode_error(err: Error) -> ErrorCode {
-    match err.code() {
-        -25291 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecNotAvailable
-        -25292 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecReadOnly
-        -25300 => ErrorCode::NoEntry,                        // errSecItemNotFound
-        _ => ErrorCode::PlatformFailure(Box::new(err)),
-    }
+    err.into()
 }
 
 #[cfg(test)]
 mod tests {
     use super::{IosCredential, default_credential_builder};
-    use crate::credential::CredentialPersistence;
+    use crate::credential::{CredentialApi, CredentialPersistence};
     use crate::{Entry, Error, tests::generate_random_string};
 
     #[test]
@@ -203,6 +826,13 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(default_credential_builder().backend_name(), "ios-keychain");
+        let name = generate_random_string();
+        assert_eq!(entry_new(&name, &name).backend_name(), "ios-keychain");
+    }
+
     fn entry_new(service: &str, user: &str) -> Entry {
         crate::tests::entry_from_constructor(IosCredential::new_with_target, service, user)
     }
@@ -226,6 +856,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_user_presence() {
+        let credential = IosCredential::new_with_user_presence("service", "user", "");
+        assert!(
+            matches!(credential, Err(Error::Invalid(_, _))),
+            "Created credential with empty prompt"
+        );
+        let credential = IosCredential::new_with_user_presence("service", "user", "unlock it")
+            .expect("Can't create credential requiring user presence");
+        assert_eq!(credential.presence_prompt.as_deref(), Some("unlock it"));
+    }
+
+    #[test]
+    fn test_with_no_prompt() {
+        let presence = IosCredential::new_with_user_presence("service", "user", "unlock it")
+            .unwrap()
+            .with_no_prompt();
+        assert!(presence.no_prompt);
+        assert_eq!(presence.presence_prompt.as_deref(), Some("unlock it"));
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name).with_no_prompt();
+        let credential: &IosCredential = entry.get_credential().downcast_ref().unwrap();
+        assert!(credential.no_prompt);
+        assert!(matches!(
+            entry.update_password("unprompted"),
+            Err(Error::NoEntry)
+        ));
+        entry
+            .set_password("unprompted")
+            .expect("Can't set password without prompting");
+        assert_eq!(entry.get_password().unwrap(), "unprompted");
+        entry
+            .update_password("updated")
+            .expect("Can't update password without prompting");
+        assert_eq!(entry.get_password().unwrap(), "updated");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential without prompting");
+    }
+
     #[test]
     fn test_missing_entry() {
         crate::tests::test_missing_entry(entry_new);
@@ -251,11 +921,112 @@ mod tests {
         crate::tests::test_round_trip_random_secret(entry_new);
     }
 
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
     #[test]
     fn test_update() {
         crate::tests::test_update(entry_new);
     }
 
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_set_keeps_attributes() {
+        crate::tests::test_set_keeps_attributes(entry_new, "comment");
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry_not_supported(entry_new);
+    }
+
     #[test]
     fn test_get_credential() {
         let name = generate_random_string();
@@ -280,6 +1051,75 @@ mod tests {
 
     #[test]
     fn test_get_update_attributes() {
-        crate::tests::test_noop_get_update_attributes(entry_new);
+        crate::tests::test_get_update_named_attributes(
+            entry_new,
+            &["label", "comment", "description"],
+        );
+    }
+
+    #[test]
+    fn test_access_group() {
+        for group in ["ABCDE12345.com.example.shared", "group.com.example-app"] {
+            let credential = IosCredential::new_with_access_group(group, "service", "user")
+                .unwrap_or_else(|err| panic!("Rejected access group {group}: {err}"));
+            assert_eq!(credential.access_group.as_deref(), Some(group));
+            let sibling = credential.sibling("other").unwrap();
+            let sibling: &IosCredential = sibling.as_any().downcast_ref().unwrap();
+            assert_eq!(sibling.access_group.as_deref(), Some(group));
+            assert!(!sibling.synchronizable);
+        }
+        for group in [
+            "",
+            "shared",
+            "group..example",
+            "group.com.example.*",
+            "team id.app",
+        ] {
+            assert!(
+                matches!(
+                    IosCredential::new_with_access_group(group, "service", "user"),
+                    Err(Error::Invalid(attr, _)) if attr == "access group"
+                ),
+                "Accepted access group {group:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_synchronizable() {
+        let credential = IosCredential::new_synchronizable("service", "user").unwrap();
+        assert!(credential.synchronizable);
+        let sibling = credential.sibling("other").unwrap();
+        let sibling: &IosCredential = sibling.as_any().downcast_ref().unwrap();
+        assert!(sibling.synchronizable);
+        assert!(
+            !IosCredential::new_with_target(None, "service", "user")
+                .unwrap()
+                .synchronizable
+        );
+        assert!(matches!(
+            IosCredential::new_synchronizable("", "user"),
+            Err(Error::Invalid(attr, _)) if attr == "service"
+        ));
+    }
+
+    #[test]
+    fn test_synchronizable_ignores_local_item() {
+        let name = generate_random_string();
+        let local = entry_new(&name, &name);
+        local.set_password("local password").unwrap();
+        let synced = Entry::new_with_credential(Box::new(
+            IosCredential::new_synchronizable(&name, &name).unwrap(),
+        ));
+        assert!(
+            matches!(synced.get_password(), Err(Error::NoEntry)),
+            "Synchronizable entry read the local item"
+        );
+        synced.set_password("synced password").unwrap();
+        assert_eq!(synced.get_password().unwrap(), "synced password");
+        assert_eq!(local.get_password().unwrap(), "local password");
+        synced.delete_credential().unwrap();
+        assert_eq!(local.get_password().unwrap(), "local password");
+        local.delete_credential().unwrap();
     }
 }
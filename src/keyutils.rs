@@ -0,0 +1,552 @@
+/*!
+
+# Linux (keyutils) credential store
+
+This module uses the Linux kernel's
+[keyutils](https://www.man7.org/linux/man-pages/man7/keyutils.7.html)
+facility to store credentials.  Unlike the secret-service, keyutils doesn't
+need a DBus session bus or any user-level daemon, so it can be used on
+headless servers and in containers.
+
+Keys are stored in the user's _session_ keyring, and they are also linked
+into the user's _persistent_ keyring (if there is one) so that they can be
+found by other sessions of the same user.  Each key is a `user` key whose
+_description_ identifies the entry; for a given service/user pair, this module
+uses the string `keyring-rs:user@service` as the description.  If you specify
+a target when creating an entry, the target is used as the description instead.
+
+Keyutils keys are kept in kernel memory, so they do *not* persist across reboots:
+every credential created by this store is lost when the machine is restarted.
+In addition, the persistent keyring expires (by default, after three days) if no
+session of the user accesses it, and session keyrings are destroyed when the
+session ends.  If you need your credentials to last longer than that, use the
+secret-service store instead.
+
+Credentials set with an expiration time are given a kernel timeout,
+so the kernel itself removes them when they expire.
+
+Keyutils keys have no attributes other than their description,
+so this store doesn't support reading or updating attributes.
+Also, the kernel doesn't allow keys with empty values,
+so empty passwords and secrets can't be stored.
+
+## Selecting this store
+
+This store is only included if the `keyutils` feature is specified.
+If both the `keyutils` and the `secret-service` features are specified,
+the secret-service store is used by default; you can use this store instead
+by calling [set_default_credential_builder](crate::set_default_credential_builder)
+with the builder returned by [default_credential_builder].
+ */
+use std::time::{Duration, SystemTime};
+
+use linux_keyutils::{KeyError, KeyRing, KeyRingIdentifier};
+use zeroize::Zeroizing;
+
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi,
+    CredentialPersistence, ttl_seconds,
+};
+use super::error::{Error as ErrorCode, Result};
+use super::logging::warn;
+
+/// The prefix used in the descriptions of keys created by this store
+const DESCRIPTION_PREFIX: &str = "keyring-rs:";
+
+/// The largest payload the kernel allows for a `user` key
+const MAX_SECRET_LENGTH: u32 = 32767;
+
+/// The representation of a key in the keyutils store.
+///
+/// The session keyring is where keys are created and searched for.
+/// The persistent keyring, if there is one, is where keys
+/// are linked so they survive the end of the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyutilsCredential {
+    pub session: KeyRing,
+    pub persistent: Option<KeyRing>,
+    pub description: String,
+}
+
+impl CredentialApi for KeyutilsCredential {
+    /// The name of this store: `"keyutils"`.
+    fn backend_name(&self) -> &'static str {
+        "keyutils"
+    }
+
+    /// Create or replace the key for this entry, with the given secret.
+    ///
+    /// The key is created in the session keyring and linked into the
+    /// persistent keyring, if there is one.
+    ///
+    /// The kernel doesn't allow keys with empty secrets, so
+    /// this returns an [Invalid](ErrorCode::Invalid) error if the secret is empty.
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        self.save_key(secret, 0)
+    }
+
+    /// Create or replace the key for this entry, with the given secret,
+    /// and have the kernel remove the key once the given time has passed.
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        let seconds = ttl_seconds(ttl)?;
+        self.save_key(secret, usize::try_from(seconds).unwrap_or(usize::MAX))
+    }
+
+    /// Retrieve the secret from the key for this entry, if any.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// key in either the session or the persistent keyring.
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        let key = self.find_key()?;
+        key.read_to_vec().map_err(decode_error)
+    }
+
+    /// Read the secret of the key for this entry.
+    ///
+    /// Keys don't record when they were created or last written, so the times are `None`.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        Ok((self.get_secret()?, None, None))
+    }
+
+    /// Check whether there's a key for this entry, without reading it.
+    fn exists(&self) -> Result<bool> {
+        match self.find_key() {
+            Ok(_) => Ok(true),
+            Err(ErrorCode::NoEntry) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Return the service and user encoded in the key's description.
+    ///
+    /// Credentials created with an explicit target don't record
+    /// their service and user, so this returns `None` for them.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        let specifier = self.description.strip_prefix(DESCRIPTION_PREFIX)?;
+        let (user, service) = specifier.rsplit_once('@')?;
+        Some((service.to_string(), user.to_string()))
+    }
+
+    /// Return the description of this credential's key, if it was given as a target.
+    fn get_target(&self) -> Option<String> {
+        match self.get_specifiers() {
+            Some(_) => None,
+            None => Some(self.description.clone()),
+        }
+    }
+
+    /// Move the key for this entry to the given service and user.
+    ///
+    /// The kernel can't change the description of a key, so a key with the
+    /// new description is created, and then the old key is invalidated
+    /// (if that fails, the new key is invalidated instead).
+    /// The new key doesn't keep the old key's timeout.
+    ///
+    /// Credentials created with an explicit target use it as their description,
+    /// so they can't be renamed: that gives an [Invalid](ErrorCode::Invalid) error.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let key = self.find_key()?;
+        if self.get_specifiers().is_none() {
+            return Err(ErrorCode::Invalid(
+                "target".to_string(),
+                "is the key's description, so the key can't be renamed".to_string(),
+            ));
+        }
+        let renamed = Self::new_with_target(None, service, user)?;
+        if renamed.description == self.description {
+            return Ok(());
+        }
+        match renamed.find_key() {
+            Ok(_) => return Err(ErrorCode::AlreadyExists),
+            Err(ErrorCode::NoEntry) => {}
+            Err(err) => return Err(err),
+        }
+        let secret = Zeroizing::new(key.read_to_vec().map_err(decode_error)?);
+        renamed.save_key(&secret, 0)?;
+        if let Err(err) = key.invalidate() {
+            warn!(
+                "can't invalidate {self:?} after renaming it, so deleting the renamed copy: {err}"
+            );
+            _ = renamed.delete_credential();
+            return Err(decode_error(err));
+        }
+        Ok(())
+    }
+
+    /// Make a credential for the given user with this credential's service.
+    ///
+    /// If this credential was made with an explicit target, its description
+    /// is used as the service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let service = match self.get_specifiers() {
+            Some((service, _)) => service,
+            None => self.description.clone(),
+        };
+        Ok(Box::new(Self::new_with_target(None, &service, user)?))
+    }
+
+    /// Delete the key for this entry, if any.
+    ///
+    /// The key is invalidated, which removes it from all the keyrings it's linked into.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no key.
+    fn delete_credential(&self) -> Result<()> {
+        let key = self.find_key()?;
+        key.invalidate().map_err(decode_error)
+    }
+
+    /// Keyutils keys can expire, but have no attributes and can't be searched for.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: false,
+            enumeration: false,
+            ttl: true,
+            binary: true,
+            labels: false,
+            machine_scope: false,
+        }
+    }
+
+    /// Return the underlying concrete object with an `Any` type so that it can
+    /// be downgraded to a [KeyutilsCredential] for platform-specific processing.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// Expose the concrete debug formatter for use via the [Credential] trait
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl KeyutilsCredential {
+    /// Create a credential for the given target, service, and user.
+    ///
+    /// Creating a credential does not create a key in the store.
+    /// The key is created when [set_password](KeyutilsCredential::set_password)
+    /// is called.
+    ///
+    /// This fails if the target is the empty string, or if
+    /// the session keyring cannot be accessed.
+    pub fn new_with_target(target: Option<&str>, service: &str, user: &str) -> Result<Self> {
+        let description = match target {
+            Some("") => {
+                return Err(ErrorCode::Invalid(
+                    "target".to_string(),
+                    "cannot be empty".to_string(),
+                ));
+            }
+            Some(target) => target.to_string(),
+            None => format!("{DESCRIPTION_PREFIX}{user}@{service}"),
+        };
+        let session =
+            KeyRing::from_special_id(KeyRingIdentifier::Session, false).map_err(decode_error)?;
+        let persistent = KeyRing::get_persistent(KeyRingIdentifier::Session).ok();
+        Ok(Self {
+            session,
+            persistent,
+            description,
+        })
+    }
+
+    /// Create or replace the key for this credential, with the given secret.
+    ///
+    /// Updating an existing key doesn't change its timeout,
+    /// so the timeout is always set (a zero timeout means none).
+    fn save_key(&self, secret: &[u8], timeout: usize) -> Result<()> {
+        if secret.is_empty() {
+            return Err(ErrorCode::Invalid(
+                "secret".to_string(),
+                "cannot be empty".to_string(),
+            ));
+        }
+        if secret.len() > MAX_SECRET_LENGTH as usize {
+            return Err(ErrorCode::TooLong("secret".to_string(), MAX_SECRET_LENGTH));
+        }
+        let key = self
+            .session
+            .add_key(&self.description, secret)
+            .map_err(decode_error)?;
+        key.set_timeout(timeout).map_err(decode_error)?;
+        if let Some(persistent) = self.persistent {
+            persistent.link_key(key).map_err(decode_error)?;
+        }
+        Ok(())
+    }
+
+    /// Find the key for this credential.
+    ///
+    /// The session keyring is searched first, then the persistent keyring.
+    fn find_key(&self) -> Result<linux_keyutils::Key> {
+        match self.session.search(&self.description) {
+            Err(KeyError::KeyDoesNotExist) => match self.persistent {
+                Some(persistent) => persistent.search(&self.description).map_err(decode_error),
+                None => Err(ErrorCode::NoEntry),
+            },
+            result => result.map_err(decode_error),
+        }
+    }
+}
+
+/// The builder for keyutils credentials.
+#[derive(Debug, Default)]
+pub struct KeyutilsCredentialBuilder {}
+
+/// Returns an instance of the keyutils credential builder.
+///
+/// If keyutils is the default store, this is called once
+/// when an entry is first created.
+pub fn default_credential_builder() -> Box<CredentialBuilder> {
+    Box::new(KeyutilsCredentialBuilder {})
+}
+
+impl CredentialBuilderApi for KeyutilsCredentialBuilder {
+    /// The name of this store: `"keyutils"`.
+    fn backend_name(&self) -> &'static str {
+        "keyutils"
+    }
+
+    /// Build a [KeyutilsCredential] for the given target, service, and user.
+    fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(KeyutilsCredential::new_with_target(
+            target, service, user,
+        )?))
+    }
+
+    /// Look up the session keyring, which is where keys are created.
+    fn probe(&self) -> Result<()> {
+        KeyRing::from_special_id(KeyRingIdentifier::Session, false).map_err(decode_error)?;
+        Ok(())
+    }
+
+    /// Return the underlying builder object with an `Any` type so that it can
+    /// be downgraded to a [KeyutilsCredentialBuilder] for platform-specific processing.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// Keyutils keys are kept in kernel memory, so they don't survive a reboot.
+    fn persistence(&self) -> CredentialPersistence {
+        CredentialPersistence::UntilReboot
+    }
+}
+
+/// Map a keyutils error to a crate error.
+pub fn decode_error(err: KeyError) -> ErrorCode {
+    err.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::credential::CredentialPersistence;
+    use crate::{Entry, Error, tests::generate_random_string};
+
+    use super::{KeyutilsCredential, default_credential_builder};
+
+    #[test]
+    fn test_persistence() {
+        assert!(matches!(
+            default_credential_builder().persistence(),
+            CredentialPersistence::UntilReboot
+        ))
+    }
+
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(default_credential_builder().backend_name(), "keyutils");
+        let name = generate_random_string();
+        assert_eq!(entry_new(&name, &name).backend_name(), "keyutils");
+    }
+
+    #[test]
+    fn test_probe() {
+        default_credential_builder()
+            .probe()
+            .expect("Can't access the session keyring")
+    }
+
+    fn entry_new(service: &str, user: &str) -> Entry {
+        crate::tests::entry_from_constructor(KeyutilsCredential::new_with_target, service, user)
+    }
+
+    #[test]
+    fn test_invalid_parameter() {
+        let credential = KeyutilsCredential::new_with_target(Some(""), "service", "user");
+        assert!(
+            matches!(credential, Err(Error::Invalid(_, _))),
+            "Created entry with empty target"
+        );
+    }
+
+    #[test]
+    fn test_empty_service_and_user() {
+        crate::tests::test_empty_service_and_user(entry_new);
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        crate::tests::test_missing_entry(entry_new);
+    }
+
+    #[test]
+    fn test_empty_password() {
+        let entry = entry_new("empty password service", "empty password user");
+        assert!(
+            matches!(entry.set_password(""), Err(Error::Invalid(_, _))),
+            "Able to set empty password"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_ascii_password() {
+        crate::tests::test_round_trip_ascii_password(entry_new);
+    }
+
+    #[test]
+    fn test_round_trip_non_ascii_password() {
+        crate::tests::test_round_trip_non_ascii_password(entry_new);
+    }
+
+    #[test]
+    fn test_round_trip_random_secret() {
+        crate::tests::test_round_trip_random_secret(entry_new);
+    }
+
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
+    #[test]
+    fn test_update() {
+        crate::tests::test_update(entry_new);
+    }
+
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_rename_with_target() {
+        let name = generate_random_string();
+        let credential = KeyutilsCredential::new_with_target(Some(&name), &name, &name)
+            .expect("Can't create credential with target");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry
+            .set_password("target password")
+            .expect("Can't set password for target rename");
+        assert!(
+            matches!(entry.rename("other", "other"), Err(Error::Invalid(_, _))),
+            "Renamed a credential with a target"
+        );
+        assert_eq!(entry.get_password().unwrap(), "target password");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential after target rename");
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("test password")
+            .expect("Can't set password for last modified");
+        assert!(
+            entry
+                .last_modified()
+                .expect("Can't get last modified time")
+                .is_none(),
+            "Keyutils recorded a last modified time"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for last modified");
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry(entry_new);
+    }
+
+    #[test]
+    fn test_get_update_attributes() {
+        crate::tests::test_noop_get_update_attributes(entry_new);
+    }
+}
@@ -90,10 +90,48 @@ default feature set:
   but you can avoid this requirement by specifying the `vendored` feature
   (which will cause the build to include a static build of the dbus library).
 
+There is also a `keyutils` feature, which is _not_ in the default feature set:
+
+- `keyutils`: Provides access to the kernel's
+  [keyutils](https://www.man7.org/linux/man-pages/man7/keyutils.7.html)
+  storage on Linux.  This keystore doesn't need DBus, so it works on
+  headless machines, but its credentials don't persist across reboots.
+  If both this feature and the `secret-service` feature are specified,
+  the secret-service is the default keystore on Linux.
+
 If you suppress the default feature set when building this crate, and you
 don't separately specify one of the included keystore features for your platform,
 then no keystore will be built in, and calls to [Entry::new] and [Entry::new_with_target]
-will fail unless the client brings their own keystore (see next section).
+will fail with a [NoDefaultCredentialBuilder](Error::NoDefaultCredentialBuilder) error
+unless the client brings their own keystore (see next section).
+The crate still builds in this case, so you can safely turn off the
+keystores you don't need on each platform.
+
+The crate also builds on targets that have no keystore at all, such as
+WebAssembly and bare-metal targets, so crates that are also built for those
+targets can depend on it unconditionally.  There, calls to [Entry::new] and
+[Entry::new_with_target] fail with a [NotSupportedByStore](Error::NotSupportedByStore)
+error (unless the client brings their own keystore).
+
+### Cross-compiling
+
+The keystores link against their platforms' libraries through the crates
+that wrap them; this crate doesn't emit any link directives of its own.
+What each cross-build needs is:
+
+- Apple targets with `apple-native`: the Security and CoreFoundation frameworks
+  are always linked dynamically (by `security-framework-sys` and `core-foundation-sys`),
+  since Apple ships them only as system frameworks, so there's no static or
+  vendored alternative to choose.  Linking needs an Apple SDK and a linker
+  that can use it (such as [osxcross](https://github.com/tpoechtrager/osxcross)
+  on Linux), with the SDK's frameworks on the linker's framework search path.
+  Building without `apple-native` (for example with `default-features = false`)
+  links no Apple frameworks at all.
+- Windows targets with `windows-native`: nothing beyond the target's standard
+  libraries, since `windows-sys` provides its own import libraries.
+- Linux and the BSDs with `secret-service`: the DBus library for the target.
+  The `vendored` feature builds it from source instead, which is usually
+  the simplest way to cross-compile.  The `keyutils` feature needs no libraries.
 
 ## Client-provided Credential Stores
 
@@ -119,12 +157,80 @@ In addition to the platform-specific credential stores, this crate
 always provides a mock credential store that clients can use to
 test their code in a platform independent way.  The mock credential
 store allows for pre-setting errors as well as password values to
-be returned from [Entry] method calls. If you want to use the mock
+be returned from [Entry] method calls.  Mock credentials are kept in
+process memory, so entries with the same target, service, and user
+share their credential.  If you want to use the mock
 credential store as your default in tests, make this call:
 ```
 keyring::set_default_credential_builder(keyring::mock::default_credential_builder())
 ```
 
+## Null Credential Store
+
+This crate also always provides a [null] credential store, for
+environments that have no secure storage at all.  Setting a password on a
+null credential succeeds but discards it, and getting a password always fails with a
+[NoEntry](Error::NoEntry) error, so your application can run end-to-end
+without saving any secrets.  Unlike mock credentials,
+null credentials never keep anything, even in memory.
+
+## Wiping Secrets from Memory
+
+Passwords and secrets returned by this crate are ordinary heap-allocated
+values, and their contents linger in memory after they are dropped.
+If that is a concern, specify the `zeroizing` feature and use the
+`get_password_zeroizing` and `get_secret_zeroizing` calls, which return values
+wrapped in a [Zeroizing] guard (from the [zeroize] crate) that wipes them on drop.
+
+Whether or not that feature is specified, the credential stores wipe the
+intermediate buffers they use to pass secrets to and from the platform,
+where the platform allows it.  (The macOS and iOS keychains copy secrets
+into immutable `CFData` objects, which can't be wiped, and the
+secret-service keeps its own copies in the D-Bus library.)
+
+## Expiring Credentials
+
+The [set_password_with_expiry](Entry::set_password_with_expiry) and
+[set_secret_with_expiry](Entry::set_secret_with_expiry) calls store a
+credential that expires after a given time; once it has expired,
+reading it returns a [NoEntry](Error::NoEntry) error.
+How expiration is enforced depends on the store:
+
+- The keyutils store uses the kernel's key timeouts, so expired keys
+  are removed by the OS.
+- The secret-service and Windows stores keep the expiration time in an attribute
+  named by [EXPIRY_ATTRIBUTE](credential::EXPIRY_ATTRIBUTE), and this crate
+  checks it when the credential is read.  Expired credentials stay in the store
+  (and are visible to other applications) until they are deleted or overwritten.
+- The mock store checks expiration times in process memory.
+- The Apple keychain stores don't support expiration, and return a
+  [NotSupportedByStore](Error::NotSupportedByStore) error.
+
+## Asynchronous Access
+
+All of the credential stores used by this crate are accessed via blocking calls.
+If you are using an async runtime, and you don't want to block the tasks
+that access entries, you can specify the `async` feature to get
+an `AsyncEntry` type whose methods return futures.
+These futures don't depend on any particular async runtime.
+
+## Logging
+
+This crate logs what it does with the [log](https://docs.rs/log) crate,
+so you can see its activity with any logger.  Each operation on an entry is logged at
+debug level when it starts and when it ends, with the entry's credential
+(whose debug format names the store, service, and user), whether the
+operation succeeded, and how long it took.  Secrets are never logged,
+and neither are attribute values (only their names).
+When a store falls back to another way of doing an operation,
+or has to undo part of one, it logs a warning.
+
+If the `tracing` feature is specified, the same events are emitted with the
+[tracing](https://docs.rs/tracing) crate instead, and each operation on an
+entry runs in a debug-level `keyring` span whose fields are the `operation`,
+the `backend` (as given by [backend_name](Entry::backend_name)), and the
+`service` and `user` of the entry's credential.
+
 ## Interoperability with Third Parties
 
 Each of the platform-specific credential stores provided by this crate uses
@@ -161,13 +267,29 @@ Service, accesses from multiple threads (and even the same thread very quickly)
 are not recommended, as they may cause the RPC mechanism to fail.
  */
 
-use log::debug;
+use logging::{debug, warn};
 use std::collections::HashMap;
-
-pub use credential::{Credential, CredentialBuilder};
-pub use error::{Error, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+pub use credential::{Capabilities, Credential, CredentialBuilder, Persistence, Scope};
+pub use entry_builder::{AccessControl, EntryBuilder};
+pub use entry_id::EntryId;
+pub use error::{DeleteServiceError, DeleteUserError, Error, ErrorKind, Result};
+pub use read_only::ReadOnlyEntry;
+pub use zeroize::Zeroizing;
+
+/// The logging macros: those of `tracing` if the `tracing` feature
+/// is specified, and otherwise those of `log`.
+mod logging {
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) use log::{debug, warn};
+    #[cfg(feature = "tracing")]
+    pub(crate) use tracing::{debug, warn};
+}
 
 pub mod mock;
+pub mod null;
 
 //
 // pick the *nix keystore
@@ -182,6 +304,10 @@ pub mod mock;
 )]
 pub mod secret_service;
 
+#[cfg(all(target_os = "linux", feature = "keyutils"))]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+pub mod keyutils;
+
 //
 // pick the Apple keystore
 //
@@ -199,17 +325,47 @@ pub mod ios;
 #[cfg(all(target_os = "windows", feature = "windows-native"))]
 #[cfg_attr(docsrs, doc(cfg(target_os = "windows")))]
 pub mod windows;
+#[cfg(all(target_os = "windows", feature = "windows-native"))]
+pub use windows::set_target_separator;
 
+pub mod cache;
+pub mod chunked;
+pub mod composite;
 pub mod credential;
+pub mod entry_builder;
+pub mod entry_id;
 pub mod error;
+pub mod prompt_hook;
+pub mod read_only;
+pub mod timeout;
+
+#[cfg(feature = "export")]
+#[cfg_attr(docsrs, doc(cfg(feature = "export")))]
+pub mod export;
+
+#[cfg(feature = "locked-memory")]
+#[cfg_attr(docsrs, doc(cfg(feature = "locked-memory")))]
+pub mod locked;
+
+#[cfg(feature = "file-backend")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+pub mod file;
+#[cfg(feature = "export")]
+pub use export::{ExportedCredential, export, import};
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod async_entry;
+#[cfg(feature = "async")]
+pub use async_entry::AsyncEntry;
 
 #[derive(Default, Debug)]
-struct EntryBuilder {
+struct DefaultBuilder {
     inner: Option<Box<CredentialBuilder>>,
 }
 
-static DEFAULT_BUILDER: std::sync::RwLock<EntryBuilder> =
-    std::sync::RwLock::new(EntryBuilder { inner: None });
+static DEFAULT_BUILDER: std::sync::RwLock<DefaultBuilder> =
+    std::sync::RwLock::new(DefaultBuilder { inner: None });
 
 /// Set the credential builder used by default to create entries.
 ///
@@ -235,24 +391,146 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
         all(target_os = "openbsd", feature = "secret-service")
     ))]
     return secret_service::default_credential_builder();
+    #[cfg(all(
+        target_os = "linux",
+        feature = "keyutils",
+        not(feature = "secret-service")
+    ))]
+    return keyutils::default_credential_builder();
     #[cfg(all(target_os = "macos", feature = "apple-native"))]
     return macos::default_credential_builder();
     #[cfg(all(target_os = "ios", feature = "apple-native"))]
     return ios::default_credential_builder();
     #[cfg(all(target_os = "windows", feature = "windows-native"))]
     return windows::default_credential_builder();
+    #[cfg(all(
+        not(any(
+            all(target_os = "linux", feature = "secret-service"),
+            all(target_os = "freebsd", feature = "secret-service"),
+            all(target_os = "openbsd", feature = "secret-service"),
+            all(target_os = "linux", feature = "keyutils"),
+            all(target_os = "macos", feature = "apple-native"),
+            all(target_os = "ios", feature = "apple-native"),
+            all(target_os = "windows", feature = "windows-native"),
+        )),
+        any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "openbsd",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "windows",
+        )
+    ))]
+    return credential::nop_credential_builder();
     #[cfg(not(any(
-        all(target_os = "linux", feature = "secret-service"),
-        all(target_os = "freebsd", feature = "secret-service"),
-        all(target_os = "openbsd", feature = "secret-service"),
-        all(target_os = "macos", feature = "apple-native"),
-        all(target_os = "ios", feature = "apple-native"),
-        all(target_os = "windows", feature = "windows-native"),
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "windows",
     )))]
-    credential::nop_credential_builder()
+    credential::unsupported_credential_builder()
 }
 
-fn build_default_credential(target: Option<&str>, service: &str, user: &str) -> Result<Entry> {
+static APP_NAMESPACE: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// Set the namespace of this application's entries.
+///
+/// Once a namespace is set, the service of every entry created with
+/// the default credential builder (by [Entry::new], [Entry::new_with_target],
+/// [Entry::search], and the like) is stored as the namespace, a colon,
+/// and the service, so that applications which use the same services
+/// don't clobber each other's credentials.  The namespace is removed again
+/// from the services returned by [Entry::get_specifiers].  Entries created with
+/// [new_with_credential](Entry::new_with_credential) aren't affected.
+///
+/// Credentials stored before the namespace was set can't be read
+/// through namespaced entries, so applications that adopt a namespace
+/// should move such credentials with [Entry::rename].
+/// Like [set_default_credential_builder], this is meant to be called
+/// at app startup before you start creating entries.
+pub fn set_app_namespace(namespace: &str) {
+    let mut guard = APP_NAMESPACE
+        .write()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    *guard = Some(namespace.to_string());
+}
+
+/// Stop namespacing the services of new entries (see [set_app_namespace]).
+pub fn clear_app_namespace() {
+    let mut guard = APP_NAMESPACE
+        .write()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    *guard = None;
+}
+
+/// The service of a network credential, as used by stores that don't
+/// keep network credentials' details separately (see [Entry::new_network]).
+fn network_service(protocol: &str, host: &str, port: Option<u16>) -> String {
+    match port {
+        Some(port) => format!("{protocol}://{host}:{port}"),
+        None => format!("{protocol}://{host}"),
+    }
+}
+
+/// Build the credential for the given user of a network service with the given
+/// builder, following the network schema if it's the secret-service builder.
+fn build_network(
+    builder: &CredentialBuilder,
+    protocol: &str,
+    host: &str,
+    port: Option<u16>,
+    user: &str,
+) -> Result<Box<Credential>> {
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    ))]
+    if builder.as_any().is::<secret_service::SsCredentialBuilder>() {
+        return Ok(Box::new(secret_service::SsCredential::new_network(
+            protocol, host, port, user,
+        )?));
+    }
+    builder.build(None, &network_service(protocol, host, port), user)
+}
+
+/// The OS login name of the user running this process.
+#[cfg(feature = "current-user")]
+fn current_user() -> String {
+    let user = whoami::username();
+    debug!("resolved the current user to {user}");
+    user
+}
+
+/// The service under which the store keeps credentials for the given service.
+fn namespaced_service(service: &str) -> String {
+    let guard = APP_NAMESPACE
+        .read()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    add_namespace(guard.as_deref(), service)
+}
+
+fn add_namespace(namespace: Option<&str>, service: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{namespace}:{service}"),
+        None => service.to_string(),
+    }
+}
+
+fn remove_namespace(namespace: Option<&str>, service: String) -> String {
+    match namespace.and_then(|namespace| service.strip_prefix(namespace)?.strip_prefix(':')) {
+        Some(service) => service.to_string(),
+        None => service,
+    }
+}
+
+/// Apply a function to the credential builder currently in use as the default.
+fn with_default_builder<F, T>(f: F) -> T
+where
+    F: FnOnce(&CredentialBuilder) -> T,
+{
     static DEFAULT: std::sync::OnceLock<Box<CredentialBuilder>> = std::sync::OnceLock::new();
     let guard = DEFAULT_BUILDER
         .read()
@@ -261,13 +539,434 @@ fn build_default_credential(target: Option<&str>, service: &str, user: &str) ->
         .inner
         .as_ref()
         .unwrap_or_else(|| DEFAULT.get_or_init(|| default_credential_builder()));
-    let credential = builder.build(target, service, user)?;
-    Ok(Entry { inner: credential })
+    f(builder.as_ref())
+}
+
+/// Run a keyring operation, logging (at debug level) when it starts,
+/// and whether it succeeded and how long it took when it ends.
+///
+/// The `subject` says what the operation is on, for example
+/// an entry or the service of a search.
+fn log_timed<T>(
+    operation: &str,
+    subject: &dyn std::fmt::Display,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    debug!("{operation} for {subject}");
+    // asking for the time panics on `wasm32-unknown-unknown`
+    let timed = !cfg!(all(target_family = "wasm", target_os = "unknown"));
+    let start = timed.then(Instant::now);
+    let result = f();
+    let elapsed = start.map(|start| start.elapsed()).unwrap_or_default();
+    match &result {
+        Ok(_) => debug!("{operation} for {subject} succeeded in {elapsed:?}"),
+        Err(err) => debug!("{operation} for {subject} failed in {elapsed:?}: {err}"),
+    }
+    result
+}
+
+/// Whether the given credential builder keeps credentials in a keychain,
+/// so that they can be made with keychain-only options.
+#[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+fn is_keychain_builder(builder: &CredentialBuilder) -> bool {
+    let builder = builder.as_any();
+    #[cfg(target_os = "macos")]
+    if builder.is::<macos::MacCredentialBuilder>() {
+        return true;
+    }
+    builder.is::<ios::IosCredentialBuilder>()
+}
+
+/// Check whether the default credential store is available.
+///
+/// This makes a lightweight call to the store used by default to create entries
+/// (e.g., connecting to the Secret Service, or opening the User keychain),
+/// but it neither creates nor reads any credentials.  It returns
+/// the same errors that real operations on that store would, so
+/// [NoStorageAccess](Error::NoStorageAccess) means the store can't be reached,
+/// [Locked](Error::Locked) means it is locked,
+/// and [NoDefaultCredentialBuilder](Error::NoDefaultCredentialBuilder)
+/// means that there is no default store.
+pub fn storage_available() -> Result<()> {
+    debug!("probe the default credential store");
+    with_default_builder(|builder| builder.probe())
+}
+
+/// The name of the store used by default to create entries.
+///
+/// See [Entry::backend_name] for the names of this crate's stores.
+/// If there is no default store, this is `"none"`.
+pub fn default_backend_name() -> &'static str {
+    with_default_builder(|builder| builder.backend_name())
+}
+
+/// Retrieve the passwords of several entries.
+///
+/// The results are in the same order as the entries, and a failure to get
+/// one entry's password doesn't stop the others from being retrieved.
+/// The secret-service store makes one connection (and session) for the whole batch,
+/// rather than one per entry, and it searches once for the items of all the entries
+/// and unlocks the locked ones with a single prompt; the other stores have no
+/// per-call connection to share, so for them this is the same as getting each
+/// password in turn.
+pub fn get_many(entries: &[Entry]) -> Vec<Result<String>> {
+    debug!("get passwords for {} entries", entries.len());
+    in_batch_for(entries.iter(), || {
+        entries.iter().map(Entry::get_password).collect()
+    })
+}
+
+/// Set the passwords of several entries.
+///
+/// See [get_many] for how the results are reported and how the operations are batched.
+pub fn set_many(entries: &[(&Entry, &str)]) -> Vec<Result<()>> {
+    debug!("set passwords for {} entries", entries.len());
+    in_batch_for(entries.iter().map(|(entry, _)| *entry), || {
+        entries
+            .iter()
+            .map(|(entry, password)| entry.set_password(password))
+            .collect()
+    })
 }
 
-#[derive(Debug)]
+/// Delete the credentials of several entries.
+///
+/// See [get_many] for how the results are reported and how the operations are batched.
+pub fn delete_many(entries: &[Entry]) -> Vec<Result<()>> {
+    debug!("delete {} entries", entries.len());
+    in_batch_for(entries.iter(), || {
+        entries.iter().map(Entry::delete_credential).collect()
+    })
+}
+
+/// Iterate over the existing entries for the given service, or over
+/// all the existing entries if no service is given.
+///
+/// This finds the same entries as [Entry::search] does for a service,
+/// and as an [Entry::query] with no attributes does otherwise (leaving out,
+/// if there's an [app namespace](set_app_namespace), the entries whose
+/// services aren't in it).  No search is done until the first entry is asked
+/// for, and entries are made one at a time as they are yielded, so callers
+/// that stop early don't pay for the rest.  None of the stores can fetch their
+/// credentials a page at a time, so the search itself still finds them all.
+///
+/// If the search fails, its error is the only item yielded.
+pub fn iter_entries(service: Option<&str>) -> impl Iterator<Item = Result<Entry>> + use<> {
+    let service = service.map(namespaced_service);
+    iter_found(move || match service {
+        Some(service) => with_default_builder(|builder| builder.search(&service)),
+        None => {
+            let guard = APP_NAMESPACE
+                .read()
+                .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+            let prefix = guard.as_deref().map(|namespace| format!("{namespace}:"));
+            drop(guard);
+            let mut found = with_default_builder(|builder| builder.query(&HashMap::new()))?;
+            if let Some(prefix) = prefix {
+                found.retain(|credential| {
+                    credential
+                        .get_specifiers()
+                        .is_some_and(|(service, _)| service.starts_with(&prefix))
+                });
+            }
+            Ok(found)
+        }
+    })
+}
+
+/// Yield entries for the credentials found by the given search,
+/// which isn't done until the first entry is asked for.
+fn iter_found<F>(search: F) -> impl Iterator<Item = Result<Entry>>
+where
+    F: FnOnce() -> Result<Vec<Box<Credential>>>,
+{
+    let mut search = Some(search);
+    let mut found: std::vec::IntoIter<Box<Credential>> = Vec::new().into_iter();
+    std::iter::from_fn(move || {
+        if let Some(search) = search.take() {
+            match search() {
+                Ok(credentials) => found = credentials.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        found.next().map(|credential| {
+            Ok(Entry {
+                inner: credential.into(),
+            })
+        })
+    })
+}
+
+/// Delete all the credentials for the given service.
+///
+/// The default credential builder is used to [search](Entry::search) for
+/// the service's credentials, which are then deleted one at a time; the
+/// number deleted is returned.  If a credential can't be deleted, the
+/// remaining ones are left alone, and the returned [DeleteServiceError]
+/// tells how many were deleted and which one failed.  (If the search fails,
+/// including because the default credential store can't search for credentials,
+/// nothing is deleted.)
+pub fn delete_service(service: &str) -> std::result::Result<usize, DeleteServiceError> {
+    debug!("delete all credentials for service {service}");
+    in_batch(|| {
+        let entries = Entry::search(service).map_err(|error| DeleteServiceError {
+            deleted: 0,
+            failed: None,
+            error,
+        })?;
+        delete_entries(&entries)
+    })
+}
+
+/// Delete all the credentials for the given user, whatever their service.
+///
+/// The default credential builder is used to [query](Entry::query) for
+/// the credentials whose `username` attribute is the given user (and,
+/// if there's an [app namespace](set_app_namespace), whose service is in it),
+/// which are then deleted one at a time; the number deleted is returned.
+/// A credential that can't be deleted doesn't stop the others from being
+/// deleted: all the failures are listed in the returned [DeleteUserError].
+/// (If the query fails, including because the default credential store
+/// can't query by attributes, nothing is deleted.)
+pub fn delete_user(username: &str) -> std::result::Result<usize, DeleteUserError> {
+    debug!("delete all credentials for user {username}");
+    in_batch(|| {
+        let query = HashMap::from([("username".to_string(), username.to_string())]);
+        let entries = Entry::query(&query).map_err(|error| DeleteUserError {
+            deleted: 0,
+            failures: vec![(None, error)],
+        })?;
+        let guard = APP_NAMESPACE
+            .read()
+            .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+        let prefix = guard.as_deref().map(|namespace| format!("{namespace}:"));
+        drop(guard);
+        let entries: Vec<Entry> = entries
+            .into_iter()
+            .filter(|entry| match (entry.inner.get_specifiers(), &prefix) {
+                (Some((service, user)), prefix) => {
+                    user == username
+                        && prefix
+                            .as_deref()
+                            .is_none_or(|prefix| service.starts_with(prefix))
+                }
+                (None, _) => false,
+            })
+            .collect();
+        delete_all_entries(&entries)
+    })
+}
+
+/// Delete the credentials of all the given entries, collecting the failures.
+fn delete_all_entries(entries: &[Entry]) -> std::result::Result<usize, DeleteUserError> {
+    let mut deleted = 0;
+    let mut failures = Vec::new();
+    for entry in entries {
+        match entry.delete_credential() {
+            Ok(()) => deleted += 1,
+            Err(error) => failures.push((entry.get_specifiers(), error)),
+        }
+    }
+    if failures.is_empty() {
+        Ok(deleted)
+    } else {
+        Err(DeleteUserError { deleted, failures })
+    }
+}
+
+/// Delete the credentials of the given entries in order, stopping at the first failure.
+fn delete_entries(entries: &[Entry]) -> std::result::Result<usize, DeleteServiceError> {
+    for (deleted, entry) in entries.iter().enumerate() {
+        if let Err(error) = entry.delete_credential() {
+            return Err(DeleteServiceError {
+                deleted,
+                failed: entry.get_specifiers(),
+                error,
+            });
+        }
+    }
+    Ok(entries.len())
+}
+
+/// Run a batch of operations, letting the stores that connect
+/// to a service share one connection across all of them.
+fn in_batch<T>(f: impl FnOnce() -> T) -> T {
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    ))]
+    return secret_service::batch(f);
+    #[cfg(not(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    )))]
+    f()
+}
+
+/// Run a batch of operations on the given entries (see [in_batch]), letting
+/// the stores that can find the credentials of all the entries at once do that first.
+fn in_batch_for<'a, T>(entries: impl Iterator<Item = &'a Entry>, f: impl FnOnce() -> T) -> T {
+    in_batch(|| {
+        #[cfg(all(
+            any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+            feature = "secret-service"
+        ))]
+        {
+            let credentials: Vec<&secret_service::SsCredential> = entries
+                .filter_map(|entry| entry.get_credential().downcast_ref())
+                .collect();
+            secret_service::search_for_batch(&credentials);
+        }
+        #[cfg(not(all(
+            any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+            feature = "secret-service"
+        )))]
+        drop(entries);
+        f()
+    })
+}
+
+fn build_default_credential(target: Option<&str>, service: &str, user: &str) -> Result<Entry> {
+    let service = namespaced_service(service);
+    let credential = with_default_builder(|builder| builder.build(target, &service, user))?;
+    Ok(Entry {
+        inner: credential.into(),
+    })
+}
+
+/// A description of an entry's credential, for showing
+/// to users: everything but its secret.
+///
+/// This is returned by [Entry::get_info] and [Entry::list_detailed].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialInfo {
+    /// The credential's service (empty if the store doesn't know it)
+    pub service: String,
+    /// The credential's user (empty if the store doesn't know it)
+    pub username: String,
+    /// When the credential was created, if the store records it
+    pub created: Option<SystemTime>,
+    /// When the credential was last written, if the store records it
+    pub modified: Option<SystemTime>,
+    /// The length of the credential's secret, in bytes
+    pub secret_len: usize,
+}
+
+/// A portable description of an entry's credential, returned
+/// along with its password by [Entry::get_password_and_credential].
+///
+/// The fields have the same meaning on every store.  Anything
+/// else a store knows about the credential can be had from the
+/// store's own credential type, with [platform](CredentialSummary::platform).
+#[derive(Debug, Clone)]
+pub struct CredentialSummary {
+    /// The credential's service (empty if the store doesn't know it)
+    pub service: String,
+    /// The credential's user (empty if the store doesn't know it)
+    pub username: String,
+    /// The name of the credential's store (see [Entry::backend_name])
+    pub backend: &'static str,
+    /// When the credential was created, if the store records it
+    pub created: Option<SystemTime>,
+    /// When the credential was last written, if the store records it
+    pub modified: Option<SystemTime>,
+    /// The credential's attributes (see [Entry::get_attributes])
+    pub attributes: HashMap<String, String>,
+    entry: Entry,
+}
+
+impl CredentialSummary {
+    /// The store's credential, if it has the given type.
+    ///
+    /// For example, on Windows `summary.platform::<windows::WinCredential>()`
+    /// gives the credential's target name and persistence.
+    pub fn platform<T: 'static>(&self) -> Option<&T> {
+        self.entry.get_credential().downcast_ref()
+    }
+
+    /// The entry for the described credential.
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+}
+
+/// A named entry in a credential store.
+///
+/// Entries are `Send` and `Sync`, so they can be shared across threads.
+/// Cloning an entry is cheap: the clone shares the original's
+/// underlying credential, so operations on either of them
+/// affect the same item in the credential store.
+#[derive(Debug, Clone)]
 pub struct Entry {
-    inner: Box<Credential>,
+    inner: Arc<Credential>,
+}
+
+/// Entries display as `service/user` (without any
+/// [app namespace](set_app_namespace)) when their service and user are known,
+/// and otherwise as their target (or `<unknown>`).
+/// Neither this nor the `Debug` format of an entry ever shows its secret.
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.get_specifiers(), self.target()) {
+            (Some((service, user)), _) => write!(f, "{service}/{user}"),
+            (None, Some(target)) => f.write_str(&target),
+            (None, None) => f.write_str("<unknown>"),
+        }
+    }
+}
+
+/// Entries are equal when they locate the same credential: that is, when
+/// they have the same [backend](Entry::backend_name), [target](Entry::target),
+/// service, and user (and, for the secret-service, the same collection, and for
+/// the file store, the same file, and for the mock store, the same mock data).
+/// Secrets are never compared.  Each store reports the
+/// target, service, and user of a credential the way it composes them (for
+/// example, the secret-service reports the `default` target for entries made
+/// without one), so entries made in different ways for the same credential
+/// are equal.  Entries whose credentials have neither a target nor a service and
+/// user are only equal to themselves (and their clones).
+///
+/// Since equal entries hash the same, entries can be used as `HashMap` keys.
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.locator(), other.locator()) {
+            (Some(locator), Some(other)) => locator == other,
+            (None, None) => Arc::ptr_eq(&self.inner, &other.inner),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Entry {}
+
+impl std::hash::Hash for Entry {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        match self.locator() {
+            Some(locator) => locator.hash(state),
+            None => Arc::as_ptr(&self.inner).cast::<()>().hash(state),
+        }
+    }
+}
+
+/// Compare two secrets in time that depends only on their lengths,
+/// so the comparison doesn't reveal how much of a secret was guessed.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    use subtle::ConstantTimeEq;
+    a.ct_eq(b).into()
+}
+
+/// What locates an entry's credential in its store, used to compare entries.
+#[derive(PartialEq, Eq, Hash)]
+struct Locator {
+    backend: &'static str,
+    target: Option<String>,
+    specifiers: Option<(String, String)>,
+    /// The secret-service collection the credential is limited to
+    collection: Option<String>,
+    /// The file of a file store
+    path: Option<std::path::PathBuf>,
+    /// The address of the shared data of a mock credential
+    mock: Option<usize>,
 }
 
 impl Entry {
@@ -292,9 +991,34 @@ impl Entry {
         Ok(entry)
     }
 
+    /// Create an entry for the given service and user, after checking
+    /// that neither of them is empty.
+    ///
+    /// This is like [Entry::new], except that a service or user that's
+    /// empty (or only whitespace) gives an [Invalid](Error::Invalid) error
+    /// whose attribute is `service` or `user`.  Some stores reject such entries
+    /// and others treat empty attributes as wildcards (so the entry could match
+    /// credentials it wasn't meant to), so use this when the service and user
+    /// come from configuration or user input that hasn't already been checked.
+    pub fn try_new(service: &str, user: &str) -> Result<Entry> {
+        for (attr, value) in [("service", service), ("user", user)] {
+            if value.trim().is_empty() {
+                return Err(Error::Invalid(
+                    attr.to_string(),
+                    "cannot be empty or whitespace".to_string(),
+                ));
+            }
+        }
+        Entry::new(service, user)
+    }
+
     /// Create an entry for the given target, service, and user.
     ///
-    /// The default credential builder is used.
+    /// The default credential builder is used.  The meaning of the target
+    /// is store-specific (see the documentation of each store).  In particular,
+    /// on Windows the target is used verbatim as the credential's _target name_,
+    /// so you can read and write credentials that other tools store under an
+    /// exact name (such as `git:https://github.com`).
     pub fn new_with_target(target: &str, service: &str, user: &str) -> Result<Entry> {
         debug!("creating entry with service {service}, user {user}, and target {target}");
         let entry = build_default_credential(Some(target), service, user)?;
@@ -302,22 +1026,293 @@ impl Entry {
         Ok(entry)
     }
 
+    /// Create an entry for the given service and the user who is logged in.
+    ///
+    /// The user is the OS login name of the user running this process
+    /// (as given by the `whoami` crate), and the default credential builder is used,
+    /// just as it is by [Entry::new].  The login name is looked up each time
+    /// this is called; use [username](Entry::username) to find out which name was used.
+    #[cfg(feature = "current-user")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "current-user")))]
+    pub fn new_for_current_user(service: &str) -> Result<Entry> {
+        Entry::new(service, &current_user())
+    }
+
+    /// Create an entry for the given service and user in the given scope.
+    ///
+    /// The default credential builder is used.  Entries created with
+    /// [Entry::new] are in the [User](Scope::User) scope.  Entries in the
+    /// [Machine](Scope::Machine) scope are stored in the macOS System keychain
+    /// and in Windows credentials persisted with `CRED_PERSIST_LOCAL_MACHINE`;
+    /// on other stores, creating them fails with a
+    /// [NotSupportedByStore](Error::NotSupportedByStore) error.
+    ///
+    /// Writing machine-scope credentials usually requires elevated privileges;
+    /// without them, setting a password fails with a
+    /// [NoStorageAccess](Error::NoStorageAccess) error.
+    pub fn new_in_scope(scope: Scope, service: &str, user: &str) -> Result<Entry> {
+        debug!("creating entry with service {service}, user {user}, and scope {scope:?}");
+        let service = namespaced_service(service);
+        let credential =
+            with_default_builder(|builder| builder.build_in_scope(scope, &service, user))?;
+        let entry = Entry {
+            inner: credential.into(),
+        };
+        debug!("created entry {:?}", entry.inner);
+        Ok(entry)
+    }
+
+    /// Create an entry for the given service and user whose credential
+    /// is in the macOS keychain in the file at the given path.
+    ///
+    /// If there is no keychain file at the path, the entry's operations
+    /// fail with an [Invalid](Error::Invalid) error whose attribute is `keychain path`;
+    /// the keychain is never created.
+    #[cfg(all(target_os = "macos", feature = "apple-native"))]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "macos")))]
+    pub fn new_with_keychain_path(
+        path: &std::path::Path,
+        service: &str,
+        user: &str,
+    ) -> Result<Entry> {
+        let service = namespaced_service(service);
+        let credential = macos::MacCredential::new_with_keychain_path(path, &service, user)?;
+        Ok(Entry::new_with_credential(Box::new(credential)))
+    }
+
+    /// Create an entry for the given service and user whose credential
+    /// is in the given keychain access group.
+    ///
+    /// Access groups let apps in the same app group share credentials.
+    /// On macOS and iOS, the credential is in the data protection keychain
+    /// (the `Protected` target on macOS), and is created and searched for
+    /// with the group as its `kSecAttrAccessGroup`.  The group must be in
+    /// entitlement form (such as `ABCDE12345.com.example.shared` or
+    /// `group.com.example.shared`), or this fails with an
+    /// [Invalid](Error::Invalid) error whose attribute is `access group`.
+    ///
+    /// On other platforms, the group is ignored, and this is the same as [Entry::new].
+    pub fn new_with_access_group(group: &str, service: &str, user: &str) -> Result<Entry> {
+        debug!("creating entry with service {service}, user {user}, and access group {group}");
+        #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+        {
+            let service = namespaced_service(service);
+            let credential = ios::IosCredential::new_with_access_group(group, &service, user)?;
+            let entry = Entry::new_with_credential(Box::new(credential));
+            debug!("created entry {:?}", entry.inner);
+            Ok(entry)
+        }
+        #[cfg(not(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native")))]
+        Entry::new(service, user)
+    }
+
+    /// Create an entry for the given user of a network service, such as
+    /// an IMAP server, identified by its protocol, host, and (optionally) port.
+    ///
+    /// When the default credential builder is the secret-service one, the
+    /// credential's item follows GNOME's network schema, so that other
+    /// applications that keep network passwords can find it (see
+    /// `SsCredential::new_network` in the `secret_service` module).
+    /// With any other builder, the credential is made by the default credential
+    /// builder with a service composed from the details, such as
+    /// `imap://mail.example.com:993`, so every application that composes
+    /// it the same way shares the credential.
+    /// In neither case is the service qualified by the [app namespace](set_app_namespace).
+    pub fn new_network(protocol: &str, host: &str, port: Option<u16>, user: &str) -> Result<Entry> {
+        debug!("creating network entry for {protocol} on {host}, port {port:?}, user {user}");
+        let credential =
+            with_default_builder(|builder| build_network(builder, protocol, host, port, user))?;
+        let entry = Entry::new_with_credential(credential);
+        debug!("created entry {:?}", entry.inner);
+        Ok(entry)
+    }
+
+    /// Create an entry for the given service and user whose credential
+    /// is synchronized across the user's devices by iCloud Keychain.
+    ///
+    /// On macOS and iOS, when the default credential builder is a keychain
+    /// one, the credential is in the data protection keychain and is read
+    /// and written with a `kSecAttrSynchronizable` of true, so a local item
+    /// for the same service and user is left alone.
+    /// See `IosCredential::new_synchronizable` in the `ios` module
+    /// for the details.
+    ///
+    /// With any other default credential builder, and on other platforms,
+    /// this is the same as [Entry::new].
+    pub fn new_synchronizable(service: &str, user: &str) -> Result<Entry> {
+        debug!("creating synchronizable entry with service {service} and user {user}");
+        #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+        if with_default_builder(is_keychain_builder) {
+            let service = namespaced_service(service);
+            let credential = ios::IosCredential::new_synchronizable(&service, user)?;
+            let entry = Entry::new_with_credential(Box::new(credential));
+            debug!("created entry {:?}", entry.inner);
+            return Ok(entry);
+        }
+        Entry::new(service, user)
+    }
+
+    /// Create an entry for the given service and user whose credential
+    /// is given a human-readable label when it's created.
+    ///
+    /// The default credential builder is used.  The label is what tools
+    /// such as Keychain Access and seahorse show for the credential: it's the
+    /// keychain item's label on macOS, the item's label on the secret-service,
+    /// and the credential's comment on Windows.  Other stores ignore it.
+    pub fn new_with_label(label: &str, service: &str, user: &str) -> Result<Entry> {
+        debug!("creating entry with service {service}, user {user}, and label {label}");
+        let service = namespaced_service(service);
+        let credential =
+            with_default_builder(|builder| builder.build_with_label(label, &service, user))?;
+        let entry = Entry {
+            inner: credential.into(),
+        };
+        debug!("created entry {:?}", entry.inner);
+        Ok(entry)
+    }
+
     /// Create an entry from a credential that may be in any credential store.
     pub fn new_with_credential(credential: Box<Credential>) -> Entry {
         debug!("create entry from {credential:?}");
-        Entry { inner: credential }
+        Entry {
+            inner: credential.into(),
+        }
+    }
+
+    /// Create an entry from a credential that may be in any credential store.
+    ///
+    /// This is the same as [new_with_credential](Entry::new_with_credential),
+    /// and is the inverse of [as_credential](Entry::as_credential).
+    /// Since an entry doesn't depend on the store of its credential,
+    /// entries made this way from credentials in different stores
+    /// can be kept together (for example, in a `Vec<Entry>`).
+    pub fn from_credential(credential: Box<Credential>) -> Entry {
+        Entry::new_with_credential(credential)
+    }
+
+    /// Run an operation on this entry's credential, logging (at debug level)
+    /// when it starts, and whether it succeeded and how long it took when it ends.
+    ///
+    /// The entry is logged with the debug format of its credential, which
+    /// names the store but never includes a secret.
+    fn logged<T>(&self, operation: &str, f: impl FnOnce(&Credential) -> Result<T>) -> Result<T> {
+        #[cfg(feature = "tracing")]
+        let _span = {
+            let (service, user) = self.inner.get_specifiers().unwrap_or_default();
+            tracing::debug_span!(
+                "keyring",
+                operation,
+                backend = self.inner.backend_name(),
+                service,
+                user
+            )
+            .entered()
+        };
+        log_timed(operation, &format_args!("entry {:?}", self.inner), || {
+            f(self.inner.as_ref())
+        })
+    }
+
+    /// Return this entry's wrapped credential, as a [Credential] trait object.
+    ///
+    /// This gives direct access to the [CredentialApi](credential::CredentialApi) methods of the credential,
+    /// whichever store it's in.  (To get at the concrete credential type,
+    /// use [get_credential](Entry::get_credential) instead.)
+    pub fn as_credential(&self) -> &Credential {
+        self.inner.as_ref()
+    }
+
+    /// Find all the existing entries for the given service.
+    ///
+    /// The default credential builder is used to search its store;
+    /// the returned entries can be used to get, set, and delete
+    /// their credentials just like any other entries.  If there
+    /// are no matching credentials in the store, an empty vector is returned.
+    ///
+    /// Returns a [NotSupportedByStore](Error::NotSupportedByStore) error
+    /// if the default credential store can't search for credentials.
+    pub fn search(service: &str) -> Result<Vec<Entry>> {
+        let credentials = log_timed("search", &format_args!("service {service}"), || {
+            with_default_builder(|builder| builder.search(&namespaced_service(service)))
+        })?;
+        debug!("found {} entries with service {service}", credentials.len());
+        Ok(credentials
+            .into_iter()
+            .map(|inner| Entry {
+                inner: inner.into(),
+            })
+            .collect())
+    }
+
+    /// Count the existing entries for the given service, without making them.
+    ///
+    /// This gives the number of entries [search](Entry::search) would
+    /// return, but the default credential store only has to list its
+    /// credentials (and on the macOS keychain and Windows, it does so without
+    /// reading their secrets).
+    ///
+    /// Returns a [NotSupportedByStore](Error::NotSupportedByStore) error
+    /// if the default credential store can't search for credentials.
+    pub fn count(service: &str) -> Result<usize> {
+        let count = log_timed("count entries", &format_args!("service {service}"), || {
+            with_default_builder(|builder| builder.count(&namespaced_service(service)))
+        })?;
+        debug!("counted {count} entries with service {service}");
+        Ok(count)
+    }
+
+    /// Find all the existing entries whose credentials have all the given attributes.
+    ///
+    /// The default credential builder is used to search its store, as it
+    /// is by [search](Entry::search).  A `service` attribute, if given,
+    /// is qualified by the [app namespace](set_app_namespace), just as the
+    /// services of new entries are.  Which other attributes can be queried
+    /// depends on the store: the secret-service can query any item attribute;
+    /// the macOS keychain can query `service`, `username`, `label`, `comment`,
+    /// and `description`; Windows can query `service`, `username`, `comment`,
+    /// `target_alias`, and any custom attributes.  If there are no matching
+    /// credentials in the store, an empty vector is returned.
+    ///
+    /// Returns a [NotSupportedByStore](Error::NotSupportedByStore) error
+    /// if the default credential store can't query by attributes.
+    pub fn query(attributes: &HashMap<String, String>) -> Result<Vec<Entry>> {
+        let mut names: Vec<&str> = attributes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        debug!("querying for entries with attributes {names:?}");
+        let service = attributes.get("service").map(|s| namespaced_service(s));
+        let mut query: HashMap<&str, &str> = attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        if let Some(service) = service.as_deref() {
+            query.insert("service", service);
+        }
+        let credentials = with_default_builder(|builder| builder.query(&query))?;
+        debug!("found {} entries with attributes", credentials.len());
+        Ok(credentials
+            .into_iter()
+            .map(|inner| Entry {
+                inner: inner.into(),
+            })
+            .collect())
     }
 
     /// Set the password for this entry.
     ///
+    /// If the entry's credential already exists, only its password is changed:
+    /// the [attributes](Entry::get_attributes) it has are kept.  To start over
+    /// with a credential that has only the password, use
+    /// [set_password_replacing_attributes](Entry::set_password_replacing_attributes).
+    ///
     /// Can return an [Ambiguous](Error::Ambiguous) error
     /// if there is more than one platform credential
     /// that matches this entry.  This can only happen
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn set_password(&self, password: &str) -> Result<()> {
-        debug!("set password for entry {:?}", self.inner);
-        self.inner.set_password(password)
+        self.logged("set password", |credential| {
+            credential.set_password(password)
+        })
     }
 
     /// Set the secret for this entry.
@@ -328,8 +1323,214 @@ impl Entry {
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn set_secret(&self, secret: &[u8]) -> Result<()> {
-        debug!("set secret for entry {:?}", self.inner);
-        self.inner.set_secret(secret)
+        self.logged("set secret", |credential| credential.set_secret(secret))
+    }
+
+    /// Set the password for this entry, which expires after the given time.
+    ///
+    /// Once the password has expired, reading it returns
+    /// a [NoEntry](Error::NoEntry) error.  See the
+    /// [Expiring Credentials](crate#expiring-credentials) section
+    /// for which stores support expiration.
+    pub fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        self.logged(&format!("set password with expiry {ttl:?}"), |credential| {
+            credential.set_password_with_expiry(password, ttl)
+        })
+    }
+
+    /// Set the secret for this entry, which expires after the given time.
+    ///
+    /// See [set_password_with_expiry](Entry::set_password_with_expiry) for details.
+    pub fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        self.logged(&format!("set secret with expiry {ttl:?}"), |credential| {
+            credential.set_secret_with_expiry(secret, ttl)
+        })
+    }
+
+    /// Set the password for this entry on a new credential, replacing
+    /// any existing credential (and so dropping its attributes).
+    ///
+    /// The new credential has the attributes that the store gives every
+    /// new credential (such as a default label).  The macOS and iOS keychains
+    /// and the secret-service replace the credential in a single operation.
+    /// Windows sets the password first and drops the attributes in the
+    /// same write, and stores without attributes just set the password, so
+    /// on all of these a failure leaves the old credential as it was.  Other
+    /// (third-party) stores may delete the existing credential before
+    /// setting the password; see
+    /// [set_secret_replacing_attributes](credential::CredentialApi::set_secret_replacing_attributes).
+    pub fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        self.logged("set password replacing attributes", |credential| {
+            credential.set_password_replacing_attributes(password)
+        })
+    }
+
+    /// Set the secret for this entry on a new credential, replacing
+    /// any existing credential (and so dropping its attributes).
+    ///
+    /// See [set_password_replacing_attributes](Entry::set_password_replacing_attributes)
+    /// for details.
+    pub fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        self.logged("set secret replacing attributes", |credential| {
+            credential.set_secret_replacing_attributes(secret)
+        })
+    }
+
+    /// Check that a credential can be written to this entry's store.
+    ///
+    /// This is useful before starting a multi-step flow (such as onboarding)
+    /// that will end by setting this entry's password: it surfaces problems
+    /// like a locked keychain or missing permissions up front.
+    ///
+    /// _This has transient side effects._  A scratch credential, with this entry's
+    /// service and a unique user whose name starts with `keyring-can-set-`,
+    /// is written to the same place as this entry's credential and then deleted.
+    /// This entry's own credential is never read or written.
+    /// If the write or the delete fails, its error is returned.
+    ///
+    /// Returns a [NotSupportedByStore](Error::NotSupportedByStore) error if the
+    /// store can't make a scratch credential.
+    pub fn can_set(&self) -> Result<()> {
+        self.logged("check can set", |credential| {
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let user = format!("keyring-can-set-{}-{nanos}", std::process::id());
+            let scratch = credential.sibling(&user)?;
+            scratch.set_password("keyring scratch password")?;
+            scratch.delete_credential()
+        })
+    }
+
+    /// Update the password on this entry's existing credential.
+    ///
+    /// Unlike [set_password](Entry::set_password), this never creates a credential:
+    /// returns a [NoEntry](Error::NoEntry) error if there isn't one.
+    pub fn update_password(&self, password: &str) -> Result<()> {
+        self.logged("update password", |credential| {
+            credential.update_password(password)
+        })
+    }
+
+    /// Update the secret on this entry's existing credential.
+    ///
+    /// Unlike [set_secret](Entry::set_secret), this never creates a credential:
+    /// returns a [NoEntry](Error::NoEntry) error if there isn't one.
+    pub fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        self.logged("update secret", |credential| {
+            credential.update_secret(secret)
+        })
+    }
+
+    /// Replace the password on this entry's existing credential with `new`,
+    /// but only if its current password is `expected`.
+    ///
+    /// Returns `Ok(true)` if the password was replaced, and `Ok(false)` if
+    /// the current password is something else (in which case the credential
+    /// is left alone).  Returns a [NoEntry](Error::NoEntry) error if there
+    /// isn't a credential for this entry.
+    ///
+    /// No credential store can compare and swap atomically, so this reads the
+    /// password, compares it, and then writes the new one.  Calls made in this
+    /// process are serialized, so they can't interfere with each other, but
+    /// there is a race window between the read and the write: another process
+    /// (or a write made in this process other than by this method) can change
+    /// the credential in between, and that change is then lost.  Don't rely on
+    /// this for mutual exclusion between processes unless something else
+    /// keeps them from writing the credential at the same time.
+    pub fn compare_and_swap(&self, expected: &str, new: &str) -> Result<bool> {
+        static SWAPPING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+        self.logged("compare and swap", |credential| {
+            let _guard = SWAPPING.lock().unwrap_or_else(|err| err.into_inner());
+            let current = Zeroizing::new(credential.get_password()?);
+            if !constant_time_eq(current.as_bytes(), expected.as_bytes()) {
+                return Ok(false);
+            }
+            credential.update_password(new)?;
+            Ok(true)
+        })
+    }
+
+    /// Check whether the password saved for this entry is the given candidate,
+    /// without returning it.
+    ///
+    /// This is meant for "confirm your password" checks.  The stored password
+    /// is read into memory that's wiped as soon as it has been compared, and
+    /// the comparison takes time that depends only on the passwords' lengths,
+    /// so it doesn't reveal how much of the candidate was right.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential
+    /// for this entry, and otherwise the errors of [get_password](Entry::get_password).
+    pub fn verify(&self, candidate: &str) -> Result<bool> {
+        self.logged("verify", |credential| {
+            let current = Zeroizing::new(credential.get_password()?);
+            Ok(constant_time_eq(current.as_bytes(), candidate.as_bytes()))
+        })
+    }
+
+    /// Replace the password on this entry's existing credential,
+    /// returning the password it replaces.
+    ///
+    /// This is meant for rotating keys: once the new password is stored,
+    /// the old one can be revoked wherever it was issued.
+    /// The credential is [updated](Entry::update_password) in place,
+    /// so on stores that update atomically (such as the macOS keychain,
+    /// which uses `SecItemUpdate`) there's no moment when neither
+    /// password is stored.  If the update fails, the old password is written
+    /// back (in case the update was partly done) and the update's error is returned.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential
+    /// for this entry, in which case nothing is written.
+    pub fn rotate(&self, new_password: &str) -> Result<String> {
+        self.logged("rotate password", |credential| {
+            let old = credential.get_password()?;
+            if let Err(err) = credential.update_password(new_password) {
+                if let Err(restore) = credential.update_password(&old) {
+                    warn!("can't restore the old password after a failed rotation: {restore}");
+                }
+                return Err(err);
+            }
+            Ok(old)
+        })
+    }
+
+    /// Replace the secret on this entry's existing credential,
+    /// returning the secret it replaces.
+    ///
+    /// See [rotate](Entry::rotate) for details.
+    pub fn rotate_secret(&self, new_secret: &[u8]) -> Result<Vec<u8>> {
+        self.logged("rotate secret", |credential| {
+            let old = credential.get_secret()?;
+            if let Err(err) = credential.update_secret(new_secret) {
+                if let Err(restore) = credential.update_secret(&old) {
+                    warn!("can't restore the old secret after a failed rotation: {restore}");
+                }
+                return Err(err);
+            }
+            Ok(old)
+        })
+    }
+
+    /// Create a credential for this entry with the given password.
+    ///
+    /// Unlike [set_password](Entry::set_password), this never replaces a credential:
+    /// returns an [AlreadyExists](Error::AlreadyExists) error if there is one.
+    pub fn create_password(&self, password: &str) -> Result<()> {
+        self.logged("create password", |credential| {
+            credential.create_password(password)
+        })
+    }
+
+    /// Create a credential for this entry with the given secret.
+    ///
+    /// Unlike [set_secret](Entry::set_secret), this never replaces a credential:
+    /// returns an [AlreadyExists](Error::AlreadyExists) error if there is one.
+    pub fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        self.logged("create secret", |credential| {
+            credential.create_secret(secret)
+        })
     }
 
     /// Retrieve the password saved for this entry.
@@ -342,8 +1543,7 @@ impl Entry {
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn get_password(&self) -> Result<String> {
-        debug!("get password from entry {:?}", self.inner);
-        self.inner.get_password()
+        self.logged("get password", |credential| credential.get_password())
     }
 
     /// Retrieve the secret saved for this entry.
@@ -356,17 +1556,298 @@ impl Entry {
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn get_secret(&self) -> Result<Vec<u8>> {
-        debug!("get secret from entry {:?}", self.inner);
-        self.inner.get_secret()
+        self.logged("get secret", |credential| credential.get_secret())
     }
 
-    /// Get the attributes on the underlying credential for this entry.
+    /// Retrieve the password saved for this entry, if there is one.
     ///
-    /// Some of the underlying credential stores allow credentials to have named attributes
-    /// that can be set to string values. See the documentation for each credential store
-    /// for a list of which attribute names are supported by that store.
+    /// Returns `Ok(None)` where [get_password](Entry::get_password) would return
+    /// a [NoEntry](Error::NoEntry) error, and any other error just as it would.
+    pub fn get_password_opt(&self) -> Result<Option<String>> {
+        self.logged("get password opt", |credential| {
+            match credential.get_password() {
+                Ok(password) => Ok(Some(password)),
+                Err(Error::NoEntry) => Ok(None),
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    /// Set the password for this entry to the base64 encoding of the given bytes.
     ///
-    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
+    /// This stores binary data through the password API, so it can be read
+    /// by anything that reads the password, on any store.  Read it back with
+    /// [get_password_b64](Entry::get_password_b64).
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn set_password_b64(&self, bytes: &[u8]) -> Result<()> {
+        use base64::Engine;
+        let encoded = Zeroizing::new(base64::engine::general_purpose::STANDARD.encode(bytes));
+        self.logged("set password b64", |credential| {
+            credential.set_password(&encoded)
+        })
+    }
+
+    /// Retrieve the bytes saved with [set_password_b64](Entry::set_password_b64).
+    ///
+    /// Returns a [BadEncoding](Error::BadEncoding) error (holding the stored
+    /// password's bytes) if the password isn't valid base64.
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn get_password_b64(&self) -> Result<Vec<u8>> {
+        use base64::Engine;
+        self.logged("get password b64", |credential| {
+            let encoded = Zeroizing::new(credential.get_password()?);
+            match base64::engine::general_purpose::STANDARD.decode(encoded.as_bytes()) {
+                Ok(bytes) => Ok(bytes),
+                Err(_) => Err(Error::BadEncoding(encoded.as_bytes().to_vec())),
+            }
+        })
+    }
+
+    /// Retrieve the secret saved for this entry, if there is one.
+    ///
+    /// Returns `Ok(None)` where [get_secret](Entry::get_secret) would return
+    /// a [NoEntry](Error::NoEntry) error, and any other error just as it would.
+    pub fn get_secret_opt(&self) -> Result<Option<Vec<u8>>> {
+        self.logged("get secret opt", |credential| {
+            match credential.get_secret() {
+                Ok(secret) => Ok(Some(secret)),
+                Err(Error::NoEntry) => Ok(None),
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    /// Check whether the underlying credential for this entry exists.
+    ///
+    /// Where the store allows, this doesn't retrieve the credential's secret,
+    /// so it's cheaper (and leaves fewer copies of the secret in memory)
+    /// than calling [get_password](Entry::get_password) and checking for a
+    /// [NoEntry](Error::NoEntry) error.  Expired credentials don't exist.
+    ///
+    /// If there is more than one platform credential that matches this
+    /// entry, this returns `true`: use [get_secret](Entry::get_secret)
+    /// to get an [Ambiguous](Error::Ambiguous) error describing them.
+    pub fn exists(&self) -> Result<bool> {
+        self.logged("check existence", |credential| credential.exists())
+    }
+
+    /// Get the time at which the underlying credential for this entry was last written.
+    ///
+    /// This is the modification date of the keychain item on macOS and iOS,
+    /// the `LastWritten` time of the credential on Windows, and the `Modified`
+    /// property of the item on the secret-service.  Stores that don't record
+    /// when credentials are written (such as keyutils) return `None`.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
+    pub fn last_modified(&self) -> Result<Option<SystemTime>> {
+        self.logged("get last modified time", |credential| {
+            credential.last_modified()
+        })
+    }
+
+    /// Get the time at which the underlying credential for this entry was created.
+    ///
+    /// This is the creation date of the keychain item on macOS and iOS,
+    /// and the `Created` property of the item on the secret-service.
+    /// Stores that don't record when credentials are created (such as
+    /// Windows and keyutils) return `None`.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
+    pub fn created(&self) -> Result<Option<SystemTime>> {
+        self.logged("get creation time", |credential| credential.created())
+    }
+
+    /// Describe the underlying credential for this entry, without revealing its secret.
+    ///
+    /// The secret is read along with the credential's times (in one
+    /// operation, on stores that allow it; see
+    /// [get_secret_with_times](credential::CredentialApi::get_secret_with_times))
+    /// so that its length can be reported, and then wiped
+    /// from memory.  The service and user are those of
+    /// [get_specifiers](Entry::get_specifiers), or empty if they aren't known.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
+    pub fn get_info(&self) -> Result<CredentialInfo> {
+        let (secret, created, modified) =
+            self.logged("get info", |credential| credential.get_secret_with_times())?;
+        let secret_len = Zeroizing::new(secret).len();
+        let (service, username) = self.get_specifiers().unwrap_or_default();
+        Ok(CredentialInfo {
+            service,
+            username,
+            created,
+            modified,
+            secret_len,
+        })
+    }
+
+    /// Retrieve the password saved for this entry along with a
+    /// [summary](CredentialSummary) of its underlying credential.
+    ///
+    /// The password is read along with the times in the summary, as
+    /// [get_info](Entry::get_info) reads them, and then the attributes are read
+    /// as they are by [get_attributes](Entry::get_attributes).
+    /// The service and user are those of [get_specifiers](Entry::get_specifiers),
+    /// or empty if they aren't known.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry,
+    /// a [BadEncoding](Error::BadEncoding) error if its secret isn't UTF-8,
+    /// and an [Ambiguous](Error::Ambiguous) error if more than one
+    /// platform credential matches this entry.
+    pub fn get_password_and_credential(&self) -> Result<(String, CredentialSummary)> {
+        let (secret, created, modified, attributes) =
+            self.logged("get password and credential", |credential| {
+                let (secret, created, modified) = credential.get_secret_with_times()?;
+                Ok((secret, created, modified, credential.get_attributes()?))
+            })?;
+        let password = error::decode_password(secret)?;
+        let (service, username) = self.get_specifiers().unwrap_or_default();
+        let summary = CredentialSummary {
+            service,
+            username,
+            backend: self.backend_name(),
+            created,
+            modified,
+            attributes,
+            entry: self.clone(),
+        };
+        Ok((password, summary))
+    }
+
+    /// Find all the existing entries for the given service, and describe their credentials.
+    ///
+    /// This is like [search](Entry::search), except that each entry is paired with
+    /// the [info](Entry::get_info) for its credential.  Entries whose
+    /// credentials vanish (or expire) before they can be described are left out.
+    pub fn list_detailed(service: &str) -> Result<Vec<(Entry, CredentialInfo)>> {
+        in_batch(|| {
+            let mut listed = Vec::new();
+            for entry in Entry::search(service)? {
+                match entry.get_info() {
+                    Ok(mut info) => {
+                        if info.service.is_empty() {
+                            info.service = service.to_string();
+                        }
+                        listed.push((entry, info));
+                    }
+                    Err(Error::NoEntry) => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+            Ok(listed)
+        })
+    }
+
+    /// Retrieve the passwords of all the platform credentials that match this entry,
+    /// each along with an entry for its credential.
+    ///
+    /// This is how to resolve the [Ambiguous](Error::Ambiguous) error that
+    /// [get_password](Entry::get_password) returns when more than one
+    /// credential matches this entry: each of the returned entries refers
+    /// to exactly one of the matching credentials.  If just one credential
+    /// matches, it is returned with this entry, and if none do, the returned
+    /// vector is empty.
+    pub fn get_all_passwords(&self) -> Result<Vec<(Entry, String)>> {
+        self.logged("get all passwords", |credential| {
+            match credential.get_password() {
+                Ok(password) => Ok(vec![(self.clone(), password)]),
+                Err(Error::NoEntry) => Ok(Vec::new()),
+                Err(Error::Ambiguous(credentials)) => credentials
+                    .into_iter()
+                    .map(|credential| {
+                        let entry = Entry::new_with_credential(credential);
+                        let password = entry.get_password()?;
+                        Ok((entry, password))
+                    })
+                    .collect(),
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    /// Retrieve the password saved for this entry, wrapped so that
+    /// it is wiped from memory when dropped.
+    ///
+    /// This has the same behavior (and errors) as [get_password](Entry::get_password).
+    #[cfg(feature = "zeroizing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zeroizing")))]
+    pub fn get_password_zeroizing(&self) -> Result<Zeroizing<String>> {
+        Ok(Zeroizing::new(self.get_password()?))
+    }
+
+    /// Retrieve the secret saved for this entry, wrapped so that
+    /// it is wiped from memory when dropped.
+    ///
+    /// This has the same behavior (and errors) as [get_secret](Entry::get_secret).
+    #[cfg(feature = "zeroizing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zeroizing")))]
+    pub fn get_secret_zeroizing(&self) -> Result<Zeroizing<Vec<u8>>> {
+        Ok(Zeroizing::new(self.get_secret()?))
+    }
+
+    /// Get the service and user that identify this entry's credential, if known.
+    ///
+    /// This is useful for entries returned by [search](Entry::search).
+    /// Some stores don't keep the service and user of every
+    /// credential, in which case this returns `None`.
+    ///
+    /// If an [app namespace](set_app_namespace) is set,
+    /// it's removed from the returned service.
+    pub fn get_specifiers(&self) -> Option<(String, String)> {
+        let (service, user) = self.inner.get_specifiers()?;
+        let guard = APP_NAMESPACE
+            .read()
+            .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+        Some((remove_namespace(guard.as_deref(), service), user))
+    }
+
+    /// Get the user of this entry's credential, if known.
+    ///
+    /// This is the user half of [get_specifiers](Entry::get_specifiers), so it's
+    /// useful for finding out which login name an entry made with
+    /// `new_for_current_user` has.
+    ///
+    /// The user is returned as an owned string because an entry doesn't keep
+    /// its user: the store of its credential reports it.  It's `None` when the
+    /// store doesn't know it, which can happen for entries returned by
+    /// [search](Entry::search) or [query](Entry::query) whose credentials were
+    /// written by other applications (or that only have a target).
+    pub fn username(&self) -> Option<String> {
+        Some(self.inner.get_specifiers()?.1)
+    }
+
+    /// Get the service of this entry's credential, if known.
+    ///
+    /// This is the service half of [get_specifiers](Entry::get_specifiers),
+    /// so any [app namespace](set_app_namespace) is removed from it.
+    /// Like the [user](Entry::username), it's returned as an owned string
+    /// and is `None` when the store doesn't know it, which can happen for entries
+    /// returned by [search](Entry::search) or [query](Entry::query).
+    pub fn service(&self) -> Option<String> {
+        Some(self.get_specifiers()?.0)
+    }
+
+    /// Get the target of this entry's credential, if it has one.
+    ///
+    /// What counts as a target depends on the store.  For example, the
+    /// secret-service reports the `default` target for entries made without one,
+    /// while Windows and keyutils only report targets that were given explicitly
+    /// (and the macOS keychain only reports keychains other than the User keychain).
+    /// Entries returned by [search](Entry::search) or [query](Entry::query)
+    /// have the targets their stores report for the credentials found.
+    pub fn target(&self) -> Option<String> {
+        self.inner.get_target()
+    }
+
+    /// Get the attributes on the underlying credential for this entry.
+    ///
+    /// Some of the underlying credential stores allow credentials to have named attributes
+    /// that can be set to string values. See the documentation for each credential store
+    /// for a list of which attribute names are supported by that store.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry.
     ///
     /// Can return an [Ambiguous](Error::Ambiguous) error
     /// if there is more than one platform credential
@@ -374,8 +1855,39 @@ impl Entry {
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn get_attributes(&self) -> Result<HashMap<String, String>> {
-        debug!("get attributes from entry {:?}", self.inner);
-        self.inner.get_attributes()
+        self.logged("get attributes", |credential| credential.get_attributes())
+    }
+
+    /// Retrieve the password saved for this entry along with the attributes
+    /// on its underlying credential.
+    ///
+    /// This gives the same results as calling [get_password](Entry::get_password)
+    /// and then [get_attributes](Entry::get_attributes), but stores that can
+    /// read both at once (such as the macOS keychain, the secret-service, and
+    /// the Windows credential manager) do it with a single operation.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry,
+    /// and a [BadEncoding](Error::BadEncoding) error if its secret isn't UTF-8.
+    ///
+    /// Can return an [Ambiguous](Error::Ambiguous) error
+    /// if there is more than one platform credential
+    /// that matches this entry.  This can only happen
+    /// on some platforms, and then only if a third-party
+    /// application wrote the ambiguous credential.
+    pub fn get_password_with_attributes(&self) -> Result<(String, HashMap<String, String>)> {
+        let (secret, attributes) = self.get_secret_with_attributes()?;
+        Ok((error::decode_password(secret)?, attributes))
+    }
+
+    /// Retrieve the secret saved for this entry along with the attributes
+    /// on its underlying credential.
+    ///
+    /// See [get_password_with_attributes](Entry::get_password_with_attributes)
+    /// for details.
+    pub fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        self.logged("get secret with attributes", |credential| {
+            credential.get_secret_with_attributes()
+        })
     }
 
     /// Update the attributes on the underlying credential for this entry.
@@ -394,11 +1906,38 @@ impl Entry {
     /// on some platforms, and then only if a third-party
     /// application wrote the ambiguous credential.
     pub fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
-        debug!(
-            "update attributes for entry {:?} from map {attributes:?}",
-            self.inner
-        );
-        self.inner.update_attributes(attributes)
+        // attribute values can be sensitive, so only their names are logged
+        let mut names: Vec<&str> = attributes.keys().copied().collect();
+        names.sort_unstable();
+        let operation = format!("update attributes {names:?}");
+        self.logged(&operation, |credential| {
+            credential.update_attributes(attributes)
+        })
+    }
+
+    /// Move the underlying credential for this entry to a new service and user.
+    ///
+    /// The credential keeps its secret, its attributes, and its target (if any),
+    /// and afterward there is no credential for this entry's service and user.
+    /// This entry still refers to them, though, so make a new entry
+    /// to use the moved credential.
+    ///
+    /// Stores that can change a credential's service and user in place
+    /// (such as the macOS keychain and the secret-service) do so.  The others
+    /// write the new credential before deleting the old one, and if the old one
+    /// can't be deleted, they delete the new one, so the secret is never lost.
+    ///
+    /// Returns a [NoEntry](Error::NoEntry) error if there isn't a credential for this entry,
+    /// and an [AlreadyExists](Error::AlreadyExists) error if there is already
+    /// a credential for the new service and user.
+    ///
+    /// If an [app namespace](set_app_namespace) is set, it's added to the new service,
+    /// so renaming is how to move credentials stored before the namespace was set.
+    pub fn rename(&self, new_service: &str, new_user: &str) -> Result<()> {
+        let operation = format!("rename to service {new_service}, user {new_user}");
+        self.logged(&operation, |credential| {
+            credential.rename(&namespaced_service(new_service), new_user)
+        })
     }
 
     /// Delete the underlying credential for this entry.
@@ -415,8 +1954,26 @@ impl Entry {
     /// structure, which is controlled by Rust.  It only
     /// affects the underlying credential store.
     pub fn delete_credential(&self) -> Result<()> {
-        debug!("delete entry {:?}", self.inner);
-        self.inner.delete_credential()
+        self.logged("delete credential", |credential| {
+            credential.delete_credential()
+        })
+    }
+
+    /// Delete the underlying credential for this entry, if there is one.
+    ///
+    /// Returns `Ok(true)` if a credential was deleted and `Ok(false)` if
+    /// there wasn't one, so cleanup code doesn't have to treat a missing
+    /// credential as an error.  Any other error is returned just as
+    /// [delete_credential](Entry::delete_credential) would return it.
+    pub fn delete_credential_if_exists(&self) -> Result<bool> {
+        self.logged(
+            "delete credential if exists",
+            |credential| match credential.delete_credential() {
+                Ok(()) => Ok(true),
+                Err(Error::NoEntry) => Ok(false),
+                Err(err) => Err(err),
+            },
+        )
     }
 
     /// Return a reference to this entry's wrapped credential.
@@ -424,9 +1981,185 @@ impl Entry {
     /// The reference is of the [Any](std::any::Any) type, so it can be
     /// downgraded to a concrete credential object.  The client must know
     /// what type of concrete object to cast to.
+    ///
+    /// The concrete credential types (such as `windows::WinCredential`
+    /// or `macos::MacCredential`) give access to platform-native data;
+    /// for example, `WinCredential::get_metadata` reads the native
+    /// `CREDENTIALW` fields, and `MacCredential::get_keychain_item`
+    /// finds the native keychain item.  These types only exist on their own
+    /// platforms (and with their store's feature), so code that uses them isn't portable.
     pub fn get_credential(&self) -> &dyn std::any::Any {
         self.inner.as_any()
     }
+
+    /// The name of the store this entry's credential is in.
+    ///
+    /// This is meant for diagnostics, such as reporting which store
+    /// an application is actually using.  The names of this crate's stores are
+    /// `"secret-service"`, `"keyutils"`, `"macos-keychain"`, `"ios-keychain"`
+    /// (which is also used for entries in the macOS Protected keychain),
+    /// `"windows-credential-manager"`, `"file"`, `"mock"`, and `"null"`.
+    /// Client-provided stores that don't name themselves are `"unknown"`.
+    pub fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    /// The optional features supported by the store of this entry's credential.
+    ///
+    /// Use this to find out, say, whether credentials can be given an
+    /// expiration time, rather than assuming it from the platform.
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    /// The locator of this entry's credential, if it has a target or a service and user.
+    fn locator(&self) -> Option<Locator> {
+        let target = self.inner.get_target();
+        let specifiers = self.inner.get_specifiers();
+        if target.is_none() && specifiers.is_none() {
+            return None;
+        }
+        #[allow(unused_mut)]
+        let mut path = None;
+        #[cfg(feature = "file-backend")]
+        if let Some(credential) = self.inner.as_any().downcast_ref::<file::FileCredential>() {
+            path = Some(credential.path().to_path_buf());
+        }
+        // default mocks all have empty specifiers, but none of them share data
+        let mock = self
+            .inner
+            .as_any()
+            .downcast_ref::<mock::MockCredential>()
+            .map(|credential| Arc::as_ptr(&credential.inner) as usize);
+        Some(Locator {
+            backend: self.inner.backend_name(),
+            target,
+            specifiers,
+            collection: self.collection(),
+            path,
+            mock,
+        })
+    }
+
+    /// Choose whether this entry's credential may unlock its store.
+    ///
+    /// Only the secret-service store unlocks locked items and collections,
+    /// which it does by default (possibly prompting the user).  Turning
+    /// auto-unlock off makes operations on locked items fail with
+    /// a [Locked](Error::Locked) error instead, so that the user is never prompted.
+    /// On other stores, this returns the entry unchanged.
+    pub fn with_auto_unlock(self, auto_unlock: bool) -> Entry {
+        self.with_store_option(|credential| auto_unlock_option(credential, auto_unlock))
+    }
+
+    /// Choose how long this entry's credential persists when it's written.
+    ///
+    /// Only the Windows store lets you choose: its credentials are persisted
+    /// with `CRED_PERSIST_SESSION`, `CRED_PERSIST_LOCAL_MACHINE`, or (by default)
+    /// `CRED_PERSIST_ENTERPRISE`, according to the given persistence.
+    /// On other stores, this returns the entry unchanged.
+    pub fn with_persistence(self, persistence: Persistence) -> Entry {
+        self.with_store_option(|credential| {
+            #[cfg(all(target_os = "windows", feature = "windows-native"))]
+            if let Some(credential) = credential.as_any().downcast_ref::<windows::WinCredential>() {
+                let credential = credential.clone().with_persistence(persistence);
+                return Some(Box::new(credential));
+            }
+            let _ = (credential, persistence);
+            None
+        })
+    }
+
+    /// Choose the text shown to the user if reading this entry's secret
+    /// requires their authorization.
+    ///
+    /// Only the macOS store uses this: the text becomes the
+    /// operation prompt (`kSecUseOperationPrompt`) of keychain reads, which
+    /// is shown in the authorization dialogs that macOS lets apps customize.
+    /// To use a prompt for a single read, call the read on the returned entry
+    /// (for example, `entry.clone().with_prompt("...").get_password()`).
+    /// On other stores, this returns the entry unchanged.
+    pub fn with_prompt(self, prompt: &str) -> Entry {
+        self.with_store_option(|credential| {
+            #[cfg(all(target_os = "macos", feature = "apple-native"))]
+            if let Some(credential) = credential.as_any().downcast_ref::<macos::MacCredential>() {
+                let credential = credential.clone().with_prompt(prompt);
+                return Some(Box::new(credential));
+            }
+            let _ = (credential, prompt);
+            None
+        })
+    }
+
+    /// Choose that this entry's operations fail, rather than prompt the user,
+    /// if they need the user to unlock or authorize something.
+    ///
+    /// This is for daemons, CI jobs, and other processes that must never
+    /// wait on a dialog that no one will answer.  Where a store would
+    /// prompt, the operation fails with a [Locked](Error::Locked) error instead.
+    /// On the secret-service this turns off [auto-unlock](Entry::with_auto_unlock),
+    /// on macOS it makes keychain reads fail rather than show authentication UI,
+    /// on iOS it does the same for keychain reads and writes, and on other stores,
+    /// which never prompt, it does nothing.
+    pub fn with_no_prompt(self) -> Entry {
+        self.with_store_option(|credential| {
+            #[cfg(all(target_os = "macos", feature = "apple-native"))]
+            if let Some(credential) = credential.as_any().downcast_ref::<macos::MacCredential>() {
+                let credential = credential.clone().with_no_prompt();
+                return Some(Box::new(credential));
+            }
+            #[cfg(all(any(target_os = "macos", target_os = "ios"), feature = "apple-native"))]
+            if let Some(credential) = credential.as_any().downcast_ref::<ios::IosCredential>() {
+                let credential = credential.clone().with_no_prompt();
+                return Some(Box::new(credential));
+            }
+            auto_unlock_option(credential, false)
+        })
+    }
+
+    /// Apply a store-specific option to this entry's store credential,
+    /// keeping any wrappers (such as a [timeout](Entry::with_timeout)) around it.
+    ///
+    /// The option gives the changed store credential, or `None` if it
+    /// doesn't apply to the store; then this returns the entry unchanged.
+    fn with_store_option(self, option: impl Fn(&Credential) -> Option<Box<Credential>>) -> Entry {
+        Entry {
+            inner: apply_store_option(&self.inner, &option),
+        }
+    }
+}
+
+/// Apply a store-specific option to the given credential or, if it
+/// wraps another, to the credential it wraps, and wrap the result again.
+fn apply_store_option(
+    credential: &Arc<Credential>,
+    option: &dyn Fn(&Credential) -> Option<Box<Credential>>,
+) -> Arc<Credential> {
+    if let Some(rewrapped) = credential.rewrap(&|inner| apply_store_option(inner, option)) {
+        return rewrapped.into();
+    }
+    match option(credential.as_ref()) {
+        Some(changed) => changed.into(),
+        None => credential.clone(),
+    }
+}
+
+/// The given credential with auto-unlock turned on or off,
+/// if it's a secret-service credential.
+fn auto_unlock_option(credential: &Credential, auto_unlock: bool) -> Option<Box<Credential>> {
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+        feature = "secret-service"
+    ))]
+    if let Some(credential) = credential
+        .as_any()
+        .downcast_ref::<secret_service::SsCredential>()
+    {
+        let credential = credential.clone().with_auto_unlock(auto_unlock);
+        return Some(Box::new(credential));
+    }
+    let _ = (credential, auto_unlock);
+    None
 }
 
 #[cfg(doctest)]
@@ -440,8 +2173,440 @@ doc_comment::doctest!("../README.md", readme);
 // Since iOS doesn't use any of these generics, we allow dead code.
 #[allow(dead_code)]
 mod tests {
-    use super::{Entry, Error, Result, credential::CredentialApi};
+    use super::{
+        Entry, Error, Persistence, Result, Scope,
+        credential::{CredentialApi, CredentialBuilder},
+    };
     use std::collections::HashMap;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_entry_is_send_sync_clone() {
+        fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+        assert_send_sync_clone::<Entry>();
+    }
+
+    #[test]
+    fn test_batch_results_in_order() {
+        use crate::mock::MockCredential;
+        let entries: Vec<Entry> = (0..3)
+            .map(|_| Entry::new_with_credential(Box::new(MockCredential::default())))
+            .collect();
+        let results = super::set_many(&[(&entries[0], "first"), (&entries[2], "third")]);
+        assert!(results.iter().all(|r| r.is_ok()), "Can't set passwords");
+        let mock: &MockCredential = entries[0].get_credential().downcast_ref().unwrap();
+        mock.set_error(Error::Invalid("mock".to_string(), "error".to_string()));
+        let results = super::get_many(&entries);
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Err(Error::Invalid(_, _))));
+        assert!(matches!(results[1], Err(Error::NoEntry)));
+        assert_eq!(results[2].as_ref().expect("Can't get password"), "third");
+        let results = super::delete_many(&entries);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[1], Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_build_network() {
+        let builder = crate::mock::default_credential_builder();
+        let credential = super::build_network(
+            builder.as_ref(),
+            "imap",
+            "mail.example.com",
+            Some(993),
+            "alice",
+        )
+        .unwrap();
+        assert_eq!(credential.backend_name(), "mock");
+        assert_eq!(
+            credential.get_specifiers(),
+            Some((
+                "imap://mail.example.com:993".to_string(),
+                "alice".to_string()
+            ))
+        );
+        #[cfg(all(
+            any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"),
+            feature = "secret-service"
+        ))]
+        {
+            let builder = crate::secret_service::default_credential_builder();
+            let credential =
+                super::build_network(builder.as_ref(), "imap", "mail.example.com", None, "alice")
+                    .unwrap();
+            let credential: &crate::secret_service::SsCredential =
+                credential.as_any().downcast_ref().unwrap();
+            assert_eq!(credential.attributes["server"], "mail.example.com");
+            assert_eq!(credential.attributes["protocol"], "imap");
+        }
+    }
+
+    #[test]
+    fn test_clone_shares_credential() {
+        let entry = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        let clone = entry.clone();
+        std::thread::spawn(move || clone.set_password("cloned password"))
+            .join()
+            .unwrap()
+            .expect("Can't set password on clone");
+        assert_eq!(
+            entry
+                .get_password()
+                .expect("Can't get password set by clone"),
+            "cloned password"
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_empty() {
+        for (service, user, attr) in [("", "user", "service"), ("service", " \t", "user")] {
+            match Entry::try_new(service, user) {
+                Err(Error::Invalid(found, _)) => assert_eq!(found, attr),
+                other => panic!("Accepted {service:?}/{user:?}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_found() {
+        let builder = crate::mock::default_credential_builder();
+        let name = generate_random_string();
+        for user in ["one", "two", "three"] {
+            let entry = Entry::new_with_credential(builder.build(None, &name, user).unwrap());
+            entry.set_password(user).unwrap();
+        }
+        let searched = std::cell::Cell::new(false);
+        let mut entries = super::iter_found(|| {
+            searched.set(true);
+            builder.search(&name)
+        });
+        assert!(
+            !searched.get(),
+            "Searched before the first entry was asked for"
+        );
+        let first = entries.next().unwrap().unwrap();
+        assert!(searched.get());
+        let (_, user) = first.get_specifiers().unwrap();
+        assert_eq!(first.get_password().unwrap(), user);
+        assert_eq!(entries.count(), 2);
+        for entry in super::iter_found(|| builder.search(&name)) {
+            entry.unwrap().delete_credential().unwrap();
+        }
+        let unsupported = crate::credential::unsupported_credential_builder();
+        let results: Vec<_> = super::iter_found(|| unsupported.search(&name)).collect();
+        assert!(matches!(
+            results.as_slice(),
+            [Err(Error::NotSupportedByStore(_))]
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_target() {
+        let builder = crate::credential::unsupported_credential_builder();
+        assert_eq!(builder.backend_name(), "unsupported");
+        assert!(matches!(
+            builder.build(None, "service", "user"),
+            Err(Error::NotSupportedByStore(_))
+        ));
+        assert!(matches!(
+            builder.search("service"),
+            Err(Error::NotSupportedByStore(_))
+        ));
+        assert!(matches!(
+            builder.count("service"),
+            Err(Error::NotSupportedByStore(_))
+        ));
+        assert!(matches!(
+            builder.probe(),
+            Err(Error::NotSupportedByStore(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_label_support() {
+        let credential = crate::mock::default_credential_builder()
+            .build_with_label("label", "test service", "test user")
+            .expect("Can't build labeled credential");
+        let mock: &crate::mock::MockCredential = credential
+            .as_any()
+            .downcast_ref()
+            .expect("Not a mock credential");
+        assert_eq!(mock.service, "test service");
+        assert_eq!(mock.user, "test user");
+    }
+
+    #[test]
+    fn test_default_scope_support() {
+        let builder = crate::mock::default_credential_builder();
+        let credential = builder
+            .build_in_scope(Scope::User, "test service", "test user")
+            .expect("Can't build user-scope credential");
+        let _: &crate::mock::MockCredential = credential
+            .as_any()
+            .downcast_ref()
+            .expect("Not a mock credential");
+        assert!(matches!(
+            builder.build_in_scope(Scope::Machine, "test service", "test user"),
+            Err(Error::NotSupportedByStore(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_all_passwords() {
+        let mock = crate::mock::MockCredential::default();
+        let entry = Entry::new_with_credential(Box::new(mock));
+        assert!(entry.get_all_passwords().unwrap().is_empty());
+        entry.set_password("only password").unwrap();
+        let found = entry.get_all_passwords().unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1, "only password");
+        let ambiguous: Vec<Box<crate::Credential>> = ["first", "second"]
+            .into_iter()
+            .map(|password| {
+                let credential = crate::mock::MockCredential::default();
+                credential.set_password(password).unwrap();
+                Box::new(credential) as Box<crate::Credential>
+            })
+            .collect();
+        let mock: &crate::mock::MockCredential = entry.get_credential().downcast_ref().unwrap();
+        mock.set_error(Error::Ambiguous(ambiguous));
+        let found = entry.get_all_passwords().unwrap();
+        let passwords: Vec<&str> = found.iter().map(|(_, p)| p.as_str()).collect();
+        assert_eq!(passwords, ["first", "second"]);
+    }
+
+    #[test]
+    fn test_auto_unlock_ignored_by_other_stores() {
+        let entry = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        entry.set_password("test password").unwrap();
+        let entry = entry.with_auto_unlock(false);
+        assert_eq!(entry.get_password().unwrap(), "test password");
+    }
+
+    #[test]
+    fn test_delete_entries_stops_at_failure() {
+        use crate::mock::MockCredential;
+        let builder = crate::mock::default_credential_builder();
+        let service = generate_random_string();
+        let entries: Vec<Entry> = ["first", "second", "third"]
+            .into_iter()
+            .map(|user| {
+                let entry =
+                    Entry::new_with_credential(builder.build(None, &service, user).unwrap());
+                entry.set_password("test password").unwrap();
+                entry
+            })
+            .collect();
+        let mock: &MockCredential = entries[1].get_credential().downcast_ref().unwrap();
+        mock.set_error(Error::PlatformFailure(Box::new(std::fmt::Error)));
+        let err = super::delete_entries(&entries).unwrap_err();
+        assert_eq!(err.deleted, 1);
+        assert_eq!(err.failed, Some((service.clone(), "second".to_string())));
+        assert!(matches!(err.error, Error::PlatformFailure(_)));
+        assert!(matches!(entries[0].get_password(), Err(Error::NoEntry)));
+        assert_eq!(entries[2].get_password().unwrap(), "test password");
+        assert_eq!(super::delete_entries(&entries[1..]).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_app_namespace() {
+        use super::{add_namespace, remove_namespace};
+        let namespace = Some("com.example.app");
+        assert_eq!(add_namespace(None, "github"), "github");
+        assert_eq!(add_namespace(namespace, "github"), "com.example.app:github");
+        assert_eq!(
+            remove_namespace(namespace, "com.example.app:github".to_string()),
+            "github"
+        );
+        assert_eq!(remove_namespace(namespace, "github".to_string()), "github");
+        assert_eq!(
+            remove_namespace(None, "com.example.app:github".to_string()),
+            "com.example.app:github"
+        );
+    }
+
+    #[test]
+    fn test_persistence_ignored_by_other_stores() {
+        let entry = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        entry.set_password("test password").unwrap();
+        let entry = entry.with_persistence(Persistence::Session);
+        assert_eq!(entry.get_password().unwrap(), "test password");
+    }
+
+    #[test]
+    fn test_prompt_ignored_by_other_stores() {
+        let entry = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        entry.set_password("test password").unwrap();
+        let entry = entry.with_prompt("Test wants your password");
+        assert_eq!(entry.get_password().unwrap(), "test password");
+    }
+
+    #[test]
+    fn test_heterogeneous_entries() {
+        let entries = [
+            Entry::from_credential(Box::new(crate::mock::MockCredential::default())),
+            Entry::from_credential(Box::new(crate::null::NullCredential {})),
+        ];
+        entries[0].set_password("test password").unwrap();
+        let found: Vec<bool> = entries
+            .iter()
+            .map(|entry| entry.as_credential().exists().unwrap_or(false))
+            .collect();
+        assert_eq!(found, [true, false]);
+        assert!(
+            entries[1]
+                .as_credential()
+                .as_any()
+                .is::<crate::null::NullCredential>()
+        );
+    }
+
+    #[test]
+    fn test_backend_name() {
+        let mock = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        assert_eq!(mock.backend_name(), "mock");
+        let null = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        assert_eq!(null.backend_name(), "null");
+    }
+
+    #[test]
+    fn test_locator_accessors() {
+        let name = generate_random_string();
+        let credential = crate::mock::default_credential_builder()
+            .build(None, "test username", &name)
+            .unwrap();
+        let entry = Entry::new_with_credential(credential);
+        assert_eq!(entry.username(), Some(name.clone()));
+        assert_eq!(entry.service(), Some("test username".to_string()));
+        assert_eq!(entry.target(), None);
+        let credential = crate::mock::default_credential_builder()
+            .build(Some("test target"), "test username", &name)
+            .unwrap();
+        let entry = Entry::new_with_credential(credential);
+        assert_eq!(entry.target(), Some("test target".to_string()));
+        let null = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        assert_eq!(null.username(), None);
+        assert_eq!(null.service(), None);
+        assert_eq!(null.target(), None);
+    }
+
+    #[test]
+    fn test_delete_all_entries() {
+        let name = generate_random_string();
+        let builder = crate::mock::default_credential_builder();
+        let entries: Vec<Entry> = ["first", "second", "third"]
+            .iter()
+            .map(|service| {
+                let entry = Entry::new_with_credential(
+                    builder
+                        .build(None, &format!("{name}-{service}"), &name)
+                        .unwrap(),
+                );
+                entry.set_password("test delete user").unwrap();
+                entry
+            })
+            .collect();
+        let mock: &crate::mock::MockCredential =
+            entries[1].get_credential().downcast_ref().unwrap();
+        mock.set_error(Error::NoStorageAccess("can't delete".into()));
+        let query = HashMap::from([("username", name.as_str())]);
+        assert_eq!(builder.query(&query).unwrap().len(), 3);
+        let err = super::delete_all_entries(&entries).expect_err("Deleted a failing entry");
+        assert_eq!(err.deleted, 2);
+        assert_eq!(err.failures.len(), 1);
+        assert_eq!(
+            err.failures[0].0,
+            Some((format!("{name}-second"), name.clone()))
+        );
+        assert!(matches!(err.failures[0].1, Error::NoStorageAccess(_)));
+        assert_eq!(super::delete_all_entries(&entries[1..2]).unwrap(), 1);
+        assert!(builder.query(&query).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_entry_equality() {
+        use std::collections::HashSet;
+
+        let name = generate_random_string();
+        let builder = crate::mock::default_credential_builder();
+        let entry = Entry::new_with_credential(builder.build(None, &name, &name).unwrap());
+        let same = Entry::new_with_credential(builder.build(None, &name, &name).unwrap());
+        let other_user = Entry::new_with_credential(builder.build(None, &name, "other").unwrap());
+        let other_target =
+            Entry::new_with_credential(builder.build(Some("target"), &name, &name).unwrap());
+        assert_eq!(entry, same);
+        assert_eq!(entry, entry.clone().with_timeout(Duration::from_secs(1)));
+        assert_ne!(entry, other_user);
+        assert_ne!(entry, other_target);
+        let default = Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        let other_default =
+            Entry::new_with_credential(Box::new(crate::mock::MockCredential::default()));
+        assert_eq!(default, default.clone());
+        assert_ne!(default, other_default);
+        let null = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        let other_null = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        assert_eq!(null, null.clone());
+        assert_ne!(null, other_null);
+        assert_ne!(null, entry);
+        let set: HashSet<Entry> = [entry, same, other_user, null.clone(), null]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_password_b64() {
+        let entry = crate::tests::mock_entry();
+        let bytes = [0u8, 0xff, 0xfe, b'k', 0x80];
+        entry.set_password_b64(&bytes).unwrap();
+        assert_eq!(entry.get_password().unwrap(), "AP/+a4A=");
+        assert_eq!(entry.get_password_b64().unwrap(), bytes);
+        entry.set_password("not base64!").unwrap();
+        match entry.get_password_b64() {
+            Err(Error::BadEncoding(stored)) => assert_eq!(stored, b"not base64!"),
+            other => panic!("Decoded a password that isn't base64: {other:?}"),
+        }
+        entry.delete_credential().unwrap();
+    }
+
+    #[cfg(feature = "current-user")]
+    #[test]
+    fn test_current_user() {
+        let credential = crate::mock::default_credential_builder()
+            .build(None, "test current user", &super::current_user())
+            .unwrap();
+        let entry = Entry::new_with_credential(credential);
+        assert_eq!(entry.username(), Some(whoami::username()));
+    }
+
+    #[test]
+    fn test_display() {
+        let name = generate_random_string();
+        let credential = crate::mock::default_credential_builder()
+            .build(None, "test display", &name)
+            .unwrap();
+        let entry = Entry::new_with_credential(credential);
+        entry.set_password("display secret").unwrap();
+        assert_eq!(entry.to_string(), format!("test display/{name}"));
+        assert_eq!(
+            entry.as_credential().to_string(),
+            format!("test display/{name}")
+        );
+        assert!(!format!("{entry:?}").contains("display secret"));
+        entry.delete_credential().unwrap();
+        let null = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        assert_eq!(null.to_string(), "<unknown>");
+    }
+
+    #[test]
+    fn test_can_set_not_supported() {
+        let entry = Entry::new_with_credential(Box::new(crate::null::NullCredential {}));
+        assert!(matches!(
+            entry.can_set(),
+            Err(Error::NotSupportedByStore(_))
+        ));
+    }
 
     /// Create a platform-specific credential given the constructor, service, and user
     pub fn entry_from_constructor<F, T>(f: F, service: &str, user: &str) -> Entry
@@ -540,6 +2705,44 @@ mod tests {
         generate_random_string_of_len(30)
     }
 
+    /// Make an entry in the mock store whose service and user are
+    /// the same random string.
+    pub fn mock_entry() -> Entry {
+        let name = generate_random_string();
+        let credential = crate::mock::default_credential_builder()
+            .build(None, &name, &name)
+            .expect("Can't build mock credential");
+        Entry::new_with_credential(credential)
+    }
+
+    /// Check that an entry wrapped by the given function passes
+    /// its operations through to the mock credential it wraps.
+    pub fn test_wrapper_passes_through<F>(wrap: F)
+    where
+        F: FnOnce(Entry) -> Entry,
+    {
+        let entry = mock_entry();
+        let specifiers = entry.get_specifiers();
+        let entry = wrap(entry);
+        entry
+            .set_password("test wrapper")
+            .expect("Can't set password through wrapper");
+        assert_eq!(entry.get_password().unwrap(), "test wrapper");
+        assert_eq!(entry.backend_name(), "mock");
+        assert_eq!(entry.get_specifiers(), specifiers);
+        assert!(
+            entry.get_credential().is::<crate::mock::MockCredential>(),
+            "Wrapper hides the mock credential"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential through wrapper");
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Read a deleted password through wrapper"
+        );
+    }
+
     fn generate_random_bytes_of_len(len: usize) -> Vec<u8> {
         use fastrand;
         use std::iter::repeat_with;
@@ -606,6 +2809,60 @@ mod tests {
         test_round_trip_secret("non-ascii password", &entry, secret.as_slice());
     }
 
+    pub fn test_bad_encoding<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        // an odd number of bytes that is neither valid UTF-8 nor valid UTF-16
+        let secret = b"\xff\xfe\xfd".to_vec();
+        entry
+            .set_secret(&secret)
+            .unwrap_or_else(|err| panic!("Can't set non-UTF-8 secret: {err:?}"));
+        match entry.get_password() {
+            Err(Error::BadEncoding(bytes)) => assert_eq!(bytes, secret, "Wrong bytes in error"),
+            Err(err) => panic!("Wrong error reading non-UTF-8 secret as password: {err:?}"),
+            Ok(password) => panic!("Read non-UTF-8 secret as password: {password:?}"),
+        }
+        let out_secret = entry
+            .get_secret()
+            .unwrap_or_else(|err| panic!("Can't get non-UTF-8 secret: {err:?}"));
+        assert_eq!(secret, out_secret, "Secrets don't match");
+        entry
+            .delete_credential()
+            .unwrap_or_else(|err| panic!("Can't delete non-UTF-8 secret: {err:?}"));
+    }
+
+    #[cfg(feature = "zeroizing")]
+    pub fn test_round_trip_zeroizing<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        let password = "zeroizing password";
+        entry
+            .set_password(password)
+            .unwrap_or_else(|err| panic!("Can't set password for zeroizing: {err:?}"));
+        let out_password = entry
+            .get_password_zeroizing()
+            .unwrap_or_else(|err| panic!("Can't get zeroizing password: {err:?}"));
+        assert_eq!(password, out_password.as_str(), "Passwords don't match");
+        let out_secret = entry
+            .get_secret_zeroizing()
+            .unwrap_or_else(|err| panic!("Can't get zeroizing secret: {err:?}"));
+        let expected = entry.get_secret().expect("Can't get secret for zeroizing");
+        assert_eq!(expected, *out_secret, "Secrets don't match");
+        entry
+            .delete_credential()
+            .unwrap_or_else(|err| panic!("Can't delete zeroizing password: {err:?}"));
+        assert!(
+            matches!(entry.get_password_zeroizing(), Err(Error::NoEntry)),
+            "Read deleted zeroizing password"
+        );
+    }
+
     pub fn test_update<F>(f: F)
     where
         F: FnOnce(&str, &str) -> Entry,
@@ -620,21 +2877,683 @@ mod tests {
         );
     }
 
-    pub fn test_noop_get_update_attributes<F>(f: F)
+    pub fn test_create_update<F>(f: F)
     where
         F: FnOnce(&str, &str) -> Entry,
     {
         let name = generate_random_string();
         let entry = f(&name, &name);
         assert!(
-            matches!(entry.get_attributes(), Err(Error::NoEntry)),
-            "Read missing credential in attribute test",
+            matches!(
+                entry.update_password("updated password"),
+                Err(Error::NoEntry)
+            ),
+            "Updated a missing credential"
         );
-        let mut map: HashMap<&str, &str> = HashMap::new();
-        map.insert("test attribute name", "test attribute value");
         assert!(
-            matches!(entry.update_attributes(&map), Err(Error::NoEntry)),
-            "Updated missing credential in attribute test",
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Update of a missing credential created it"
+        );
+        entry
+            .create_password("created password")
+            .expect("Can't create credential");
+        assert!(
+            matches!(entry.create_secret(b"\x00\x01"), Err(Error::AlreadyExists)),
+            "Created an existing credential"
+        );
+        assert_eq!(
+            entry.get_password().expect("Can't get created password"),
+            "created password"
+        );
+        entry
+            .update_secret("updated secret".as_bytes())
+            .expect("Can't update secret");
+        assert_eq!(
+            entry.get_secret().expect("Can't get updated secret"),
+            b"updated secret"
+        );
+        entry
+            .update_password("updated password")
+            .expect("Can't update password");
+        assert_eq!(
+            entry.get_password().expect("Can't get updated password"),
+            "updated password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete created credential");
+    }
+
+    pub fn test_rename<F>(f: F)
+    where
+        F: Fn(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let new_name = generate_random_string();
+        let entry = f(&name, &name);
+        let moved = f(&new_name, &new_name);
+        assert!(
+            matches!(entry.rename(&new_name, &new_name), Err(Error::NoEntry)),
+            "Renamed a missing credential"
+        );
+        entry
+            .set_password("renamed password")
+            .expect("Can't set password for rename");
+        moved
+            .set_password("existing password")
+            .expect("Can't set password at new name");
+        assert!(
+            matches!(
+                entry.rename(&new_name, &new_name),
+                Err(Error::AlreadyExists)
+            ),
+            "Renamed over an existing credential"
+        );
+        assert_eq!(
+            entry.get_password().expect("Failed rename lost password"),
+            "renamed password"
+        );
+        moved
+            .delete_credential()
+            .expect("Can't delete credential at new name");
+        entry
+            .rename(&new_name, &new_name)
+            .expect("Can't rename credential");
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Renamed credential is still at its old name"
+        );
+        assert_eq!(
+            moved.get_password().expect("Can't get renamed password"),
+            "renamed password"
+        );
+        moved
+            .delete_credential()
+            .expect("Can't delete renamed credential");
+    }
+
+    pub fn test_exists<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            !entry.exists().expect("Can't check missing credential"),
+            "Missing credential exists"
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for exists");
+        assert!(
+            entry.exists().expect("Can't check credential"),
+            "Credential doesn't exist"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for exists");
+        assert!(
+            !entry.exists().expect("Can't check deleted credential"),
+            "Deleted credential exists"
+        );
+    }
+
+    pub fn test_delete_if_exists<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            !entry
+                .delete_credential_if_exists()
+                .expect("Can't delete missing credential"),
+            "Deleted a missing credential"
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for delete");
+        assert!(
+            entry
+                .delete_credential_if_exists()
+                .expect("Can't delete credential"),
+            "Didn't delete an existing credential"
+        );
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Able to read a deleted password"
+        );
+    }
+
+    pub fn test_capabilities<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        let capabilities = entry.capabilities();
+        let result = entry.set_password_with_expiry("test ttl", Duration::from_secs(60));
+        if capabilities.ttl {
+            result.expect("Can't set password with expiry");
+            entry
+                .delete_credential()
+                .expect("Can't delete credential for capabilities");
+        } else {
+            assert!(
+                matches!(result, Err(Error::NotSupportedByStore(_))),
+                "Set password with expiry on a store without ttl: {result:?}"
+            );
+        }
+        assert!(capabilities.binary, "Store doesn't support binary secrets");
+    }
+
+    pub fn test_compare_and_swap<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.compare_and_swap("old", "new"), Err(Error::NoEntry)),
+            "Swapped a missing password"
+        );
+        entry
+            .set_password("old")
+            .expect("Can't set password for compare and swap");
+        assert!(
+            !entry
+                .compare_and_swap("wrong", "new")
+                .expect("Can't compare and swap"),
+            "Swapped a password that didn't match"
+        );
+        assert_eq!(entry.get_password().unwrap(), "old");
+        assert!(
+            entry
+                .compare_and_swap("old", "new")
+                .expect("Can't compare and swap"),
+            "Didn't swap a password that matched"
+        );
+        assert_eq!(entry.get_password().unwrap(), "new");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for compare and swap");
+    }
+
+    pub fn test_set_replacing_attributes<F>(f: F)
+    where
+        F: Fn(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        entry
+            .set_password_replacing_attributes("first password")
+            .expect("Can't set password on missing credential");
+        assert_eq!(entry.get_password().unwrap(), "first password");
+        entry
+            .set_password_replacing_attributes("second password")
+            .expect("Can't replace credential");
+        assert_eq!(entry.get_password().unwrap(), "second password");
+        entry
+            .set_secret_replacing_attributes(b"third secret")
+            .expect("Can't replace credential with secret");
+        assert_eq!(entry.get_secret().unwrap(), b"third secret");
+        entry
+            .delete_credential()
+            .expect("Can't delete replaced credential");
+    }
+
+    pub fn test_set_keeps_attributes<F>(f: F, attribute: &str)
+    where
+        F: Fn(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        entry
+            .set_password("first password")
+            .expect("Can't set password for attribute test");
+        let created = entry
+            .get_attributes()
+            .expect("Can't get attributes of new credential");
+        entry
+            .update_attributes(&HashMap::from([(attribute, "kept value")]))
+            .expect("Can't update attributes");
+        entry
+            .set_password("second password")
+            .expect("Can't change password for attribute test");
+        assert_eq!(entry.get_password().unwrap(), "second password");
+        let attributes = entry
+            .get_attributes()
+            .expect("Can't get attributes after password change");
+        assert_eq!(
+            attributes.get(attribute).map(String::as_str),
+            Some("kept value"),
+            "Changing the password lost attribute {attribute}"
+        );
+        entry
+            .set_password_replacing_attributes("third password")
+            .expect("Can't replace credential");
+        assert_eq!(entry.get_password().unwrap(), "third password");
+        let attributes = entry
+            .get_attributes()
+            .expect("Can't get attributes after replacing");
+        assert_eq!(
+            attributes.get(attribute),
+            created.get(attribute),
+            "Replacing the credential kept attribute {attribute}"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for attribute test");
+    }
+
+    pub fn test_verify<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.verify("password"), Err(Error::NoEntry)),
+            "Verified a missing password"
+        );
+        entry
+            .set_password("password")
+            .expect("Can't set password for verify");
+        assert!(entry.verify("password").expect("Can't verify"));
+        for wrong in ["", "passwore", "password ", "Password"] {
+            assert!(
+                !entry.verify(wrong).expect("Can't verify"),
+                "Verified wrong password {wrong:?}"
+            );
+        }
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for verify");
+    }
+
+    pub fn test_get_opt<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert_eq!(
+            entry
+                .get_password_opt()
+                .expect("Can't get missing password"),
+            None
+        );
+        assert_eq!(
+            entry.get_secret_opt().expect("Can't get missing secret"),
+            None
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for get opt");
+        assert_eq!(
+            entry.get_password_opt().expect("Can't get password"),
+            Some("test password".to_string())
+        );
+        assert_eq!(
+            entry.get_secret_opt().expect("Can't get secret"),
+            Some(b"test password".to_vec())
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for get opt");
+    }
+
+    pub fn test_get_with_attributes<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.get_password_with_attributes(), Err(Error::NoEntry)),
+            "Read a missing credential"
+        );
+        entry
+            .set_password("test with attributes")
+            .expect("Can't set password for get with attributes");
+        let (password, attributes) = entry
+            .get_password_with_attributes()
+            .expect("Can't get password with attributes");
+        assert_eq!(password, "test with attributes");
+        assert_eq!(attributes, entry.get_attributes().unwrap());
+        let (secret, _) = entry.get_secret_with_attributes().unwrap();
+        assert_eq!(secret, b"test with attributes");
+        entry
+            .delete_credential()
+            .expect("Can't delete after get with attributes");
+    }
+
+    /// Query a store's builder for entries by service, and by service and user.
+    pub fn test_query<F>(f: F, builder: &CredentialBuilder)
+    where
+        F: Fn(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let user1 = generate_random_string();
+        let user2 = generate_random_string();
+        for user in [&user1, &user2] {
+            f(&name, user)
+                .set_password(user)
+                .expect("Can't set password for query");
+        }
+        let query = HashMap::from([("service", name.as_str())]);
+        let found = builder.query(&query).expect("Can't query by service");
+        assert_eq!(found.len(), 2, "Didn't find both entries");
+        let query = HashMap::from([("service", name.as_str()), ("username", user1.as_str())]);
+        let found = builder
+            .query(&query)
+            .expect("Can't query by service and user");
+        assert_eq!(found.len(), 1, "Didn't find just one entry");
+        let entry = Entry::new_with_credential(found.into_iter().next().unwrap());
+        assert_eq!(
+            entry.get_password().expect("Can't get queried password"),
+            user1
+        );
+        let query = HashMap::from([("service", name.as_str()), ("username", "no such user")]);
+        let found = builder.query(&query).expect("Can't query for missing user");
+        assert!(found.is_empty(), "Found entries for a missing user");
+        for user in [&user1, &user2] {
+            f(&name, user)
+                .delete_credential()
+                .expect("Can't delete queried entry");
+        }
+    }
+
+    pub fn test_chunked<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        let password = "long password ".repeat(1000);
+        entry
+            .set_password_chunked(&password)
+            .expect("Can't set chunked password");
+        assert_eq!(
+            entry
+                .get_password_chunked()
+                .expect("Can't get chunked password"),
+            password
+        );
+        entry
+            .set_password_chunked("short password")
+            .expect("Can't replace chunked password");
+        assert_eq!(
+            entry.get_password().expect("Can't get unchunked password"),
+            "short password"
+        );
+        entry
+            .delete_chunked()
+            .expect("Can't delete chunked credential");
+        assert!(
+            matches!(entry.get_password_chunked(), Err(Error::NoEntry)),
+            "Able to read a deleted chunked password"
+        );
+    }
+
+    pub fn test_rotate<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.rotate("new password"), Err(Error::NoEntry)),
+            "Rotated a missing credential"
+        );
+        entry
+            .set_password("old password")
+            .expect("Can't set password for rotation");
+        assert_eq!(
+            entry.rotate("new password").expect("Can't rotate password"),
+            "old password"
+        );
+        assert_eq!(
+            entry.get_password().expect("Can't get rotated password"),
+            "new password"
+        );
+        let before = entry
+            .get_secret()
+            .expect("Can't get secret before rotation");
+        assert_eq!(
+            entry
+                .rotate_secret(b"new secret")
+                .expect("Can't rotate secret"),
+            before
+        );
+        assert_eq!(
+            entry.get_secret().expect("Can't get rotated secret"),
+            b"new secret"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for rotation");
+    }
+
+    pub fn test_get_info<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.get_info(), Err(Error::NoEntry)),
+            "Got info for a missing credential"
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for info");
+        let info = entry.get_info().expect("Can't get info");
+        assert_eq!(info.secret_len, "test password".len());
+        assert_eq!(
+            info.created,
+            entry.created().expect("Can't get creation time")
+        );
+        assert_eq!(
+            info.modified,
+            entry.last_modified().expect("Can't get modification time")
+        );
+        if let Some((service, user)) = entry.get_specifiers() {
+            assert_eq!(
+                (info.service.as_str(), info.username.as_str()),
+                (service.as_str(), user.as_str())
+            );
+        }
+        if let (Some(created), Some(modified)) = (info.created, info.modified) {
+            assert!(
+                created <= modified,
+                "Credential was modified before it was created"
+            );
+        }
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for info");
+    }
+
+    pub fn test_password_and_credential<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.get_password_and_credential(), Err(Error::NoEntry)),
+            "Got a summary of a missing credential"
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for summary");
+        let (password, summary) = entry
+            .get_password_and_credential()
+            .expect("Can't get password and summary");
+        assert_eq!(password, "test password");
+        assert_eq!(summary.backend, entry.backend_name());
+        assert_eq!(
+            summary.attributes,
+            entry.get_attributes().expect("Can't get attributes")
+        );
+        if let Some((service, user)) = entry.get_specifiers() {
+            assert_eq!(
+                (summary.service.as_str(), summary.username.as_str()),
+                (service.as_str(), user.as_str())
+            );
+        }
+        assert_eq!(summary.entry(), &entry);
+        assert!(summary.platform::<String>().is_none());
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for summary");
+    }
+
+    pub fn test_can_set<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        entry.can_set().expect("Can't set entry");
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "can_set created the entry's credential"
+        );
+        entry
+            .set_password("test password")
+            .expect("Can't set password for can_set");
+        entry.can_set().expect("Can't set existing entry");
+        assert_eq!(
+            entry
+                .get_password()
+                .expect("Can't get password after can_set"),
+            "test password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for can_set");
+    }
+
+    pub fn test_last_modified<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.last_modified(), Err(Error::NoEntry)),
+            "Got a modification time for a missing credential"
+        );
+        // stores may round modification times to the second
+        let before = SystemTime::now() - Duration::from_secs(1);
+        entry
+            .set_password("test password")
+            .expect("Can't set password for last modified");
+        let after = SystemTime::now() + Duration::from_secs(1);
+        let modified = entry
+            .last_modified()
+            .expect("Can't get last modified time")
+            .expect("Store didn't record a last modified time");
+        assert!(
+            before <= modified && modified <= after,
+            "Last modified time is wrong"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for last modified");
+    }
+
+    pub fn test_expiry<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(
+                entry.set_password_with_expiry("expired", Duration::ZERO),
+                Err(Error::Invalid(_, _))
+            ),
+            "Set a password that has already expired"
+        );
+        assert!(
+            matches!(
+                entry.set_password_with_expiry("forever", Duration::MAX),
+                Err(Error::Invalid(_, _))
+            ),
+            "Set a password that never expires"
+        );
+        entry
+            .set_password_with_expiry("expiring password", Duration::from_secs(1))
+            .expect("Can't set expiring password");
+        assert_eq!(
+            entry.get_password().expect("Can't get expiring password"),
+            "expiring password"
+        );
+        std::thread::sleep(Duration::from_millis(2100));
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Got an expired password"
+        );
+        assert!(
+            !entry.exists().expect("Can't check expired password"),
+            "Expired password exists"
+        );
+        // setting a password without an expiry removes the expiry
+        entry
+            .set_secret_with_expiry(b"expiring secret", Duration::from_secs(1))
+            .expect("Can't set expiring secret");
+        entry
+            .set_password("lasting password")
+            .expect("Can't set password after expiring secret");
+        std::thread::sleep(Duration::from_millis(2100));
+        assert_eq!(
+            entry
+                .get_password()
+                .expect("Password without expiry expired"),
+            "lasting password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete password after expiry");
+    }
+
+    pub fn test_expiry_not_supported<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(
+                entry.set_password_with_expiry("expiring", Duration::from_secs(60)),
+                Err(Error::NotSupportedByStore(_))
+            ),
+            "Set an expiring password"
+        );
+        assert!(
+            matches!(entry.get_password(), Err(Error::NoEntry)),
+            "Unsupported expiry created a credential"
+        );
+    }
+
+    pub fn test_noop_get_update_attributes<F>(f: F)
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        assert!(
+            matches!(entry.get_attributes(), Err(Error::NoEntry)),
+            "Read missing credential in attribute test",
+        );
+        let mut map: HashMap<&str, &str> = HashMap::new();
+        map.insert("test attribute name", "test attribute value");
+        assert!(
+            matches!(entry.update_attributes(&map), Err(Error::NoEntry)),
+            "Updated missing credential in attribute test",
         );
         // create the credential and test again
         entry
@@ -662,4 +3581,44 @@ mod tests {
             "Read deleted credential in attribute test",
         );
     }
+
+    pub fn test_get_update_named_attributes<F>(f: F, names: &[&str])
+    where
+        F: FnOnce(&str, &str) -> Entry,
+    {
+        let name = generate_random_string();
+        let entry = f(&name, &name);
+        let mut map: HashMap<&str, &str> = HashMap::new();
+        for name in names {
+            map.insert(name, "test attribute value");
+        }
+        map.insert("test attribute name", "ignored attribute value");
+        assert!(
+            matches!(entry.update_attributes(&map), Err(Error::NoEntry)),
+            "Updated missing credential in attribute test",
+        );
+        entry
+            .set_password("test password for attributes")
+            .unwrap_or_else(|err| panic!("Can't set password for attribute test: {err:?}"));
+        entry
+            .update_attributes(&map)
+            .unwrap_or_else(|err| panic!("Couldn't update attributes: {err:?}"));
+        let attrs = entry
+            .get_attributes()
+            .unwrap_or_else(|err| panic!("Couldn't get attributes after update: {err:?}"));
+        for name in names {
+            assert_eq!(
+                attrs.get(*name).map(String::as_str),
+                Some("test attribute value"),
+                "Attribute {name} wasn't updated"
+            );
+        }
+        assert!(
+            !attrs.contains_key("test attribute name"),
+            "Unsupported attribute was stored"
+        );
+        entry
+            .delete_credential()
+            .unwrap_or_else(|err| panic!("Can't delete credential for attribute test: {err:?}"));
+    }
 }
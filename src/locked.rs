@@ -0,0 +1,173 @@
+/*!
+
+# Secrets in locked memory
+
+The secrets returned by [Entry::get_secret] are in ordinary memory, which
+the operating system may write out to swap (and so to disk) at any time.
+[Entry::get_secret_locked] instead returns the secret in a [LockedBuffer],
+whose memory is locked into RAM (with the [region] crate, which uses `mlock`
+on Unix and `VirtualLock` on Windows), so it's never paged out.  When the buffer is dropped, its memory
+is zeroed before it's unlocked and freed.
+
+This guards against one threat only: secrets being left in swap.  Locked
+memory is still written to disk when the machine hibernates, it's still
+included in core dumps, and the secret passes through ordinary memory
+(which is zeroed straight away) on its way from the store to the buffer.
+
+## Limits on locked memory
+
+Operating systems limit how much memory a process can lock, and each buffer
+uses at least one whole page of it (4 KiB on most platforms, 16 KiB on
+Apple Silicon), since that's the unit in which memory is locked.  If a buffer
+can't be locked, [get_secret_locked](Entry::get_secret_locked) fails with a
+[PlatformFailure](Error::PlatformFailure) error rather than returning the
+secret in memory that isn't locked.
+
+- On Linux, the limit is the `RLIMIT_MEMLOCK` resource limit (see `ulimit -l`),
+  which is as little as 64 KiB on older systems, and 8 MiB on most current ones.
+  Privileged processes (those with `CAP_IPC_LOCK`) have no limit.
+- On macOS and the BSDs, the limit is also `RLIMIT_MEMLOCK`, along with
+  a system-wide limit on wired memory.
+- On Windows, a process can lock only as much memory as its minimum working set
+  size (a few hundred KiB by default) less what it needs for other purposes.
+  Use `SetProcessWorkingSetSize` to raise it if you need to keep many secrets.
+- On other targets, memory can't be locked (or `region` doesn't support them),
+  so every call fails.
+
+```rust
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?;
+entry.set_secret(b"my secret")?;
+let secret = entry.get_secret_locked()?;
+assert_eq!(&*secret, b"my secret");
+# Ok::<(), keyring::Error>(())
+```
+ */
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+use zeroize::Zeroize;
+
+use super::{Entry, Error, Result, Zeroizing};
+
+/// A secret held in memory that's locked into RAM.
+///
+/// The buffer dereferences to the secret's bytes.  Its memory is zeroed,
+/// unlocked, and freed when the buffer is dropped.  See the
+/// [module docs](crate::locked) for the limits on locking memory.
+pub struct LockedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+    /// Unlocks the memory when dropped (which must be after it's zeroed)
+    guard: Option<region::LockGuard>,
+}
+
+// The buffer owns its memory, which is only changed when the buffer is dropped.
+unsafe impl Send for LockedBuffer {}
+unsafe impl Sync for LockedBuffer {}
+
+impl LockedBuffer {
+    /// Copy the given bytes into newly allocated, locked memory.
+    ///
+    /// The memory is a whole number of pages, aligned on a page boundary,
+    /// so it doesn't share a page with any other allocation (whose
+    /// unlocking would otherwise unlock this one).
+    pub fn new(bytes: &[u8]) -> Result<Self> {
+        let page = region::page::size();
+        let size = bytes.len().max(1).div_ceil(page) * page;
+        let layout = Layout::from_size_align(size, page)
+            .map_err(|err| Error::PlatformFailure(Box::new(err)))?;
+        let Some(ptr) = NonNull::new(unsafe { std::alloc::alloc_zeroed(layout) }) else {
+            std::alloc::handle_alloc_error(layout);
+        };
+        let guard = match region::lock(ptr.as_ptr(), size) {
+            Ok(guard) => guard,
+            Err(err) => {
+                unsafe { std::alloc::dealloc(ptr.as_ptr(), layout) };
+                return Err(Error::PlatformFailure(Box::new(err)));
+            }
+        };
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), bytes.len()) };
+        Ok(Self {
+            ptr,
+            len: bytes.len(),
+            layout,
+            guard: Some(guard),
+        })
+    }
+}
+
+impl std::ops::Deref for LockedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for LockedBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for LockedBuffer {
+    fn drop(&mut self) {
+        let size = self.layout.size();
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), size).zeroize() };
+        // if unlocking fails there's nothing to do but free the memory anyway
+        drop(self.guard.take());
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+    }
+}
+
+// The secret is left out of the debug format.
+impl std::fmt::Debug for LockedBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LockedBuffer")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Entry {
+    /// Retrieve the secret saved for this entry, in memory that's locked into RAM.
+    ///
+    /// This reads the secret just as [get_secret](Entry::get_secret) does, then
+    /// copies it into a [LockedBuffer] (zeroing the unlocked copy).
+    /// See the [locked](crate::locked) module for the limits on locking memory;
+    /// if the memory can't be locked, this fails with a
+    /// [PlatformFailure](Error::PlatformFailure) error.
+    pub fn get_secret_locked(&self) -> Result<LockedBuffer> {
+        let secret = Zeroizing::new(self.get_secret()?);
+        LockedBuffer::new(&secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockedBuffer;
+    use crate::{Error, tests::mock_entry};
+
+    #[test]
+    fn test_locked_buffer() {
+        for bytes in [&b""[..], b"short secret", &[7u8; 10_000]] {
+            let buffer = LockedBuffer::new(bytes).expect("Can't lock memory");
+            assert_eq!(&*buffer, bytes);
+            assert_eq!(buffer.as_ptr().align_offset(region::page::size()), 0);
+        }
+        let buffer = LockedBuffer::new(b"hidden").unwrap();
+        assert!(!format!("{buffer:?}").contains("hidden"));
+    }
+
+    #[test]
+    fn test_get_secret_locked() {
+        let entry = mock_entry();
+        assert!(matches!(entry.get_secret_locked(), Err(Error::NoEntry)));
+        entry.set_secret(b"locked secret").unwrap();
+        assert_eq!(&*entry.get_secret_locked().unwrap(), b"locked secret");
+        entry.delete_credential().unwrap();
+    }
+}
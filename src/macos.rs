@@ -30,40 +30,103 @@ also generic credentials, so existing _notes_ created by third-party
 applications can be accessed by this module if you know the value
 of their _account_ attribute (which is not displayed by _Keychain Access_).
 
-Credentials on macOS can have a large number of _key/value_ attributes,
-but this module controls the _account_ and _name_ attributes and
-ignores all the others. so clients can't use it to access or update any attributes.
+Credentials on macOS can have a large number of _key/value_ attributes.
+This module controls the _account_ and _name_ attributes, but
+the [get_attributes](crate::Entry::get_attributes) and
+[update_attributes](crate::Entry::update_attributes) calls give access to
+the `label`, `comment`, and `description` attributes (using those strings
+as the attribute names).  Any other attribute names are ignored.
+
+To keep your credentials in a keychain file of your own rather than in one of
+the keychains above, create your entries with
+[Entry::new_with_keychain_path](crate::Entry::new_with_keychain_path).
+
+## The keychain search list
+
+A credential can be in any of the keychains on the user's keychain search
+list (the login keychain, the System keychain, and any others the user
+has added), but an entry only looks in its own keychain.  To find
+credentials wherever they are on the search list, use
+[MacCredential::find_in_search_list] (for a given service and user) or
+[search_keychains] (for all the users of a service).  The credentials they
+return are in the domain of the keychain they were found in, so that's how
+you tell which keychain a credential came from, and writes to them go
+to that keychain only.  To use one of them as an entry, wrap it with
+[Entry::new_with_credential](crate::Entry::new_with_credential).
+
+Entries created in the [Machine](crate::Scope::Machine) scope
+(see [Entry::new_in_scope](crate::Entry::new_in_scope)) are in the System keychain
+(`/Library/Keychains/System.keychain`), as are entries whose target is `System`.
+Only privileged processes can write to that keychain; for others, writes fail
+with a [NoStorageAccess](crate::Error::NoStorageAccess) error.
  */
-use super::credential::{Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi};
+use std::collections::HashMap;
+use std::ffi::{OsString, c_char, c_void};
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi, Scope,
+};
 use super::error::{Error as ErrorCode, Result, decode_password};
-use crate::ios::IosCredential;
+use super::prompt_hook::{PromptKind, before_prompt};
+use crate::ios::{
+    IosCredential, extract_attributes, extract_created, extract_data, extract_modified,
+    replace_options, set_no_prompt, set_operation_prompt, update_options,
+};
+use core_foundation::array::{CFArray, CFArrayRef};
+use core_foundation::base::TCFType;
+use core_foundation::data::CFData;
+use core_foundation::string::{CFString, CFStringRef};
 use security_framework::base::Error;
+use security_framework::item::{
+    ItemAddOptions, ItemAddValue, ItemClass, ItemSearchOptions, ItemUpdateOptions, ItemUpdateValue,
+    Limit, Location, update_item,
+};
 use security_framework::os::macos::keychain::{SecKeychain, SecPreferencesDomain};
-use security_framework::os::macos::passwords::find_generic_password;
+use security_framework::os::macos::keychain_item::SecKeychainItem;
+use security_framework::os::macos::passwords::{SecKeychainItemPassword, find_generic_password};
+use security_framework::passwords::{PasswordOptions, generic_password};
+use zeroize::Zeroize;
 
 /// The representation of a generic Keychain credential.
 ///
 /// The actual credentials can have lots of attributes
-/// not represented here.  There's no way to use this
-/// module to get at those attributes.
+/// not represented here.  See the module header for
+/// the ones that can be read and updated.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MacCredential {
     pub domain: MacKeychainDomain,
     pub service: String,
     pub account: String,
+    /// The label given to the keychain item when it's written,
+    /// if it's not the default (the service)
+    pub label: Option<String>,
+    /// The text shown to the user if reading the secret
+    /// requires their authorization
+    pub prompt: Option<String>,
+    /// Whether reading the secret fails, rather than asking the user,
+    /// if it requires their authorization
+    pub no_prompt: bool,
 }
 
 impl CredentialApi for MacCredential {
+    /// The name of this store: `"macos-keychain"`.
+    fn backend_name(&self) -> &'static str {
+        "macos-keychain"
+    }
+
     /// Create and write a credential with password for this entry.
     ///
     /// The new credential replaces any existing one in the store.
     /// Since there is only one credential with a given _account_ and _user_
     /// in any given keychain, there is no chance of ambiguity.
     fn set_password(&self, password: &str) -> Result<()> {
-        get_keychain(self)?
+        get_keychain(&self.domain)?
             .set_generic_password(&self.service, &self.account, password.as_bytes())
             .map_err(decode_error)?;
-        Ok(())
+        self.apply_label()
     }
 
     /// Create and write a credential with secret for this entry.
@@ -72,10 +135,80 @@ impl CredentialApi for MacCredential {
     /// Since there is only one credential with a given _account_ and _user_
     /// in any given keychain, there is no chance of ambiguity.
     fn set_secret(&self, secret: &[u8]) -> Result<()> {
-        get_keychain(self)?
+        get_keychain(&self.domain)?
             .set_generic_password(&self.service, &self.account, secret)
             .map_err(decode_error)?;
-        Ok(())
+        self.apply_label()
+    }
+
+    /// Replace the secret of the credential for this entry, give it its
+    /// default label, and empty its comment and description, or create the
+    /// credential if there isn't one.
+    ///
+    /// An existing credential is replaced in place, with a single update,
+    /// so a failure leaves it as it was.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        let mut search = ItemSearchOptions::new();
+        search
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account);
+        let label = self.label.as_deref().unwrap_or(&self.service);
+        match update_item(&search, &replace_options(secret, label)).map_err(decode_error) {
+            Err(ErrorCode::NoEntry) => self.set_secret(secret),
+            result => result,
+        }
+    }
+
+    /// Update the password on the existing credential for this entry.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.update_secret(password.as_bytes())
+    }
+
+    /// Update the secret on the existing credential for this entry.
+    ///
+    /// The credential is updated in place, so its other attributes are preserved.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        let mut search = ItemSearchOptions::new();
+        search
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account);
+        let mut update = ItemUpdateOptions::new();
+        update.set_value(ItemUpdateValue::Data(CFData::from_buffer(secret)));
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Create a credential with password for this entry.
+    ///
+    /// Returns an [AlreadyExists](ErrorCode::AlreadyExists) error if there
+    /// is already a credential in the store.
+    fn create_password(&self, password: &str) -> Result<()> {
+        self.create_secret(password.as_bytes())
+    }
+
+    /// Create a credential with secret for this entry.
+    ///
+    /// Returns an [AlreadyExists](ErrorCode::AlreadyExists) error if there
+    /// is already a credential in the store.
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        ItemAddOptions::new(ItemAddValue::Data {
+            class: ItemClass::generic_password(),
+            data: CFData::from_buffer(secret),
+        })
+        .set_service(&self.service)
+        .set_account_name(&self.account)
+        .set_label(self.label.as_deref().unwrap_or(&self.service))
+        .set_location(Location::FileKeychain(get_keychain(&self.domain)?))
+        .add()
+        .map_err(decode_error)
     }
 
     /// Look up the password for this entry, if any.
@@ -83,10 +216,7 @@ impl CredentialApi for MacCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_password(&self) -> Result<String> {
-        let (password_bytes, _) =
-            find_generic_password(Some(&[get_keychain(self)?]), &self.service, &self.account)
-                .map_err(decode_error)?;
-        decode_password(password_bytes.to_vec())
+        decode_password(self.get_secret()?)
     }
 
     /// Look up the secret for this entry, if any.
@@ -94,10 +224,209 @@ impl CredentialApi for MacCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_secret(&self) -> Result<Vec<u8>> {
-        let (password_bytes, _) =
-            find_generic_password(Some(&[get_keychain(self)?]), &self.service, &self.account)
-                .map_err(decode_error)?;
-        Ok(password_bytes.to_vec())
+        if !self.no_prompt {
+            before_prompt(PromptKind::KeychainAccess, "macos-keychain", Some(self));
+        }
+        if self.prompt.is_some() || self.no_prompt {
+            return self.get_secret_with_options();
+        }
+        let (password_bytes, _) = find_generic_password(
+            Some(&[get_keychain(&self.domain)?]),
+            &self.service,
+            &self.account,
+        )
+        .map_err(decode_error)?;
+        let secret = password_bytes.to_vec();
+        unsafe { zeroize_item_password(&password_bytes) };
+        Ok(secret)
+    }
+
+    /// Check whether there's a generic credential for this entry,
+    /// by searching for its attributes (but not its data).
+    fn exists(&self) -> Result<bool> {
+        let result = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error);
+        match result {
+            Ok(_) => Ok(true),
+            Err(ErrorCode::NoEntry) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the label, comment, and description of the credential for this entry, if any.
+    ///
+    /// Only the attributes that are present (and not empty) on the credential are returned.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_attributes(&results))
+    }
+
+    /// Get the secret and the label, comment, and description of the credential
+    /// for this entry, if any.
+    ///
+    /// Both are read with a single keychain search, except when this credential
+    /// has a prompt or mustn't prompt, in which case the secret is read on its own.
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        if self.prompt.is_some() || self.no_prompt {
+            let secret = self.get_secret()?;
+            return Ok((secret, self.get_attributes()?));
+        }
+        before_prompt(PromptKind::KeychainAccess, "macos-keychain", Some(self));
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account)
+            .load_data(true)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok((extract_data(&results)?, extract_attributes(&results)))
+    }
+
+    /// Get the secret and the creation and modification dates of the credential
+    /// for this entry, if any.
+    ///
+    /// All three are read with a single keychain search, except when this credential
+    /// has a prompt or mustn't prompt, in which case the secret is read on its own.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        let search = |load_data| {
+            ItemSearchOptions::new()
+                .class(ItemClass::generic_password())
+                .keychains(&[get_keychain(&self.domain)?])
+                .service(&self.service)
+                .account(&self.account)
+                .load_data(load_data)
+                .load_attributes(true)
+                .limit(1)
+                .search()
+                .map_err(decode_error)
+        };
+        let (secret, results) = if self.prompt.is_some() || self.no_prompt {
+            (self.get_secret()?, search(false)?)
+        } else {
+            before_prompt(PromptKind::KeychainAccess, "macos-keychain", Some(self));
+            let results = search(true)?;
+            (extract_data(&results)?, results)
+        };
+        Ok((
+            secret,
+            extract_created(&results),
+            extract_modified(&results),
+        ))
+    }
+
+    /// Get the modification date of the credential for this entry, if it exists.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_modified(&results))
+    }
+
+    /// Get the creation date of the credential for this entry, if it exists.
+    fn created(&self) -> Result<Option<SystemTime>> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account)
+            .load_attributes(true)
+            .limit(1)
+            .search()
+            .map_err(decode_error)?;
+        Ok(extract_created(&results))
+    }
+
+    /// Update the label, comment, and description of the credential for this entry, if any.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        let Some(update) = update_options(attributes) else {
+            // nothing to update, but this should fail if there's no credential
+            return self.get_secret().map(|_| ());
+        };
+        let mut search = ItemSearchOptions::new();
+        search
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account);
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Return the service and account of this credential.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        Some((self.service.clone(), self.account.clone()))
+    }
+
+    /// Return the name of this credential's keychain, unless it's the User keychain.
+    ///
+    /// The User keychain is where credentials made without a target are
+    /// stored, so it isn't considered a target.
+    fn get_target(&self) -> Option<String> {
+        match self.domain {
+            MacKeychainDomain::User => None,
+            _ => Some(self.domain.to_string()),
+        }
+    }
+
+    /// Change the service and account of the underlying generic credential for this entry.
+    ///
+    /// The credential is changed in place (with `SecItemUpdate`), so it keeps
+    /// its secret and other attributes.  Items are labeled with their service
+    /// unless they're given a label of their own, so an item whose label is its
+    /// service is relabeled with the new service, but any other label is kept.
+    /// Returns an [AlreadyExists](ErrorCode::AlreadyExists)
+    /// error if there is already a credential with the new service and account.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let label = self.get_attributes()?.remove("label");
+        let mut search = ItemSearchOptions::new();
+        search
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account);
+        let mut update = ItemUpdateOptions::new();
+        update.set_service(service).set_account_name(user);
+        if label.is_none_or(|label| label == self.service) {
+            update.set_label(service);
+        }
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Make a credential for the given user with this credential's keychain and service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(Self::new_with_target(
+            Some(self.domain.clone()),
+            &self.service,
+            user,
+        )?))
     }
 
     /// Delete the underlying generic credential for this entry, if any.
@@ -105,13 +434,29 @@ impl CredentialApi for MacCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn delete_credential(&self) -> Result<()> {
-        let (_, item) =
-            find_generic_password(Some(&[get_keychain(self)?]), &self.service, &self.account)
-                .map_err(decode_error)?;
+        let (password, item) = find_generic_password(
+            Some(&[get_keychain(&self.domain)?]),
+            &self.service,
+            &self.account,
+        )
+        .map_err(decode_error)?;
+        unsafe { zeroize_item_password(&password) };
         item.delete();
         Ok(())
     }
 
+    /// Keychain items have attributes and labels, and can be in the System keychain, but don't expire.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: true,
+            enumeration: true,
+            ttl: false,
+            binary: true,
+            labels: true,
+            machine_scope: true,
+        }
+    }
+
     /// Return the underlying concrete object with an `Any` type so that it can
     /// be downgraded to a [MacCredential] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -131,12 +476,34 @@ impl MacCredential {
     /// other than the ones we use to find the generic credential.
     /// But at least this checks whether the underlying credential exists.
     pub fn get_credential(&self) -> Result<Self> {
-        let (_, _) =
-            find_generic_password(Some(&[get_keychain(self)?]), &self.service, &self.account)
-                .map_err(decode_error)?;
+        let (password, _) = find_generic_password(
+            Some(&[get_keychain(&self.domain)?]),
+            &self.service,
+            &self.account,
+        )
+        .map_err(decode_error)?;
+        unsafe { zeroize_item_password(&password) };
         Ok(self.clone())
     }
 
+    /// Find the native keychain item for this credential.
+    ///
+    /// This gives access to the underlying `SecKeychainItemRef`
+    /// (via [TCFType](core_foundation::base::TCFType)), so you can use
+    /// the Keychain Services API to read attributes this crate doesn't expose.
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    pub fn get_keychain_item(&self) -> Result<SecKeychainItem> {
+        let (password, item) = find_generic_password(
+            Some(&[get_keychain(&self.domain)?]),
+            &self.service,
+            &self.account,
+        )
+        .map_err(decode_error)?;
+        unsafe { zeroize_item_password(&password) };
+        Ok(item)
+    }
+
     /// Create a credential representing a Mac keychain entry.
     ///
     /// Creating a credential does not put anything into the keychain.
@@ -173,8 +540,109 @@ impl MacCredential {
             domain,
             service: service.to_string(),
             account: user.to_string(),
+            label: None,
+            prompt: None,
+            no_prompt: false,
         })
     }
+
+    /// Choose the text shown to the user if reading this credential's
+    /// secret requires their authorization.
+    ///
+    /// The text is passed to the keychain as the `kSecUseOperationPrompt`
+    /// of the read, so it should say which app wants the secret and why
+    /// (e.g., "MyApp wants to access your GitHub token").
+    /// macOS shows it in the authorization dialogs that apps may customize
+    /// (such as those for items protected by Touch ID or a password);
+    /// the dialogs that guard access to a keychain item from an
+    /// untrusted app always use the system's own wording.
+    /// An empty prompt removes any previously chosen one.
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = (!prompt.is_empty()).then(|| prompt.to_string());
+        self
+    }
+
+    /// Choose that reading this credential's secret fails, rather than
+    /// asking the user, if it requires their authorization.
+    ///
+    /// Reads are made with a `kSecUseAuthenticationUI` of `kSecUseAuthenticationUIFail`,
+    /// so where the keychain would show an authorization dialog the read fails with a
+    /// [Locked](ErrorCode::Locked) error instead.  This is for daemons and other
+    /// processes that have no one to answer a dialog.
+    pub fn with_no_prompt(mut self) -> Self {
+        self.no_prompt = true;
+        self
+    }
+
+    /// Read the secret with `SecItemCopyMatching`, so that this credential's
+    /// operation prompt (or refusal to prompt) applies if the user has
+    /// to authorize the read.
+    fn get_secret_with_options(&self) -> Result<Vec<u8>> {
+        let keychains = CFArray::from_CFTypes(&[get_keychain(&self.domain)?]);
+        let key = unsafe { CFString::wrap_under_get_rule(kSecMatchSearchList) };
+        let mut options = PasswordOptions::new_generic_password(&self.service, &self.account);
+        #[allow(deprecated)]
+        options.query.push((key, keychains.into_CFType()));
+        if let Some(prompt) = self.prompt.as_deref() {
+            set_operation_prompt(&mut options, prompt);
+        }
+        if self.no_prompt {
+            set_no_prompt(&mut options);
+        }
+        generic_password(options).map_err(decode_error)
+    }
+
+    /// Give the keychain item for this credential its label, if it has one.
+    ///
+    /// The Keychain Services call that sets generic passwords can't set a label,
+    /// so this is done after the item is written.
+    fn apply_label(&self) -> Result<()> {
+        let Some(label) = self.label.as_deref() else {
+            return Ok(());
+        };
+        let mut search = ItemSearchOptions::new();
+        search
+            .class(ItemClass::generic_password())
+            .keychains(&[get_keychain(&self.domain)?])
+            .service(&self.service)
+            .account(&self.account);
+        let mut update = ItemUpdateOptions::new();
+        update.set_label(label);
+        update_item(&search, &update).map_err(decode_error)
+    }
+
+    /// Find the credential for the given service and user in the first
+    /// keychain in the user's search list that has one.
+    ///
+    /// Unlike a credential made with [new_with_target](MacCredential::new_with_target),
+    /// which only looks in one keychain, this looks in each of the keychains given by
+    /// [keychain_search_list] in turn.  The credential returned is in the
+    /// [File](MacKeychainDomain::File) domain of the keychain it was found in,
+    /// so writes to it go to that keychain.  Returns a [NoEntry](ErrorCode::NoEntry)
+    /// error if none of the keychains has the credential.
+    pub fn find_in_search_list(service: &str, user: &str) -> Result<Self> {
+        for path in keychain_search_list()? {
+            if !path.is_file() {
+                continue;
+            }
+            let credential = Self::new_with_keychain_path(&path, service, user)?;
+            if credential.exists()? {
+                return Ok(credential);
+            }
+        }
+        Err(ErrorCode::NoEntry)
+    }
+
+    /// Create a credential representing an entry in the keychain
+    /// in the file at the given path (typically a `.keychain-db` file).
+    ///
+    /// All the credential's operations use that keychain.  If there
+    /// is no file at the path, they fail with an [Invalid](ErrorCode::Invalid)
+    /// error whose attribute is `keychain path`; the keychain is never created.
+    pub fn new_with_keychain_path(path: &Path, service: &str, user: &str) -> Result<Self> {
+        let domain = MacKeychainDomain::File(path.to_path_buf());
+        Self::new_with_target(Some(domain), service, user)
+    }
 }
 
 /// The builder for Mac keychain credentials
@@ -189,6 +657,11 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
 }
 
 impl CredentialBuilderApi for MacCredentialBuilder {
+    /// The name of this store: `"macos-keychain"`.
+    fn backend_name(&self) -> &'static str {
+        "macos-keychain"
+    }
+
     /// Build a [MacCredential] for the given target, service, and user.
     ///
     /// If a target is specified but not recognized as a keychain name,
@@ -211,6 +684,72 @@ impl CredentialBuilderApi for MacCredentialBuilder {
         }
     }
 
+    /// Build a [MacCredential] in the given scope for the given service and user.
+    ///
+    /// User-scope credentials are in the User keychain, and
+    /// machine-scope credentials are in the System keychain.
+    fn build_in_scope(&self, scope: Scope, service: &str, user: &str) -> Result<Box<Credential>> {
+        let domain = match scope {
+            Scope::Machine => MacKeychainDomain::System,
+            _ => MacKeychainDomain::User,
+        };
+        Ok(Box::new(MacCredential::new_with_target(
+            Some(domain),
+            service,
+            user,
+        )?))
+    }
+
+    /// Build a [MacCredential] in the User keychain whose item is given the label.
+    fn build_with_label(&self, label: &str, service: &str, user: &str) -> Result<Box<Credential>> {
+        let mut credential = MacCredential::new_with_target(None, service, user)?;
+        credential.label = Some(label.to_string());
+        Ok(Box::new(credential))
+    }
+
+    /// Find all the generic credentials for the given service in the User keychain.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        let accounts = search_accounts(&MacKeychainDomain::User, service)?;
+        Ok(accounts
+            .into_iter()
+            .map(|account| {
+                Box::new(MacCredential {
+                    domain: MacKeychainDomain::User,
+                    service: service.to_string(),
+                    account,
+                    label: None,
+                    prompt: None,
+                    no_prompt: false,
+                }) as Box<Credential>
+            })
+            .collect())
+    }
+
+    /// Count the generic credentials for the given service in the User keychain.
+    ///
+    /// The keychain is searched for their attributes, so no secrets
+    /// are read (and the user isn't prompted).
+    fn count(&self, service: &str) -> Result<usize> {
+        Ok(search_accounts(&MacKeychainDomain::User, service)?.len())
+    }
+
+    /// Find all the generic credentials with the given attributes in the User keychain.
+    ///
+    /// The attributes that can be queried are `service`, `username`, and the
+    /// readable attributes returned by [get_attributes](MacCredential::get_attributes).
+    fn query(&self, attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        Ok(query_credentials(&MacKeychainDomain::User, attributes)?
+            .into_iter()
+            .map(|credential| Box::new(credential) as Box<Credential>)
+            .collect())
+    }
+
+    /// Open the User keychain, which is where entries are created by default.
+    fn probe(&self) -> Result<()> {
+        get_keychain(&MacKeychainDomain::User)?;
+        Ok(())
+    }
+
     /// Return the underlying builder object with an `Any` type so that it can
     /// be downgraded to a [MacCredentialBuilder] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -219,13 +758,19 @@ impl CredentialBuilderApi for MacCredentialBuilder {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-/// The four pre-defined Mac keychains.
+/// The pre-defined Mac keychains, and keychain files opened by path.
+///
+/// More kinds of keychain may be added, so matches on this need a wildcard arm.
+#[non_exhaustive]
 pub enum MacKeychainDomain {
     User,
     System,
     Common,
     Dynamic,
     Protected,
+    /// The keychain in the file at the given path
+    /// (see [new_with_keychain_path](MacCredential::new_with_keychain_path)).
+    File(PathBuf),
 }
 
 impl std::fmt::Display for MacKeychainDomain {
@@ -236,6 +781,7 @@ impl std::fmt::Display for MacKeychainDomain {
             MacKeychainDomain::Common => "Common".fmt(f),
             MacKeychainDomain::Dynamic => "Dynamic".fmt(f),
             MacKeychainDomain::Protected => "Protected".fmt(f),
+            MacKeychainDomain::File(path) => path.display().fmt(f),
         }
     }
 }
@@ -264,13 +810,83 @@ impl std::str::FromStr for MacKeychainDomain {
     }
 }
 
-fn get_keychain(cred: &MacCredential) -> Result<SecKeychain> {
-    let domain = match cred.domain {
+#[link(name = "Security", kind = "framework")]
+unsafe extern "C" {
+    static kSecMatchSearchList: CFStringRef;
+    fn SecKeychainCopySearchList(search_list: *mut CFArrayRef) -> i32;
+    fn SecKeychainGetPath(keychain: *const c_void, length: *mut u32, path: *mut c_char) -> i32;
+}
+
+/// The paths of the keychains in the user's keychain search list, in search order.
+///
+/// This is the list shown (and edited) by `security list-keychains`.
+/// It usually starts with the login keychain and ends with the System keychain.
+pub fn keychain_search_list() -> Result<Vec<PathBuf>> {
+    let mut list: CFArrayRef = std::ptr::null();
+    let status = unsafe { SecKeychainCopySearchList(&mut list) };
+    if status != 0 {
+        return Err(decode_error(Error::from_code(status)));
+    }
+    let list: CFArray<SecKeychain> = unsafe { CFArray::wrap_under_create_rule(list) };
+    list.iter()
+        .map(|keychain| keychain_path(&keychain))
+        .collect()
+}
+
+/// The path of the file that holds the given keychain.
+fn keychain_path(keychain: &SecKeychain) -> Result<PathBuf> {
+    let mut buffer = vec![0u8; 1024];
+    let mut length = buffer.len() as u32;
+    let status = unsafe {
+        SecKeychainGetPath(
+            keychain.as_concrete_TypeRef().cast(),
+            &mut length,
+            buffer.as_mut_ptr().cast(),
+        )
+    };
+    if status != 0 {
+        return Err(decode_error(Error::from_code(status)));
+    }
+    buffer.truncate(length as usize);
+    Ok(PathBuf::from(OsString::from_vec(buffer)))
+}
+
+/// Find the generic credentials for the given service in all the keychains
+/// in the user's search list (see [keychain_search_list]).
+///
+/// Each credential found is in the [File](MacKeychainDomain::File) domain of
+/// the keychain it came from, so its domain tells you where it is, and
+/// writing to it changes only that keychain.  The credentials are in
+/// search order.  Keychains in the list whose file is missing are skipped.
+pub fn search_keychains(service: &str) -> Result<Vec<MacCredential>> {
+    let mut found = Vec::new();
+    for path in keychain_search_list()? {
+        if !path.is_file() {
+            continue;
+        }
+        let domain = MacKeychainDomain::File(path);
+        for account in search_accounts(&domain, service)? {
+            found.push(MacCredential {
+                domain: domain.clone(),
+                service: service.to_string(),
+                account,
+                label: None,
+                prompt: None,
+                no_prompt: false,
+            });
+        }
+    }
+    Ok(found)
+}
+
+fn get_keychain(domain: &MacKeychainDomain) -> Result<SecKeychain> {
+    let domain = match domain {
         MacKeychainDomain::User => SecPreferencesDomain::User,
         MacKeychainDomain::System => SecPreferencesDomain::System,
         MacKeychainDomain::Common => SecPreferencesDomain::Common,
         MacKeychainDomain::Dynamic => SecPreferencesDomain::Dynamic,
         MacKeychainDomain::Protected => panic!("Protected is not a keychain domain on macOS"),
+        MacKeychainDomain::File(path) => return open_keychain(path),
     };
     match SecKeychain::default_for_domain(domain) {
         Ok(keychain) => Ok(keychain),
@@ -278,27 +894,118 @@ fn get_keychain(cred: &MacCredential) -> Result<SecKeychain> {
     }
 }
 
+/// Open the keychain in the file at the given path.
+///
+/// `SecKeychainOpen` succeeds even if there is no such file (and later
+/// operations would then create it), so we check that the file exists first.
+fn open_keychain(path: &Path) -> Result<SecKeychain> {
+    if !path.is_file() {
+        return Err(ErrorCode::Invalid(
+            "keychain path".to_string(),
+            format!("there is no keychain file at '{}'", path.display()),
+        ));
+    }
+    SecKeychain::open(path).map_err(decode_error)
+}
+
+/// Find the generic credentials in a keychain that have all the given attributes.
+///
+/// The `service` and `username` attributes are matched against the
+/// items' service and account; the others against their readable attributes.
+fn query_credentials(
+    domain: &MacKeychainDomain,
+    attributes: &HashMap<&str, &str>,
+) -> Result<Vec<MacCredential>> {
+    let mut options = ItemSearchOptions::new();
+    options
+        .class(ItemClass::generic_password())
+        .keychains(&[get_keychain(domain)?])
+        .load_attributes(true)
+        .limit(Limit::All);
+    if let Some(service) = attributes.get("service") {
+        options.service(service);
+    }
+    if let Some(user) = attributes.get("username") {
+        options.account(user);
+    }
+    let results = match options.search().map_err(decode_error) {
+        Ok(results) => results,
+        Err(ErrorCode::NoEntry) => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut found = Vec::new();
+    for result in &results {
+        let Some(mut dict) = result.simplify_dict() else {
+            continue;
+        };
+        let (Some(service), Some(account)) = (dict.remove("svce"), dict.remove("acct")) else {
+            continue;
+        };
+        let mut actual = extract_attributes(std::slice::from_ref(result));
+        actual.insert("service".to_string(), service.clone());
+        actual.insert("username".to_string(), account.clone());
+        let matches = attributes
+            .iter()
+            .all(|(name, value)| actual.get(*name).map(String::as_str) == Some(*value));
+        if matches {
+            found.push(MacCredential {
+                domain: domain.clone(),
+                service,
+                account,
+                label: None,
+                prompt: None,
+                no_prompt: false,
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Find the accounts of all the generic credentials for a service in a keychain.
+fn search_accounts(domain: &MacKeychainDomain, service: &str) -> Result<Vec<String>> {
+    let results = ItemSearchOptions::new()
+        .class(ItemClass::generic_password())
+        .keychains(&[get_keychain(domain)?])
+        .service(service)
+        .load_attributes(true)
+        .limit(Limit::All)
+        .search();
+    let results = match results.map_err(decode_error) {
+        Ok(results) => results,
+        Err(ErrorCode::NoEntry) => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    Ok(results
+        .iter()
+        .filter_map(|result| result.simplify_dict()?.remove("acct"))
+        .collect())
+}
+
 /// Map a Mac API error to a crate error with appropriate annotation
 ///
 /// The macOS error code values used here are from
 /// [this reference](https://opensource.apple.com/source/libsecurity_keychain/libsecurity_keychain-78/lib/SecBase.h.auto.html)
 pub fn decode_error(err: Error) -> ErrorCode {
-    match err.code() {
-        -25291 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecNotAvailable
-        -25292 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecReadOnly
-        -25294 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecNoSuchKeychain
-        -25295 => ErrorCode::NoStorageAccess(Box::new(err)), // errSecInvalidKeychain
-        -25300 => ErrorCode::NoEntry,                        // errSecItemNotFound
-        _ => ErrorCode::PlatformFailure(Box::new(err)),
-    }
+    err.into()
+}
+
+/// Wipe the copy of a secret that the keychain made for us, before it is freed.
+///
+/// # Safety
+///
+/// The password must not be read again.  Its content is allocated (writable)
+/// by `SecKeychainItemCopyContent`, and is only freed by its `Drop`.
+unsafe fn zeroize_item_password(password: &SecKeychainItemPassword) {
+    let bytes: &[u8] = password;
+    unsafe { std::slice::from_raw_parts_mut(bytes.as_ptr().cast_mut(), bytes.len()) }.zeroize();
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::credential::CredentialPersistence;
+    use crate::credential::{CredentialApi, CredentialPersistence};
     use crate::{Entry, Error, tests::generate_random_string};
 
-    use super::{MacCredential, default_credential_builder};
+    use super::{MacCredential, MacKeychainDomain, decode_error, default_credential_builder};
 
     #[test]
     fn test_persistence() {
@@ -308,6 +1015,191 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(
+            default_credential_builder().backend_name(),
+            "macos-keychain"
+        );
+        let name = generate_random_string();
+        assert_eq!(entry_new(&name, &name).backend_name(), "macos-keychain");
+    }
+
+    #[test]
+    fn test_probe() {
+        default_credential_builder()
+            .probe()
+            .expect("Can't open the User keychain")
+    }
+
+    #[test]
+    fn test_platform_code() {
+        // errSecReadOnly
+        let err = decode_error(security_framework::base::Error::from_code(-25292));
+        assert!(matches!(err, Error::NoStorageAccess(_)));
+        assert_eq!(err.platform_code(), Some(-25292));
+    }
+
+    #[test]
+    fn test_platform_message() {
+        // errSecItemNotFound
+        let err = decode_error(security_framework::base::Error::from_code(-25300));
+        assert!(matches!(err, Error::NoEntry));
+        assert_eq!(err.platform_message(), None);
+        // errSecReadOnly
+        let err = decode_error(security_framework::base::Error::from_code(-25292));
+        let message = err
+            .platform_message()
+            .expect("No message for errSecReadOnly");
+        assert!(!message.is_empty());
+    }
+
+    #[test]
+    fn test_search_list() {
+        let list = super::keychain_search_list().expect("Can't get the keychain search list");
+        assert!(!list.is_empty(), "The keychain search list is empty");
+        let name = generate_random_string();
+        assert!(matches!(
+            MacCredential::find_in_search_list(&name, &name),
+            Err(Error::NoEntry)
+        ));
+        let entry = entry_new(&name, &name);
+        entry.set_password("test search list").unwrap();
+        let found = MacCredential::find_in_search_list(&name, &name)
+            .expect("Didn't find the credential in the search list");
+        assert!(matches!(found.domain, MacKeychainDomain::File(_)));
+        assert_eq!(found.get_password().unwrap(), "test search list");
+        let all = super::search_keychains(&name).expect("Can't search the keychains");
+        assert_eq!(all.len(), 1, "Didn't find exactly one credential");
+        assert_eq!(all[0].domain, found.domain);
+        entry.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_missing_keychain_path() {
+        let path = std::env::temp_dir().join(format!("{}.keychain-db", generate_random_string()));
+        let entry = Entry::new_with_keychain_path(&path, "test service", "test user")
+            .expect("Can't create entry with keychain path");
+        for result in [
+            entry.set_password("test password"),
+            entry.get_password().map(|_| ()),
+            entry.delete_credential(),
+        ] {
+            assert!(
+                matches!(result, Err(Error::Invalid(ref attr, _)) if attr == "keychain path"),
+                "Missing keychain file wasn't reported: {result:?}"
+            );
+        }
+        assert!(!path.exists(), "Keychain file was created");
+    }
+
+    #[test]
+    fn test_new_with_label() {
+        let name = generate_random_string();
+        let entry = Entry::new_with_label("My App's Password", &name, &name)
+            .expect("Can't create labeled entry");
+        entry
+            .set_password("labeled password")
+            .expect("Can't set labeled password");
+        let attributes = entry
+            .get_attributes()
+            .expect("Can't get labeled attributes");
+        assert_eq!(attributes["label"], "My App's Password");
+        entry
+            .delete_credential()
+            .expect("Can't delete labeled credential");
+    }
+
+    #[test]
+    fn test_with_prompt() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("prompted password")
+            .expect("Can't set password for prompt");
+        let prompted = entry.clone().with_prompt("Test wants your password");
+        let credential: &MacCredential = prompted
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a mac credential");
+        assert_eq!(
+            credential.prompt.as_deref(),
+            Some("Test wants your password")
+        );
+        assert_eq!(
+            prompted
+                .get_password()
+                .expect("Can't get password with prompt"),
+            "prompted password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for prompt");
+    }
+
+    #[test]
+    fn test_with_no_prompt() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("unprompted password")
+            .expect("Can't set password for no prompt");
+        let unprompted = entry.clone().with_no_prompt();
+        let credential: &MacCredential = unprompted
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a mac credential");
+        assert!(credential.no_prompt);
+        assert_eq!(
+            unprompted
+                .get_password()
+                .expect("Can't get password without prompting"),
+            "unprompted password"
+        );
+        unprompted
+            .set_password("rewritten password")
+            .expect("Can't set password without prompting");
+        unprompted
+            .update_password("updated password")
+            .expect("Can't update password without prompting");
+        assert_eq!(
+            entry.get_password().expect("Can't get unprompted update"),
+            "updated password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for no prompt");
+    }
+
+    #[test]
+    fn test_machine_scope() {
+        let entry = Entry::new_in_scope(crate::Scope::Machine, "test service", "test user")
+            .expect("Can't create machine-scope entry");
+        let credential: &MacCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a mac credential");
+        assert!(matches!(
+            credential.domain,
+            super::MacKeychainDomain::System
+        ));
+    }
+
+    #[test]
+    fn test_no_write_permission() {
+        let err = decode_error(security_framework::base::Error::from_code(-61));
+        assert!(matches!(err, Error::NoStorageAccess(_)));
+    }
+
+    #[test]
+    fn test_locked() {
+        for code in [-25293, -25308] {
+            let err = decode_error(security_framework::base::Error::from_code(code));
+            assert!(matches!(err, Error::Locked(_)), "{code} isn't locked");
+            assert_eq!(err.platform_code(), Some(code as i64));
+        }
+    }
+
     fn entry_new(service: &str, user: &str) -> Entry {
         crate::tests::entry_from_constructor(
             |_, s, u| MacCredential::new_with_target(None, s, u),
@@ -355,11 +1247,134 @@ mod tests {
         crate::tests::test_round_trip_random_secret(entry_new);
     }
 
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
     #[test]
     fn test_update() {
         crate::tests::test_update(entry_new);
     }
 
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_rename_keeps_label() {
+        let name = generate_random_string();
+        let new_name = generate_random_string();
+        let mut credential = MacCredential::new_with_target(None, &name, &name).unwrap();
+        credential.label = Some("custom label".to_string());
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry.set_password("labeled password").unwrap();
+        entry.rename(&new_name, &new_name).unwrap();
+        let moved = Entry::new_with_credential(Box::new(
+            MacCredential::new_with_target(None, &new_name, &new_name).unwrap(),
+        ));
+        assert_eq!(moved.get_attributes().unwrap()["label"], "custom label");
+        moved.delete_credential().unwrap();
+        // a default label follows the service
+        let entry = entry_new(&name, &name);
+        entry.set_password("unlabeled password").unwrap();
+        entry.rename(&new_name, &new_name).unwrap();
+        assert_eq!(moved.get_attributes().unwrap()["label"], new_name);
+        moved.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_set_keeps_attributes() {
+        crate::tests::test_set_keeps_attributes(entry_new, "comment");
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry_not_supported(entry_new);
+    }
+
     #[test]
     fn test_get_credential() {
         let name = generate_random_string();
@@ -376,15 +1391,58 @@ mod tests {
             .set_password("test get_credential")
             .expect("Can't set password for get_credential");
         assert!(credential.get_credential().is_ok());
+        credential
+            .get_keychain_item()
+            .expect("Can't get keychain item");
         entry
             .delete_credential()
             .expect("Couldn't delete after get_credential");
         assert!(matches!(entry.get_password(), Err(Error::NoEntry)));
     }
 
+    #[test]
+    fn test_query() {
+        crate::tests::test_query(entry_new, default_credential_builder().as_ref());
+    }
+
+    #[test]
+    fn test_search() {
+        let name = generate_random_string();
+        let builder = default_credential_builder();
+        let found = builder
+            .search(&name)
+            .expect("Can't search for missing service");
+        assert!(found.is_empty(), "Found entries for a missing service");
+        let user1 = generate_random_string();
+        let user2 = generate_random_string();
+        for user in [&user1, &user2] {
+            entry_new(&name, user)
+                .set_password(user)
+                .expect("Can't set password for search");
+        }
+        let found = builder.search(&name).expect("Can't search for service");
+        assert_eq!(found.len(), 2, "Didn't find both entries");
+        for credential in found {
+            let entry = Entry::new_with_credential(credential);
+            let cred: &MacCredential = entry.get_credential().downcast_ref().unwrap();
+            let user = cred.account.clone();
+            assert!(user == user1 || user == user2, "Found the wrong user");
+            assert_eq!(
+                entry.get_password().expect("Can't get found password"),
+                user
+            );
+            entry.delete_credential().expect("Can't delete found entry");
+        }
+        let found = builder.search(&name).expect("Can't search after delete");
+        assert!(found.is_empty(), "Found entries after delete");
+    }
+
     #[test]
     fn test_get_update_attributes() {
-        crate::tests::test_noop_get_update_attributes(entry_new);
+        crate::tests::test_get_update_named_attributes(
+            entry_new,
+            &["label", "comment", "description"],
+        );
     }
 
     #[test]
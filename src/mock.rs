@@ -3,9 +3,10 @@
 # Mock credential store
 
 To facilitate testing of clients, this crate provides a Mock credential store
-that is platform-independent, provides no persistence, and allows the client
-to specify the return values (including errors) for each call. The credentials
-in this store have no attributes at all.
+that is platform-independent, keeps its credentials in process memory, and allows
+the client to specify the return values (including errors) for each call. Besides
+their `target`, `service`, and `username`, the credentials in this store can have
+any other attributes, which are empty when a credential is created.
 
 To use this credential store instead of the default, make this call during
 application startup _before_ creating any entries:
@@ -14,9 +15,12 @@ keyring::set_default_credential_builder(keyring::mock::default_credential_builde
 ```
 
 You can then create entries as you usually do, and call their usual methods
-to set, get, and delete passwords.  There is no persistence other than
-in the entry itself, so getting a password before setting it will always result
-in a [NoEntry](Error::NoEntry) error.
+to set, get, and delete passwords.  Credentials are kept in an in-memory map
+keyed by the target, service, and user of their entry, so two entries created with
+the same target, service, and user share the same credential, just as they would
+with the platform stores.  Nothing persists after the process terminates, so getting
+a password before setting it in the same process will result in a
+[NoEntry](Error::NoEntry) error.
 
 If you want a method call on an entry to fail in a specific way, you can
 downcast the entry to a [MockCredential] and then call [set_error](MockCredential::set_error)
@@ -32,31 +36,51 @@ mock.set_error(Error::Invalid("mock error".to_string(), "takes precedence".to_st
 entry.set_password("test").expect_err("error will override");
 entry.set_password("test").expect("error has been cleared");
 ```
+
+Tests that share a process (as the tests in a crate do) also share the
+mock store.  To keep their credentials from affecting one another, call
+[clear] to empty the store, or use [snapshot] and [restore] to put it back
+the way it was (for example, after setting up fixtures).
  */
 use std::cell::RefCell;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use super::credential::{
-    Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi, CredentialPersistence,
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi,
+    CredentialPersistence, expiry_after, has_expired,
 };
 use super::error::{Error, Result, decode_password};
 
 /// The concrete mock credential
 ///
 /// Mocks use an internal mutability pattern since entries are read-only.
-/// The mutex is used to make sure these are Sync.
-#[derive(Debug)]
+/// The mutex is used to make sure these are Sync, and the data is
+/// reference-counted so it can be shared by all the mocks with the same
+/// target, service, and user.
+///
+/// A default mock credential has no target and empty service and user,
+/// and its data is not shared with any other mock.
+#[derive(Debug, Default)]
 pub struct MockCredential {
-    pub inner: Mutex<RefCell<MockData>>,
+    pub target: Option<String>,
+    pub service: String,
+    pub user: String,
+    pub inner: SharedMockData,
 }
 
-impl Default for MockCredential {
-    fn default() -> Self {
-        Self {
-            inner: Mutex::new(RefCell::new(Default::default())),
-        }
-    }
-}
+/// The identifying information for a mock credential
+type MockKey = (Option<String>, String, String);
+
+/// The shared data for a mock credential
+type SharedMockData = Arc<Mutex<RefCell<MockData>>>;
+
+/// The attributes that identify a mock credential, which can't be updated
+const IDENTIFYING_ATTRIBUTES: [&str; 3] = ["target", "service", "username"];
+
+/// The in-memory store with the data for all the mock credentials
+static MOCK_STORE: Mutex<Option<HashMap<MockKey, SharedMockData>>> = Mutex::new(None);
 
 /// The (in-memory) persisted data for a mock credential.
 ///
@@ -65,13 +89,51 @@ impl Default for MockCredential {
 ///
 /// (Everything about this structure is public for transparency.
 /// Most keystore implementation hide their internals.)
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct MockData {
     pub secret: Option<Vec<u8>>,
     pub error: Option<Error>,
+    /// When the secret expires, in seconds since the Unix epoch
+    pub expiry: Option<u64>,
+    /// When the secret was last set
+    pub modified: Option<SystemTime>,
+    /// When the secret was first set (since the credential was last deleted)
+    pub created: Option<SystemTime>,
+    /// The attributes set with [update_attributes](CredentialApi::update_attributes)
+    pub attributes: HashMap<String, String>,
+}
+
+// The secret and attribute values are left out of the debug format,
+// since entries are logged with it.
+impl std::fmt::Debug for MockData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockData")
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("error", &self.error)
+            .field("expiry", &self.expiry)
+            .field("modified", &self.modified)
+            .field("created", &self.created)
+            .field("attributes", &self.attributes.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MockData {
+    /// The secret, unless it has expired.
+    fn live_secret(&self) -> Option<&Vec<u8>> {
+        match self.expiry {
+            Some(expiry) if has_expired(expiry) => None,
+            _ => self.secret.as_ref(),
+        }
+    }
 }
 
 impl CredentialApi for MockCredential {
+    /// The name of this store: `"mock"`.
+    fn backend_name(&self) -> &'static str {
+        "mock"
+    }
+
     /// Set a password on a mock credential.
     ///
     /// If there is an error in the mock, it will be returned
@@ -83,7 +145,13 @@ impl CredentialApi for MockCredential {
         let err = data.error.take();
         match err {
             None => {
+                if data.live_secret().is_none() {
+                    data.created = Some(SystemTime::now());
+                    data.attributes.clear();
+                }
                 data.secret = Some(password.as_bytes().to_vec());
+                data.expiry = None;
+                data.modified = Some(SystemTime::now());
                 Ok(())
             }
             Some(err) => Err(err),
@@ -96,12 +164,65 @@ impl CredentialApi for MockCredential {
     /// and the password will _not_ be set.  The error will
     /// be cleared, so calling again will set the password.
     fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        let mut inner = self.inner.lock().expect("Can't access mock data for set");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => {
+                if data.live_secret().is_none() {
+                    data.created = Some(SystemTime::now());
+                    data.attributes.clear();
+                }
+                data.secret = Some(secret.to_vec());
+                data.expiry = None;
+                data.modified = Some(SystemTime::now());
+                Ok(())
+            }
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Set a secret that expires on a mock credential.
+    ///
+    /// The expiration time is kept in the mock's data.
+    /// If there is an error in the mock, it will be returned
+    /// and the secret will _not_ be set.
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        let expiry = expiry_after(ttl)?;
+        let mut inner = self.inner.lock().expect("Can't access mock data for set");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => {
+                if data.live_secret().is_none() {
+                    data.created = Some(SystemTime::now());
+                    data.attributes.clear();
+                }
+                data.secret = Some(secret.to_vec());
+                data.expiry = Some(expiry);
+                data.modified = Some(SystemTime::now());
+                Ok(())
+            }
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Set a secret on a mock credential, dropping its attributes.
+    ///
+    /// The credential is replaced in one step, so it counts as created now.
+    /// If there is an error in the mock, it will be returned
+    /// and neither the secret nor the attributes will change.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
         let mut inner = self.inner.lock().expect("Can't access mock data for set");
         let data = inner.get_mut();
         let err = data.error.take();
         match err {
             None => {
                 data.secret = Some(secret.to_vec());
+                data.expiry = None;
+                data.attributes.clear();
+                data.created = Some(SystemTime::now());
+                data.modified = data.created;
                 Ok(())
             }
             Some(err) => Err(err),
@@ -117,7 +238,7 @@ impl CredentialApi for MockCredential {
         let data = inner.get_mut();
         let err = data.error.take();
         match err {
-            None => match &data.secret {
+            None => match data.live_secret() {
                 None => Err(Error::NoEntry),
                 Some(val) => decode_password(val.clone()),
             },
@@ -134,7 +255,7 @@ impl CredentialApi for MockCredential {
         let data = inner.get_mut();
         let err = data.error.take();
         match err {
-            None => match &data.secret {
+            None => match data.live_secret() {
                 None => Err(Error::NoEntry),
                 Some(val) => Ok(val.clone()),
             },
@@ -142,13 +263,186 @@ impl CredentialApi for MockCredential {
         }
     }
 
+    /// Get the attributes of a mock credential.
+    ///
+    /// If there is an error set in the mock, it will
+    /// be returned instead.
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        Ok(self.get_secret_with_attributes()?.1)
+    }
+
+    /// Get the secret and the attributes of a mock credential.
+    ///
+    /// If there is an error set in the mock, it will
+    /// be returned instead.
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        let mut inner = self.inner.lock().expect("Can't access mock data for get");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => match data.live_secret() {
+                None => Err(Error::NoEntry),
+                Some(secret) => Ok((secret.clone(), data.attributes.clone())),
+            },
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Update the attributes of a mock credential.
+    ///
+    /// The `target`, `service`, and `username` attributes identify the
+    /// credential, so they are ignored.  If there is an error set in the mock,
+    /// it will be returned and the attributes will _not_ be updated.
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Can't access mock data for update");
+        let data = inner.get_mut();
+        if let Some(err) = data.error.take() {
+            return Err(err);
+        }
+        if data.live_secret().is_none() {
+            return Err(Error::NoEntry);
+        }
+        for (name, value) in attributes {
+            if !IDENTIFYING_ATTRIBUTES.contains(name) {
+                data.attributes.insert(name.to_string(), value.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether this mock credential has a secret that hasn't expired.
+    ///
+    /// If there is an error set on the credential, it is returned (and cleared).
+    fn exists(&self) -> Result<bool> {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Can't access mock data for exists");
+        let data = inner.get_mut();
+        match data.error.take() {
+            None => Ok(data.live_secret().is_some()),
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Get the secret of a mock credential and the times at which it was first and last set.
+    ///
+    /// If there is an error set in the mock, it will
+    /// be returned instead.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        let mut inner = self.inner.lock().expect("Can't access mock data for get");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => match data.live_secret() {
+                None => Err(Error::NoEntry),
+                Some(secret) => Ok((secret.to_vec(), data.created, data.modified)),
+            },
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Get the time at which the secret of a mock credential was last set.
+    ///
+    /// If there is an error set in the mock, it will
+    /// be returned instead.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        let mut inner = self.inner.lock().expect("Can't access mock data for get");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => match data.live_secret() {
+                None => Err(Error::NoEntry),
+                Some(_) => Ok(data.modified),
+            },
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Get the time at which the secret of a mock credential was first set.
+    ///
+    /// If there is an error set in the mock, it will
+    /// be returned instead.
+    fn created(&self) -> Result<Option<SystemTime>> {
+        let mut inner = self.inner.lock().expect("Can't access mock data for get");
+        let data = inner.get_mut();
+        let err = data.error.take();
+        match err {
+            None => match data.live_secret() {
+                None => Err(Error::NoEntry),
+                Some(_) => Ok(data.created),
+            },
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Return the service and user of this credential.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        Some((self.service.clone(), self.user.clone()))
+    }
+
+    /// Return the target of this credential, if it has one.
+    fn get_target(&self) -> Option<String> {
+        self.target.clone()
+    }
+
+    /// Move the data of a mock credential to the mock
+    /// with the same target and the given service and user.
+    ///
+    /// If there is an error in the mock, it will be returned
+    /// and the credential will _not_ be moved.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let renamed = MockCredential::new_with_target(self.target.as_deref(), service, user)?;
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Can't access mock data for rename");
+        let data = inner.get_mut();
+        if let Some(err) = data.error.take() {
+            return Err(err);
+        }
+        let Some(secret) = data.live_secret().cloned() else {
+            return Err(Error::NoEntry);
+        };
+        if Arc::ptr_eq(&self.inner, &renamed.inner) {
+            return Ok(());
+        }
+        let mut renamed_inner = renamed
+            .inner
+            .lock()
+            .expect("Can't access renamed mock data for rename");
+        let renamed_data = renamed_inner.get_mut();
+        if renamed_data.live_secret().is_some() {
+            return Err(Error::AlreadyExists);
+        }
+        renamed_data.secret = Some(secret);
+        renamed_data.expiry = data.expiry.take();
+        renamed_data.modified = data.modified.take();
+        renamed_data.created = data.created.take();
+        renamed_data.attributes = std::mem::take(&mut data.attributes);
+        data.secret = None;
+        Ok(())
+    }
+
+    /// Make a mock credential for the given user with this credential's target and service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(Self::new_with_target(
+            self.target.as_deref(),
+            &self.service,
+            user,
+        )?))
+    }
+
     /// Delete the password in a mock credential
     ///
     /// If there is an error, it will be returned and
     /// the deletion will not happen.
     ///
-    /// If there is no password, a [NoEntry](Error::NoEntry) error
-    /// will be returned.
+    /// If there is no password, or it has expired, a [NoEntry](Error::NoEntry)
+    /// error will be returned.
     fn delete_credential(&self) -> Result<()> {
         let mut inner = self
             .inner
@@ -157,9 +451,10 @@ impl CredentialApi for MockCredential {
         let data = inner.get_mut();
         let err = data.error.take();
         match err {
-            None => match data.secret {
+            None => match data.live_secret() {
                 Some(_) => {
                     data.secret = None;
+                    data.attributes.clear();
                     Ok(())
                 }
                 None => Err(Error::NoEntry),
@@ -168,6 +463,18 @@ impl CredentialApi for MockCredential {
         }
     }
 
+    /// Mock credentials have attributes, can be found by service, and can expire.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: true,
+            enumeration: true,
+            ttl: true,
+            binary: true,
+            labels: false,
+            machine_scope: false,
+        }
+    }
+
     /// Return this mock credential concrete object
     /// wrapped in the [Any](std::any::Any) trait,
     /// so it can be downcast.
@@ -184,10 +491,29 @@ impl CredentialApi for MockCredential {
 impl MockCredential {
     /// Make a new mock credential.
     ///
-    /// Since mocks have no persistence between sessions,
-    /// new mocks always have no password.
-    fn new_with_target(_target: Option<&str>, _service: &str, _user: &str) -> Result<Self> {
-        Ok(Default::default())
+    /// If a mock with the same target, service, and user has been
+    /// made before in this process, the new mock shares its data.
+    /// Otherwise, the new mock has no password.
+    fn new_with_target(target: Option<&str>, service: &str, user: &str) -> Result<Self> {
+        let key: MockKey = (
+            target.map(str::to_string),
+            service.to_string(),
+            user.to_string(),
+        );
+        let mut store = MOCK_STORE
+            .lock()
+            .expect("Can't access mock store for create");
+        let inner = store
+            .get_or_insert_with(HashMap::new)
+            .entry(key)
+            .or_default()
+            .clone();
+        Ok(Self {
+            target: target.map(str::to_string),
+            service: service.to_string(),
+            user: user.to_string(),
+            inner,
+        })
     }
 
     /// Set an error to be returned from this mock credential.
@@ -205,10 +531,123 @@ impl MockCredential {
     }
 }
 
+/// The saved data of a mock credential, as kept in a [MockSnapshot].
+#[derive(Clone)]
+struct SavedMockData {
+    secret: Vec<u8>,
+    expiry: Option<u64>,
+    modified: Option<SystemTime>,
+    created: Option<SystemTime>,
+    attributes: HashMap<String, String>,
+}
+
+/// A copy of the credentials in the mock store, made by [snapshot]
+/// and put back by [restore].
+///
+/// Errors set with [set_error](MockCredential::set_error) are not saved.
+#[derive(Clone, Default)]
+pub struct MockSnapshot {
+    credentials: HashMap<MockKey, SavedMockData>,
+}
+
+impl MockSnapshot {
+    /// The number of credentials in the snapshot.
+    pub fn len(&self) -> usize {
+        self.credentials.len()
+    }
+
+    /// Whether the snapshot has no credentials.
+    pub fn is_empty(&self) -> bool {
+        self.credentials.is_empty()
+    }
+}
+
+// The secrets are left out of the debug format.
+impl std::fmt::Debug for MockSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockSnapshot")
+            .field("credentials", &self.credentials.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Remove all the credentials from the mock store.
+///
+/// Use this between tests that share a process, so that no test sees
+/// the credentials of another.  Afterward, no credential has a password
+/// or an error set on it, including those of entries made before the store
+/// was cleared (which are still usable, and still share their credential
+/// with new entries for the same target, service, and user).
+///
+/// Since the mock store is shared by the whole process, clearing it
+/// affects tests running at the same time on other threads.
+pub fn clear() {
+    let store = MOCK_STORE
+        .lock()
+        .expect("Can't access mock store for clear");
+    for inner in store.iter().flat_map(HashMap::values) {
+        let mut inner = inner.lock().expect("Can't access mock data for clear");
+        *inner.get_mut() = MockData::default();
+    }
+}
+
+/// Make a copy of all the credentials in the mock store that have a password.
+///
+/// Put the copy back with [restore], for example to set up the same
+/// fixtures for each of several tests.
+pub fn snapshot() -> MockSnapshot {
+    let store = MOCK_STORE
+        .lock()
+        .expect("Can't access mock store for snapshot");
+    let mut snapshot = MockSnapshot::default();
+    for (key, inner) in store.iter().flat_map(HashMap::iter) {
+        let mut inner = inner.lock().expect("Can't access mock data for snapshot");
+        let data = inner.get_mut();
+        if let Some(secret) = data.live_secret() {
+            let saved = SavedMockData {
+                secret: secret.clone(),
+                expiry: data.expiry,
+                modified: data.modified,
+                created: data.created,
+                attributes: data.attributes.clone(),
+            };
+            snapshot.credentials.insert(key.clone(), saved);
+        }
+    }
+    snapshot
+}
+
+/// Replace the contents of the mock store with the given snapshot.
+///
+/// The store is [cleared](clear) first, so afterward exactly the
+/// credentials in the snapshot have passwords.
+pub fn restore(snapshot: &MockSnapshot) {
+    clear();
+    let mut store = MOCK_STORE
+        .lock()
+        .expect("Can't access mock store for restore");
+    let store = store.get_or_insert_with(HashMap::new);
+    for (key, saved) in &snapshot.credentials {
+        let inner = store.entry(key.clone()).or_default();
+        let mut inner = inner.lock().expect("Can't access mock data for restore");
+        let data = inner.get_mut();
+        data.secret = Some(saved.secret.clone());
+        data.expiry = saved.expiry;
+        data.modified = saved.modified;
+        data.created = saved.created;
+        data.attributes = saved.attributes.clone();
+    }
+}
+
 /// The builder for mock credentials.
 pub struct MockCredentialBuilder {}
 
 impl CredentialBuilderApi for MockCredentialBuilder {
+    /// The name of this store: `"mock"`.
+    fn backend_name(&self) -> &'static str {
+        "mock"
+    }
+
     /// Build a mock credential for the given target, service, and user.
     ///
     /// Since mocks don't persist between sessions,  all mocks
@@ -218,14 +657,82 @@ impl CredentialBuilderApi for MockCredentialBuilder {
         Ok(Box::new(credential))
     }
 
+    /// Find all the mock credentials with the given service that have a secret.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        let store = MOCK_STORE
+            .lock()
+            .expect("Can't access mock store for search");
+        let Some(store) = store.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let mut credentials: Vec<Box<Credential>> = vec![];
+        for ((target, found_service, user), inner) in store.iter() {
+            if found_service != service {
+                continue;
+            }
+            let has_secret = inner
+                .lock()
+                .expect("Can't access mock data for search")
+                .get_mut()
+                .live_secret()
+                .is_some();
+            if has_secret {
+                credentials.push(Box::new(MockCredential {
+                    target: target.clone(),
+                    service: service.to_string(),
+                    user: user.clone(),
+                    inner: inner.clone(),
+                }));
+            }
+        }
+        Ok(credentials)
+    }
+
+    /// Find all the mock credentials with the given attributes that have a secret.
+    ///
+    /// The `target`, `service`, and `username` attributes match the
+    /// entry a credential was made for, and any other attribute matches
+    /// the attributes set on the credential.
+    fn query(&self, attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        let store = MOCK_STORE
+            .lock()
+            .expect("Can't access mock store for query");
+        let Some(store) = store.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let mut credentials: Vec<Box<Credential>> = vec![];
+        for ((target, service, user), inner) in store.iter() {
+            let mut data = inner.lock().expect("Can't access mock data for query");
+            let data = data.get_mut();
+            if data.live_secret().is_none() {
+                continue;
+            }
+            let matches = attributes.iter().all(|(name, value)| match *name {
+                "target" => target.as_deref() == Some(*value),
+                "service" => service == value,
+                "username" => user == value,
+                name => data.attributes.get(name).map(String::as_str) == Some(*value),
+            });
+            if matches {
+                credentials.push(Box::new(MockCredential {
+                    target: target.clone(),
+                    service: service.clone(),
+                    user: user.clone(),
+                    inner: inner.clone(),
+                }));
+            }
+        }
+        Ok(credentials)
+    }
+
     /// Get an [Any][std::any::Any] reference to the mock credential builder.
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
-    /// This keystore keeps the password in the entry!
+    /// This keystore keeps the password in process memory!
     fn persistence(&self) -> CredentialPersistence {
-        CredentialPersistence::EntryOnly
+        CredentialPersistence::ProcessOnly
     }
 }
 
@@ -236,6 +743,8 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::{MockCredential, default_credential_builder};
     use crate::credential::CredentialPersistence;
     use crate::{Entry, Error, tests::generate_random_string};
@@ -244,10 +753,24 @@ mod tests {
     fn test_persistence() {
         assert!(matches!(
             default_credential_builder().persistence(),
-            CredentialPersistence::EntryOnly
+            CredentialPersistence::ProcessOnly
         ))
     }
 
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(default_credential_builder().backend_name(), "mock");
+        let name = generate_random_string();
+        assert_eq!(entry_new(&name, &name).backend_name(), "mock");
+    }
+
+    #[test]
+    fn test_probe() {
+        default_credential_builder()
+            .probe()
+            .expect("Mock store is unavailable")
+    }
+
     fn entry_new(service: &str, user: &str) -> Entry {
         let credential = MockCredential::new_with_target(None, service, user).unwrap();
         Entry::new_with_credential(Box::new(credential))
@@ -278,14 +801,363 @@ mod tests {
         crate::tests::test_round_trip_random_secret(entry_new);
     }
 
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
     #[test]
     fn test_update() {
         crate::tests::test_update(entry_new);
     }
 
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_set_keeps_attributes() {
+        crate::tests::test_set_keeps_attributes(entry_new, "test attribute name");
+    }
+
+    #[test]
+    fn test_replace_failure_keeps_attributes() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry.set_password("old password").unwrap();
+        entry
+            .update_attributes(&HashMap::from([("name", "value")]))
+            .unwrap();
+        let mock: &MockCredential = entry.get_credential().downcast_ref().unwrap();
+        mock.set_error(Error::NoStorageAccess("mock".into()));
+        assert!(matches!(
+            entry.set_password_replacing_attributes("new password"),
+            Err(Error::NoStorageAccess(_))
+        ));
+        assert_eq!(entry.get_password().unwrap(), "old password");
+        assert_eq!(
+            entry
+                .get_attributes()
+                .unwrap()
+                .get("name")
+                .map(String::as_str),
+            Some("value"),
+            "Failed replace lost the attributes"
+        );
+        entry.delete_credential().expect("Can't delete credential");
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_created_survives_update() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("first")
+            .expect("Can't set first password");
+        let created = entry.created().expect("Can't get creation time");
+        assert!(created.is_some(), "Mock didn't record creation time");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        entry
+            .set_password("second")
+            .expect("Can't set second password");
+        let info = entry.get_info().expect("Can't get info");
+        assert_eq!(info.created, created, "Update changed creation time");
+        assert!(
+            info.modified > created,
+            "Update didn't change modification time"
+        );
+        assert_eq!(info.secret_len, "second".len());
+        entry.delete_credential().expect("Can't delete credential");
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry(entry_new);
+    }
+
+    #[test]
+    fn test_delete_expired() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("expired password")
+            .expect("Can't set password");
+        let mock: &MockCredential = entry.get_credential().downcast_ref().unwrap();
+        mock.inner.lock().unwrap().get_mut().expiry = Some(1);
+        assert!(
+            matches!(entry.delete_credential(), Err(Error::NoEntry)),
+            "Deleted an expired credential"
+        );
+    }
+
     #[test]
     fn test_get_update_attributes() {
-        crate::tests::test_noop_get_update_attributes(entry_new);
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let map = HashMap::from([("name", name.as_str()), ("service", "other service")]);
+        assert!(
+            matches!(entry.update_attributes(&map), Err(Error::NoEntry)),
+            "Updated missing credential in attribute test",
+        );
+        entry
+            .set_password("test password for attributes")
+            .expect("Can't set password for attribute test");
+        assert!(entry.get_attributes().unwrap().is_empty());
+        entry
+            .update_attributes(&map)
+            .expect("Can't update attributes");
+        assert_eq!(
+            entry.get_attributes().unwrap(),
+            HashMap::from([("name".to_string(), name.clone())]),
+            "Identifying attribute was updated"
+        );
+        let found = default_credential_builder()
+            .query(&HashMap::from([("name", name.as_str())]))
+            .expect("Can't query by attribute");
+        assert_eq!(found.len(), 1, "Didn't find credential by attribute");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for attribute test");
+        entry
+            .set_password("recreated password")
+            .expect("Can't recreate credential");
+        assert!(
+            entry.get_attributes().unwrap().is_empty(),
+            "Recreated credential kept attributes"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete recreated credential");
+    }
+
+    #[test]
+    fn test_shared_data() {
+        let name = generate_random_string();
+        let entry1 = entry_new(&name, &name);
+        let entry2 = entry_new(&name, &name);
+        entry1
+            .set_password("shared password")
+            .expect("Can't set shared password");
+        assert_eq!(
+            entry2.get_password().expect("Can't get shared password"),
+            "shared password"
+        );
+        let other = entry_new(&name, "other user");
+        assert!(
+            matches!(other.get_password(), Err(Error::NoEntry)),
+            "Entry for a different user shares data"
+        );
+        entry2
+            .delete_credential()
+            .expect("Can't delete shared password");
+        assert!(
+            matches!(entry1.get_password(), Err(Error::NoEntry)),
+            "Able to read a deleted shared password"
+        );
+    }
+
+    #[test]
+    fn test_query() {
+        crate::tests::test_query(entry_new, default_credential_builder().as_ref());
+    }
+
+    #[test]
+    fn test_search() {
+        let name = generate_random_string();
+        let builder = default_credential_builder();
+        let found = builder
+            .search(&name)
+            .expect("Can't search for missing service");
+        assert!(found.is_empty(), "Found entries for a missing service");
+        let user1 = generate_random_string();
+        let user2 = generate_random_string();
+        for user in [&user1, &user2] {
+            entry_new(&name, user)
+                .set_password(user)
+                .expect("Can't set password for search");
+        }
+        // an entry without a password shouldn't be found
+        entry_new(&name, &name);
+        let found = builder.search(&name).expect("Can't search for service");
+        assert_eq!(found.len(), 2, "Didn't find both entries");
+        assert_eq!(builder.count(&name).expect("Can't count service"), 2);
+        for credential in found {
+            let entry = Entry::new_with_credential(credential);
+            let mock: &MockCredential = entry.get_credential().downcast_ref().unwrap();
+            let user = mock.user.clone();
+            assert_eq!(
+                entry.get_specifiers(),
+                Some((name.clone(), user.clone())),
+                "Found entry has the wrong specifiers"
+            );
+            assert!(user == user1 || user == user2, "Found the wrong user");
+            assert_eq!(
+                entry.get_password().expect("Can't get found password"),
+                user
+            );
+            entry.delete_credential().expect("Can't delete found entry");
+        }
+        let found = builder.search(&name).expect("Can't search after delete");
+        assert!(found.is_empty(), "Found entries after delete");
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("unloggable password")
+            .expect("Can't set password");
+        let debug = format!("{entry:?}");
+        let secret = format!("{:?}", b"unloggable password".to_vec());
+        let secret = secret.trim_matches(|c| c == '[' || c == ']');
+        assert!(!debug.contains(secret), "Debug format shows secret");
+        entry.delete_credential().expect("Can't delete credential");
+    }
+
+    #[test]
+    fn test_rotate_failure_keeps_old_password() {
+        use crate::credential::CredentialApi;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// A mock whose first write is made but then reported as failed,
+        /// as if the store had partly done it.
+        struct FailingWrite(MockCredential, AtomicBool);
+
+        impl CredentialApi for FailingWrite {
+            fn set_secret(&self, secret: &[u8]) -> crate::Result<()> {
+                self.0.set_secret(secret)?;
+                if self.1.swap(true, Ordering::SeqCst) {
+                    Ok(())
+                } else {
+                    Err(Error::NoStorageAccess("mock".into()))
+                }
+            }
+
+            fn get_secret(&self) -> crate::Result<Vec<u8>> {
+                self.0.get_secret()
+            }
+
+            fn delete_credential(&self) -> crate::Result<()> {
+                self.0.delete_credential()
+            }
+
+            fn as_any(&self) -> &dyn std::any::Any {
+                self
+            }
+        }
+
+        let name = generate_random_string();
+        let mock = MockCredential::new_with_target(None, &name, &name).unwrap();
+        mock.set_password("old password")
+            .expect("Can't set password");
+        let entry =
+            Entry::new_with_credential(Box::new(FailingWrite(mock, AtomicBool::new(false))));
+        assert!(matches!(
+            entry.rotate("new password"),
+            Err(Error::NoStorageAccess(_))
+        ));
+        assert_eq!(entry.get_password().unwrap(), "old password");
+        entry.delete_credential().expect("Can't delete credential");
+    }
+
+    #[test]
+    fn test_exists_error() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let mock: &MockCredential = entry
+            .inner
+            .as_any()
+            .downcast_ref()
+            .expect("Downcast failed");
+        mock.set_error(Error::NoStorageAccess("mock".into()));
+        assert!(
+            matches!(entry.exists(), Err(Error::NoStorageAccess(_))),
+            "exists: No error"
+        );
+        assert!(!entry.exists().expect("exists: Error not cleared"));
     }
 
     #[test]
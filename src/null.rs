@@ -0,0 +1,171 @@
+/*!
+
+# Null credential store
+
+Some environments (such as CI runners and minimal containers) have no
+secure storage at all.  If you would rather your application run without
+saving secrets than fail in such an environment, you can use this store,
+which never stores anything: setting a password or secret succeeds but
+discards it, and getting, deleting, or reading the attributes of a credential
+always fails with a [NoEntry](Error::NoEntry) error.
+
+Unlike the [mock](crate::mock) store, which keeps credentials in process memory
+so that tests can read back what they set, this store forgets everything
+immediately, so your application's "no saved password" paths are the ones exercised.
+
+To use this credential store instead of the default, make this call during
+application startup _before_ creating any entries:
+```rust
+keyring::set_default_credential_builder(keyring::null::default_credential_builder());
+```
+ */
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::credential::{
+    Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi, CredentialPersistence,
+};
+use super::error::{Error, Result};
+
+/// A credential that discards its secrets.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NullCredential {}
+
+impl CredentialApi for NullCredential {
+    /// The name of this store: `"null"`.
+    fn backend_name(&self) -> &'static str {
+        "null"
+    }
+
+    /// Discard the secret.  This always succeeds.
+    fn set_secret(&self, _: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Discard the secret.  This always succeeds.
+    fn set_secret_with_expiry(&self, _: &[u8], _: Duration) -> Result<()> {
+        Ok(())
+    }
+
+    /// There is never a secret, so this always returns a [NoEntry](Error::NoEntry) error.
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        Err(Error::NoEntry)
+    }
+
+    /// There is never a credential, so this always returns a [NoEntry](Error::NoEntry) error.
+    fn rename(&self, _: &str, _: &str) -> Result<()> {
+        Err(Error::NoEntry)
+    }
+
+    /// There is never a credential, so this always returns a [NoEntry](Error::NoEntry) error.
+    fn delete_credential(&self) -> Result<()> {
+        Err(Error::NoEntry)
+    }
+
+    /// Return this null credential concrete object
+    /// wrapped in the [Any](std::any::Any) trait,
+    /// so it can be downcast.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// Expose the concrete debug formatter for use via the [Credential] trait
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The builder for null credentials.
+#[derive(Debug, Default)]
+pub struct NullCredentialBuilder {}
+
+impl CredentialBuilderApi for NullCredentialBuilder {
+    /// The name of this store: `"null"`.
+    fn backend_name(&self) -> &'static str {
+        "null"
+    }
+
+    /// Build a null credential.  The target, service, and user are ignored.
+    fn build(&self, _: Option<&str>, _: &str, _: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(NullCredential {}))
+    }
+
+    /// There are never any credentials, so this always returns an empty vector.
+    fn search(&self, _: &str) -> Result<Vec<Box<Credential>>> {
+        Ok(Vec::new())
+    }
+
+    /// There are never any credentials, so this always returns an empty vector.
+    fn query(&self, _: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        Ok(Vec::new())
+    }
+
+    /// Return the underlying builder object with an `Any` type so that it can
+    /// be downgraded to a [NullCredentialBuilder] for platform-specific processing.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    /// Null credentials are discarded as soon as they are set.
+    fn persistence(&self) -> CredentialPersistence {
+        CredentialPersistence::EntryOnly
+    }
+}
+
+/// Return a null credential builder for use by clients.
+pub fn default_credential_builder() -> Box<CredentialBuilder> {
+    Box::new(NullCredentialBuilder {})
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{NullCredential, default_credential_builder};
+    use crate::credential::CredentialPersistence;
+    use crate::{Entry, Error};
+
+    fn entry_new(service: &str, user: &str) -> Entry {
+        crate::tests::entry_from_constructor(|_, _, _| Ok(NullCredential {}), service, user)
+    }
+
+    #[test]
+    fn test_persistence() {
+        assert!(matches!(
+            default_credential_builder().persistence(),
+            CredentialPersistence::EntryOnly
+        ))
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        crate::tests::test_missing_entry(entry_new);
+    }
+
+    #[test]
+    fn test_set_discards() {
+        let entry = entry_new("null service", "null user");
+        entry
+            .set_password("discarded")
+            .expect("Can't set null password");
+        entry
+            .set_secret_with_expiry(b"discarded", Duration::from_secs(60))
+            .expect("Can't set expiring null secret");
+        assert!(matches!(entry.get_password(), Err(Error::NoEntry)));
+        assert!(matches!(entry.get_secret(), Err(Error::NoEntry)));
+        assert!(matches!(entry.get_attributes(), Err(Error::NoEntry)));
+        assert!(matches!(
+            entry.rename("new service", "new user"),
+            Err(Error::NoEntry)
+        ));
+        assert!(matches!(entry.delete_credential(), Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_search() {
+        let found = default_credential_builder()
+            .search("null service")
+            .expect("Can't search null store");
+        assert!(found.is_empty(), "Found null credentials");
+    }
+}
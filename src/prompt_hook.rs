@@ -0,0 +1,382 @@
+/*!
+
+# Prompt callbacks
+
+Some operations make the operating system show the user a dialog and
+wait for them to answer it: reading a macOS keychain item that the user
+must authorize, reading an iOS keychain item that requires user presence,
+or unlocking a locked secret-service collection or item.  While the dialog
+is up, the operation blocks, so an interactive application seems frozen
+unless it says what it's waiting for.
+
+A prompt callback is called just before such an operation, so the
+application can show something like an "authenticating…" indicator
+(and hide it when the operation returns).  The callback is given a
+[PromptEvent] that says what kind of prompt may appear and which
+credential it's for.  A callback can be set for all entries with
+[set_prompt_callback], or for a single entry with
+[Entry::with_prompt_callback], which takes precedence.
+
+```rust
+# use keyring::{Entry, mock, prompt_hook::PromptEvent};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+keyring::prompt_hook::set_prompt_callback(|event: &PromptEvent| {
+    eprintln!("waiting for the user to allow access to {:?}", event.specifiers);
+});
+let entry = Entry::new("my service", "my user")?;
+entry.set_password("my password")?;
+# keyring::prompt_hook::clear_prompt_callback();
+# Ok::<(), keyring::Error>(())
+```
+
+Callbacks are called on the thread that runs the operation, and
+the operation waits for them to return, so they should be quick.
+A callback isn't called for operations that can't prompt,
+and on the macOS keychain (which doesn't say in advance whether a read
+will prompt) it's called before every read of a secret.
+
+An entry's own callback applies to the operations run on the thread that
+calls the entry's methods, so give an entry its callback before any
+[timeout](Entry::with_timeout), which runs operations on threads of their own.
+ */
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use super::credential::{Capabilities, Credential, CredentialApi};
+use super::{Entry, Result};
+
+/// The kinds of system prompt an operation can trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PromptKind {
+    /// The macOS keychain may ask the user to allow access to an item.
+    KeychainAccess,
+    /// The keychain will ask for Touch ID, Face ID, or the device passcode
+    /// to read an item that requires user presence.
+    UserPresence,
+    /// The secret-service will ask the user to unlock a collection or item.
+    Unlock,
+}
+
+/// What a prompt callback is told about the prompt that may appear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptEvent {
+    /// What the prompt will ask the user for
+    pub kind: PromptKind,
+    /// The name of the credential store (see [Entry::backend_name])
+    pub backend: &'static str,
+    /// The target of the credential the prompt is for, if it has one
+    pub target: Option<String>,
+    /// The service and user of the credential the prompt is for, if they are known
+    pub specifiers: Option<(String, String)>,
+}
+
+/// A function called before operations that may prompt the user.
+pub type PromptCallback = dyn Fn(&PromptEvent) + Send + Sync;
+
+static CALLBACK: RwLock<Option<Arc<PromptCallback>>> = RwLock::new(None);
+
+thread_local! {
+    /// The callback of the entry whose operation is running on this thread, if any.
+    static ENTRY_CALLBACK: RefCell<Option<Arc<PromptCallback>>> = const { RefCell::new(None) };
+}
+
+/// Set the callback called before operations that may prompt the user.
+///
+/// This replaces any callback set before, and applies to every
+/// entry that doesn't have one of its own.
+pub fn set_prompt_callback(callback: impl Fn(&PromptEvent) + Send + Sync + 'static) {
+    let mut guard = CALLBACK
+        .write()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    *guard = Some(Arc::new(callback));
+}
+
+/// Remove the callback set by [set_prompt_callback].
+pub fn clear_prompt_callback() {
+    let mut guard = CALLBACK
+        .write()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    *guard = None;
+}
+
+/// Call the prompt callback that applies, if there is one,
+/// before an operation on the given credential that may prompt the user.
+///
+/// Stores call this just before the operation.  Those that don't know which
+/// credential the operation is for pass `None`, and the event then has
+/// no target or specifiers.
+#[allow(dead_code)]
+pub(crate) fn before_prompt(
+    kind: PromptKind,
+    backend: &'static str,
+    credential: Option<&dyn CredentialApi>,
+) {
+    let callback = ENTRY_CALLBACK.with(|callback| callback.borrow().clone());
+    let callback = callback.or_else(|| {
+        CALLBACK
+            .read()
+            .expect("Poisoned RwLock in keyring-rs: please report a bug!")
+            .clone()
+    });
+    if let Some(callback) = callback {
+        let event = PromptEvent {
+            kind,
+            backend,
+            target: credential.and_then(|credential| credential.get_target()),
+            specifiers: credential.and_then(|credential| credential.get_specifiers()),
+        };
+        callback(&event);
+    }
+}
+
+/// Restores the previous entry callback of the thread when dropped.
+struct EntryCallbackGuard(Option<Arc<PromptCallback>>);
+
+impl Drop for EntryCallbackGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ENTRY_CALLBACK.with(|callback| *callback.borrow_mut() = previous);
+    }
+}
+
+/// A credential whose operations call their own prompt callback.
+///
+/// See the [module docs](crate::prompt_hook) for details.
+///
+/// The wrapped credential's [as_any](CredentialApi::as_any) is returned as this
+/// credential's, so [Entry::get_credential] still gives the concrete store credential.
+pub struct PromptHookCredential {
+    inner: Arc<Credential>,
+    callback: Arc<PromptCallback>,
+}
+
+impl PromptHookCredential {
+    /// Wrap the given credential so its operations call the given callback
+    /// (rather than the one set with [set_prompt_callback]) before they prompt.
+    pub fn new(inner: Arc<Credential>, callback: Arc<PromptCallback>) -> Self {
+        Self { inner, callback }
+    }
+
+    /// Run an operation on the wrapped credential with this credential's callback.
+    fn run<T>(&self, f: impl FnOnce(&Credential) -> T) -> T {
+        let previous =
+            ENTRY_CALLBACK.with(|callback| callback.replace(Some(self.callback.clone())));
+        let _guard = EntryCallbackGuard(previous);
+        f(self.inner.as_ref())
+    }
+}
+
+impl CredentialApi for PromptHookCredential {
+    fn set_password(&self, password: &str) -> Result<()> {
+        self.run(|inner| inner.set_password(password))
+    }
+
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        self.run(|inner| inner.set_secret(secret))
+    }
+
+    fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        self.run(|inner| inner.set_password_with_expiry(password, ttl))
+    }
+
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        self.run(|inner| inner.set_secret_with_expiry(secret, ttl))
+    }
+
+    fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        self.run(|inner| inner.set_password_replacing_attributes(password))
+    }
+
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        self.run(|inner| inner.set_secret_replacing_attributes(secret))
+    }
+
+    fn get_password(&self) -> Result<String> {
+        self.run(|inner| inner.get_password())
+    }
+
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        self.run(|inner| inner.get_secret())
+    }
+
+    fn exists(&self) -> Result<bool> {
+        self.run(|inner| inner.exists())
+    }
+
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.run(|inner| inner.update_password(password))
+    }
+
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        self.run(|inner| inner.update_secret(secret))
+    }
+
+    fn create_password(&self, password: &str) -> Result<()> {
+        self.run(|inner| inner.create_password(password))
+    }
+
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        self.run(|inner| inner.create_secret(secret))
+    }
+
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        self.run(|inner| inner.get_attributes())
+    }
+
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        self.run(|inner| inner.get_secret_with_attributes())
+    }
+
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        self.run(|inner| inner.update_attributes(attributes))
+    }
+
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        self.run(|inner| inner.get_secret_with_times())
+    }
+
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        self.run(|inner| inner.last_modified())
+    }
+
+    fn created(&self) -> Result<Option<SystemTime>> {
+        self.run(|inner| inner.created())
+    }
+
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        self.inner.get_specifiers()
+    }
+
+    fn get_target(&self) -> Option<String> {
+        self.inner.get_target()
+    }
+
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        self.run(|inner| inner.rename(service, user))
+    }
+
+    /// Make a sibling of the wrapped credential with the same callback.
+    fn rewrap(&self, f: &dyn Fn(&Arc<Credential>) -> Arc<Credential>) -> Option<Box<Credential>> {
+        Some(Box::new(Self::new(f(&self.inner), self.callback.clone())))
+    }
+
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let sibling = self.inner.sibling(user)?;
+        Ok(Box::new(Self::new(sibling.into(), self.callback.clone())))
+    }
+
+    fn delete_credential(&self) -> Result<()> {
+        self.run(|inner| inner.delete_credential())
+    }
+
+    /// Return the wrapped credential as an [Any](std::any::Any) reference.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromptHookCredential")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Entry {
+    /// Make an entry for the same credential whose operations call the given
+    /// callback before they may prompt the user.
+    ///
+    /// The callback is used instead of any set with [set_prompt_callback].
+    /// See the [prompt_hook](crate::prompt_hook) module for when it's called.
+    pub fn with_prompt_callback(
+        self,
+        callback: impl Fn(&PromptEvent) + Send + Sync + 'static,
+    ) -> Entry {
+        Entry::new_with_credential(Box::new(PromptHookCredential::new(
+            self.inner,
+            Arc::new(callback),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::sync::{Arc, Mutex};
+
+    use super::{PromptEvent, PromptKind, before_prompt};
+    use crate::credential::CredentialApi;
+    use crate::{Entry, Result, tests::mock_entry};
+
+    /// A credential whose reads prompt the user.
+    struct PromptingCredential;
+
+    impl CredentialApi for PromptingCredential {
+        fn set_secret(&self, _: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_secret(&self) -> Result<Vec<u8>> {
+            before_prompt(PromptKind::Unlock, "prompting", Some(self));
+            Ok(Vec::new())
+        }
+
+        fn get_specifiers(&self) -> Option<(String, String)> {
+            Some(("service".to_string(), "user".to_string()))
+        }
+
+        fn delete_credential(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_entry_callback() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let entry = Entry::new_with_credential(Box::new(PromptingCredential)).with_prompt_callback(
+            move |event: &PromptEvent| recorded.lock().unwrap().push(event.clone()),
+        );
+        entry.set_password("no prompt").unwrap();
+        assert!(events.lock().unwrap().is_empty());
+        entry.get_secret().unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![PromptEvent {
+                kind: PromptKind::Unlock,
+                backend: "prompting",
+                target: None,
+                specifiers: Some(("service".to_string(), "user".to_string())),
+            }]
+        );
+        // the callback only applies while the entry's operations run
+        before_prompt(PromptKind::Unlock, "prompting", None);
+        assert_eq!(events.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_callback_passes_through() {
+        crate::tests::test_wrapper_passes_through(|entry| entry.with_prompt_callback(|_| {}));
+        let entry = mock_entry().with_prompt_callback(|_| {});
+        let (service, _) = entry.get_specifiers().unwrap();
+        let sibling = entry.inner.sibling("other").unwrap();
+        assert_eq!(
+            sibling.get_specifiers(),
+            Some((service, "other".to_string()))
+        );
+    }
+}
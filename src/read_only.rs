@@ -0,0 +1,132 @@
+/*!
+
+# Read-only entries
+
+Code that should only ever read secrets can hold a [ReadOnlyEntry]
+rather than an [Entry].  A read-only entry has the read operations of
+an entry, but no operations that set, update, rename, or delete its
+credential, so that code can't change the credential store by mistake:
+a call to one of those operations doesn't compile.
+
+Make a read-only entry from an entry with [Entry::read_only]
+(or the [From] trait).  There's no way to get the entry back from it.
+
+```rust
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?;
+entry.set_password("my password")?;
+let reader = entry.read_only();
+assert_eq!(reader.get_password()?, "my password");
+# Ok::<(), keyring::Error>(())
+```
+
+```compile_fail
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let reader = Entry::new("my service", "my user")?.read_only();
+reader.set_password("my password")?;
+# Ok::<(), keyring::Error>(())
+```
+ */
+use std::collections::HashMap;
+
+use super::{Entry, Result};
+
+/// An entry that can read its credential, but never change it.
+///
+/// See the [module docs](crate::read_only) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadOnlyEntry {
+    inner: Entry,
+}
+
+impl From<Entry> for ReadOnlyEntry {
+    fn from(entry: Entry) -> Self {
+        Self { inner: entry }
+    }
+}
+
+impl std::fmt::Display for ReadOnlyEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl ReadOnlyEntry {
+    /// See [Entry::get_password].
+    pub fn get_password(&self) -> Result<String> {
+        self.inner.get_password()
+    }
+
+    /// See [Entry::get_secret].
+    pub fn get_secret(&self) -> Result<Vec<u8>> {
+        self.inner.get_secret()
+    }
+
+    /// See [Entry::get_password_opt].
+    pub fn get_password_opt(&self) -> Result<Option<String>> {
+        self.inner.get_password_opt()
+    }
+
+    /// See [Entry::get_secret_opt].
+    pub fn get_secret_opt(&self) -> Result<Option<Vec<u8>>> {
+        self.inner.get_secret_opt()
+    }
+
+    /// See [Entry::exists].
+    pub fn exists(&self) -> Result<bool> {
+        self.inner.exists()
+    }
+
+    /// See [Entry::get_attributes].
+    pub fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        self.inner.get_attributes()
+    }
+
+    /// See [Entry::get_specifiers].
+    pub fn get_specifiers(&self) -> Option<(String, String)> {
+        self.inner.get_specifiers()
+    }
+
+    /// See [Entry::target].
+    pub fn target(&self) -> Option<String> {
+        self.inner.target()
+    }
+
+    /// See [Entry::backend_name].
+    pub fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+}
+
+impl Entry {
+    /// Make a read-only entry for this entry's credential.
+    ///
+    /// See the [read_only](crate::read_only) module for what that means.
+    pub fn read_only(self) -> ReadOnlyEntry {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, tests::mock_entry};
+
+    #[test]
+    fn test_read_only() {
+        let entry = mock_entry();
+        let reader = entry.clone().read_only();
+        assert!(!reader.exists().unwrap());
+        assert!(matches!(reader.get_password(), Err(Error::NoEntry)));
+        assert_eq!(reader.get_password_opt().unwrap(), None);
+        entry.set_password("read only").unwrap();
+        assert!(reader.exists().unwrap());
+        assert_eq!(reader.get_password().unwrap(), "read only");
+        assert_eq!(reader.get_secret().unwrap(), b"read only");
+        assert_eq!(reader.get_specifiers(), entry.get_specifiers());
+        assert_eq!(reader.backend_name(), "mock");
+        assert_eq!(reader.to_string(), entry.to_string());
+        entry.delete_credential().unwrap();
+    }
+}
@@ -37,6 +37,29 @@ specified for the entry, in which case the item
 will be created in a collection (created if necessary)
 that is labeled with the specified target.
 
+If you keep your items in a specific collection, and you want
+all searches and item creation to happen only in that collection,
+use [new_with_collection](SsCredential::new_with_collection)
+to create your credentials, and then wrap them in entries
+with [Entry::new_with_credential](crate::Entry::new_with_credential).
+Credentials created this way never create a collection:
+if there is no collection with the given label, their operations
+fail with an [Invalid](crate::Error::Invalid) error whose attribute is `collection`.
+If the collection is locked, it is unlocked (which may prompt the user)
+before it is used.
+
+In general, locked items and collections are unlocked when they are needed,
+which may prompt the user.  If your application must never prompt,
+use [Entry::with_auto_unlock](crate::Entry::with_auto_unlock)
+(or [with_auto_unlock](SsCredential::with_auto_unlock)) to turn this off:
+operations that need a locked item or collection will then fail
+with a [Locked](crate::Error::Locked) error, as they do when the user
+dismisses an unlock prompt.  (When the user dismisses the prompt, the
+`Locked` error's platform error is `dbus_secret_service::Error::Prompt`;
+if the prompt can't be shown, or the secret-service fails while it's up,
+the error is a [PlatformFailure](crate::Error::PlatformFailure) instead,
+so a user declining to unlock can be told from a broken service.)
+
 Setting the password on an entry will always update the password on an
 existing item in preference to creating a new item.
 This provides better compatibility with 3rd party clients, as well as earlier
@@ -44,6 +67,68 @@ versions of this crate, that may already
 have created items that match the entry, and thus reduces the chance
 of ambiguity in later searches.
 
+If more than one item matches an entry, operations on the entry
+fail with an [Ambiguous](crate::Error::Ambiguous) error rather than
+picking one of the items.  The error holds a credential for each of
+the matching items, and each of those credentials refers only to its own item,
+so you can use them to read, fix, or delete the items one at a time.
+[Entry::get_all_passwords](crate::Entry::get_all_passwords) does
+this for you when you want the passwords of all the matching items.
+
+Credentials set with an expiration time record it (in seconds since
+the Unix epoch) in their `keyring-expires` attribute, and reading their
+password or secret once that time has passed fails with a
+[NoEntry](crate::Error::NoEntry) error.  The secret-service itself
+knows nothing about this attribute, so expired items stay in their
+collection until they are deleted or overwritten.
+
+## Sharing items with libsecret applications
+
+Applications that use `libsecret` store their items under a *schema*:
+the item's `xdg:schema` attribute holds the schema's name, and the schema
+says which other attributes identify the item (and what they are called).
+Since those attributes aren't the `service` and `username` attributes
+this store uses, libsecret applications and keyring don't find each other's items.
+To share items with such an application, describe its schema with an
+[SsSchema] and make your credentials with
+[new_with_schema](SsCredential::new_with_schema):
+
+```no_run
+# use keyring::{Entry, secret_service::{SsCredential, SsSchema}};
+let schema = SsSchema::new("org.example.MyApp.Password", "server", "user")?;
+let credential = SsCredential::new_with_schema(&schema, "example.com", "alice")?;
+let entry = Entry::new_with_credential(Box::new(credential));
+entry.set_password("shared with libsecret")?;
+# Ok::<(), keyring::Error>(())
+```
+
+A credential made this way searches for (and creates) items whose
+`xdg:schema` attribute is the schema name and whose service and user are
+in the schema's attributes, and it has no `target` attribute.  New items are
+created in the default collection.  The schema's attributes are
+controlled just as `service` and `username` are for other credentials.
+
+## Network passwords
+
+Applications that keep passwords for network services (such as GNOME's
+own network clients) store them under GNOME's network schema, whose
+items are identified by the server, user, protocol, and port rather than
+by a service and user.  Credentials made with
+[new_network](SsCredential::new_network) (or with
+[Entry::new_network](crate::Entry::new_network)) follow that schema,
+so those applications find them:
+
+```no_run
+# use keyring::{Entry, secret_service::SsCredential};
+let credential = SsCredential::new_network("imap", "mail.example.com", Some(993), "alice")?;
+let entry = Entry::new_with_credential(Box::new(credential));
+entry.set_password("shared with GNOME")?;
+# Ok::<(), keyring::Error>(())
+```
+
+Their service is the server, so renaming them changes the server
+(and keeps the protocol and port).
+
 ## Headless usage
 
 If you must use the secret-service on a headless linux box,
@@ -70,6 +155,47 @@ function unlock-keyring ()
 For an excellent treatment of all the headless dbus issues, see
 [this answer on ServerFault](https://serverfault.com/a/906224/79617).
 
+## Which bus is used
+
+This store always talks to the secret-service on the D-Bus _session_ bus,
+because the underlying `dbus-secret-service` crate only connects to that bus.
+There's no option to use the system bus instead.  If the secret-service
+provider your application needs is listening on another bus, you can run your
+application with the `DBUS_SESSION_BUS_ADDRESS` environment variable set to
+that bus's address, since that's where the session bus is looked for.
+Either way, a secret-service provider must actually be running on that bus,
+or every operation fails with a [PlatformFailure](ErrorCode::PlatformFailure) error.
+To find out which provider is answering (for example, when a problem only
+happens with one of them), call [provider_info].
+
+## Connections
+
+Each operation normally makes its own connection to the secret-service
+(and opens its own session), except that the operations in a batch
+(such as those made by [get_many](crate::get_many) or
+[Entry::search](crate::Entry::search)) share one connection.
+The batches made by [get_many](crate::get_many), [set_many](crate::set_many),
+and [delete_many](crate::delete_many) also start with a single search for the
+items of all their entries, and unlock all the locked ones with a single
+prompt, so their entries' operations don't each search (and prompt) again.
+Applications that make many calls can instead turn on connection caching with
+[set_connection_caching]: each thread then connects once, and its later
+operations reuse that connection and session.  (Collections are still looked up
+by each operation, since they can be locked, unlocked, or deleted by other clients.)
+If a reused connection turns out to have been lost, because the bus or the
+secret-service provider went away, a new connection is made and the operation is
+tried once more, unless it's one that may already have made a change that can't
+safely be made twice (creating an item, renaming one, or deleting one): those
+return the error, and the next operation reconnects.  The bus connection used
+to unlock items and collections (which this store makes itself, to track
+the unlock prompt) is shared and replaced in just the same way.
+
+Connections are always made by this store: there's no way to hand it a
+D-Bus connection that your application already has (say, one it made with
+`zbus`), because the `dbus-secret-service` crate only talks over connections
+that it opens itself.  Connection caching is the way to keep the number of
+connections down: with it on, each thread uses just one.
+
 ## Usage - not! - on Windows Subsystem for Linux
 
 As noted in
@@ -78,12 +204,24 @@ there is no "default" collection defined under WSL.  So
 this keystore doesn't work "out of the box" on WSL.  See the
 issue for more details and possible workarounds.
  */
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-
-use dbus_secret_service::{Collection, EncryptionType, Error, Item, SecretService};
-
-use super::credential::{Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dbus_secret_service::{
+    Collection, EncryptionType, Error, Item, SearchItemsResult, SecretService,
+};
+
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi,
+    EXPIRY_ATTRIBUTE, expiry_after, has_expired,
+};
 use super::error::{Error as ErrorCode, Result, decode_password};
+use super::logging::{debug, warn};
+use super::network_service;
+use super::prompt_hook::{PromptKind, before_prompt};
 
 /// The representation of an item in the secret-service.
 ///
@@ -98,9 +236,117 @@ pub struct SsCredential {
     pub attributes: HashMap<String, String>,
     pub label: String,
     target: Option<String>,
+    collection: Option<String>,
+    auto_unlock: bool,
+    item_path: Option<String>,
+    schema: SsSchema,
+}
+
+/// How a credential's service and user are laid out in its item's attributes.
+///
+/// The default layout is the one this store has always used: no schema name,
+/// with the service and user in the `service` and `username` attributes.
+/// See [Sharing items with libsecret applications](crate::secret_service#sharing-items-with-libsecret-applications).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsSchema {
+    name: Option<String>,
+    service_attribute: String,
+    user_attribute: String,
+    /// Other attributes that are searched on (when credentials have them)
+    other_attributes: Vec<String>,
+}
+
+impl Default for SsSchema {
+    fn default() -> Self {
+        Self {
+            name: None,
+            service_attribute: "service".to_string(),
+            user_attribute: "username".to_string(),
+            other_attributes: Vec::new(),
+        }
+    }
+}
+
+impl SsSchema {
+    /// The name libsecret uses for its generic schema, whose attributes are up to the application.
+    pub const GENERIC: &'static str = "org.freedesktop.Secret.Generic";
+
+    /// The name of GNOME's schema for network passwords, which
+    /// [new_network](SsCredential::new_network) credentials follow.
+    pub const NETWORK: &'static str = "org.gnome.keyring.NetworkPassword";
+
+    /// Describe a libsecret schema with the given name, which keeps the service
+    /// and user in the attributes with the given names.
+    ///
+    /// Returns an [Invalid](ErrorCode::Invalid) error whose attribute is `schema` if
+    /// any of the names is empty, if the two attribute names are the same, or if
+    /// either of them is `xdg:schema`.
+    pub fn new(name: &str, service_attribute: &str, user_attribute: &str) -> Result<Self> {
+        let invalid = |reason: &str| ErrorCode::Invalid("schema".to_string(), reason.to_string());
+        if name.is_empty() || service_attribute.is_empty() || user_attribute.is_empty() {
+            return Err(invalid("names cannot be empty"));
+        }
+        if service_attribute == user_attribute {
+            return Err(invalid("service and user attributes must differ"));
+        }
+        if service_attribute == SCHEMA_ATTRIBUTE || user_attribute == SCHEMA_ATTRIBUTE {
+            return Err(invalid("xdg:schema is reserved for the schema name"));
+        }
+        Ok(Self {
+            name: Some(name.to_string()),
+            service_attribute: service_attribute.to_string(),
+            user_attribute: user_attribute.to_string(),
+            other_attributes: Vec::new(),
+        })
+    }
+
+    /// Describe GNOME's schema for network passwords.
+    ///
+    /// The service is the server (in the `server` attribute) and the user is
+    /// in the `user` attribute.  Items are also identified by their `protocol`
+    /// and `port` attributes, when credentials have them.
+    pub fn network() -> Self {
+        Self {
+            name: Some(Self::NETWORK.to_string()),
+            service_attribute: "server".to_string(),
+            user_attribute: "user".to_string(),
+            other_attributes: vec!["protocol".to_string(), "port".to_string()],
+        }
+    }
+
+    /// The schema name kept in the `xdg:schema` attribute, if there is one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The name of the attribute that holds the service.
+    pub fn service_attribute(&self) -> &str {
+        &self.service_attribute
+    }
+
+    /// The name of the attribute that holds the user.
+    pub fn user_attribute(&self) -> &str {
+        &self.user_attribute
+    }
+
+    /// Whether the given attribute is controlled by credentials with this schema.
+    fn controls(&self, attribute: &str) -> bool {
+        attribute == self.service_attribute
+            || attribute == self.user_attribute
+            || self.other_attributes.iter().any(|name| name == attribute)
+            || (self.name.is_some() && attribute == SCHEMA_ATTRIBUTE)
+    }
 }
 
+/// The attribute in which libsecret keeps an item's schema name.
+const SCHEMA_ATTRIBUTE: &str = "xdg:schema";
+
 impl CredentialApi for SsCredential {
+    /// The name of this store: `"secret-service"`.
+    fn backend_name(&self) -> &'static str {
+        "secret-service"
+    }
+
     /// Sets the password on a unique matching item, if it exists, or creates one if necessary.
     ///
     /// If there are multiple matches,
@@ -122,32 +368,60 @@ impl CredentialApi for SsCredential {
     /// When creating, the item is put into a collection named by the credential's `target`
     /// attribute.  
     fn set_secret(&self, secret: &[u8]) -> Result<()> {
-        // first try to find a unique, existing, matching item and set its password
-        match self.map_matching_items(|i| set_item_secret(i, secret), true) {
-            Ok(_) => return Ok(()),
-            Err(ErrorCode::NoEntry) => {}
+        self.save_secret(secret, None)
+    }
+
+    /// Sets the secret on a unique matching item, if it exists, or creates one if necessary,
+    /// and records when it expires in the item's `keyring-expires` attribute.
+    ///
+    /// The expiration is checked (by this crate) when the secret is read.
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        self.save_secret(secret, Some(expiry_after(ttl)?))
+    }
+
+    /// Creates an item with only this credential's own attributes, which replaces
+    /// the unique matching item (if there is one) in a single operation.
+    ///
+    /// If there are multiple matches,
+    /// returns an [Ambiguous](ErrorCode::Ambiguous) error with a credential for each
+    /// matching item.  If the service doesn't replace the matching item itself
+    /// (because it's in another collection, say), it is deleted once the new
+    /// item has been created, so the old item is kept if creating fails.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        match self.map_matching_items(|_| Ok(()), true) {
+            Ok(_) | Err(ErrorCode::NoEntry) => {}
             Err(err) => return Err(err),
         }
-        // if there is no existing item, create one for this credential.  In order to create
-        // an item, the credential must have an explicit target.  All entries created with
-        // the [new] or [new_with_target] commands will have explicit targets.  But entries
-        // created to wrap 3rd-party items that don't have `target` attributes may not.
-        #[cfg(feature = "encrypted")]
-        let session_type = EncryptionType::Dh;
-        #[cfg(not(feature = "encrypted"))]
-        let session_type = EncryptionType::Plain;
-        let ss = SecretService::connect(session_type).map_err(platform_failure)?;
-        let name = self.target.as_ref().ok_or_else(empty_target)?;
-        let collection = get_collection(&ss, name).or_else(|_| create_collection(&ss, name))?;
-        collection
-            .create_item(
-                self.label.as_str(),
-                self.all_attributes(),
-                secret,
-                true, // replace
-                "text/plain",
-            )
-            .map_err(platform_failure)?;
+        let created = self.create_item(secret, None)?;
+        let delete_replaced = |item: &Item| {
+            if item.path == created {
+                Ok(())
+            } else {
+                delete_item(item)
+            }
+        };
+        self.map_matching_items_once(delete_replaced, false)?;
+        Ok(())
+    }
+
+    /// Sets the password on a unique matching item, if it exists.
+    ///
+    /// If there are no matching items, returns a [NoEntry](ErrorCode::NoEntry) error.
+    /// If there are multiple matches,
+    /// returns an [Ambiguous](ErrorCode::Ambiguous) error with a credential for each
+    /// matching item.
+    fn update_password(&self, password: &str) -> Result<()> {
+        self.update_secret(password.as_bytes())
+    }
+
+    /// Sets the secret on a unique matching item, if it exists.
+    ///
+    /// If there are no matching items, returns a [NoEntry](ErrorCode::NoEntry) error.
+    /// If there are multiple matches,
+    /// returns an [Ambiguous](ErrorCode::Ambiguous) error with a credential for each
+    /// matching item.
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        self.map_matching_items(|i| set_item_secret_and_expiry(i, secret, None), true)?;
         Ok(())
     }
 
@@ -159,7 +433,8 @@ impl CredentialApi for SsCredential {
     /// returns an [Ambiguous](ErrorCode::Ambiguous)
     /// error with a credential for each matching item.
     fn get_password(&self) -> Result<String> {
-        Ok(self.map_matching_items(get_item_password, true)?.remove(0))
+        let password = |i: &Item| check_item_expiry(i).and_then(|_| get_item_password(i));
+        Ok(self.map_matching_items(password, true)?.remove(0))
     }
 
     /// Gets the secret on a unique matching item, if it exists.
@@ -170,22 +445,133 @@ impl CredentialApi for SsCredential {
     /// returns an [Ambiguous](ErrorCode::Ambiguous)
     /// error with a credential for each matching item.
     fn get_secret(&self) -> Result<Vec<u8>> {
-        Ok(self.map_matching_items(get_item_secret, true)?.remove(0))
+        let secret = |i: &Item| check_item_expiry(i).and_then(|_| get_item_secret(i));
+        Ok(self.map_matching_items(secret, true)?.remove(0))
+    }
+
+    /// Check whether there are matching items that haven't expired,
+    /// by searching for them (but not reading their secrets).
+    ///
+    /// As with reading, matching items that are locked give a
+    /// [Locked](ErrorCode::Locked) error unless auto-unlock is on.
+    fn exists(&self) -> Result<bool> {
+        let live = |i: &Item| match check_item_expiry(i) {
+            Ok(()) => Ok(true),
+            Err(ErrorCode::NoEntry) => Ok(false),
+            Err(err) => Err(err),
+        };
+        Ok(self.map_matching_items(live, false)?.contains(&true))
+    }
+
+    /// Get the secret and the `Created` and `Modified` properties of a
+    /// unique matching item, if it exists, with a single search.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        let time = |seconds: u64| (seconds > 0).then(|| UNIX_EPOCH + Duration::from_secs(seconds));
+        let read = |i: &Item| {
+            check_item_expiry(i)?;
+            let created = i.get_created().map_err(decode_error)?;
+            let modified = i.get_modified().map_err(decode_error)?;
+            Ok((get_item_secret(i)?, time(created), time(modified)))
+        };
+        Ok(self.map_matching_items(read, true)?.remove(0))
+    }
+
+    /// Get the `Modified` property of a unique matching item, if it exists.
+    ///
+    /// Items whose modification time is unknown (zero) give `None`.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        let modified = |i: &Item| i.get_modified().map_err(decode_error);
+        let seconds = self.map_matching_items(modified, true)?.remove(0);
+        Ok((seconds > 0).then(|| UNIX_EPOCH + Duration::from_secs(seconds)))
+    }
+
+    /// Get the `Created` property of a unique matching item, if it exists.
+    fn created(&self) -> Result<Option<SystemTime>> {
+        let created = |i: &Item| i.get_created().map_err(decode_error);
+        let seconds = self.map_matching_items(created, true)?.remove(0);
+        Ok((seconds > 0).then(|| UNIX_EPOCH + Duration::from_secs(seconds)))
     }
 
     /// Get attributes on a unique matching item, if it exists
+    ///
+    /// The attributes of this credential's schema are left out.
     fn get_attributes(&self) -> Result<HashMap<String, String>> {
         let attributes: Vec<HashMap<String, String>> =
-            self.map_matching_items(get_item_attributes, true)?;
+            self.map_matching_items(|i| self.get_item_attributes(i), true)?;
         Ok(attributes.into_iter().next().unwrap())
     }
 
+    /// Get the secret and attributes of a unique matching item, if it exists.
+    ///
+    /// Both are read in the same search of the store.
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        let both = |i: &Item| {
+            check_item_expiry(i)?;
+            Ok((get_item_secret(i)?, self.get_item_attributes(i)?))
+        };
+        Ok(self.map_matching_items(both, true)?.remove(0))
+    }
+
     /// Update attributes on a unique matching item, if it exists
+    ///
+    /// The attributes of this credential's schema are not changed.
     fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
-        self.map_matching_items(|i| update_item_attributes(i, attributes), true)?;
+        let attributes: HashMap<&str, &str> = attributes
+            .iter()
+            .filter(|(k, _)| !self.schema.controls(k))
+            .map(|(k, v)| (*k, *v))
+            .collect();
+        self.map_matching_items(|i| update_item_attributes(i, &attributes), true)?;
+        Ok(())
+    }
+
+    /// Return the service and user attributes of this credential, if it has both.
+    ///
+    /// These are the `service` and `username` attributes, unless the
+    /// credential was made with a [schema](SsCredential::new_with_schema).
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        let service = self.attributes.get(&self.schema.service_attribute)?;
+        let user = self.attributes.get(&self.schema.user_attribute)?;
+        Some((service.clone(), user.clone()))
+    }
+
+    /// Return the `target` attribute of this credential, if it has one.
+    ///
+    /// Credentials made without a target have the target `default`.
+    fn get_target(&self) -> Option<String> {
+        self.target.clone()
+    }
+
+    /// Changes the `service` and `username` attributes of the unique matching item,
+    /// as well as its label, to those of a credential with this credential's
+    /// target and the given service and user.
+    ///
+    /// The item is changed in place, so it keeps its secret and other attributes.
+    /// If there are no matching items, returns a [NoEntry](ErrorCode::NoEntry) error.
+    /// If there are multiple matches, returns an [Ambiguous](ErrorCode::Ambiguous)
+    /// error with a credential for each matching item.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let renamed = self.respecified(service, user)?;
+        if renamed.search_attributes(false) != self.search_attributes(false)
+            && !renamed.map_matching_items(|_| Ok(()), false)?.is_empty()
+        {
+            return Err(ErrorCode::AlreadyExists);
+        }
+        self.map_matching_items_once(|item| rename_item(item, &renamed), true)?;
         Ok(())
     }
 
+    /// Make a credential for the given user with this credential's
+    /// target, collection, schema, and service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let service = self
+            .attributes
+            .get(&self.schema.service_attribute)
+            .cloned()
+            .unwrap_or_default();
+        Ok(Box::new(self.respecified(&service, user)?))
+    }
+
     /// Deletes the unique matching item, if it exists.
     ///
     /// If there are no
@@ -194,10 +580,22 @@ impl CredentialApi for SsCredential {
     /// returns an [Ambiguous](ErrorCode::Ambiguous)
     /// error with a credential for each matching item.
     fn delete_credential(&self) -> Result<()> {
-        self.map_matching_items(delete_item, true)?;
+        self.map_matching_items_once(delete_item, true)?;
         Ok(())
     }
 
+    /// Secret-service items support everything but the machine scope.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: true,
+            enumeration: true,
+            ttl: true,
+            binary: true,
+            labels: true,
+            machine_scope: false,
+        }
+    }
+
     /// Return the underlying credential object with an `Any` type so that it can
     /// be downgraded to an [SsCredential] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -238,9 +636,36 @@ impl SsCredential {
                 env!("CARGO_PKG_VERSION"),
             ),
             target: Some(target.to_string()),
+            collection: None,
+            auto_unlock: true,
+            item_path: None,
+            schema: SsSchema::default(),
         })
     }
 
+    /// Create a credential for the given service and user whose item
+    /// is in the collection labeled with the given name.
+    ///
+    /// The name `default` refers to the default collection.
+    /// The collection is also used as the credential's target.
+    ///
+    /// Unlike credentials made with [new_with_target](SsCredential::new_with_target),
+    /// this credential only looks for its item in the given collection,
+    /// and it never creates the collection: if there is no such collection,
+    /// the credential's operations return an [Invalid](ErrorCode::Invalid) error
+    /// whose attribute is `collection`.
+    pub fn new_with_collection(collection: &str, service: &str, user: &str) -> Result<Self> {
+        if collection.is_empty() {
+            return Err(ErrorCode::Invalid(
+                "collection".to_string(),
+                "cannot be empty".to_string(),
+            ));
+        }
+        let mut credential = Self::new_with_target(Some(collection), service, user)?;
+        credential.collection = Some(collection.to_string());
+        Ok(credential)
+    }
+
     /// Create a credential that has *no* target and the given service and user.
     ///
     /// This emulates what keyring v1 did, and can be very handy when you need to
@@ -258,13 +683,18 @@ impl SsCredential {
                 env!("CARGO_PKG_VERSION"),
             ),
             target: None,
+            collection: None,
+            auto_unlock: true,
+            item_path: None,
+            schema: SsSchema::default(),
         })
     }
 
     /// Create a credential from an underlying item.
     ///
     /// The created credential will have all the attributes and label
-    /// of the underlying item, so you can examine them.
+    /// of the underlying item, so you can examine them.  It refers
+    /// to that item only, even if other items have the same attributes.
     pub fn new_from_item(item: &Item) -> Result<Self> {
         let attributes = item.get_attributes().map_err(decode_error)?;
         let target = attributes.get("target").cloned();
@@ -272,14 +702,216 @@ impl SsCredential {
             attributes,
             label: item.get_label().map_err(decode_error)?,
             target,
+            collection: None,
+            auto_unlock: true,
+            item_path: Some(item.path.to_string()),
+            schema: SsSchema::default(),
+        })
+    }
+
+    /// Create a credential for the given service and user whose item
+    /// follows the given libsecret schema.
+    ///
+    /// The item's `xdg:schema` attribute is the schema name, and the service
+    /// and user are kept in the schema's attributes.  The credential has
+    /// no target, and its item is created (if need be) in the default collection.
+    /// See [Sharing items with libsecret applications](crate::secret_service#sharing-items-with-libsecret-applications).
+    pub fn new_with_schema(schema: &SsSchema, service: &str, user: &str) -> Result<Self> {
+        let Some(name) = schema.name.as_deref() else {
+            return Self::new_with_target(None, service, user);
+        };
+        let attributes = HashMap::from([
+            (SCHEMA_ATTRIBUTE.to_string(), name.to_string()),
+            (schema.service_attribute.clone(), service.to_string()),
+            (schema.user_attribute.clone(), user.to_string()),
+        ]);
+        Ok(Self {
+            attributes,
+            label: format!(
+                "{user}@{service} ({name}, keyring v{})",
+                env!("CARGO_PKG_VERSION"),
+            ),
+            target: None,
+            collection: None,
+            auto_unlock: true,
+            item_path: None,
+            schema: schema.clone(),
+        })
+    }
+
+    /// Create a credential for the given user of a network service, whose
+    /// item follows GNOME's [network schema](SsSchema::network).
+    ///
+    /// The item's `server`, `user`, and `protocol` attributes are the given host,
+    /// user, and protocol, and its `port` attribute is the port (if one is given),
+    /// so applications that look up network passwords this way find it.
+    /// See [Network passwords](crate::secret_service#network-passwords).
+    pub fn new_network(protocol: &str, host: &str, port: Option<u16>, user: &str) -> Result<Self> {
+        let mut credential = Self::new_with_schema(&SsSchema::network(), host, user)?;
+        credential
+            .attributes
+            .insert("protocol".to_string(), protocol.to_string());
+        if let Some(port) = port {
+            credential
+                .attributes
+                .insert("port".to_string(), port.to_string());
+        }
+        credential.label = format!(
+            "{user}@{} (keyring v{})",
+            network_service(protocol, host, port),
+            env!("CARGO_PKG_VERSION"),
+        );
+        Ok(credential)
+    }
+
+    /// The schema that lays out this credential's service and user attributes.
+    pub fn schema(&self) -> &SsSchema {
+        &self.schema
+    }
+
+    /// The collection this credential's item is kept in, if it was made
+    /// with [new_with_collection](SsCredential::new_with_collection).
+    pub fn collection(&self) -> Option<&str> {
+        self.collection.as_deref()
+    }
+
+    /// Make a credential like this one (with the same target, collection,
+    /// schema, and auto-unlock setting) for the given service and user.
+    fn respecified(&self, service: &str, user: &str) -> Result<Self> {
+        let mut credential = if self.schema.name.is_some() {
+            Self::new_with_schema(&self.schema, service, user)?
+        } else {
+            match self.target.as_deref() {
+                Some(target) => Self::new_with_target(Some(target), service, user)?,
+                None => Self::new_with_no_target(service, user)?,
+            }
+        };
+        for name in &self.schema.other_attributes {
+            if let Some(value) = self.attributes.get(name) {
+                credential.attributes.insert(name.clone(), value.clone());
+            }
+        }
+        credential.collection = self.collection.clone();
+        credential.auto_unlock = self.auto_unlock;
+        Ok(credential)
+    }
+
+    /// Create a credential for the given item with this credential's schema.
+    fn new_for_item(&self, item: &Item) -> Result<Self> {
+        let mut credential = Self::new_from_item(item)?;
+        credential.schema = self.schema.clone();
+        Ok(credential)
+    }
+
+    /// Retrieve the attributes of the given item that aren't controlled by this credential.
+    fn get_item_attributes(&self, item: &Item) -> Result<HashMap<String, String>> {
+        let mut attributes = get_item_attributes(item)?;
+        attributes.retain(|k, _| !self.schema.controls(k));
+        Ok(attributes)
+    }
+
+    /// Choose whether this credential unlocks locked items and collections.
+    ///
+    /// By default, a locked item or collection is unlocked before it is used,
+    /// which may prompt the user; if the prompt is dismissed, the operation fails
+    /// with a [Locked](ErrorCode::Locked) error.  If `auto_unlock` is false,
+    /// operations that need a locked item or collection fail with
+    /// a [Locked](ErrorCode::Locked) error without prompting the user.
+    pub fn with_auto_unlock(mut self, auto_unlock: bool) -> Self {
+        self.auto_unlock = auto_unlock;
+        self
+    }
+
+    /// Set the secret (and expiration time, if any) on a unique matching item,
+    /// if it exists, or create one if necessary.
+    fn save_secret(&self, secret: &[u8], expiry: Option<u64>) -> Result<()> {
+        // first try to find a unique, existing, matching item and set its password
+        match self.map_matching_items(|i| set_item_secret_and_expiry(i, secret, expiry), true) {
+            Ok(_) => return Ok(()),
+            Err(ErrorCode::NoEntry) => {}
+            Err(err) => return Err(err),
+        }
+        // if there is no existing item, create one for this credential.
+        self.create_item(secret, expiry)?;
+        Ok(())
+    }
+
+    /// Create an item with this credential's attributes and the given secret
+    /// (and expiration time, if any), replacing any item in its collection
+    /// that has the same attributes, and return the new item's path.
+    ///
+    /// In order to create an item, the credential must have an explicit target.
+    /// All entries created with the [new](SsCredential::new) or
+    /// [new_with_target](SsCredential::new_with_target) commands will have
+    /// explicit targets.  But entries created to wrap 3rd-party items that
+    /// don't have `target` attributes may not.
+    fn create_item(&self, secret: &[u8], expiry: Option<u64>) -> Result<dbus::Path<'static>> {
+        // Creating can't be retried on a new connection, since it may have made the item.
+        with_connection_once(|ss| {
+            let collection = if let Some(name) = self.collection.as_deref() {
+                get_existing_collection(ss, name, self.auto_unlock, Some(self))?
+            } else {
+                let name = self.target.as_ref().ok_or_else(empty_target)?;
+                let collection =
+                    find_collection(ss, name).or_else(|_| create_collection(ss, name))?;
+                unlock_collection(&collection, self.auto_unlock, Some(self))?;
+                collection
+            };
+            let expiry = expiry.map(|expiry| expiry.to_string());
+            let mut attributes = self.all_attributes();
+            if let Some(expiry) = expiry.as_deref() {
+                attributes.insert(EXPIRY_ATTRIBUTE, expiry);
+            }
+            let item = collection
+                .create_item(
+                    self.label.as_str(),
+                    attributes.clone(),
+                    secret,
+                    true, // replace
+                    "text/plain",
+                )
+                .map_err(platform_failure)?;
+            changed_in_batch(&item.path, Some(attributes));
+            Ok(item.path.clone())
         })
     }
 
+    /// Whether an item with the given attributes matches this credential's search.
+    fn matches(&self, attributes: &HashMap<String, String>) -> bool {
+        self.search_attributes(false)
+            .iter()
+            .all(|(name, value)| attributes.get(*name).map(String::as_str) == Some(*value))
+    }
+
     /// Construct a credential for this credential's underlying matching item,
     /// if there is exactly one.
     pub fn new_from_matching_item(&self) -> Result<Self> {
         Ok(self
-            .map_matching_items(Self::new_from_item, true)?
+            .map_matching_items(|item| self.new_for_item(item), true)?
+            .remove(0))
+    }
+
+    /// The D-Bus object path of the item this credential refers to, if it
+    /// refers to a single item.
+    ///
+    /// Credentials made from items (such as those returned by
+    /// [new_from_matching_item](SsCredential::new_from_matching_item), and those in
+    /// an [Ambiguous](ErrorCode::Ambiguous) error) have a path, such as
+    /// `/org/freedesktop/secrets/collection/login/42`, which you can pass
+    /// to other Secret Service calls.  Credentials made from a service and user
+    /// don't; use [get_item_path](SsCredential::get_item_path) to look up theirs.
+    pub fn item_path(&self) -> Option<String> {
+        self.item_path.clone()
+    }
+
+    /// Find the D-Bus object path of this credential's unique matching item.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// matching item, and an [Ambiguous](ErrorCode::Ambiguous) error
+    /// if there is more than one.
+    pub fn get_item_path(&self) -> Result<String> {
+        Ok(self
+            .map_matching_items(|item| Ok(item.path.to_string()), true)?
             .remove(0))
     }
 
@@ -296,13 +928,14 @@ impl SsCredential {
     /// (This is useful if [delete_credential](SsCredential::delete_credential)
     /// returns an [Ambiguous](ErrorCode::Ambiguous) error.)
     pub fn delete_all_passwords(&self) -> Result<()> {
-        self.map_matching_items(delete_item, false)?;
+        self.map_matching_items_once(delete_item, false)?;
         Ok(())
     }
 
     /// Map a function over the items matching this credential.
     ///
-    /// Items are unlocked before the function is applied.
+    /// Locked items are unlocked before the function is applied,
+    /// unless this credential was made [without auto-unlock](SsCredential::with_auto_unlock).
     ///
     /// If `require_unique` is true, and there are no matching items, then
     /// a [NoEntry](ErrorCode::NoEntry) error is returned.
@@ -315,40 +948,72 @@ impl SsCredential {
         F: Fn(&Item) -> Result<T>,
         T: Sized,
     {
-        #[cfg(feature = "encrypted")]
-        let session_type = EncryptionType::Dh;
-        #[cfg(not(feature = "encrypted"))]
-        let session_type = EncryptionType::Plain;
-        let ss = SecretService::connect(session_type).map_err(platform_failure)?;
-        let attributes: HashMap<&str, &str> = self.search_attributes(false).into_iter().collect();
-        let search = ss.search_items(attributes).map_err(decode_error)?;
-        let count = search.locked.len() + search.unlocked.len();
-        if count == 0 {
-            if let Some("default") = self.target.as_deref() {
-                return self.map_matching_legacy_items(&ss, f, require_unique);
+        self.map_items(f, require_unique, true)
+    }
+
+    /// Like [map_matching_items](SsCredential::map_matching_items), but for functions
+    /// that change the items in ways that can't safely be repeated, so they aren't
+    /// retried if the shared connection turns out to have been lost.
+    fn map_matching_items_once<F, T>(&self, f: F, require_unique: bool) -> Result<Vec<T>>
+    where
+        F: Fn(&Item) -> Result<T>,
+    {
+        self.map_items(f, require_unique, false)
+    }
+
+    fn map_items<F, T>(&self, f: F, require_unique: bool, retry: bool) -> Result<Vec<T>>
+    where
+        F: Fn(&Item) -> Result<T>,
+    {
+        run_connected(retry, |ss| {
+            if let Some(name) = self.collection.as_deref() {
+                return self.map_matching_collection_items(ss, name, &f, require_unique);
             }
-        }
-        if require_unique {
-            if count == 0 {
-                return Err(ErrorCode::NoEntry);
-            } else if count > 1 {
-                let mut creds: Vec<Box<Credential>> = vec![];
-                for item in search.locked.iter().chain(search.unlocked.iter()) {
-                    let cred = Self::new_from_item(item)?;
-                    creds.push(Box::new(cred))
+            let attributes: HashMap<&str, &str> =
+                self.search_attributes(false).into_iter().collect();
+            let (mut search, refused) = match found_in_batch(ss, &attributes) {
+                Some(found) => found,
+                None => (ss.search_items(attributes).map_err(decode_error)?, false),
+            };
+            if let Some(path) = self.item_path.as_deref() {
+                search.locked.retain(|item| &*item.path == path);
+                search.unlocked.retain(|item| &*item.path == path);
+            }
+            let count = search.locked.len() + search.unlocked.len();
+            if count == 0 && self.item_path.is_none() && self.schema.name.is_none() {
+                if let Some("default") = self.target.as_deref() {
+                    return self.map_matching_legacy_items(ss, &f, require_unique);
                 }
-                return Err(ErrorCode::Ambiguous(creds));
             }
-        }
-        let mut results: Vec<T> = vec![];
-        for item in search.unlocked.iter() {
-            results.push(f(item)?);
-        }
-        for item in search.locked.iter() {
-            item.unlock().map_err(decode_error)?;
-            results.push(f(item)?);
-        }
-        Ok(results)
+            if require_unique {
+                if count == 0 {
+                    return Err(ErrorCode::NoEntry);
+                } else if count > 1 {
+                    let mut creds: Vec<Box<Credential>> = vec![];
+                    for item in search.locked.iter().chain(search.unlocked.iter()) {
+                        let cred = self.new_for_item(item)?;
+                        creds.push(Box::new(cred))
+                    }
+                    return Err(ErrorCode::Ambiguous(creds));
+                }
+            }
+            let mut results: Vec<T> = vec![];
+            for item in search.unlocked.iter() {
+                results.push(f(item)?);
+            }
+            for item in search.locked.iter() {
+                if !self.auto_unlock {
+                    return Err(locked(Error::Locked));
+                }
+                if refused {
+                    // the user already dismissed the batch's prompt for this item
+                    return Err(locked(Error::Prompt));
+                }
+                unlock_object(&item.path, Some(self))?;
+                results.push(f(item)?);
+            }
+            Ok(results)
+        })
     }
 
     /// Map a function over items that older versions of keyring
@@ -384,12 +1049,59 @@ impl SsCredential {
         F: Fn(&Item) -> Result<T>,
         T: Sized,
     {
+        debug!("no items match {self:?}, so searching for legacy items");
         let collection = ss.get_default_collection().map_err(decode_error)?;
         let attributes = self.search_attributes(true);
         let search = collection.search_items(attributes).map_err(decode_error)?;
+        if !search.is_empty() {
+            warn!(
+                "using {} legacy item(s) without a target attribute for {self:?}",
+                search.len()
+            );
+        }
         if require_unique {
             if search.is_empty() && require_unique {
                 return Err(ErrorCode::NoEntry);
+            } else if search.len() > 1 {
+                let mut creds: Vec<Box<Credential>> = vec![];
+                for item in search.iter() {
+                    let cred = self.new_for_item(item)?;
+                    creds.push(Box::new(cred))
+                }
+                return Err(ErrorCode::Ambiguous(creds));
+            }
+        }
+        let mut results: Vec<T> = vec![];
+        for item in search.iter() {
+            results.push(f(item)?);
+        }
+        Ok(results)
+    }
+
+    /// Map a function over the items matching this credential in the named collection.
+    ///
+    /// This is used instead of a service-wide search for credentials made with
+    /// [new_with_collection](SsCredential::new_with_collection).  As with a
+    /// service-wide search, the collection and any locked items in it are unlocked
+    /// before the function is applied, unless this credential was made
+    /// [without auto-unlock](SsCredential::with_auto_unlock).
+    fn map_matching_collection_items<F, T>(
+        &self,
+        ss: &SecretService,
+        name: &str,
+        f: F,
+        require_unique: bool,
+    ) -> Result<Vec<T>>
+    where
+        F: Fn(&Item) -> Result<T>,
+        T: Sized,
+    {
+        let collection = get_existing_collection(ss, name, self.auto_unlock, Some(self))?;
+        let attributes = self.search_attributes(false);
+        let search = collection.search_items(attributes).map_err(decode_error)?;
+        if require_unique {
+            if search.is_empty() {
+                return Err(ErrorCode::NoEntry);
             } else if search.len() > 1 {
                 let mut creds: Vec<Box<Credential>> = vec![];
                 for item in search.iter() {
@@ -401,6 +1113,12 @@ impl SsCredential {
         }
         let mut results: Vec<T> = vec![];
         for item in search.iter() {
+            if item.is_locked().map_err(decode_error)? {
+                if !self.auto_unlock {
+                    return Err(locked(Error::Locked));
+                }
+                unlock_object(&item.path, Some(self))?;
+            }
             results.push(f(item)?);
         }
         Ok(results)
@@ -424,8 +1142,17 @@ impl SsCredential {
         if self.target.is_some() && !omit_target {
             result.insert("target", self.attributes["target"].as_str());
         }
-        result.insert("service", self.attributes["service"].as_str());
-        result.insert("username", self.attributes["username"].as_str());
+        if self.schema.name.is_some() {
+            result.insert(SCHEMA_ATTRIBUTE, self.attributes[SCHEMA_ATTRIBUTE].as_str());
+        }
+        for name in [&self.schema.service_attribute, &self.schema.user_attribute] {
+            result.insert(name, self.attributes[name].as_str());
+        }
+        for name in &self.schema.other_attributes {
+            if let Some(value) = self.attributes.get(name) {
+                result.insert(name, value.as_str());
+            }
+        }
         result
     }
 }
@@ -443,6 +1170,11 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
 }
 
 impl CredentialBuilderApi for SsCredentialBuilder {
+    /// The name of this store: `"secret-service"`.
+    fn backend_name(&self) -> &'static str {
+        "secret-service"
+    }
+
     /// Build an [SsCredential] for the given target, service, and user.
     fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
         Ok(Box::new(SsCredential::new_with_target(
@@ -450,6 +1182,59 @@ impl CredentialBuilderApi for SsCredentialBuilder {
         )?))
     }
 
+    /// Build an [SsCredential] for the given service and user
+    /// whose item is created with the given label.
+    fn build_with_label(&self, label: &str, service: &str, user: &str) -> Result<Box<Credential>> {
+        let mut credential = SsCredential::new_with_target(None, service, user)?;
+        credential.label = label.to_string();
+        Ok(Box::new(credential))
+    }
+
+    /// Find all the items for the given service, in all collections.
+    ///
+    /// Only items that have both a `service` and a `username` attribute
+    /// are returned, since those are needed to identify the item.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        with_connection(|ss| {
+            let attributes = HashMap::from([("service", service)]);
+            let search = ss.search_items(attributes).map_err(decode_error)?;
+            let mut credentials: Vec<Box<Credential>> = vec![];
+            for item in search.unlocked.iter().chain(search.locked.iter()) {
+                let credential = SsCredential::new_from_item(item)?;
+                if credential.attributes.contains_key("username") {
+                    credentials.push(Box::new(credential));
+                }
+            }
+            Ok(credentials)
+        })
+    }
+
+    /// Find all the items with the given attributes, in all collections.
+    ///
+    /// Unlike [search](SsCredentialBuilder::search), this returns items
+    /// whether or not they have `service` and `username` attributes: each
+    /// returned credential refers to its item by its path.
+    fn query(&self, attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        with_connection(|ss| {
+            let search = ss.search_items(attributes.clone()).map_err(decode_error)?;
+            let mut credentials: Vec<Box<Credential>> = vec![];
+            for item in search.unlocked.iter().chain(search.locked.iter()) {
+                credentials.push(Box::new(SsCredential::new_from_item(item)?));
+            }
+            Ok(credentials)
+        })
+    }
+
+    /// Connect to the Secret Service and look up its default collection.
+    ///
+    /// The collection is not unlocked, so this never prompts the user.
+    fn probe(&self) -> Result<()> {
+        with_connection(|ss| {
+            ss.get_default_collection().map_err(decode_error)?;
+            Ok(())
+        })
+    }
+
     /// Return the underlying builder object with an `Any` type so that it can
     /// be downgraded to an [SsCredentialBuilder] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -461,11 +1246,550 @@ impl CredentialBuilderApi for SsCredentialBuilder {
 // Secret Service utilities
 //
 
-/// Find the secret service collection whose label is the given name.
+thread_local! {
+    /// Whether a batch is being run on this thread.
+    static IN_BATCH: Cell<bool> = const { Cell::new(false) };
+
+    /// The connection shared by the operations on this thread, if one has been
+    /// made by a batch that's still running or while connection caching is on.
+    static SHARED: RefCell<Option<Rc<SecretService>>> = const { RefCell::new(None) };
+
+    /// The bus connection used to unlock items and collections on this thread,
+    /// which is kept when (and for as long as) [SHARED] is.
+    static BUS: RefCell<Option<Rc<dbus::blocking::Connection>>> = const { RefCell::new(None) };
+
+    /// The items found by the search made for the running batch, if it made one.
+    static FOUND: RefCell<Option<BatchSearch>> = const { RefCell::new(None) };
+}
+
+/// The items found by a batch's search for the items of all its credentials.
+struct BatchSearch {
+    /// The attributes searched for, which are those that all the credentials share.
+    attributes: HashMap<String, String>,
+    items: Vec<FoundItem>,
+    /// Whether the user dismissed the prompt to unlock the locked items.
+    refused: bool,
+}
+
+/// An item found by a batch's search.
+struct FoundItem {
+    path: dbus::Path<'static>,
+    attributes: HashMap<String, String>,
+    locked: bool,
+}
+
+/// Whether connections are cached (see [set_connection_caching]).
+static CACHING: AtomicBool = AtomicBool::new(false);
+
+/// Choose whether each thread keeps its connection to the secret-service
+/// for use by all its operations.
+///
+/// Connection caching is off by default, so each operation (or batch of
+/// operations) makes its own connection.  See the
+/// [module documentation](crate::secret_service#connections) for details.
+/// Turning caching off closes the calling thread's cached connection;
+/// the cached connections of other threads are closed
+/// by their next operation (or when they exit).
+pub fn set_connection_caching(enabled: bool) {
+    CACHING.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        forget_connections();
+    }
+}
+
+/// Run a function during which all the secret-service operations
+/// made on this thread share one connection (and session).
+///
+/// Nested batches share the connection of the outermost one.
+pub(crate) fn batch<T>(f: impl FnOnce() -> T) -> T {
+    /// Ends the batch when dropped, even if the function panics.
+    struct BatchGuard;
+
+    impl Drop for BatchGuard {
+        fn drop(&mut self) {
+            IN_BATCH.with(|in_batch| in_batch.set(false));
+            FOUND.with(|found| found.replace(None));
+            if !CACHING.load(Ordering::Relaxed) {
+                forget_connections();
+            }
+        }
+    }
+
+    if IN_BATCH.with(|in_batch| in_batch.replace(true)) {
+        return f();
+    }
+    let _guard = BatchGuard;
+    f()
+}
+
+/// Search once for the items of all the given credentials, in the running batch,
+/// and unlock the locked ones with a single prompt.
+///
+/// The batch's operations on these credentials then use the items found
+/// rather than searching for (and unlocking) their own.  Credentials in a
+/// named collection are left to search their collection.  This does nothing
+/// outside a batch, and if the search fails, the operations just search on their own.
+pub(crate) fn search_for_batch(credentials: &[&SsCredential]) {
+    if !IN_BATCH.with(Cell::get) {
+        return;
+    }
+    let credentials: Vec<&SsCredential> = credentials
+        .iter()
+        .copied()
+        .filter(|credential| credential.collection.is_none())
+        .collect();
+    let Some((first, others)) = credentials.split_first() else {
+        return;
+    };
+    let mut attributes: HashMap<String, String> = first
+        .search_attributes(false)
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    for credential in others {
+        let theirs = credential.search_attributes(false);
+        attributes.retain(|name, value| theirs.get(name.as_str()) == Some(&value.as_str()));
+    }
+    match with_connection(|ss| find_batch_items(ss, &credentials, &attributes)) {
+        Ok((items, refused)) => {
+            debug!(
+                "found {} items for a batch of {}",
+                items.len(),
+                credentials.len()
+            );
+            let search = BatchSearch {
+                attributes,
+                items,
+                refused,
+            };
+            FOUND.with(|found| found.replace(Some(search)));
+        }
+        Err(err) => debug!("can't search for a batch's items, so each will search: {err}"),
+    }
+}
+
+/// Search for the items with the given attributes, and unlock those that
+/// are locked and match a credential that unlocks automatically.
+///
+/// Also returns whether the user dismissed the prompt to unlock them.
+fn find_batch_items(
+    ss: &SecretService,
+    credentials: &[&SsCredential],
+    attributes: &HashMap<String, String>,
+) -> Result<(Vec<FoundItem>, bool)> {
+    let query: HashMap<&str, &str> = attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let search = ss.search_items(query).map_err(decode_error)?;
+    let found = (search.unlocked.iter().map(|item| (item, false)))
+        .chain(search.locked.iter().map(|item| (item, true)));
+    let mut items = vec![];
+    for (item, locked) in found {
+        items.push(FoundItem {
+            path: item.path.clone(),
+            attributes: item.get_attributes().map_err(decode_error)?,
+            locked,
+        });
+    }
+    let to_unlock: Vec<dbus::Path> = items
+        .iter()
+        .filter(|item| item.locked)
+        .filter(|item| {
+            (credentials.iter()).any(|cred| cred.auto_unlock && cred.matches(&item.attributes))
+        })
+        .map(|item| item.path.clone())
+        .collect();
+    if to_unlock.is_empty() {
+        return Ok((items, false));
+    }
+    match unlock_objects(&to_unlock, None) {
+        Ok(()) => {}
+        Err(ErrorCode::Locked(_)) => return Ok((items, true)),
+        Err(err) => return Err(err),
+    }
+    for item in items.iter_mut() {
+        if to_unlock.contains(&item.path) {
+            item.locked = false;
+        }
+    }
+    Ok((items, false))
+}
+
+/// The items that the running batch's search found with the given attributes,
+/// and whether the user dismissed the prompt to unlock them.
+///
+/// This is `None` if there's no batch search, or if the given attributes
+/// don't include those searched for (so the search might have missed items).
+fn found_in_batch<'a>(
+    ss: &'a SecretService,
+    attributes: &HashMap<&str, &str>,
+) -> Option<(SearchItemsResult<Item<'a>>, bool)> {
+    FOUND.with(|found| {
+        let found = found.borrow();
+        let batch = found.as_ref()?;
+        let covered = (batch.attributes.iter())
+            .all(|(name, value)| attributes.get(name.as_str()) == Some(&value.as_str()));
+        if !covered {
+            return None;
+        }
+        let mut result = SearchItemsResult {
+            unlocked: vec![],
+            locked: vec![],
+        };
+        let matching = batch.items.iter().filter(|item| {
+            (attributes.iter())
+                .all(|(name, value)| item.attributes.get(*name).map(String::as_str) == Some(*value))
+        });
+        for item in matching {
+            let found = Item::new(ss, item.path.clone());
+            match item.locked {
+                true => result.locked.push(found),
+                false => result.unlocked.push(found),
+            }
+        }
+        Some((result, batch.refused))
+    })
+}
+
+/// Keep the running batch's search up to date after the item with the
+/// given path is given the given attributes (or is deleted, if there are none).
+fn changed_in_batch(path: &dbus::Path, attributes: Option<HashMap<&str, &str>>) {
+    FOUND.with(|found| {
+        let mut found = found.borrow_mut();
+        let Some(batch) = found.as_mut() else {
+            return;
+        };
+        batch.items.retain(|item| &item.path != path);
+        if let Some(attributes) = attributes {
+            let attributes = attributes
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            batch.items.push(FoundItem {
+                path: path.clone().into_static(),
+                attributes,
+                locked: false,
+            });
+        }
+    })
+}
+
+/// Call a function with a connection to the secret service.
+///
+/// Outside a batch, and without connection caching, each call makes its own
+/// connection.  Otherwise, the first call on this thread connects, and the
+/// others reuse its connection.  If a reused connection has been lost,
+/// the function is called again with a new connection.
+fn with_connection<T>(f: impl Fn(&SecretService) -> Result<T>) -> Result<T> {
+    run_connected(true, f)
+}
+
+/// Like [with_connection], but for functions that make changes that can't safely
+/// be made twice, so a lost connection is forgotten and its error returned
+/// rather than the function being called again.
+fn with_connection_once<T>(f: impl Fn(&SecretService) -> Result<T>) -> Result<T> {
+    run_connected(false, f)
+}
+
+/// Call a function with a connection, calling it again on a new connection
+/// if the shared one has been lost and `retry` is true.
+///
+/// The thread's shared connection is only borrowed long enough to clone it,
+/// so the function can itself run operations (or change connection caching).
+fn run_connected<T>(retry: bool, f: impl Fn(&SecretService) -> Result<T>) -> Result<T> {
+    run_shared(&SHARED, connect, retry, f)
+}
+
+/// Call a function with a bus connection (for calling the secret-service directly),
+/// which is shared, and replaced when lost, just like the secret-service connection.
+fn with_bus<T>(f: impl Fn(&dbus::blocking::Connection) -> Result<T>) -> Result<T> {
+    run_shared(&BUS, connect_bus, true, f)
+}
+
+/// Close this thread's shared connections.
+fn forget_connections() {
+    SHARED.with(|shared| shared.replace(None));
+    BUS.with(|bus| bus.replace(None));
+}
+
+/// Call a function with the connection in the given thread-local slot, as
+/// described by [run_connected], making the connection with the given function.
+fn run_shared<C, T>(
+    slot: &'static std::thread::LocalKey<RefCell<Option<Rc<C>>>>,
+    connect: fn() -> Result<C>,
+    retry: bool,
+    f: impl Fn(&C) -> Result<T>,
+) -> Result<T> {
+    let share = IN_BATCH.with(Cell::get) || CACHING.load(Ordering::Relaxed);
+    if !share {
+        // caching may have been turned off by another thread
+        slot.with(|shared| shared.replace(None));
+        return f(&connect()?);
+    }
+    let (ss, reused) = slot.with(|shared| -> Result<_> {
+        let mut shared = shared.borrow_mut();
+        match shared.as_ref() {
+            Some(ss) => Ok((ss.clone(), true)),
+            None => {
+                let ss = Rc::new(connect()?);
+                *shared = Some(ss.clone());
+                Ok((ss, false))
+            }
+        }
+    })?;
+    match f(&ss) {
+        Err(err) if reused && is_lost_connection(&err) => {
+            slot.with(|shared| {
+                let mut shared = shared.borrow_mut();
+                // a nested call may already have replaced it
+                if shared
+                    .as_ref()
+                    .is_some_and(|cached| Rc::ptr_eq(cached, &ss))
+                {
+                    *shared = None;
+                }
+            });
+            if !retry {
+                warn!("lost the secret-service connection ({err})");
+                return Err(err);
+            }
+            warn!("lost the secret-service connection ({err}), so reconnecting");
+            let ss = Rc::new(connect()?);
+            slot.with(|shared| shared.replace(Some(ss.clone())));
+            f(&ss)
+        }
+        result => result,
+    }
+}
+
+/// Whether an error shows that the connection it occurred on is no longer usable,
+/// because the bus connection was closed or the secret-service provider
+/// (and so the session) went away.
+fn is_lost_connection(err: &ErrorCode) -> bool {
+    const LOST: [&str; 3] = [
+        "org.freedesktop.DBus.Error.Disconnected",
+        "org.freedesktop.DBus.Error.ServiceUnknown",
+        "org.freedesktop.Secret.Error.NoSession",
+    ];
+    let (ErrorCode::PlatformFailure(err) | ErrorCode::NoStorageAccess(err)) = err else {
+        return false;
+    };
+    let name = match err.downcast_ref::<Error>() {
+        Some(Error::Dbus(err)) => err.name(),
+        // the errors of calls made directly over the bus
+        _ => err
+            .downcast_ref::<dbus::Error>()
+            .and_then(dbus::Error::name),
+    };
+    name.is_some_and(|name| LOST.contains(&name))
+}
+
+/// The secret-service provider that's serving requests, as found by [provider_info].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderInfo {
+    /// The unique name (such as `:1.42`) of the provider's connection to the bus,
+    /// which owns the `org.freedesktop.secrets` service name.
+    pub unique_name: String,
+    /// The provider's process ID, if the bus knows it.
+    pub pid: Option<u32>,
+    /// The name of the provider's program (such as `gnome-keyring-d`,
+    /// `keepassxc`, or `kwalletd6`), if it can be found from its process ID.
+    /// On Linux this is read from `/proc`, which truncates it to 15 bytes.
+    pub process_name: Option<String>,
+    /// The provider's version, if it advertises one in a `Version` property
+    /// of its service object.  This isn't part of the Secret Service API,
+    /// so most providers don't.
+    pub version: Option<String>,
+}
+
+/// Find out which secret-service provider is serving requests.
+///
+/// This asks the session bus which connection owns the `org.freedesktop.secrets`
+/// name, and looks up the owner's process, so it tells you (for diagnostics)
+/// whether it's gnome-keyring, KeePassXC, KWallet, or some other provider.
+/// Nothing in the store is read or changed.  If no provider is running,
+/// this fails with a [NoStorageAccess](ErrorCode::NoStorageAccess) error.
+pub fn provider_info() -> Result<ProviderInfo> {
+    use dbus::blocking::Connection;
+    use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+
+    let connection =
+        Connection::new_session().map_err(|err| ErrorCode::PlatformFailure(Box::new(err)))?;
+    let bus = connection.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", TIMEOUT);
+    let (unique_name,): (String,) = bus
+        .method_call("org.freedesktop.DBus", "GetNameOwner", (SERVICE,))
+        .map_err(|err| match err.name() {
+            Some("org.freedesktop.DBus.Error.NameHasNoOwner") => {
+                ErrorCode::NoStorageAccess(Box::new(err))
+            }
+            _ => ErrorCode::PlatformFailure(Box::new(err)),
+        })?;
+    let pid = bus
+        .method_call(
+            "org.freedesktop.DBus",
+            "GetConnectionUnixProcessID",
+            (unique_name.as_str(),),
+        )
+        .ok()
+        .map(|(pid,): (u32,)| pid);
+    let process_name = pid
+        .and_then(|pid| std::fs::read_to_string(format!("/proc/{pid}/comm")).ok())
+        .map(|name| name.trim_end().to_string());
+    let version = connection
+        .with_proxy(SERVICE, SERVICE_PATH, TIMEOUT)
+        .get("org.freedesktop.Secret.Service", "Version")
+        .ok();
+    let info = ProviderInfo {
+        unique_name,
+        pid,
+        process_name,
+        version,
+    };
+    debug!("the secret-service provider is {info:?}");
+    Ok(info)
+}
+
+/// Unlock the item or collection with the given path, prompting the user if need be.
+///
+/// This calls the secret-service directly, rather than through
+/// `dbus-secret-service`, so that it can tell a prompt the user dismissed
+/// from one that failed: the first gives a [Locked](ErrorCode::Locked)
+/// error (whose platform error is a `Prompt` error), the second a
+/// [PlatformFailure](ErrorCode::PlatformFailure) error.
+/// Once the prompt is shown, this waits up to [PROMPT_TIMEOUT] for the user
+/// to answer it, and then dismisses it and gives a [Timeout](ErrorCode::Timeout)
+/// error.  The bus connection is shared like the secret-service one
+/// (see [Connections](crate::secret_service#connections)).  The prompt callback (see [prompt_hook](crate::prompt_hook))
+/// is called with the given credential just before the prompt is shown.
+fn unlock_object(path: &dbus::Path, credential: Option<&dyn CredentialApi>) -> Result<()> {
+    unlock_objects(std::slice::from_ref(path), credential)
+}
+
+/// Unlock the items and collections with the given paths, prompting the
+/// user (just once) if need be.
+///
+/// See [unlock_object] for the errors this gives.
+fn unlock_objects(paths: &[dbus::Path], credential: Option<&dyn CredentialApi>) -> Result<()> {
+    with_bus(|connection| unlock_objects_on(connection, paths, credential))
+}
+
+fn unlock_objects_on(
+    connection: &dbus::blocking::Connection,
+    paths: &[dbus::Path],
+    credential: Option<&dyn CredentialApi>,
+) -> Result<()> {
+    use dbus::message::MatchRule;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    let failure = |err: dbus::Error| ErrorCode::PlatformFailure(Box::new(err));
+    let (_, prompt): (Vec<dbus::Path>, dbus::Path) = connection
+        .with_proxy(SERVICE, SERVICE_PATH, TIMEOUT)
+        .method_call(
+            "org.freedesktop.Secret.Service",
+            "Unlock",
+            (paths.to_vec(),),
+        )
+        .map_err(failure)?;
+    if &*prompt == "/" {
+        return Ok(());
+    }
+    debug!("prompting the user to unlock {} objects", paths.len());
+    before_prompt(PromptKind::Unlock, "secret-service", credential);
+    let (sender, receiver) = mpsc::channel();
+    let rule = MatchRule::new_signal("org.freedesktop.Secret.Prompt", "Completed")
+        .with_path(prompt.clone());
+    let token = connection
+        .add_match(rule, move |(dismissed,): (bool,), _, _| {
+            _ = sender.send(dismissed);
+            false
+        })
+        .map_err(failure)?;
+    let deadline = Instant::now() + PROMPT_TIMEOUT;
+    let answer = connection
+        .with_proxy(SERVICE, prompt.clone(), TIMEOUT)
+        .method_call::<(), _, _, _>("org.freedesktop.Secret.Prompt", "Prompt", ("",))
+        .map_err(failure)
+        .and_then(|()| {
+            loop {
+                if let Ok(dismissed) = receiver.try_recv() {
+                    break Ok(dismissed);
+                }
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    break Err(ErrorCode::Timeout(PROMPT_TIMEOUT));
+                }
+                if let Err(err) = connection.process(left.min(Duration::from_secs(1))) {
+                    break Err(failure(err));
+                }
+            }
+        });
+    // the connection may be reused, so the match mustn't be left behind
+    // (but it has already gone if the signal arrived)
+    _ = connection.remove_match(token);
+    let dismissed = match answer {
+        Err(ErrorCode::Timeout(timeout)) => {
+            debug!("the prompt to unlock {} objects timed out", paths.len());
+            // an unanswered prompt shouldn't be left on the screen
+            _ = connection
+                .with_proxy(SERVICE, prompt, TIMEOUT)
+                .method_call::<(), _, _, _>("org.freedesktop.Secret.Prompt", "Dismiss", ());
+            return Err(ErrorCode::Timeout(timeout));
+        }
+        answer => answer?,
+    };
+    if dismissed {
+        debug!(
+            "the user dismissed the prompt to unlock {} objects",
+            paths.len()
+        );
+        return Err(locked(Error::Prompt));
+    }
+    Ok(())
+}
+
+/// The bus name and object path of the secret-service.
+const SERVICE: &str = "org.freedesktop.secrets";
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+
+/// How long calls made directly to the secret-service can take.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the user has to answer a prompt to unlock an item or collection.
+pub const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+fn connect() -> Result<SecretService> {
+    SecretService::connect(session_type()).map_err(platform_failure)
+}
+
+fn connect_bus() -> Result<dbus::blocking::Connection> {
+    dbus::blocking::Connection::new_session()
+        .map_err(|err| ErrorCode::PlatformFailure(Box::new(err)))
+}
+
+/// The session type used to talk to the secret service, as determined
+/// by whether the `encrypted` feature is specified.
+fn session_type() -> EncryptionType {
+    #[cfg(feature = "encrypted")]
+    return EncryptionType::Dh;
+    #[cfg(not(feature = "encrypted"))]
+    EncryptionType::Plain
+}
+
+/// Find the secret service collection whose label is the given name.
 ///
 /// The name `default` is treated specially and is interpreted as naming
 /// the default collection regardless of its label (which might be different).
 pub fn get_collection<'a>(ss: &'a SecretService, name: &str) -> Result<Collection<'a>> {
+    let collection = find_collection(ss, name)?;
+    unlock_collection(&collection, true, None)?;
+    Ok(collection)
+}
+
+/// Find the secret service collection whose label is the given name,
+/// without unlocking it.
+fn find_collection<'a>(ss: &'a SecretService, name: &str) -> Result<Collection<'a>> {
     let collection = if name.eq("default") {
         ss.get_default_collection().map_err(decode_error)?
     } else {
@@ -475,9 +1799,48 @@ pub fn get_collection<'a>(ss: &'a SecretService, name: &str) -> Result<Collectio
             .find(|c| c.get_label().map(|l| l.eq(name)).unwrap_or(false));
         found.ok_or(ErrorCode::NoEntry)?
     };
+    Ok(collection)
+}
+
+/// Unlock the given collection if it's locked.
+///
+/// If `auto_unlock` is false, a locked collection is not unlocked
+/// (so the user is never prompted), and a [Locked](ErrorCode::Locked)
+/// error is returned instead.  The given credential is the one the
+/// collection is unlocked for, if any.
+fn unlock_collection(
+    collection: &Collection,
+    auto_unlock: bool,
+    credential: Option<&dyn CredentialApi>,
+) -> Result<()> {
     if collection.is_locked().map_err(decode_error)? {
-        collection.unlock().map_err(decode_error)?;
+        if !auto_unlock {
+            return Err(locked(Error::Locked));
+        }
+        unlock_object(&collection.path, credential)?;
     }
+    Ok(())
+}
+
+/// Find the secret service collection whose label is the given name,
+/// without falling back to creating it.
+///
+/// If there is no such collection, an [Invalid](ErrorCode::Invalid) error
+/// is returned, so that it can't be confused with a missing item.
+fn get_existing_collection<'a>(
+    ss: &'a SecretService,
+    name: &str,
+    auto_unlock: bool,
+    credential: Option<&dyn CredentialApi>,
+) -> Result<Collection<'a>> {
+    let collection = match find_collection(ss, name) {
+        Err(ErrorCode::NoEntry) => Err(ErrorCode::Invalid(
+            "collection".to_string(),
+            format!("there is no collection labeled '{name}'"),
+        )),
+        result => result,
+    }?;
+    unlock_collection(&collection, auto_unlock, credential)?;
     Ok(collection)
 }
 
@@ -552,9 +1915,62 @@ pub fn update_item_attributes(item: &Item, attributes: &HashMap<&str, &str>) ->
     Ok(())
 }
 
+/// Given an existing item, give it the service, username, and label of the given credential.
+fn rename_item(item: &Item, renamed: &SsCredential) -> Result<()> {
+    let mut attributes = item.get_attributes().map_err(decode_error)?;
+    for name in [
+        &renamed.schema.service_attribute,
+        &renamed.schema.user_attribute,
+    ] {
+        attributes.insert(name.clone(), renamed.attributes[name].clone());
+    }
+    let attributes: HashMap<&str, &str> = attributes
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    item.set_attributes(attributes.clone())
+        .map_err(decode_error)?;
+    changed_in_batch(&item.path, Some(attributes));
+    item.set_label(&renamed.label).map_err(decode_error)
+}
+
+/// Given an existing item, set its secret and expiration time.
+///
+/// If there is no expiration time, any existing one is removed.
+fn set_item_secret_and_expiry(item: &Item, secret: &[u8], expiry: Option<u64>) -> Result<()> {
+    set_item_secret(item, secret)?;
+    let mut attributes = item.get_attributes().map_err(decode_error)?;
+    let expiry = expiry.map(|expiry| expiry.to_string());
+    if attributes.remove(EXPIRY_ATTRIBUTE) == expiry {
+        return Ok(());
+    }
+    if let Some(expiry) = expiry {
+        attributes.insert(EXPIRY_ATTRIBUTE.to_string(), expiry);
+    }
+    let attributes: HashMap<&str, &str> = attributes
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    item.set_attributes(attributes).map_err(decode_error)
+}
+
+/// Given an existing item, fail with a [NoEntry](ErrorCode::NoEntry) error if it has expired.
+fn check_item_expiry(item: &Item) -> Result<()> {
+    let attributes = item.get_attributes().map_err(decode_error)?;
+    match attributes
+        .get(EXPIRY_ATTRIBUTE)
+        .and_then(|e| e.parse().ok())
+    {
+        Some(expiry) if has_expired(expiry) => Err(ErrorCode::NoEntry),
+        _ => Ok(()),
+    }
+}
+
 // Given an existing item, delete it.
 pub fn delete_item(item: &Item) -> Result<()> {
-    item.delete().map_err(decode_error)
+    item.delete().map_err(decode_error)?;
+    changed_in_batch(&item.path, None);
+    Ok(())
 }
 
 //
@@ -564,12 +1980,7 @@ pub fn delete_item(item: &Item) -> Result<()> {
 /// Map underlying secret-service errors to crate errors with
 /// appropriate annotation.
 pub fn decode_error(err: Error) -> ErrorCode {
-    match err {
-        Error::Locked => no_access(err),
-        Error::NoResult => no_access(err),
-        Error::Prompt => no_access(err),
-        _ => platform_failure(err),
-    }
+    err.into()
 }
 
 fn empty_target() -> ErrorCode {
@@ -580,8 +1991,8 @@ fn platform_failure(err: Error) -> ErrorCode {
     ErrorCode::PlatformFailure(wrap(err))
 }
 
-fn no_access(err: Error) -> ErrorCode {
-    ErrorCode::NoStorageAccess(wrap(err))
+fn locked(err: Error) -> ErrorCode {
+    ErrorCode::Locked(wrap(err))
 }
 
 fn wrap(err: Error) -> Box<dyn std::error::Error + Send + Sync> {
@@ -590,11 +2001,14 @@ fn wrap(err: Error) -> Box<dyn std::error::Error + Send + Sync> {
 
 #[cfg(test)]
 mod tests {
-    use crate::credential::CredentialPersistence;
-    use crate::{Entry, Error, tests::generate_random_string};
+    use crate::credential::{CredentialBuilderApi, CredentialPersistence};
+    use crate::{Entry, EntryId, Error, tests::generate_random_string};
     use std::collections::HashMap;
 
-    use super::{EncryptionType, SecretService, SsCredential, default_credential_builder};
+    use super::{
+        EncryptionType, FOUND, SecretService, SsCredential, SsCredentialBuilder, SsSchema, batch,
+        default_credential_builder, search_for_batch,
+    };
 
     #[test]
     fn test_persistence() {
@@ -604,10 +2018,179 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(
+            default_credential_builder().backend_name(),
+            "secret-service"
+        );
+        let name = generate_random_string();
+        assert_eq!(entry_new(&name, &name).backend_name(), "secret-service");
+    }
+
+    #[test]
+    fn test_locked() {
+        for err in [
+            dbus_secret_service::Error::Locked,
+            dbus_secret_service::Error::Prompt,
+        ] {
+            assert!(matches!(super::decode_error(err), Error::Locked(_)));
+        }
+        assert!(matches!(
+            super::decode_error(dbus_secret_service::Error::NoResult),
+            Error::NoStorageAccess(_)
+        ));
+    }
+
+    #[test]
+    fn test_auto_unlock() {
+        let name = generate_random_string();
+        let entry = Entry::new(&name, &name)
+            .expect("Can't create entry")
+            .with_auto_unlock(false);
+        let credential: &SsCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a secret service credential");
+        assert!(!credential.auto_unlock, "Auto-unlock wasn't turned off");
+        crate::tests::test_round_trip("no auto-unlock", &entry, "test password");
+        let entry = entry.with_auto_unlock(true);
+        let credential: &SsCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a secret service credential");
+        assert!(credential.auto_unlock, "Auto-unlock wasn't turned on");
+    }
+
+    #[test]
+    fn test_no_prompt_keeps_wrappers() {
+        let name = generate_random_string();
+        let credential = SsCredential::new_with_target(None, &name, &name).unwrap();
+        let entry = Entry::new_with_credential(Box::new(credential))
+            .with_timeout(std::time::Duration::from_secs(5))
+            .with_no_prompt();
+        let credential: &SsCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a secret service credential");
+        assert!(!credential.auto_unlock, "No-prompt entry can unlock");
+        assert!(
+            format!("{entry:?}").contains("TimeoutCredential"),
+            "No-prompt entry lost its timeout"
+        );
+    }
+
+    #[test]
+    fn test_no_prompt() {
+        let name = generate_random_string();
+        let credential = SsCredential::new_with_target(None, &name, &name).unwrap();
+        let entry = Entry::new_with_credential(Box::new(credential)).with_no_prompt();
+        let credential: &SsCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a secret service credential");
+        assert!(!credential.auto_unlock, "No-prompt entry can unlock");
+    }
+
+    #[test]
+    fn test_probe() {
+        default_credential_builder()
+            .probe()
+            .expect("Can't reach the Secret Service")
+    }
+
     fn entry_new(service: &str, user: &str) -> Entry {
         crate::tests::entry_from_constructor(SsCredential::new_with_target, service, user)
     }
 
+    #[test]
+    fn test_connection_caching() {
+        super::set_connection_caching(true);
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        entry
+            .set_password("cached password")
+            .expect("Can't set password with cached connection");
+        assert!(
+            super::SHARED.with(|shared| shared.borrow().is_some()),
+            "Connection wasn't cached"
+        );
+        assert_eq!(
+            entry
+                .get_password()
+                .expect("Can't get password with cached connection"),
+            "cached password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential with cached connection");
+        super::set_connection_caching(false);
+        assert!(
+            super::SHARED.with(|shared| shared.borrow().is_none()),
+            "Cached connection wasn't closed"
+        );
+    }
+
+    #[test]
+    fn test_entry_equality() {
+        let name = generate_random_string();
+        let in_collection = |collection| {
+            Entry::new_with_credential(Box::new(
+                SsCredential::new_with_collection(collection, &name, &name).unwrap(),
+            ))
+        };
+        assert_eq!(in_collection("first"), in_collection("first"));
+        assert_ne!(
+            in_collection("first"),
+            in_collection("second"),
+            "Entries in different collections are equal"
+        );
+    }
+
+    #[test]
+    fn test_nested_connection() {
+        super::set_connection_caching(true);
+        let result = super::with_connection(|_| {
+            // this used to panic, because the shared connection was still borrowed
+            super::set_connection_caching(false);
+            super::with_connection(|_| Ok(()))
+        });
+        super::set_connection_caching(false);
+        result.expect("Nested operation failed");
+    }
+
+    #[test]
+    fn test_is_lost_connection() {
+        assert!(!super::is_lost_connection(&Error::NoEntry));
+        assert!(!super::is_lost_connection(&super::platform_failure(
+            dbus_secret_service::Error::Unavailable
+        )));
+        let bus_error = |name| {
+            Error::PlatformFailure(Box::new(dbus::Error::new_custom(name, "test bus error")))
+        };
+        assert!(super::is_lost_connection(&bus_error(
+            "org.freedesktop.DBus.Error.Disconnected"
+        )));
+        assert!(!super::is_lost_connection(&bus_error(
+            "org.freedesktop.DBus.Error.Failed"
+        )));
+    }
+
+    #[test]
+    fn test_bus_connection_caching() {
+        super::set_connection_caching(true);
+        let first = super::with_bus(|bus| Ok(bus.unique_name().to_string()))
+            .expect("Can't connect to the bus");
+        let second = super::with_bus(|bus| Ok(bus.unique_name().to_string()))
+            .expect("Can't reuse the bus connection");
+        assert_eq!(first, second, "Bus connection wasn't reused");
+        super::set_connection_caching(false);
+        assert!(
+            super::BUS.with(|bus| bus.borrow().is_none()),
+            "Cached bus connection wasn't closed"
+        );
+    }
+
     #[test]
     fn test_invalid_parameter() {
         let credential = SsCredential::new_with_target(Some(""), "service", "user");
@@ -647,11 +2230,237 @@ mod tests {
         crate::tests::test_round_trip_random_secret(entry_new);
     }
 
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
     #[test]
     fn test_update() {
         crate::tests::test_update(entry_new);
     }
 
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_set_keeps_attributes() {
+        crate::tests::test_set_keeps_attributes(entry_new, "test attribute name");
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_provider_info() {
+        let info = super::provider_info().expect("Can't find the secret-service provider");
+        assert!(
+            info.unique_name.starts_with(':'),
+            "Not a unique name: {info:?}"
+        );
+    }
+
+    #[test]
+    fn test_item_path() {
+        use crate::credential::CredentialApi;
+
+        let name = generate_random_string();
+        let credential = SsCredential::new_with_target(None, &name, &name).unwrap();
+        assert_eq!(credential.item_path(), None);
+        assert!(matches!(credential.get_item_path(), Err(Error::NoEntry)));
+        credential.set_password("test item path").unwrap();
+        let path = credential.get_item_path().unwrap();
+        assert!(
+            path.starts_with("/org/freedesktop/secrets/"),
+            "Bad path: {path}"
+        );
+        let item = credential.new_from_matching_item().unwrap();
+        assert_eq!(item.item_path(), Some(path));
+        credential.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_batch() {
+        let name = generate_random_string();
+        let entries: Vec<Entry> = (0..3)
+            .map(|i| entry_new(&name, &format!("{name}-{i}")))
+            .collect();
+        let passwords: Vec<(&Entry, &str)> =
+            entries.iter().map(|e| (e, "batch password")).collect();
+        for result in crate::set_many(&passwords) {
+            result.expect("Can't set password in batch");
+        }
+        for result in crate::get_many(&entries) {
+            assert_eq!(
+                result.expect("Can't get password in batch"),
+                "batch password"
+            );
+        }
+        for result in crate::delete_many(&entries) {
+            result.expect("Can't delete credential in batch");
+        }
+        for result in crate::get_many(&entries) {
+            assert!(
+                matches!(result, Err(Error::NoEntry)),
+                "Got deleted password"
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_search() {
+        let name = generate_random_string();
+        let entries: Vec<Entry> = (0..3)
+            .map(|i| entry_new(&name, &format!("{name}-{i}")))
+            .collect();
+        for entry in &entries {
+            entry
+                .set_password("batch search password")
+                .expect("Can't set password for batch search");
+        }
+        batch(|| {
+            let credentials: Vec<&SsCredential> = entries
+                .iter()
+                .map(|entry| entry.get_credential().downcast_ref().unwrap())
+                .collect();
+            search_for_batch(&credentials);
+            let found = FOUND.with(|found| found.borrow().as_ref().map(|b| b.items.len()));
+            assert!(
+                found.is_some_and(|n| n >= 3),
+                "Batch search didn't find the items"
+            );
+            // the batch's operations see its own changes
+            entries[0]
+                .delete_credential()
+                .expect("Can't delete credential in batch");
+            assert!(matches!(entries[0].get_password(), Err(Error::NoEntry)));
+            assert_eq!(
+                entries[1]
+                    .get_password()
+                    .expect("Can't get password in batch"),
+                "batch search password"
+            );
+            entries[0]
+                .set_password("recreated password")
+                .expect("Can't recreate credential in batch");
+            assert_eq!(
+                entries[0]
+                    .get_password()
+                    .expect("Can't get recreated password"),
+                "recreated password"
+            );
+        });
+        assert!(
+            FOUND.with(|found| found.borrow().is_none()),
+            "Batch search outlived its batch"
+        );
+        for result in crate::delete_many(&entries) {
+            result.expect("Can't delete credential after batch search");
+        }
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry(entry_new);
+    }
+
+    #[test]
+    fn test_new_with_label() {
+        let name = generate_random_string();
+        let entry = Entry::new_with_label("My App's Password", &name, &name)
+            .expect("Can't create labeled entry");
+        entry
+            .set_password("labeled password")
+            .expect("Can't set labeled password");
+        let attributes = entry
+            .get_attributes()
+            .expect("Can't get labeled attributes");
+        assert_eq!(attributes["label"], "My App's Password");
+        entry
+            .delete_credential()
+            .expect("Can't delete labeled credential");
+    }
+
     #[test]
     fn test_get_credential() {
         let name = generate_random_string();
@@ -814,6 +2623,215 @@ mod tests {
         delete_collection(&name2);
     }
 
+    #[test]
+    fn test_query() {
+        crate::tests::test_query(entry_new, default_credential_builder().as_ref());
+    }
+
+    #[test]
+    fn test_search() {
+        let name = generate_random_string();
+        let builder = default_credential_builder();
+        let found = builder
+            .search(&name)
+            .expect("Can't search for missing service");
+        assert!(found.is_empty(), "Found entries for a missing service");
+        let user1 = generate_random_string();
+        let user2 = generate_random_string();
+        for user in [&user1, &user2] {
+            Entry::new(&name, user)
+                .expect("Can't create entry for search")
+                .set_password(user)
+                .expect("Can't set password for search");
+        }
+        let found = builder.search(&name).expect("Can't search for service");
+        assert_eq!(found.len(), 2, "Didn't find both entries");
+        for credential in found {
+            let entry = Entry::new_with_credential(credential);
+            let ss: &SsCredential = entry.get_credential().downcast_ref().unwrap();
+            let user = ss.attributes["username"].clone();
+            assert!(user == user1 || user == user2, "Found the wrong user");
+            assert_eq!(
+                entry.get_password().expect("Can't get found password"),
+                user
+            );
+            entry.delete_credential().expect("Can't delete found entry");
+        }
+        let found = builder.search(&name).expect("Can't search after delete");
+        assert!(found.is_empty(), "Found entries after delete");
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let credential: &SsCredential = entry.get_credential().downcast_ref().unwrap();
+        let ss =
+            SecretService::connect(EncryptionType::Plain).expect("Can't connect to secret service");
+        let collection = ss
+            .get_default_collection()
+            .expect("Can't get default collection");
+        for password in ["first", "second"] {
+            collection
+                .create_item(
+                    credential.label.as_str(),
+                    credential.all_attributes(),
+                    password.as_bytes(),
+                    false, // don't replace
+                    "text/plain",
+                )
+                .expect("Can't create duplicate item");
+        }
+        assert!(
+            matches!(entry.get_password(), Err(Error::Ambiguous(_))),
+            "Duplicate items aren't ambiguous"
+        );
+        let found = entry.get_all_passwords().expect("Can't get all passwords");
+        let mut passwords: Vec<&str> = found.iter().map(|(_, p)| p.as_str()).collect();
+        passwords.sort();
+        assert_eq!(passwords, ["first", "second"]);
+        for (entry, _) in found.iter() {
+            entry
+                .delete_credential()
+                .expect("Can't delete one of the ambiguous items");
+        }
+        assert!(matches!(entry.get_password(), Err(Error::NoEntry)));
+    }
+
+    #[test]
+    fn test_new_with_collection() {
+        let credential = SsCredential::new_with_collection("", "service", "user");
+        assert!(
+            matches!(credential, Err(Error::Invalid(_, _))),
+            "Created credential with empty collection"
+        );
+        let name = generate_random_string();
+        let missing = SsCredential::new_with_collection(&name, &name, &name)
+            .expect("Can't create credential for missing collection");
+        let entry = Entry::new_with_credential(Box::new(missing));
+        for result in [
+            entry.set_password("missing"),
+            entry.get_password().map(|_| ()),
+        ] {
+            match result {
+                Err(Error::Invalid(attr, _)) => assert_eq!(attr, "collection"),
+                other => panic!("Missing collection gave wrong result: {other:?}"),
+            }
+        }
+        let credential = SsCredential::new_with_collection("default", &name, &name)
+            .expect("Can't create credential for default collection");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        crate::tests::test_round_trip("default collection", &entry, "collection password");
+    }
+
+    #[test]
+    fn test_id() {
+        let name = generate_random_string();
+        let entry = Entry::new(&name, &name).expect("Can't create entry");
+        let id = entry.id().expect("Entry has no id");
+        assert_eq!(id, EntryId::new_with_target("default", &name, &name));
+        let same = Entry::from_id(&id).expect("Can't create entry from id");
+        assert_eq!(same.id(), Some(id));
+        let credential = SsCredential::new_with_collection("default", &name, &name)
+            .expect("Can't create credential for default collection");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        let id = entry.id().expect("Entry has no id");
+        assert_eq!(id, EntryId::new_with_collection("default", &name, &name));
+        let same = Entry::from_id(&id).expect("Can't create entry from id");
+        let credential: &SsCredential = same
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a secret-service credential");
+        assert_eq!(credential.collection(), Some("default"));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        for (name, service, user) in [
+            ("", "server", "user"),
+            ("org.example.Schema", "", "user"),
+            ("org.example.Schema", "user", "user"),
+            ("org.example.Schema", "xdg:schema", "user"),
+        ] {
+            assert!(
+                matches!(SsSchema::new(name, service, user), Err(Error::Invalid(attr, _)) if attr == "schema"),
+                "Accepted invalid schema ({name:?}, {service:?}, {user:?})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let name = generate_random_string();
+        let schema = SsSchema::new("org.example.KeyringTest", "server", "user")
+            .expect("Can't create schema");
+        let credential = SsCredential::new_with_schema(&schema, &name, &name)
+            .expect("Can't create credential with schema");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry
+            .set_password("schema password")
+            .expect("Can't set password with schema");
+        assert_eq!(entry.get_specifiers(), Some((name.clone(), name.clone())));
+        let found = SsCredentialBuilder {}
+            .query(&HashMap::from([
+                ("xdg:schema", "org.example.KeyringTest"),
+                ("server", name.as_str()),
+                ("user", name.as_str()),
+            ]))
+            .expect("Can't query for item with schema");
+        assert_eq!(found.len(), 1, "Item doesn't follow the schema");
+        let item: &SsCredential = found[0].as_any().downcast_ref().unwrap();
+        assert!(!item.attributes.contains_key("service"));
+        assert!(!item.attributes.contains_key("target"));
+        let attributes = entry.get_attributes().expect("Can't get attributes");
+        assert!(!attributes.contains_key("xdg:schema"));
+        assert!(!attributes.contains_key("server"));
+        let plain = Entry::new(&name, &name).expect("Can't create plain entry");
+        assert!(matches!(plain.get_password(), Err(Error::NoEntry)));
+        entry
+            .delete_credential()
+            .expect("Can't delete credential with schema");
+    }
+
+    #[test]
+    fn test_network_credential() {
+        let name = generate_random_string();
+        let credential = SsCredential::new_network("imap", &name, Some(993), "alice")
+            .expect("Can't create network credential");
+        assert_eq!(
+            credential.search_attributes(false),
+            HashMap::from([
+                ("xdg:schema", SsSchema::NETWORK),
+                ("server", name.as_str()),
+                ("user", "alice"),
+                ("protocol", "imap"),
+                ("port", "993"),
+            ])
+        );
+        let sibling = credential.respecified(&name, "bob").unwrap();
+        assert_eq!(sibling.attributes["protocol"], "imap");
+        assert_eq!(sibling.attributes["port"], "993");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry
+            .set_password("network password")
+            .expect("Can't set network password");
+        assert_eq!(
+            entry.get_specifiers(),
+            Some((name.clone(), "alice".to_string()))
+        );
+        let other_port = SsCredential::new_network("imap", &name, Some(143), "alice").unwrap();
+        assert!(matches!(
+            Entry::new_with_credential(Box::new(other_port)).get_password(),
+            Err(Error::NoEntry)
+        ));
+        let attributes = entry.get_attributes().expect("Can't get attributes");
+        assert!(!attributes.contains_key("protocol"));
+        assert!(!attributes.contains_key("port"));
+        entry
+            .delete_credential()
+            .expect("Can't delete network credential");
+    }
+
     #[test]
     fn test_legacy_entry() {
         let name = generate_random_string();
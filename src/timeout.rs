@@ -0,0 +1,337 @@
+/*!
+
+# Timeouts
+
+A credential store that isn't responding can make an operation on an
+entry hang indefinitely: for example, the secret-service can wait
+for a D-Bus service that has stopped responding.  [Entry::with_timeout] gives an entry whose operations each fail
+with a [Timeout](Error::Timeout) error if they don't finish in time, so
+a hung store can't freeze the application.
+
+Timeouts work the same way on every store: each operation is run on a
+thread of its own, and the calling thread waits for it only as long as the
+timeout.  When an operation times out it is left running in the background
+(there's no portable way to cancel a call into a platform store),
+so it may still complete later, and its thread isn't freed until it does.
+To keep a store that hangs for good from using up threads, at most
+[MAX_STUCK_OPERATIONS] timed-out operations are left running at once: while
+that many are, other operations with a timeout fail straight away with a
+[Timeout](Error::Timeout) error rather than start another thread.  Running
+an operation on its own thread has some consequences:
+
+- Store settings that are kept per thread don't apply.  In particular,
+  operations on the secret-service never share a connection from a
+  batch or the connection cache, so each one makes its own connection.
+- Operations are a little slower, because each one starts a thread.
+
+```rust
+# use std::time::Duration;
+# use keyring::{Entry, mock};
+# keyring::set_default_credential_builder(mock::default_credential_builder());
+let entry = Entry::new("my service", "my user")?.with_timeout(Duration::from_secs(5));
+entry.set_password("my password")?;
+assert_eq!(entry.get_password()?, "my password");
+# Ok::<(), keyring::Error>(())
+```
+ */
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, SystemTime};
+
+use super::credential::{Capabilities, Credential, CredentialApi};
+use super::{Entry, Error, Result, Zeroizing};
+
+/// The most timed-out operations that are left running at once.
+///
+/// See the [module docs](crate::timeout) for what happens when there are this many.
+pub const MAX_STUCK_OPERATIONS: usize = 16;
+
+/// The number of operations that timed out and haven't yet finished.
+static STUCK: AtomicUsize = AtomicUsize::new(0);
+
+/// A credential whose operations time out.
+///
+/// Each operation is run on the wrapped credential in a thread of its own;
+/// if it doesn't finish within the timeout, a [Timeout](Error::Timeout)
+/// error is returned.  See the [module docs](crate::timeout) for details.
+///
+/// The wrapped credential's [as_any](CredentialApi::as_any) is returned as this
+/// credential's, so [Entry::get_credential] still gives the concrete store credential.
+pub struct TimeoutCredential {
+    inner: Arc<Credential>,
+    timeout: Duration,
+}
+
+impl TimeoutCredential {
+    /// Wrap the given credential so its operations time out after the given duration.
+    pub fn new(inner: Arc<Credential>, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    /// The time each operation is allowed to take.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Run an operation on the wrapped credential, waiting for it at most the timeout.
+    fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Credential) -> Result<T> + Send + 'static,
+    {
+        if STUCK.load(Ordering::SeqCst) >= MAX_STUCK_OPERATIONS {
+            return Err(Error::Timeout(self.timeout));
+        }
+        let (sender, receiver) = mpsc::channel();
+        let inner = self.inner.clone();
+        // set by whichever of the operation finishing and the wait timing out comes first
+        let settled = Arc::new(AtomicBool::new(false));
+        let thread_settled = settled.clone();
+        std::thread::Builder::new()
+            .name("keyring-timeout".to_string())
+            .spawn(move || {
+                // the receiver is gone if the operation timed out
+                _ = sender.send(f(inner.as_ref()));
+                if thread_settled.swap(true, Ordering::SeqCst) {
+                    STUCK.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+            .map_err(|err| Error::PlatformFailure(Box::new(err)))?;
+        match receiver.recv_timeout(self.timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // counted before settling, so the thread can't uncount it first
+                STUCK.fetch_add(1, Ordering::SeqCst);
+                if settled.swap(true, Ordering::SeqCst) {
+                    STUCK.fetch_sub(1, Ordering::SeqCst);
+                }
+                Err(Error::Timeout(self.timeout))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(Error::PlatformFailure(
+                "the credential store operation panicked".into(),
+            )),
+        }
+    }
+}
+
+impl CredentialApi for TimeoutCredential {
+    fn set_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |inner| inner.set_password(&password))
+    }
+
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |inner| inner.set_secret(&secret))
+    }
+
+    fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |inner| inner.set_password_with_expiry(&password, ttl))
+    }
+
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |inner| inner.set_secret_with_expiry(&secret, ttl))
+    }
+
+    fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |inner| inner.set_password_replacing_attributes(&password))
+    }
+
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |inner| inner.set_secret_replacing_attributes(&secret))
+    }
+
+    fn get_password(&self) -> Result<String> {
+        self.run(|inner| inner.get_password())
+    }
+
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        self.run(|inner| inner.get_secret())
+    }
+
+    fn exists(&self) -> Result<bool> {
+        self.run(|inner| inner.exists())
+    }
+
+    fn update_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |inner| inner.update_password(&password))
+    }
+
+    fn update_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |inner| inner.update_secret(&secret))
+    }
+
+    fn create_password(&self, password: &str) -> Result<()> {
+        let password = Zeroizing::new(password.to_string());
+        self.run(move |inner| inner.create_password(&password))
+    }
+
+    fn create_secret(&self, secret: &[u8]) -> Result<()> {
+        let secret = Zeroizing::new(secret.to_vec());
+        self.run(move |inner| inner.create_secret(&secret))
+    }
+
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        self.run(|inner| inner.get_attributes())
+    }
+
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        self.run(|inner| inner.get_secret_with_attributes())
+    }
+
+    fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+        let attributes: HashMap<String, String> = attributes
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        self.run(move |inner| {
+            let attributes = attributes
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            inner.update_attributes(&attributes)
+        })
+    }
+
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        self.run(|inner| inner.get_secret_with_times())
+    }
+
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        self.run(|inner| inner.last_modified())
+    }
+
+    fn created(&self) -> Result<Option<SystemTime>> {
+        self.run(|inner| inner.created())
+    }
+
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        self.inner.get_specifiers()
+    }
+
+    fn get_target(&self) -> Option<String> {
+        self.inner.get_target()
+    }
+
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let (service, user) = (service.to_string(), user.to_string());
+        self.run(move |inner| inner.rename(&service, &user))
+    }
+
+    /// Make a sibling of the wrapped credential with the same timeout.
+    fn rewrap(&self, f: &dyn Fn(&Arc<Credential>) -> Arc<Credential>) -> Option<Box<Credential>> {
+        Some(Box::new(Self::new(f(&self.inner), self.timeout)))
+    }
+
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let sibling = self.inner.sibling(user)?;
+        Ok(Box::new(Self::new(sibling.into(), self.timeout)))
+    }
+
+    fn delete_credential(&self) -> Result<()> {
+        self.run(|inner| inner.delete_credential())
+    }
+
+    /// Return the wrapped credential as an [Any](std::any::Any) reference.
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutCredential")
+            .field("inner", &self.inner)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Entry {
+    /// Make an entry for the same credential whose operations time out.
+    ///
+    /// Each operation on the returned entry fails with a
+    /// [Timeout](Error::Timeout) error if it doesn't finish within the
+    /// given duration.  See the [timeout](crate::timeout) module for how this works.
+    pub fn with_timeout(self, timeout: Duration) -> Entry {
+        Entry::new_with_credential(Box::new(TimeoutCredential::new(self.inner, timeout)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::time::Duration;
+
+    use crate::credential::CredentialApi;
+    use crate::{Entry, Error, Result};
+
+    /// A credential whose reads never finish in time.
+    struct SlowCredential;
+
+    impl CredentialApi for SlowCredential {
+        fn set_secret(&self, _: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_secret(&self) -> Result<Vec<u8>> {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(Vec::new())
+        }
+
+        fn delete_credential(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_store_options_keep_timeout() {
+        let entry = Entry::new_with_credential(Box::new(SlowCredential))
+            .with_timeout(Duration::from_millis(50))
+            .with_no_prompt()
+            .with_auto_unlock(true)
+            .with_prompt("keep the timeout")
+            .with_persistence(crate::Persistence::Session);
+        assert!(
+            matches!(entry.get_password(), Err(Error::Timeout(_))),
+            "A store option dropped the timeout"
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let entry = Entry::new_with_credential(Box::new(SlowCredential))
+            .with_timeout(Duration::from_millis(50));
+        entry
+            .set_password("fast")
+            .expect("Fast operation timed out");
+        match entry.get_password() {
+            Err(Error::Timeout(timeout)) => assert_eq!(timeout, Duration::from_millis(50)),
+            other => panic!("Slow operation didn't time out: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_timeout_passes_through() {
+        crate::tests::test_wrapper_passes_through(|entry| {
+            entry.with_timeout(Duration::from_secs(5))
+        });
+    }
+}
@@ -20,6 +20,9 @@ rather than concatenating the username and service.
 So if you have a custom algorithm you want to use for computing the Windows target name,
 you can specify the target name directly.  (You still need to provide a service and username,
 because they are used in the credential's metadata.)
+Some programs write credentials with only a target name and no user name;
+to read or write those, give a target and an empty user.  Credentials with an
+empty user are written with no `UserName` field, just as those programs write them.
 
 The [get_attributes](crate::Entry::get_attributes)
 call will return the values in the `username`, `comment`, and `target_alias` fields
@@ -27,6 +30,112 @@ call will return the values in the `username`, `comment`, and `target_alias` fie
 and the [update_attributes](crate::Entry::update_attributes)
 call allows setting those fields.
 
+Any other attribute names passed to
+[update_attributes](crate::Entry::update_attributes)
+are stored in the credential's attribute array,
+with their values encoded as UTF-8, and they are returned
+by [get_attributes](crate::Entry::get_attributes) along with the fields above.
+These custom attributes are kept when the credential's password is changed,
+and so are attributes written by other programs: those are rewritten just as
+they are stored, even if their values aren't UTF-8 (in which case the invalid
+sequences are replaced in the values returned by
+[get_attributes](crate::Entry::get_attributes)).
+(The exception is the `keyring-expires` attribute, which holds the expiration
+time of credentials set with an expiry; reading the password or secret of an
+expired credential fails with a [NoEntry](crate::Error::NoEntry) error.)
+Windows limits a credential to 64 attributes, each of whose names can be at most
+256 UTF-16 code units and each of whose values can be at most 256 bytes; updates that
+exceed these limits fail with a [TooLong](crate::Error::TooLong) or
+[Invalid](crate::Error::Invalid) error.
+
+Windows also limits the secret of a credential to 2560 bytes.  (Passwords
+are stored as UTF-16, so the limit for them is 1280 UTF-16 code units.)
+Longer secrets and passwords are rejected with a [TooLong](crate::Error::TooLong)
+error, whose attribute is `secret` or `password encoded as UTF-16`, before any
+credential is written.
+
+Entries created in the [Machine](crate::Scope::Machine) scope
+(see [Entry::new_in_scope](crate::Entry::new_in_scope)) are persisted with
+`CRED_PERSIST_LOCAL_MACHINE` rather than `CRED_PERSIST_ENTERPRISE`, so they stay on
+this machine rather than roaming with the user's profile.  Note that the Windows
+Credential Manager keeps every credential in the vault of the account that wrote it,
+so a service that needs machine-scope credentials should write them while running
+as its own (system) account.  Writes that Windows refuses fail with a
+[NoStorageAccess](crate::Error::NoStorageAccess) error.
+
+You can also choose the persistence of an entry's credential yourself
+with [Entry::with_persistence](crate::Entry::with_persistence):
+[Session](crate::Persistence::Session) credentials are persisted with
+`CRED_PERSIST_SESSION`, so they vanish when the user logs off, while
+[Enterprise](crate::Persistence::Enterprise) credentials roam with the user's profile
+to the other machines in their domain.  An existing credential keeps its
+persistence until its secret is set again.
+
+## Domain credentials
+
+By default this module reads and writes Generic credentials (`CRED_TYPE_GENERIC`).
+To interoperate with the Windows network authentication subsystems, you can
+instead use domain-password (`CRED_TYPE_DOMAIN_PASSWORD`) or domain-certificate
+(`CRED_TYPE_DOMAIN_CERTIFICATE`) credentials, by making a credential with
+[with_credential_type](WinCredential::with_credential_type) and wrapping it with
+[Entry::new_with_credential](crate::Entry::new_with_credential):
+
+```no_run
+# use keyring::{Entry, windows::{WinCredential, WinCredentialType}};
+let credential = WinCredential::new_with_target(Some("fileserver.example.com"), "fileserver", r"EXAMPLE\alice")?
+    .with_credential_type(WinCredentialType::DomainPassword)?;
+let entry = Entry::new_with_credential(Box::new(credential));
+entry.set_password("alice's network password")?;
+# Ok::<(), keyring::Error>(())
+```
+
+The target name of a domain credential names the server (or servers) it's
+used for, so it must be a NetBIOS or DNS server name (such as `fileserver`
+or `fileserver.example.com`), a DNS host name suffix starting with a wildcard
+(such as `*.example.com`), a domain name followed by a wildcard (such as `EXAMPLE\*`),
+`*Session`, or just `*`.  Its user can't be empty, and is usually of the form
+`DOMAIN\user` or `user@example.com` (for certificate credentials, it's the
+marshaled certificate reference).  Credentials that break these rules are rejected
+with an [Invalid](crate::Error::Invalid) error before anything is written.
+
+Windows doesn't let applications read back the secret of a domain credential,
+so reading its password or secret fails with a
+[NotSupportedByStore](crate::Error::NotSupportedByStore) error,
+but it can be set, updated, and deleted like that of any other credential.
+
+## Password encodings
+
+Passwords are stored as UTF-16LE, the native encoding of Windows strings,
+which is what most Windows applications expect.  Some tools (especially
+cross-platform ones) store passwords as UTF-8 instead; to read and write their
+credentials, make a credential with
+[with_password_encoding](WinCredential::with_password_encoding) and wrap it with
+[Entry::new_with_credential](crate::Entry::new_with_credential).  Whichever
+encoding is used, a byte order mark at the start of a stored password is
+ignored when it's read.  Secrets (as opposed to passwords) are always stored
+exactly as given.
+
+## Finding credentials by target
+
+Tools such as git credential helpers write credentials under target names
+that you may only know the start of (for example, `git:https://github.com`
+followed by a path).  [find_by_target] finds the Generic credentials whose
+target names match a pattern that ends in a wildcard, such as
+`git:https://github.com*`, and returns entries for them.  So that patterns
+can be copied from the output of `cmdkey /list`, a leading
+`LegacyGeneric:target=` is ignored.
+
+## Target separators
+
+Other tools that share credentials with this crate may join the username
+and service with something other than a period (for example, `username:service`).
+To compute the same target names as such a tool, call [set_target_separator]
+at startup, before any entries are made.  The separator is used both to make
+the target names of new entries and to recognize the service and username in
+the target names of existing credentials, so credentials written with a
+different separator aren't found by [search](WinCredentialBuilder::search)
+and have no specifiers.
+
 ## Caveat
 
 Reads and writes of the same entry from multiple threads
@@ -41,18 +150,60 @@ use std::collections::HashMap;
 use std::iter::once;
 use std::mem::MaybeUninit;
 use std::str;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use windows_sys::Win32::Foundation::{
-    ERROR_BAD_USERNAME, ERROR_INVALID_FLAGS, ERROR_INVALID_PARAMETER, ERROR_NO_SUCH_LOGON_SESSION,
-    ERROR_NOT_FOUND, FILETIME, GetLastError,
+    ERROR_ACCESS_DENIED, ERROR_BAD_USERNAME, ERROR_INVALID_FLAGS, ERROR_INVALID_PARAMETER,
+    ERROR_NO_SUCH_LOGON_SESSION, ERROR_NOT_FOUND, FILETIME, GetLastError,
 };
 use windows_sys::Win32::Security::Credentials::{
-    CRED_FLAGS, CRED_MAX_CREDENTIAL_BLOB_SIZE, CRED_MAX_GENERIC_TARGET_NAME_LENGTH,
-    CRED_MAX_STRING_LENGTH, CRED_MAX_USERNAME_LENGTH, CRED_PERSIST_ENTERPRISE, CRED_TYPE_GENERIC,
-    CREDENTIAL_ATTRIBUTEW, CREDENTIALW, CredDeleteW, CredFree, CredReadW, CredWriteW,
+    CRED_FLAGS, CRED_MAX_ATTRIBUTES, CRED_MAX_CREDENTIAL_BLOB_SIZE,
+    CRED_MAX_DOMAIN_TARGET_NAME_LENGTH, CRED_MAX_GENERIC_TARGET_NAME_LENGTH,
+    CRED_MAX_STRING_LENGTH, CRED_MAX_USERNAME_LENGTH, CRED_MAX_VALUE_SIZE, CRED_PERSIST_ENTERPRISE,
+    CRED_PERSIST_LOCAL_MACHINE, CRED_PERSIST_SESSION, CRED_TYPE_DOMAIN_CERTIFICATE,
+    CRED_TYPE_DOMAIN_PASSWORD, CRED_TYPE_GENERIC, CREDENTIAL_ATTRIBUTEW, CREDENTIALW, CredDeleteW,
+    CredEnumerateW, CredFree, CredReadW, CredWriteW,
 };
+use zeroize::{Zeroize, Zeroizing};
 
-use super::credential::{Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi};
+use super::credential::{
+    Capabilities, Credential, CredentialApi, CredentialBuilder, CredentialBuilderApi,
+    EXPIRY_ATTRIBUTE, Persistence, Scope, expiry_after, has_expired,
+};
 use super::error::{Error as ErrorCode, Result};
+use super::logging::warn;
+use crate::Entry;
+
+static TARGET_SEPARATOR: std::sync::RwLock<char> = std::sync::RwLock::new('.');
+
+/// Set the character that joins the username and service in target names.
+///
+/// The default is a period, as in `username.service`.
+/// See the [module docs](crate::windows) for when to use this.
+/// Like [set_default_credential_builder](crate::set_default_credential_builder),
+/// this is meant to be called at app startup before you start creating entries.
+pub fn set_target_separator(separator: char) {
+    let mut guard = TARGET_SEPARATOR
+        .write()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!");
+    *guard = separator;
+}
+
+/// The character that joins the username and service in target names.
+fn target_separator() -> char {
+    *TARGET_SEPARATOR
+        .read()
+        .expect("Poisoned RwLock in keyring-rs: please report a bug!")
+}
+
+/// The target name for the given service and user, joined with the given separator.
+fn compose_target_name(service: &str, user: &str, separator: char) -> String {
+    format!("{user}{separator}{service}")
+}
+
+/// The service in a target name made for the given user with the given separator, if it was.
+fn split_target_name<'a>(target_name: &'a str, user: &str, separator: char) -> Option<&'a str> {
+    target_name.strip_prefix(user)?.strip_prefix(separator)
+}
 
 /// The representation of a Windows Generic credential.
 ///
@@ -63,6 +214,61 @@ pub struct WinCredential {
     pub target_name: String,
     pub target_alias: String,
     pub comment: String,
+    /// How the credential is persisted: `CRED_PERSIST_ENTERPRISE` for
+    /// user-scope credentials, `CRED_PERSIST_LOCAL_MACHINE` for machine-scope ones,
+    /// unless another persistence is chosen with [with_persistence](WinCredential::with_persistence).
+    pub persist: u32,
+    /// The type of the credential: `CRED_TYPE_GENERIC`, unless another type
+    /// is chosen with [with_credential_type](WinCredential::with_credential_type).
+    pub cred_type: u32,
+    /// How passwords are encoded in the credential's secret: as UTF-16,
+    /// unless another encoding is chosen with
+    /// [with_password_encoding](WinCredential::with_password_encoding).
+    pub password_encoding: WinPasswordEncoding,
+}
+
+/// The types of Windows credential that can be read and written.
+///
+/// See the [module documentation](crate::windows#domain-credentials)
+/// for the rules that domain credentials must follow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WinCredentialType {
+    /// A Generic credential (`CRED_TYPE_GENERIC`), usable by any application
+    #[default]
+    Generic,
+    /// A domain-password credential (`CRED_TYPE_DOMAIN_PASSWORD`),
+    /// used by the network authentication packages
+    DomainPassword,
+    /// A domain-certificate credential (`CRED_TYPE_DOMAIN_CERTIFICATE`),
+    /// used by the network authentication packages
+    DomainCertificate,
+}
+
+/// The encodings of passwords in the secrets of Windows credentials.
+///
+/// See the [module documentation](crate::windows#password-encodings).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WinPasswordEncoding {
+    /// UTF-16LE, the native encoding of Windows strings
+    #[default]
+    Utf16,
+    /// UTF-8, as used by many cross-platform tools
+    Utf8,
+}
+
+/// The fields of a native Windows Generic credential that
+/// aren't represented in a [WinCredential].
+///
+/// These are read with [get_metadata](WinCredential::get_metadata).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinCredentialMetadata {
+    /// When the credential was last written, as a `FILETIME` value
+    /// (the number of 100-nanosecond intervals since January 1, 1601 UTC).
+    pub last_written: u64,
+    /// The credential's persistence (e.g., `CRED_PERSIST_ENTERPRISE`).
+    pub persist: u32,
+    /// The credential's flags.
+    pub flags: u32,
 }
 
 // Windows API type mappings:
@@ -72,20 +278,18 @@ pub struct WinCredential {
 // PCREDENTIALW = *mut CREDENTIALW
 
 impl CredentialApi for WinCredential {
+    /// The name of this store: `"windows-credential-manager"`.
+    fn backend_name(&self) -> &'static str {
+        "windows-credential-manager"
+    }
+
     /// Create and write a credential with password for this entry.
     ///
     /// The new credential replaces any existing one in the store.
     /// Since there is only one credential with a given _target name_,
     /// there is no chance of ambiguity.
     fn set_password(&self, password: &str) -> Result<()> {
-        self.validate_attributes(None, Some(password))?;
-        // Password strings are converted to UTF-16, because that's the native
-        // charset for Windows strings.  This allows interoperability with native
-        // Windows credential APIs.  But the storage for the credential is actually
-        // a little-endian blob, because Windows credentials can contain anything.
-        let blob_u16 = to_wstr_no_null(password);
-        let mut blob = vec![0; blob_u16.len() * 2];
-        LittleEndian::write_u16_into(&blob_u16, &mut blob);
+        let blob = self.password_blob(password)?;
         self.set_secret(&blob)
     }
 
@@ -95,8 +299,46 @@ impl CredentialApi for WinCredential {
     /// Since there is only one credential with a given _target name_,
     /// there is no chance of ambiguity.
     fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        self.save_secret(secret, None)
+    }
+
+    /// Write a credential for this entry with the given password and none of
+    /// the existing credential's custom attributes.
+    ///
+    /// See [set_secret_replacing_attributes](WinCredential::set_secret_replacing_attributes).
+    fn set_password_replacing_attributes(&self, password: &str) -> Result<()> {
+        let blob = self.password_blob(password)?;
+        self.set_secret_replacing_attributes(&blob)
+    }
+
+    /// Write a credential for this entry with the given secret and none of
+    /// the existing credential's custom attributes.
+    ///
+    /// The credential gets this entry's comment, target alias, and username,
+    /// as a new credential does.  It replaces any existing one in a single
+    /// write, so a failure leaves the existing credential as it was.
+    fn set_secret_replacing_attributes(&self, secret: &[u8]) -> Result<()> {
         self.validate_attributes(Some(secret), None)?;
-        self.save_credential(secret)
+        self.save_credential(secret, &CustomAttributes::new())
+    }
+
+    /// Create and write a credential with password for this entry,
+    /// which expires after the given time.
+    ///
+    /// The expiration time is kept in the credential's `keyring-expires`
+    /// attribute, and is checked (by this crate) when the password is read.
+    fn set_password_with_expiry(&self, password: &str, ttl: Duration) -> Result<()> {
+        let blob = self.password_blob(password)?;
+        self.set_secret_with_expiry(&blob, ttl)
+    }
+
+    /// Create and write a credential with secret for this entry,
+    /// which expires after the given time.
+    ///
+    /// The expiration time is kept in the credential's `keyring-expires`
+    /// attribute, and is checked (by this crate) when the secret is read.
+    fn set_secret_with_expiry(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        self.save_secret(secret, Some(expiry_after(ttl)?))
     }
 
     /// Look up the password for this entry, if any.
@@ -104,7 +346,11 @@ impl CredentialApi for WinCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_password(&self) -> Result<String> {
-        self.extract_from_platform(extract_password)
+        self.check_readable()?;
+        self.extract_from_platform(|credential| {
+            check_expiry(credential)?;
+            extract_password(credential, self.password_encoding)
+        })
     }
 
     /// Look up the secret for this entry, if any.
@@ -112,7 +358,44 @@ impl CredentialApi for WinCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_secret(&self) -> Result<Vec<u8>> {
-        self.extract_from_platform(extract_secret)
+        self.check_readable()?;
+        self.extract_from_platform(|credential| {
+            check_expiry(credential)?;
+            extract_secret(credential)
+        })
+    }
+
+    /// Check whether there's an unexpired generic credential for this entry.
+    ///
+    /// The credential is read (Windows has no other way to look for one),
+    /// but its secret is never copied out of the platform's buffer.
+    fn exists(&self) -> Result<bool> {
+        match self.extract_from_platform(check_expiry) {
+            Ok(()) => Ok(true),
+            Err(ErrorCode::NoEntry) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the secret and `LastWritten` time of the credential for this entry,
+    /// with a single read.  Windows doesn't record when credentials are created.
+    fn get_secret_with_times(&self) -> Result<(Vec<u8>, Option<SystemTime>, Option<SystemTime>)> {
+        self.check_readable()?;
+        self.extract_from_platform(|credential| {
+            check_expiry(credential)?;
+            let last_written = &credential.LastWritten;
+            let modified = filetime_to_system_time(
+                (u64::from(last_written.dwHighDateTime) << 32)
+                    | u64::from(last_written.dwLowDateTime),
+            );
+            Ok((extract_secret(credential)?, None, modified))
+        })
+    }
+
+    /// Get the `LastWritten` time of the credential for this entry, if it exists.
+    fn last_modified(&self) -> Result<Option<SystemTime>> {
+        let metadata = self.get_metadata()?;
+        Ok(filetime_to_system_time(metadata.last_written))
     }
 
     /// Get the attributes from the credential for this entry, if it exists.
@@ -120,12 +403,18 @@ impl CredentialApi for WinCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn get_attributes(&self) -> Result<HashMap<String, String>> {
-        let cred = self.extract_from_platform(Self::extract_credential)?;
-        let mut attributes: HashMap<String, String> = HashMap::new();
-        attributes.insert("comment".to_string(), cred.comment.clone());
-        attributes.insert("target_alias".to_string(), cred.target_alias.clone());
-        attributes.insert("username".to_string(), cred.username.clone());
-        Ok(attributes)
+        self.extract_from_platform(extract_attributes)
+    }
+
+    /// Get the secret and the attributes from the credential for this entry, if it exists.
+    ///
+    /// Both come from the same read of the credential.
+    fn get_secret_with_attributes(&self) -> Result<(Vec<u8>, HashMap<String, String>)> {
+        self.check_readable()?;
+        self.extract_from_platform(|credential| {
+            check_expiry(credential)?;
+            Ok((extract_secret(credential)?, extract_attributes(credential)?))
+        })
     }
 
     /// Update the attributes on the credential for this entry, if it exists.
@@ -133,19 +422,108 @@ impl CredentialApi for WinCredential {
     /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
     /// credential in the store.
     fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
-        let secret = self.extract_from_platform(extract_secret)?;
-        let mut cred = self.extract_from_platform(Self::extract_credential)?;
-        if let Some(comment) = attributes.get(&"comment") {
-            cred.comment = comment.to_string();
+        let (secret, mut cred, mut custom) = self.extract_from_platform(|credential| {
+            Ok((
+                Zeroizing::new(extract_secret(credential)?),
+                Self::extract_credential(credential)?,
+                extract_raw_attributes(credential)?,
+            ))
+        })?;
+        for (name, value) in attributes {
+            match *name {
+                "comment" => cred.comment = value.to_string(),
+                "target_alias" => cred.target_alias = value.to_string(),
+                "username" => cred.username = value.to_string(),
+                _ => set_custom_attribute(&mut custom, name, value),
+            }
         }
-        if let Some(target_alias) = attributes.get(&"target_alias") {
-            cred.target_alias = target_alias.to_string();
+        cred.validate_attributes(Some(&secret), None)?;
+        validate_custom_attributes(&custom)?;
+        cred.save_credential(&secret, &custom)
+    }
+
+    /// Return the service and username of this credential.
+    ///
+    /// The service is only known for credentials whose target name
+    /// follows this module's `username.service` convention, so
+    /// this returns `None` for credentials with any other target name.
+    fn get_specifiers(&self) -> Option<(String, String)> {
+        let service = split_target_name(&self.target_name, &self.username, target_separator())?;
+        Some((service.to_string(), self.username.clone()))
+    }
+
+    /// Return the target name of this credential, if it was given explicitly.
+    ///
+    /// The target name of a credential that follows this module's
+    /// `username.service` convention isn't considered a target, so this
+    /// returns `None` for those credentials.
+    fn get_target(&self) -> Option<String> {
+        match self.get_specifiers() {
+            Some(_) => None,
+            None => Some(self.target_name.clone()),
         }
-        if let Some(username) = attributes.get(&"username") {
-            cred.username = username.to_string();
+    }
+
+    /// Move the underlying generic credential for this entry to the given service and user.
+    ///
+    /// Windows identifies credentials only by their target name.  If this credential's
+    /// target name follows the `username.service` convention, the moved credential's
+    /// target name does too, so a new generic credential (with this one's secret,
+    /// target alias, and custom attributes) is written before this one is deleted;
+    /// if the delete fails, the new credential is deleted instead.  Otherwise, the
+    /// target name is kept, and the username and comment are changed in place.
+    ///
+    /// Returns a [NoEntry](ErrorCode::NoEntry) error if there is no
+    /// credential in the store.
+    fn rename(&self, service: &str, user: &str) -> Result<()> {
+        let (secret, existing, custom) = self.extract_from_platform(|credential| {
+            Ok((
+                Zeroizing::new(extract_secret(credential)?),
+                Self::extract_credential(credential)?,
+                extract_raw_attributes(credential)?,
+            ))
+        })?;
+        let target = match self.get_specifiers() {
+            Some(_) => None,
+            None => Some(self.target_name.as_str()),
+        };
+        let mut renamed = Self::new_with_target(target, service, user)?;
+        renamed.target_alias = existing.target_alias;
+        renamed.persist = existing.persist;
+        renamed.cred_type = existing.cred_type;
+        renamed.password_encoding = self.password_encoding;
+        renamed.validate_attributes(Some(&secret), None)?;
+        if renamed.target_name == self.target_name {
+            return renamed.save_credential(&secret, &custom);
         }
-        cred.validate_attributes(Some(&secret), None)?;
-        cred.save_credential(&secret)
+        match renamed.extract_from_platform(|_| Ok(())) {
+            Ok(()) => return Err(ErrorCode::AlreadyExists),
+            Err(ErrorCode::NoEntry) => {}
+            Err(err) => return Err(err),
+        }
+        renamed.save_credential(&secret, &custom)?;
+        if let Err(err) = self.delete_credential() {
+            warn!("can't delete {self:?} after renaming it, so deleting the renamed copy: {err}");
+            _ = renamed.delete_credential();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Make a credential for the given user with this credential's service
+    /// and persistence.
+    ///
+    /// If this credential was made with an explicit target, its target name
+    /// is used as the service.
+    fn sibling(&self, user: &str) -> Result<Box<Credential>> {
+        let service = match self.get_specifiers() {
+            Some((service, _)) => service,
+            None => self.target_name.clone(),
+        };
+        let mut sibling = Self::new_with_target(None, &service, user)?;
+        sibling.persist = self.persist;
+        sibling.password_encoding = self.password_encoding;
+        Ok(Box::new(sibling))
     }
 
     /// Delete the underlying generic credential for this entry, if any.
@@ -155,13 +533,25 @@ impl CredentialApi for WinCredential {
     fn delete_credential(&self) -> Result<()> {
         self.validate_attributes(None, None)?;
         let target_name = to_wstr(&self.target_name);
-        let cred_type = CRED_TYPE_GENERIC;
+        let cred_type = self.cred_type;
         match unsafe { CredDeleteW(target_name.as_ptr(), cred_type, 0) } {
             0 => Err(decode_error()),
             _ => Ok(()),
         }
     }
 
+    /// Windows credentials support all the optional features.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            attributes: true,
+            enumeration: true,
+            ttl: true,
+            binary: true,
+            labels: true,
+            machine_scope: true,
+        }
+    }
+
     /// Return the underlying concrete object with an `Any` type so that it can
     /// be downgraded to a [WinCredential] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -188,7 +578,9 @@ impl WinCredential {
                 "cannot be empty".to_string(),
             ));
         }
-        if self.target_name.len() > CRED_MAX_GENERIC_TARGET_NAME_LENGTH as usize {
+        if self.cred_type != CRED_TYPE_GENERIC {
+            validate_domain_names(&self.target_name, &self.username)?;
+        } else if self.target_name.len() > CRED_MAX_GENERIC_TARGET_NAME_LENGTH as usize {
             return Err(ErrorCode::TooLong(
                 String::from("target"),
                 CRED_MAX_GENERIC_TARGET_NAME_LENGTH,
@@ -215,38 +607,118 @@ impl WinCredential {
             }
         }
         if let Some(password) = password {
-            // We're going to store the password as UTF-16, so first transform it to UTF-16,
-            // count its runes, and then multiply by 2 to get the number of bytes needed.
-            if password.encode_utf16().count() * 2 > CRED_MAX_CREDENTIAL_BLOB_SIZE as usize {
-                return Err(ErrorCode::TooLong(
-                    String::from("password encoded as UTF-16"),
-                    CRED_MAX_CREDENTIAL_BLOB_SIZE,
-                ));
+            match self.password_encoding {
+                // We're going to store the password as UTF-16, so first transform it to UTF-16,
+                // count its runes, and then multiply by 2 to get the number of bytes needed.
+                WinPasswordEncoding::Utf16 => {
+                    if password.encode_utf16().count() * 2 > CRED_MAX_CREDENTIAL_BLOB_SIZE as usize
+                    {
+                        return Err(ErrorCode::TooLong(
+                            String::from("password encoded as UTF-16"),
+                            CRED_MAX_CREDENTIAL_BLOB_SIZE,
+                        ));
+                    }
+                }
+                WinPasswordEncoding::Utf8 => {
+                    if password.len() > CRED_MAX_CREDENTIAL_BLOB_SIZE as usize {
+                        return Err(ErrorCode::TooLong(
+                            String::from("password"),
+                            CRED_MAX_CREDENTIAL_BLOB_SIZE,
+                        ));
+                    }
+                }
             }
         }
         Ok(())
     }
 
+    /// Convert a password into the blob that's stored for it.
+    ///
+    /// Password strings are converted to UTF-16, because that's the native
+    /// charset for Windows strings.  This allows interoperability with native
+    /// Windows credential APIs.  But the storage for the credential is actually
+    /// a little-endian blob, because Windows credentials can contain anything.
+    /// Both of the buffers used hold the password, so they are wiped on drop.
+    ///
+    /// If the credential's passwords are encoded as UTF-8, the blob
+    /// is just the password's bytes.
+    fn password_blob(&self, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        self.validate_attributes(None, Some(password))?;
+        if self.password_encoding == WinPasswordEncoding::Utf8 {
+            return Ok(Zeroizing::new(password.as_bytes().to_vec()));
+        }
+        let blob_u16 = Zeroizing::new(to_wstr_no_null(password));
+        let mut blob = Zeroizing::new(vec![0; blob_u16.len() * 2]);
+        LittleEndian::write_u16_into(&blob_u16, &mut blob);
+        Ok(blob)
+    }
+
+    /// Write this credential with the given secret and expiration time (if any),
+    /// keeping any existing custom attributes (just as they are stored)
+    /// other than the expiration time.
+    fn save_secret(&self, secret: &[u8], expiry: Option<u64>) -> Result<()> {
+        self.validate_attributes(Some(secret), None)?;
+        let mut custom = match self.extract_from_platform(extract_raw_attributes) {
+            Ok(custom) => custom,
+            Err(ErrorCode::NoEntry) => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+        custom.remove(EXPIRY_ATTRIBUTE);
+        if let Some(expiry) = expiry {
+            set_custom_attribute(&mut custom, EXPIRY_ATTRIBUTE, &expiry.to_string());
+            validate_custom_attributes(&custom)?;
+        }
+        self.save_credential(secret, &custom)
+    }
+
     /// Write this credential into the underlying store as a Generic credential
+    /// with the given custom attributes.
     ///
     /// You must always have validated attributes before you call this!
-    fn save_credential(&self, secret: &[u8]) -> Result<()> {
+    fn save_credential(&self, secret: &[u8], custom: &CustomAttributes) -> Result<()> {
         let mut username = to_wstr(&self.username);
         let mut target_name = to_wstr(&self.target_name);
         let mut target_alias = to_wstr(&self.target_alias);
         let mut comment = to_wstr(&self.comment);
-        let mut blob = secret.to_vec();
+        let mut blob = Zeroizing::new(secret.to_vec());
         let blob_len = blob.len() as u32;
         let flags = CRED_FLAGS::default();
-        let cred_type = CRED_TYPE_GENERIC;
-        let persist = CRED_PERSIST_ENTERPRISE;
+        let cred_type = self.cred_type;
+        let persist = self.persist;
         // Ignored by CredWriteW
         let last_written = FILETIME {
             dwLowDateTime: 0,
             dwHighDateTime: 0,
         };
-        let attribute_count = 0;
-        let attributes: *mut CREDENTIAL_ATTRIBUTEW = std::ptr::null_mut();
+        // The attribute array points into the keyword and value buffers,
+        // so those buffers must outlive the call to CredWriteW.
+        let mut names: Vec<&String> = custom.keys().collect();
+        names.sort();
+        let stored: Vec<&CustomAttribute> = names.iter().map(|name| &custom[*name]).collect();
+        let mut keywords: Vec<Vec<u16>> = stored
+            .iter()
+            .map(|attribute| attribute.keyword.iter().copied().chain(once(0)).collect())
+            .collect();
+        let mut values: Vec<Vec<u8>> = stored
+            .iter()
+            .map(|attribute| attribute.value.clone())
+            .collect();
+        let mut attributes: Vec<CREDENTIAL_ATTRIBUTEW> = stored
+            .iter()
+            .zip(keywords.iter_mut().zip(values.iter_mut()))
+            .map(|(attribute, (keyword, value))| CREDENTIAL_ATTRIBUTEW {
+                Keyword: keyword.as_mut_ptr(),
+                Flags: attribute.flags,
+                ValueSize: value.len() as u32,
+                Value: value.as_mut_ptr(),
+            })
+            .collect();
+        let attribute_count = attributes.len() as u32;
+        let attributes: *mut CREDENTIAL_ATTRIBUTEW = if attributes.is_empty() {
+            std::ptr::null_mut()
+        } else {
+            attributes.as_mut_ptr()
+        };
         let mut credential = CREDENTIALW {
             Flags: flags,
             Type: cred_type,
@@ -259,7 +731,13 @@ impl WinCredential {
             AttributeCount: attribute_count,
             Attributes: attributes,
             TargetAlias: target_alias.as_mut_ptr(),
-            UserName: username.as_mut_ptr(),
+            // Some programs write credentials with no user name at all,
+            // so an empty username means leaving the field out.
+            UserName: if self.username.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                username.as_mut_ptr()
+            },
         };
         // raw pointer to credential, is coerced from &mut
         let p_credential: *const CREDENTIALW = &mut credential;
@@ -277,6 +755,72 @@ impl WinCredential {
         self.extract_from_platform(Self::extract_credential)
     }
 
+    /// Choose how this credential is persisted when its secret is set.
+    ///
+    /// This overrides the persistence that follows from the credential's scope.
+    pub fn with_persistence(mut self, persistence: Persistence) -> Self {
+        self.persist = match persistence {
+            Persistence::Session => CRED_PERSIST_SESSION,
+            Persistence::LocalMachine => CRED_PERSIST_LOCAL_MACHINE,
+            Persistence::Enterprise => CRED_PERSIST_ENTERPRISE,
+        };
+        self
+    }
+
+    /// Choose the type of this credential's underlying Windows credential.
+    ///
+    /// Returns an [Invalid](ErrorCode::Invalid) error if this credential's
+    /// target name or user don't follow the rules for the chosen type
+    /// (see the [module documentation](crate::windows#domain-credentials)).
+    pub fn with_credential_type(mut self, cred_type: WinCredentialType) -> Result<Self> {
+        self.cred_type = match cred_type {
+            WinCredentialType::Generic => CRED_TYPE_GENERIC,
+            WinCredentialType::DomainPassword => CRED_TYPE_DOMAIN_PASSWORD,
+            WinCredentialType::DomainCertificate => CRED_TYPE_DOMAIN_CERTIFICATE,
+        };
+        self.validate_attributes(None, None)?;
+        Ok(self)
+    }
+
+    /// Choose how passwords are encoded in this credential's secret.
+    ///
+    /// Passwords are encoded as UTF-16 unless this is used to choose UTF-8
+    /// (see the [module documentation](crate::windows#password-encodings)).
+    /// Returns a [TooLong](ErrorCode::TooLong) error from
+    /// [set_password](WinCredential::set_password) if the encoded password
+    /// is too long for Windows.
+    pub fn with_password_encoding(mut self, encoding: WinPasswordEncoding) -> Self {
+        self.password_encoding = encoding;
+        self
+    }
+
+    /// Fail if Windows won't give this credential's secret to applications,
+    /// as is the case for domain credentials.
+    fn check_readable(&self) -> Result<()> {
+        match self.cred_type {
+            CRED_TYPE_GENERIC => Ok(()),
+            _ => Err(ErrorCode::NotSupportedByStore(
+                "reading the secret of a domain credential".to_string(),
+            )),
+        }
+    }
+
+    /// Read the metadata of this credential's underlying Generic credential.
+    ///
+    /// This gives access to the fields of the native `CREDENTIALW` structure
+    /// that aren't represented in a [WinCredential].
+    pub fn get_metadata(&self) -> Result<WinCredentialMetadata> {
+        self.extract_from_platform(|credential| {
+            let last_written = &credential.LastWritten;
+            Ok(WinCredentialMetadata {
+                last_written: (u64::from(last_written.dwHighDateTime) << 32)
+                    | u64::from(last_written.dwLowDateTime),
+                persist: credential.Persist,
+                flags: credential.Flags,
+            })
+        })
+    }
+
     fn extract_from_platform<F, T>(&self, f: F) -> Result<T>
     where
         F: FnOnce(&CREDENTIALW) -> Result<T>,
@@ -286,7 +830,7 @@ impl WinCredential {
         // at this point, p_credential is just a pointer to nowhere.
         // The allocation happens in the `CredReadW` call below.
         let result = {
-            let cred_type = CRED_TYPE_GENERIC;
+            let cred_type = self.cred_type;
             let target_name = to_wstr(&self.target_name);
             unsafe {
                 CredReadW(
@@ -311,7 +855,8 @@ impl WinCredential {
                 let w_credential: CREDENTIALW = unsafe { *p_credential };
                 // Now we can apply the passed extractor function to the credential.
                 let result = f(&w_credential);
-                // Finally, we free the allocated credential.
+                // Finally, we wipe the secret and free the allocated credential.
+                unsafe { zeroize_blob(&w_credential) };
                 unsafe { CredFree(p_credential as *mut _) };
                 result
             }
@@ -324,6 +869,9 @@ impl WinCredential {
             target_name: unsafe { from_wstr(w_credential.TargetName) },
             target_alias: unsafe { from_wstr(w_credential.TargetAlias) },
             comment: unsafe { from_wstr(w_credential.Comment) },
+            persist: w_credential.Persist,
+            cred_type: w_credential.Type,
+            password_encoding: WinPasswordEncoding::Utf16,
         })
     }
 
@@ -355,8 +903,12 @@ impl WinCredential {
                 target_name: target.to_string(),
                 target_alias: String::new(),
                 comment: format!("{user}@{service}:{target} (keyring v{VERSION})"),
+                persist: CRED_PERSIST_ENTERPRISE,
+                cred_type: CRED_TYPE_GENERIC,
+                password_encoding: WinPasswordEncoding::Utf16,
             }
         } else {
+            let target_name = compose_target_name(service, user, target_separator());
             Self {
                 // Note: default concatenation of user and service name is
                 // used because windows uses target_name as sole identifier.
@@ -367,9 +919,12 @@ impl WinCredential {
                 // because the format for the target name will not be empty.
                 // But it's certainly not recommended.
                 username: user.to_string(),
-                target_name: format!("{user}.{service}"),
+                comment: format!("{user}@{service}:{target_name} (keyring v{VERSION})"),
+                target_name,
                 target_alias: String::new(),
-                comment: format!("{user}@{service}:{user}.{service} (keyring v{VERSION})"),
+                persist: CRED_PERSIST_ENTERPRISE,
+                cred_type: CRED_TYPE_GENERIC,
+                password_encoding: WinPasswordEncoding::Utf16,
             }
         };
         credential.validate_attributes(None, None)?;
@@ -389,6 +944,11 @@ pub fn default_credential_builder() -> Box<CredentialBuilder> {
 }
 
 impl CredentialBuilderApi for WinCredentialBuilder {
+    /// The name of this store: `"windows-credential-manager"`.
+    fn backend_name(&self) -> &'static str {
+        "windows-credential-manager"
+    }
+
     /// Build a [WinCredential] for the given target, service, and user.
     fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
         Ok(Box::new(WinCredential::new_with_target(
@@ -396,6 +956,82 @@ impl CredentialBuilderApi for WinCredentialBuilder {
         )?))
     }
 
+    /// Build a [WinCredential] for the given service and user
+    /// whose comment is the given label.
+    fn build_with_label(&self, label: &str, service: &str, user: &str) -> Result<Box<Credential>> {
+        let mut credential = WinCredential::new_with_target(None, service, user)?;
+        credential.comment = label.to_string();
+        credential.validate_attributes(None, None)?;
+        Ok(Box::new(credential))
+    }
+
+    /// Build a [WinCredential] in the given scope for the given service and user.
+    ///
+    /// Machine-scope credentials are persisted with `CRED_PERSIST_LOCAL_MACHINE`.
+    fn build_in_scope(&self, scope: Scope, service: &str, user: &str) -> Result<Box<Credential>> {
+        let mut credential = WinCredential::new_with_target(None, service, user)?;
+        if scope == Scope::Machine {
+            credential.persist = CRED_PERSIST_LOCAL_MACHINE;
+        }
+        Ok(Box::new(credential))
+    }
+
+    /// Find all the Generic credentials for the given service.
+    ///
+    /// Since Windows credentials are identified only by their target name,
+    /// this finds the credentials whose target name is the default
+    /// `username.service` concatenation (see the module docs).  Credentials
+    /// that were created with an explicit target name are not found.
+    fn search(&self, service: &str) -> Result<Vec<Box<Credential>>> {
+        let separator = target_separator();
+        let found = enumerate_credentials(None)?;
+        Ok(found
+            .into_iter()
+            .filter(|cred| {
+                split_target_name(&cred.target_name, &cred.username, separator) == Some(service)
+            })
+            .map(|cred| Box::new(cred) as Box<Credential>)
+            .collect())
+    }
+
+    /// Count the Generic credentials for the given service.
+    ///
+    /// Like [search](WinCredentialBuilder::search), this enumerates all the
+    /// Generic credentials, but it doesn't make credentials for the matches.
+    fn count(&self, service: &str) -> Result<usize> {
+        let separator = target_separator();
+        Ok(enumerate_credentials(None)?
+            .iter()
+            .filter(|cred| {
+                split_target_name(&cred.target_name, &cred.username, separator) == Some(service)
+            })
+            .count())
+    }
+
+    /// Find all the Generic credentials with the given attributes.
+    ///
+    /// Windows can't search by attributes, so this enumerates all the Generic
+    /// credentials and matches the [attributes](WinCredential::get_attributes)
+    /// of each, along with its `service` (for credentials whose target name
+    /// follows the `username.service` convention), as they were enumerated.
+    fn query(&self, attributes: &HashMap<&str, &str>) -> Result<Vec<Box<Credential>>> {
+        let found = enumerate_matching(None, |credential| {
+            let cred = WinCredential::extract_credential(credential)?;
+            let mut actual = extract_attributes(credential)?;
+            if let Some((service, _)) = cred.get_specifiers() {
+                actual.insert("service".to_string(), service);
+            }
+            let matches = attributes
+                .iter()
+                .all(|(name, value)| actual.get(*name).map(String::as_str) == Some(*value));
+            Ok(matches.then_some(cred))
+        })?;
+        Ok(found
+            .into_iter()
+            .map(|cred| Box::new(cred) as Box<Credential>)
+            .collect())
+    }
+
     /// Return the underlying builder object with an `Any` type so that it can
     /// be downgraded to a [WinCredentialBuilder] for platform-specific processing.
     fn as_any(&self) -> &dyn std::any::Any {
@@ -403,18 +1039,273 @@ impl CredentialBuilderApi for WinCredentialBuilder {
     }
 }
 
-fn extract_password(credential: &CREDENTIALW) -> Result<String> {
+/// Find the Generic credentials whose target names match the given pattern.
+///
+/// The pattern is a target name prefix followed by an asterisk (`*`), which
+/// matches any text; the pattern `*` on its own matches every target name.
+/// A leading `LegacyGeneric:target=` (as shown by `cmdkey /list`) is ignored.
+/// Patterns with an asterisk anywhere but at the end (or with none)
+/// give an [Invalid](ErrorCode::Invalid) error whose attribute is `target`.
+///
+/// Each entry found reads and writes the credential with the target
+/// name it was found under.  See the [module docs](crate::windows) for
+/// how to use this with credentials written by other tools.
+pub fn find_by_target(pattern: &str) -> Result<Vec<Entry>> {
+    let pattern = pattern
+        .strip_prefix("LegacyGeneric:target=")
+        .unwrap_or(pattern);
+    let Some(prefix) = pattern.strip_suffix('*') else {
+        return Err(ErrorCode::Invalid(
+            "target".to_string(),
+            "the pattern must end with *".to_string(),
+        ));
+    };
+    if prefix.contains('*') {
+        return Err(ErrorCode::Invalid(
+            "target".to_string(),
+            "the pattern can only have a * at its end".to_string(),
+        ));
+    }
+    let filter = (!prefix.is_empty()).then_some(pattern);
+    Ok(enumerate_credentials(filter)?
+        .into_iter()
+        .map(|cred| Entry::new_with_credential(Box::new(cred)))
+        .collect())
+}
+
+/// Enumerate the Generic credentials whose target names match the given filter.
+///
+/// A filter is a target name prefix followed by an asterisk; if no filter is
+/// given, all the Generic credentials are enumerated.
+fn enumerate_credentials(filter: Option<&str>) -> Result<Vec<WinCredential>> {
+    enumerate_matching(filter, |credential| {
+        WinCredential::extract_credential(credential).map(Some)
+    })
+}
+
+/// Enumerate the Generic credentials whose target names match the given filter,
+/// keeping what the given function returns for those it chooses.
+///
+/// The function sees each credential as it was enumerated, so nothing is read again.
+fn enumerate_matching<T>(
+    filter: Option<&str>,
+    f: impl Fn(&CREDENTIALW) -> Result<Option<T>>,
+) -> Result<Vec<T>> {
+    let filter = filter.map(to_wstr);
+    let p_filter = filter.as_ref().map_or(std::ptr::null(), |f| f.as_ptr());
+    let mut count: u32 = 0;
+    let mut p_credentials = MaybeUninit::uninit();
+    // at this point, p_credentials is just a pointer to nowhere.
+    // The allocation happens in the `CredEnumerateW` call below.
+    let result = unsafe { CredEnumerateW(p_filter, 0, &mut count, p_credentials.as_mut_ptr()) };
+    if result == 0 {
+        // `CredEnumerateW` failed, so no allocation has been done, so no free needs to be done
+        return match decode_error() {
+            ErrorCode::NoEntry => Ok(Vec::new()),
+            err => Err(err),
+        };
+    }
+    // `CredEnumerateW` succeeded, so p_credentials points at an allocated
+    // array of `count` pointers to credentials.
+    let p_credentials: *mut *mut CREDENTIALW = unsafe { p_credentials.assume_init() };
+    let credentials = unsafe { std::slice::from_raw_parts(p_credentials, count as usize) };
+    let mut result = Ok(Vec::new());
+    for p_credential in credentials {
+        let w_credential: CREDENTIALW = unsafe { **p_credential };
+        // after a failure, the remaining secrets are still zeroized
+        if let (Ok(chosen), CRED_TYPE_GENERIC) = (&mut result, w_credential.Type) {
+            match f(&w_credential) {
+                Ok(Some(value)) => chosen.push(value),
+                Ok(None) => {}
+                Err(err) => result = Err(err),
+            }
+        }
+        unsafe { zeroize_blob(&w_credential) };
+    }
+    // Finally, we free the allocated array (which frees the credentials).
+    unsafe { CredFree(p_credentials as *mut _) };
+    result
+}
+
+/// Check that the target name and user of a domain credential follow the Windows rules.
+///
+/// The target name must be a server name, a DNS host name suffix
+/// with a leading wildcard (`*.example.com`), a domain name with a trailing
+/// wildcard (`EXAMPLE\*`), `*Session`, or `*`; the user can't be empty.
+fn validate_domain_names(target_name: &str, username: &str) -> Result<()> {
+    if target_name.len() > CRED_MAX_DOMAIN_TARGET_NAME_LENGTH as usize {
+        return Err(ErrorCode::TooLong(
+            String::from("target"),
+            CRED_MAX_DOMAIN_TARGET_NAME_LENGTH,
+        ));
+    }
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && !name.starts_with('.')
+            && !name.ends_with('.')
+            && !name.contains("..")
+            && !name
+                .chars()
+                .any(|c| c == '*' || c == '\\' || c == '/' || c == '@' || c.is_whitespace())
+    };
+    let valid = match target_name {
+        "*" | "*Session" => true,
+        _ => match (
+            target_name.strip_prefix("*."),
+            target_name.strip_suffix("\\*"),
+        ) {
+            (Some(suffix), _) => is_name(suffix),
+            (None, Some(domain)) => is_name(domain),
+            (None, None) => is_name(target_name),
+        },
+    };
+    if !valid {
+        return Err(ErrorCode::Invalid(
+            "target".to_string(),
+            "must be a server name, *.suffix, domain\\*, *Session, or * for domain credentials"
+                .to_string(),
+        ));
+    }
+    if username.is_empty() {
+        return Err(ErrorCode::Invalid(
+            "user".to_string(),
+            "cannot be empty for domain credentials".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A custom attribute of a credential, just as it is stored.
+///
+/// Attributes are kept as their stored keyword, flags, and value bytes,
+/// so that those written by other programs (whose values needn't be UTF-8)
+/// are rewritten unchanged when the credential is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CustomAttribute {
+    keyword: Vec<u16>,
+    flags: u32,
+    value: Vec<u8>,
+}
+
+/// The custom attributes of a credential, by name.
+type CustomAttributes = HashMap<String, CustomAttribute>;
+
+/// Set the value of a custom attribute, keeping its flags if it exists.
+fn set_custom_attribute(custom: &mut CustomAttributes, name: &str, value: &str) {
+    let flags = custom.get(name).map_or(0, |attribute| attribute.flags);
+    let attribute = CustomAttribute {
+        keyword: name.encode_utf16().collect(),
+        flags,
+        value: value.as_bytes().to_vec(),
+    };
+    custom.insert(name.to_string(), attribute);
+}
+
+/// Check that a set of custom attributes fits within the Windows limits.
+///
+/// The limit on names is in UTF-16 code units, and the limit on values is in bytes.
+fn validate_custom_attributes(custom: &CustomAttributes) -> Result<()> {
+    if custom.len() > CRED_MAX_ATTRIBUTES as usize {
+        return Err(ErrorCode::Invalid(
+            "attributes".to_string(),
+            format!("there can be at most {CRED_MAX_ATTRIBUTES} of them"),
+        ));
+    }
+    for (name, attribute) in custom {
+        if attribute.keyword.is_empty() {
+            return Err(ErrorCode::Invalid(
+                "attribute name".to_string(),
+                "cannot be empty".to_string(),
+            ));
+        }
+        if attribute.keyword.len() > CRED_MAX_STRING_LENGTH as usize {
+            return Err(ErrorCode::TooLong(
+                String::from("attribute name"),
+                CRED_MAX_STRING_LENGTH,
+            ));
+        }
+        if attribute.value.len() > CRED_MAX_VALUE_SIZE as usize {
+            return Err(ErrorCode::TooLong(name.clone(), CRED_MAX_VALUE_SIZE));
+        }
+    }
+    Ok(())
+}
+
+/// Extract the custom attributes of a credential allocated by the platform,
+/// just as they are stored.
+fn extract_raw_attributes(credential: &CREDENTIALW) -> Result<CustomAttributes> {
+    let mut custom = HashMap::new();
+    let count = credential.AttributeCount as usize;
+    if count == 0 || credential.Attributes.is_null() {
+        return Ok(custom);
+    }
+    let attributes = unsafe { std::slice::from_raw_parts(credential.Attributes, count) };
+    for attribute in attributes {
+        let keyword = unsafe { wstr_slice(attribute.Keyword) }.to_vec();
+        let value_len = attribute.ValueSize as usize;
+        let value = if value_len == 0 || attribute.Value.is_null() {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(attribute.Value, value_len) }.to_vec()
+        };
+        let name = String::from_utf16_lossy(&keyword);
+        let flags = attribute.Flags;
+        custom.insert(
+            name,
+            CustomAttribute {
+                keyword,
+                flags,
+                value,
+            },
+        );
+    }
+    Ok(custom)
+}
+
+/// Extract the custom attributes of a credential allocated by the platform,
+/// with their values decoded as UTF-8 (replacing any invalid sequences).
+fn extract_custom_attributes(credential: &CREDENTIALW) -> Result<HashMap<String, String>> {
+    let custom = extract_raw_attributes(credential)?;
+    Ok(custom
+        .into_iter()
+        .map(|(name, attribute)| (name, String::from_utf8_lossy(&attribute.value).into_owned()))
+        .collect())
+}
+
+/// Fail with a [NoEntry](ErrorCode::NoEntry) error if the credential has expired.
+fn check_expiry(credential: &CREDENTIALW) -> Result<()> {
+    let custom = extract_custom_attributes(credential)?;
+    match custom.get(EXPIRY_ATTRIBUTE).and_then(|e| e.parse().ok()) {
+        Some(expiry) if has_expired(expiry) => Err(ErrorCode::NoEntry),
+        _ => Ok(()),
+    }
+}
+
+/// Decode the password in a credential's secret, ignoring
+/// any byte order mark at the start of it.
+fn extract_password(credential: &CREDENTIALW, encoding: WinPasswordEncoding) -> Result<String> {
     let blob = extract_secret(credential)?;
+    if encoding == WinPasswordEncoding::Utf8 {
+        let mut blob = Zeroizing::new(blob);
+        let text = blob.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(&blob);
+        return match str::from_utf8(text) {
+            Ok(password) => Ok(password.to_string()),
+            Err(_) => Err(ErrorCode::BadEncoding(std::mem::take(&mut *blob))),
+        };
+    }
     // 3rd parties may write credential data with an odd number of bytes,
     // so we make sure that we don't try to decode those as utf16
     if blob.len() % 2 != 0 {
         return Err(ErrorCode::BadEncoding(blob));
     }
     // Now we know this _can_ be a UTF-16 string, so convert it to
-    // as UTF-16 vector and then try to decode it.
-    let mut blob_u16 = vec![0; blob.len() / 2];
+    // as UTF-16 vector and then try to decode it.  The intermediate
+    // buffers are wiped once we either have the string or have failed.
+    let mut blob = Zeroizing::new(blob);
+    let mut blob_u16 = Zeroizing::new(vec![0; blob.len() / 2]);
     LittleEndian::read_u16_into(&blob, &mut blob_u16);
-    String::from_utf16(&blob_u16).map_err(|_| ErrorCode::BadEncoding(blob))
+    let text = blob_u16.strip_prefix(&[0xFEFF]).unwrap_or(&blob_u16);
+    String::from_utf16(text).map_err(|_| ErrorCode::BadEncoding(std::mem::take(&mut *blob)))
 }
 
 fn extract_secret(credential: &CREDENTIALW) -> Result<Vec<u8>> {
@@ -427,6 +1318,31 @@ fn extract_secret(credential: &CREDENTIALW) -> Result<Vec<u8>> {
     Ok(blob.to_vec())
 }
 
+/// Extract the readable attributes of a credential allocated by the platform.
+fn extract_attributes(credential: &CREDENTIALW) -> Result<HashMap<String, String>> {
+    let cred = WinCredential::extract_credential(credential)?;
+    let mut attributes = extract_custom_attributes(credential)?;
+    attributes.insert("comment".to_string(), cred.comment);
+    attributes.insert("target_alias".to_string(), cred.target_alias);
+    attributes.insert("username".to_string(), cred.username);
+    Ok(attributes)
+}
+
+/// Wipe the secret in a credential allocated by the platform, before it is freed.
+///
+/// # Safety
+///
+/// The credential's blob must point to platform-allocated, writable memory
+/// of the credential's blob size (or be empty).
+unsafe fn zeroize_blob(credential: &CREDENTIALW) {
+    let blob_pointer: *mut u8 = credential.CredentialBlob;
+    let blob_len: usize = credential.CredentialBlobSize as usize;
+    if blob_pointer.is_null() || blob_len == 0 {
+        return;
+    }
+    unsafe { std::slice::from_raw_parts_mut(blob_pointer, blob_len) }.zeroize();
+}
+
 fn to_wstr(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(once(0)).collect()
 }
@@ -436,17 +1352,22 @@ fn to_wstr_no_null(s: &str) -> Vec<u16> {
 }
 
 unsafe fn from_wstr(ws: *const u16) -> String {
-    // null pointer case, return empty string
+    String::from_utf16_lossy(unsafe { wstr_slice(ws) })
+}
+
+/// The characters of a null-terminated wide string allocated by the platform
+/// (without the terminating null).
+unsafe fn wstr_slice<'a>(ws: *const u16) -> &'a [u16] {
+    // null pointer case, return empty slice
     if ws.is_null() {
-        return String::new();
+        return &[];
     }
     // this code from https://stackoverflow.com/a/48587463/558006
     let len = (0..).take_while(|&i| unsafe { *ws.offset(i) != 0 }).count();
     if len == 0 {
-        return String::new();
+        return &[];
     }
-    let slice = unsafe { std::slice::from_raw_parts(ws, len) };
-    String::from_utf16_lossy(slice)
+    unsafe { std::slice::from_raw_parts(ws, len) }
 }
 
 /// Windows error codes are `DWORDS` which are 32-bit unsigned ints.
@@ -457,6 +1378,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.0 {
             ERROR_NO_SUCH_LOGON_SESSION => write!(f, "Windows ERROR_NO_SUCH_LOGON_SESSION"),
+            ERROR_ACCESS_DENIED => write!(f, "Windows ERROR_ACCESS_DENIED"),
             ERROR_NOT_FOUND => write!(f, "Windows ERROR_NOT_FOUND"),
             ERROR_BAD_USERNAME => write!(f, "Windows ERROR_BAD_USERNAME"),
             ERROR_INVALID_FLAGS => write!(f, "Windows ERROR_INVALID_FLAGS"),
@@ -472,19 +1394,19 @@ impl std::error::Error for Error {
     }
 }
 
-/// Map the last encountered Windows API error to a crate error with appropriate annotation.
-pub fn decode_error() -> ErrorCode {
-    match unsafe { GetLastError() } {
-        ERROR_NOT_FOUND => ErrorCode::NoEntry,
-        ERROR_NO_SUCH_LOGON_SESSION => {
-            ErrorCode::NoStorageAccess(wrap(ERROR_NO_SUCH_LOGON_SESSION))
-        }
-        err => ErrorCode::PlatformFailure(wrap(err)),
-    }
+/// Convert a `FILETIME` value (in 100-nanosecond intervals since
+/// January 1, 1601 UTC) to a system time.
+///
+/// Times before the Unix epoch (such as a zero `FILETIME`) give `None`.
+fn filetime_to_system_time(filetime: u64) -> Option<SystemTime> {
+    const UNIX_EPOCH_AS_FILETIME: u64 = 116_444_736_000_000_000;
+    let since_epoch = filetime.checked_sub(UNIX_EPOCH_AS_FILETIME)?;
+    Some(UNIX_EPOCH + Duration::from_nanos(since_epoch) * 100)
 }
 
-fn wrap(code: u32) -> Box<dyn std::error::Error + Send + Sync> {
-    Box::new(Error(code))
+/// Map the last encountered Windows API error to a crate error with appropriate annotation.
+pub fn decode_error() -> ErrorCode {
+    Error(unsafe { GetLastError() }).into()
 }
 
 #[cfg(test)]
@@ -503,10 +1425,36 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(
+            default_credential_builder().backend_name(),
+            "windows-credential-manager"
+        );
+        let name = generate_random_string();
+        assert_eq!(
+            entry_new(&name, &name).backend_name(),
+            "windows-credential-manager"
+        );
+    }
+
     fn entry_new(service: &str, user: &str) -> Entry {
         crate::tests::entry_from_constructor(WinCredential::new_with_target, service, user)
     }
 
+    #[test]
+    fn test_platform_code() {
+        let err = ErrorCode::from(Error(ERROR_INVALID_FLAGS));
+        assert!(matches!(err, ErrorCode::PlatformFailure(_)));
+        assert_eq!(err.platform_code(), Some(ERROR_INVALID_FLAGS as i64));
+        let err = ErrorCode::from(Error(ERROR_NO_SUCH_LOGON_SESSION));
+        assert!(matches!(err, ErrorCode::NoStorageAccess(_)));
+        assert_eq!(
+            err.platform_code(),
+            Some(ERROR_NO_SUCH_LOGON_SESSION as i64)
+        );
+    }
+
     #[test]
     fn test_bad_password() {
         fn make_platform_credential(password: &mut Vec<u8>) -> CREDENTIALW {
@@ -540,7 +1488,7 @@ mod tests {
         LittleEndian::write_u16_into(&malformed_utf16, &mut malformed_bytes);
         for bytes in [&mut odd_bytes, &mut malformed_bytes] {
             let credential = make_platform_credential(bytes);
-            match extract_password(&credential) {
+            match extract_password(&credential, WinPasswordEncoding::Utf16) {
                 Err(ErrorCode::BadEncoding(str)) => assert_eq!(&str, bytes),
                 Err(other) => panic!("Bad password ({bytes:?}) decode gave wrong error: {other}"),
                 Ok(s) => panic!("Bad password ({bytes:?}) decode gave results: {s:?}"),
@@ -572,6 +1520,9 @@ mod tests {
             target_name: "target_name".to_string(),
             target_alias: "target_alias".to_string(),
             comment: "comment".to_string(),
+            persist: CRED_PERSIST_ENTERPRISE,
+            cred_type: CRED_TYPE_GENERIC,
+            password_encoding: WinPasswordEncoding::Utf16,
         };
         for (attr, len) in [
             ("user", CRED_MAX_USERNAME_LENGTH),
@@ -605,6 +1556,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate_custom_attributes() {
+        let mut custom: CustomAttributes = HashMap::new();
+        set_custom_attribute(&mut custom, "name", "value");
+        validate_custom_attributes(&custom).expect("Valid custom attributes rejected");
+        // the limit on names is in UTF-16 code units, not UTF-8 bytes
+        let wide_name = "\u{e9}".repeat(CRED_MAX_STRING_LENGTH as usize);
+        let mut wide = custom.clone();
+        set_custom_attribute(&mut wide, &wide_name, "value");
+        validate_custom_attributes(&wide).expect("Name within the UTF-16 limit rejected");
+        let long_name = generate_random_string_of_len(1 + CRED_MAX_STRING_LENGTH as usize);
+        let mut bad = custom.clone();
+        set_custom_attribute(&mut bad, &long_name, "value");
+        match validate_custom_attributes(&bad) {
+            Err(ErrorCode::TooLong(name, len)) => {
+                assert_eq!(name, "attribute name", "Error names wrong attribute");
+                assert_eq!(len, CRED_MAX_STRING_LENGTH, "Error names wrong limit");
+            }
+            other => panic!("Long attribute name not rejected: {other:?}"),
+        }
+        let long_value = generate_random_string_of_len(1 + CRED_MAX_VALUE_SIZE as usize);
+        let mut bad = custom.clone();
+        set_custom_attribute(&mut bad, "name", &long_value);
+        match validate_custom_attributes(&bad) {
+            Err(ErrorCode::TooLong(name, len)) => {
+                assert_eq!(name, "name", "Error names wrong attribute");
+                assert_eq!(len, CRED_MAX_VALUE_SIZE, "Error names wrong limit");
+            }
+            other => panic!("Long attribute value not rejected: {other:?}"),
+        }
+        let mut bad = custom.clone();
+        for i in 0..CRED_MAX_ATTRIBUTES {
+            set_custom_attribute(&mut bad, &format!("name {i}"), "value");
+        }
+        assert!(
+            matches!(
+                validate_custom_attributes(&bad),
+                Err(ErrorCode::Invalid(_, _))
+            ),
+            "Too many attributes not rejected"
+        );
+    }
+
     #[test]
     fn test_password_valid_only_after_conversion_to_utf16() {
         let cred = WinCredential {
@@ -612,6 +1606,9 @@ mod tests {
             target_name: "target_name".to_string(),
             target_alias: "target_alias".to_string(),
             comment: "comment".to_string(),
+            persist: CRED_PERSIST_ENTERPRISE,
+            cred_type: CRED_TYPE_GENERIC,
+            password_encoding: WinPasswordEncoding::Utf16,
         };
 
         let len = CRED_MAX_CREDENTIAL_BLOB_SIZE / 2;
@@ -661,11 +1658,112 @@ mod tests {
         crate::tests::test_round_trip_random_secret(entry_new);
     }
 
+    #[test]
+    fn test_bad_encoding() {
+        crate::tests::test_bad_encoding(entry_new);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroizing")]
+    fn test_round_trip_zeroizing() {
+        crate::tests::test_round_trip_zeroizing(entry_new);
+    }
+
     #[test]
     fn test_update() {
         crate::tests::test_update(entry_new);
     }
 
+    #[test]
+    fn test_create_update() {
+        crate::tests::test_create_update(entry_new);
+    }
+
+    #[test]
+    fn test_rename() {
+        crate::tests::test_rename(entry_new);
+    }
+
+    #[test]
+    fn test_can_set() {
+        crate::tests::test_can_set(entry_new);
+    }
+
+    #[test]
+    fn test_exists() {
+        crate::tests::test_exists(entry_new);
+    }
+
+    #[test]
+    fn test_rotate() {
+        crate::tests::test_rotate(entry_new);
+    }
+
+    #[test]
+    fn test_chunked() {
+        crate::tests::test_chunked(entry_new);
+    }
+
+    #[test]
+    fn test_get_with_attributes() {
+        crate::tests::test_get_with_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        crate::tests::test_capabilities(entry_new);
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        crate::tests::test_compare_and_swap(entry_new);
+    }
+
+    #[test]
+    fn test_verify() {
+        crate::tests::test_verify(entry_new);
+    }
+
+    #[test]
+    fn test_set_replacing_attributes() {
+        crate::tests::test_set_replacing_attributes(entry_new);
+    }
+
+    #[test]
+    fn test_set_keeps_attributes() {
+        crate::tests::test_set_keeps_attributes(entry_new, "attribute name");
+    }
+
+    #[test]
+    fn test_get_opt() {
+        crate::tests::test_get_opt(entry_new);
+    }
+
+    #[test]
+    fn test_delete_if_exists() {
+        crate::tests::test_delete_if_exists(entry_new);
+    }
+
+    #[test]
+    fn test_get_info() {
+        crate::tests::test_get_info(entry_new);
+    }
+
+    #[test]
+    fn test_password_and_credential() {
+        crate::tests::test_password_and_credential(entry_new);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        crate::tests::test_last_modified(entry_new);
+    }
+
+    #[test]
+    fn test_expiry() {
+        crate::tests::test_expiry(entry_new);
+    }
+
     #[test]
     fn test_get_update_attributes() {
         let name = generate_random_string();
@@ -677,8 +1775,8 @@ mod tests {
             "Read missing credential in attribute test",
         );
         let mut in_map: HashMap<&str, &str> = HashMap::new();
-        in_map.insert("label", "ignored label value");
-        in_map.insert("attribute name", "ignored attribute value");
+        in_map.insert("label", "label value");
+        in_map.insert("attribute name", "attribute value");
         in_map.insert("target_alias", "target alias value");
         in_map.insert("comment", "comment value");
         in_map.insert("username", "username value");
@@ -706,8 +1804,17 @@ mod tests {
         assert_eq!(after_map["target_alias"], in_map["target_alias"]);
         assert_eq!(after_map["comment"], in_map["comment"]);
         assert_eq!(after_map["username"], in_map["username"]);
-        assert!(!after_map.contains_key("label"));
-        assert!(!after_map.contains_key("attribute name"));
+        assert_eq!(after_map["label"], in_map["label"]);
+        assert_eq!(after_map["attribute name"], in_map["attribute name"]);
+        // custom attributes survive a password change
+        entry
+            .set_password("changed password for attributes")
+            .unwrap_or_else(|err| panic!("Can't change password for attribute test: {err:?}"));
+        let changed_map = entry
+            .get_attributes()
+            .expect("Can't get attributes after password change");
+        assert_eq!(changed_map["label"], in_map["label"]);
+        assert_eq!(changed_map["attribute name"], in_map["attribute name"]);
         entry
             .delete_credential()
             .unwrap_or_else(|err| panic!("Can't delete credential for attribute test: {err:?}"));
@@ -717,6 +1824,360 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_raw_attributes_kept() {
+        let name = generate_random_string();
+        let cred = WinCredential::new_with_target(None, &name, &name)
+            .expect("Can't create credential for raw attribute test");
+        // an attribute written by another program, whose value isn't UTF-8
+        let foreign = CustomAttribute {
+            keyword: "foreign".encode_utf16().collect(),
+            flags: 0,
+            value: vec![0xff, 0xfe, 0x00, 0x80],
+        };
+        let mut custom: CustomAttributes = HashMap::new();
+        custom.insert("foreign".to_string(), foreign.clone());
+        cred.save_credential(b"raw attribute secret", &custom)
+            .expect("Can't write credential for raw attribute test");
+        let entry = Entry::new_with_credential(Box::new(cred.clone()));
+        entry
+            .set_password("changed password for raw attributes")
+            .expect("Can't change password for raw attribute test");
+        let mut in_map: HashMap<&str, &str> = HashMap::new();
+        in_map.insert("other", "other value");
+        entry
+            .update_attributes(&in_map)
+            .expect("Can't update attributes for raw attribute test");
+        let out = cred
+            .extract_from_platform(extract_raw_attributes)
+            .expect("Can't read attributes for raw attribute test");
+        assert_eq!(out["foreign"], foreign, "Foreign attribute was changed");
+        assert_eq!(out["other"].value, b"other value");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for raw attribute test");
+    }
+
+    #[test]
+    fn test_password_encoding() {
+        let name = generate_random_string();
+        let credential = WinCredential::new_with_target(None, &name, &name)
+            .unwrap()
+            .with_password_encoding(WinPasswordEncoding::Utf8);
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry.set_password("pässwörd").unwrap();
+        assert_eq!(entry.get_secret().unwrap(), "pässwörd".as_bytes());
+        assert_eq!(entry.get_password().unwrap(), "pässwörd");
+        let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+        with_bom.extend_from_slice("pässwörd".as_bytes());
+        entry.set_secret(&with_bom).unwrap();
+        assert_eq!(entry.get_password().unwrap(), "pässwörd");
+        entry.delete_credential().unwrap();
+        let entry = entry_new(&name, &name);
+        let mut with_bom = vec![0xFF, 0xFE];
+        for unit in "pässwörd".encode_utf16() {
+            with_bom.extend_from_slice(&unit.to_le_bytes());
+        }
+        entry.set_secret(&with_bom).unwrap();
+        assert_eq!(entry.get_password().unwrap(), "pässwörd");
+        entry.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_find_by_target() {
+        let prefix = format!("keyring-test:{}/", generate_random_string());
+        let targets = [format!("{prefix}one"), format!("{prefix}two/three")];
+        for target in &targets {
+            let credential = WinCredential::new_with_target(Some(target), "service", "")
+                .expect("Can't create credential with explicit target");
+            Entry::new_with_credential(Box::new(credential))
+                .set_password(target)
+                .expect("Can't set password for find_by_target");
+        }
+        let found = find_by_target(&format!("LegacyGeneric:target={prefix}*"))
+            .expect("Can't find credentials by target");
+        assert_eq!(found.len(), 2, "Didn't find both credentials");
+        for entry in found {
+            let cred: &WinCredential = entry.get_credential().downcast_ref().unwrap();
+            let target = cred.target_name.clone();
+            assert!(targets.contains(&target), "Found the wrong target {target}");
+            assert_eq!(entry.get_password().unwrap(), target);
+            entry.delete_credential().expect("Can't delete found entry");
+        }
+        assert!(find_by_target(&format!("{prefix}*")).unwrap().is_empty());
+        for pattern in ["", "no-wildcard", "middle*wildcard*"] {
+            assert!(
+                matches!(find_by_target(pattern), Err(ErrorCode::Invalid(attr, _)) if attr == "target"),
+                "Accepted pattern {pattern:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        crate::tests::test_query(entry_new, default_credential_builder().as_ref());
+    }
+
+    #[test]
+    fn test_search() {
+        let name = generate_random_string();
+        let builder = default_credential_builder();
+        let found = builder
+            .search(&name)
+            .expect("Can't search for missing service");
+        assert!(found.is_empty(), "Found entries for a missing service");
+        let user1 = generate_random_string();
+        let user2 = generate_random_string();
+        for user in [&user1, &user2] {
+            entry_new(&name, user)
+                .set_password(user)
+                .expect("Can't set password for search");
+        }
+        let found = builder.search(&name).expect("Can't search for service");
+        assert_eq!(found.len(), 2, "Didn't find both entries");
+        for credential in found {
+            let entry = Entry::new_with_credential(credential);
+            let cred: &WinCredential = entry.get_credential().downcast_ref().unwrap();
+            let user = cred.username.clone();
+            assert!(user == user1 || user == user2, "Found the wrong user");
+            assert_eq!(
+                entry.get_password().expect("Can't get found password"),
+                user
+            );
+            entry.delete_credential().expect("Can't delete found entry");
+        }
+        let found = builder.search(&name).expect("Can't search after delete");
+        assert!(found.is_empty(), "Found entries after delete");
+    }
+
+    #[test]
+    fn test_new_with_label() {
+        let name = generate_random_string();
+        let entry = Entry::new_with_label("My App's Password", &name, &name)
+            .expect("Can't create labeled entry");
+        entry
+            .set_password("labeled password")
+            .expect("Can't set labeled password");
+        let attributes = entry
+            .get_attributes()
+            .expect("Can't get labeled attributes");
+        assert_eq!(attributes["comment"], "My App's Password");
+        entry
+            .delete_credential()
+            .expect("Can't delete labeled credential");
+    }
+
+    #[test]
+    fn test_machine_scope() {
+        let name = generate_random_string();
+        let entry = Entry::new_in_scope(Scope::Machine, &name, &name)
+            .expect("Can't create machine-scope entry");
+        entry
+            .set_password("machine password")
+            .expect("Can't set machine-scope password");
+        let credential: &WinCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a windows credential");
+        let metadata = credential
+            .get_metadata()
+            .expect("Can't get machine-scope metadata");
+        assert_eq!(metadata.persist, CRED_PERSIST_LOCAL_MACHINE);
+        entry
+            .delete_credential()
+            .expect("Can't delete machine-scope credential");
+    }
+
+    #[test]
+    fn test_persistence_option() {
+        for (persistence, persist) in [
+            (Persistence::Session, CRED_PERSIST_SESSION),
+            (Persistence::LocalMachine, CRED_PERSIST_LOCAL_MACHINE),
+            (Persistence::Enterprise, CRED_PERSIST_ENTERPRISE),
+        ] {
+            let name = generate_random_string();
+            let entry = entry_new(&name, &name).with_persistence(persistence);
+            entry
+                .set_password("persisted password")
+                .expect("Can't set password");
+            let credential: &WinCredential = entry
+                .get_credential()
+                .downcast_ref()
+                .expect("Not a windows credential");
+            let metadata = credential.get_metadata().expect("Can't get metadata");
+            assert_eq!(
+                metadata.persist, persist,
+                "Wrong persist for {persistence:?}"
+            );
+            entry
+                .delete_credential()
+                .expect("Can't delete persisted credential");
+        }
+    }
+
+    #[test]
+    fn test_domain_names() {
+        for target in [
+            "*",
+            "*Session",
+            "fileserver",
+            "fileserver.example.com",
+            "*.example.com",
+            r"EXAMPLE\*",
+        ] {
+            validate_domain_names(target, r"EXAMPLE\user")
+                .unwrap_or_else(|err| panic!("Rejected domain target {target}: {err}"));
+        }
+        for target in [
+            "",
+            "*.",
+            "*example.com",
+            "file*server",
+            r"EXAMPLE\user",
+            "user.example.com.",
+            "two words",
+            "https://fileserver",
+        ] {
+            assert!(
+                matches!(
+                    validate_domain_names(target, r"EXAMPLE\user"),
+                    Err(ErrorCode::Invalid(attr, _)) if attr == "target"
+                ),
+                "Accepted domain target {target:?}"
+            );
+        }
+        assert!(matches!(
+            validate_domain_names("fileserver", ""),
+            Err(ErrorCode::Invalid(attr, _)) if attr == "user"
+        ));
+        let long = generate_random_string_of_len(CRED_MAX_DOMAIN_TARGET_NAME_LENGTH as usize + 1);
+        assert!(matches!(
+            validate_domain_names(&long, "user"),
+            Err(ErrorCode::TooLong(attr, _)) if attr == "target"
+        ));
+    }
+
+    #[test]
+    fn test_credential_type_validation() {
+        let name = generate_random_string();
+        let credential = WinCredential::new_with_target(Some("my*server"), &name, &name)
+            .expect("Can't create credential");
+        assert!(matches!(
+            credential.with_credential_type(WinCredentialType::DomainPassword),
+            Err(ErrorCode::Invalid(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_domain_password_credential() {
+        let name = generate_random_string();
+        let target = format!("{name}.example.com");
+        let credential = WinCredential::new_with_target(Some(&target), &name, &name)
+            .expect("Can't create credential")
+            .with_credential_type(WinCredentialType::DomainPassword)
+            .expect("Can't make domain-password credential");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        entry
+            .set_password("domain password")
+            .expect("Can't set domain password");
+        assert!(entry.exists().expect("Can't check domain credential"));
+        assert!(matches!(
+            entry.get_password(),
+            Err(ErrorCode::NotSupportedByStore(_))
+        ));
+        let credential: &WinCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a windows credential");
+        let platform = credential
+            .get_credential()
+            .expect("Can't get domain credential");
+        assert_eq!(platform.cred_type, CRED_TYPE_DOMAIN_PASSWORD);
+        entry
+            .delete_credential()
+            .expect("Can't delete domain credential");
+        assert!(
+            !entry
+                .exists()
+                .expect("Can't check deleted domain credential")
+        );
+    }
+
+    #[test]
+    fn test_empty_username() {
+        let name = generate_random_string();
+        let credential = WinCredential::new_with_target(Some(&name), &name, "")
+            .expect("Can't create credential with empty user");
+        credential
+            .set_password("no user")
+            .expect("Can't set password with empty user");
+        let has_username = credential
+            .extract_from_platform(|cred| Ok(!cred.UserName.is_null()))
+            .expect("Can't read credential with empty user");
+        assert!(!has_username, "Empty user was written as a UserName");
+        let entry = Entry::new_with_credential(Box::new(credential));
+        assert_eq!(entry.get_password().unwrap(), "no user");
+        assert_eq!(entry.get_attributes().unwrap()["username"], "");
+        entry.delete_credential().unwrap();
+    }
+
+    #[test]
+    fn test_get_metadata() {
+        let name = generate_random_string();
+        let entry = entry_new(&name, &name);
+        let credential: &WinCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a windows credential");
+        assert!(
+            matches!(credential.get_metadata(), Err(ErrorCode::NoEntry)),
+            "Got metadata for a missing credential"
+        );
+        entry
+            .set_password("test metadata")
+            .expect("Can't set password for metadata");
+        let metadata = credential.get_metadata().expect("Can't get metadata");
+        assert_eq!(metadata.persist, CRED_PERSIST_ENTERPRISE);
+        assert_ne!(metadata.last_written, 0, "Credential was never written");
+        entry
+            .delete_credential()
+            .expect("Can't delete credential for metadata");
+    }
+
+    #[test]
+    fn test_explicit_target() {
+        let name = generate_random_string();
+        let target = format!("git:https://{name}.example.com");
+        let entry = Entry::new_with_credential(Box::new(
+            WinCredential::new_with_target(Some(&target), &name, &name)
+                .expect("Can't create credential with explicit target"),
+        ));
+        let credential: &WinCredential = entry
+            .get_credential()
+            .downcast_ref()
+            .expect("Not a windows credential");
+        assert_eq!(
+            credential.target_name, target,
+            "Target name isn't the target"
+        );
+        entry
+            .set_password("target password")
+            .expect("Can't set password with explicit target");
+        // the target name alone identifies the credential, so another
+        // tool's service and username don't matter when reading it
+        let other = Entry::new_with_credential(Box::new(
+            WinCredential::new_with_target(Some(&target), "other service", "other user")
+                .expect("Can't create other credential with explicit target"),
+        ));
+        assert_eq!(
+            other.get_password().expect("Can't get password via target"),
+            "target password"
+        );
+        entry
+            .delete_credential()
+            .expect("Can't delete credential with explicit target");
+    }
+
     #[test]
     fn test_get_credential() {
         let name = generate_random_string();
@@ -748,4 +2209,17 @@ mod tests {
             .expect("Couldn't delete get-credential");
         assert!(matches!(entry.get_password(), Err(ErrorCode::NoEntry)));
     }
+
+    #[test]
+    fn test_target_separator() {
+        assert_eq!(compose_target_name("service", "user", '.'), "user.service");
+        assert_eq!(compose_target_name("service", "user", ':'), "user:service");
+        assert_eq!(
+            split_target_name("user:service", "user", ':'),
+            Some("service")
+        );
+        assert_eq!(split_target_name("user:service", "user", '.'), None);
+        assert_eq!(split_target_name("user.service", "other", '.'), None);
+        assert_eq!(split_target_name("user.", "user", '.'), Some(""));
+    }
 }
@@ -0,0 +1,33 @@
+use keyring::{Entry, mock};
+
+// The default credential builder and the app namespace are shared by the
+// whole process, so the tests of iterating over the entries that the default
+// builder finds are in their own test binary, and in a single test.
+#[test]
+fn test_iter_entries() {
+    keyring::set_default_credential_builder(mock::default_credential_builder());
+    let users = |service: Option<&str>| {
+        let mut users: Vec<String> = keyring::iter_entries(service)
+            .map(|entry| entry.unwrap().get_specifiers().unwrap().1)
+            .collect();
+        users.sort();
+        users
+    };
+    let set = |service: &str, user: &str| {
+        Entry::new(service, user)
+            .unwrap()
+            .set_password("iterated password")
+            .unwrap();
+    };
+    set("iterated service", "outside");
+    keyring::set_app_namespace("iterated-app");
+    set("iterated service", "alice");
+    set("other service", "bob");
+    assert_eq!(users(Some("iterated service")), ["alice"]);
+    // all the entries, but only those in the namespace
+    assert_eq!(users(None), ["alice", "bob"]);
+    keyring::clear_app_namespace();
+    assert_eq!(users(Some("iterated service")), ["outside"]);
+    assert_eq!(users(None), ["alice", "bob", "outside"]);
+    assert!(users(Some("missing service")).is_empty());
+}
@@ -0,0 +1,29 @@
+use keyring::{Entry, Error, mock};
+
+// The mock store is shared by the whole process, so the tests of clearing
+// and restoring it are in their own test binary, and in a single test.
+#[test]
+fn test_clear_snapshot_restore() {
+    let builder = mock::default_credential_builder();
+    let entry =
+        |user: &str| Entry::new_with_credential(builder.build(None, "fixture", user).unwrap());
+    let (alice, bob) = (entry("alice"), entry("bob"));
+    alice.set_password("alice's password").unwrap();
+    bob.set_password("bob's password").unwrap();
+    let fixtures = mock::snapshot();
+    assert_eq!(fixtures.len(), 2);
+
+    mock::clear();
+    assert!(matches!(alice.get_password(), Err(Error::NoEntry)));
+    assert!(matches!(entry("bob").get_password(), Err(Error::NoEntry)));
+    assert!(builder.search("fixture").unwrap().is_empty());
+    assert!(mock::snapshot().is_empty());
+
+    entry("carol").set_password("carol's password").unwrap();
+    bob.set_password("bob's new password").unwrap();
+    mock::restore(&fixtures);
+    assert_eq!(alice.get_password().unwrap(), "alice's password");
+    assert_eq!(entry("bob").get_password().unwrap(), "bob's password");
+    assert!(matches!(entry("carol").get_password(), Err(Error::NoEntry)));
+    assert_eq!(builder.search("fixture").unwrap().len(), 2);
+}
@@ -0,0 +1,28 @@
+#![cfg(all(target_os = "windows", feature = "windows-native"))]
+
+use keyring::Entry;
+use keyring::windows::WinCredential;
+
+// The target separator is shared by the whole process, so the test
+// of changing it is in its own test binary.
+#[test]
+fn test_set_target_separator() {
+    keyring::set_target_separator(':');
+    let credential = keyring::windows::default_credential_builder()
+        .build(None, "service", "user")
+        .unwrap();
+    let win_credential: &WinCredential = credential.as_any().downcast_ref().unwrap();
+    assert_eq!(win_credential.target_name, "user:service");
+    assert!(
+        win_credential
+            .comment
+            .starts_with("user@service:user:service "),
+        "Comment doesn't have the target name: {}",
+        win_credential.comment
+    );
+    let entry = Entry::new_with_credential(credential);
+    assert_eq!(
+        entry.get_specifiers(),
+        Some(("service".to_string(), "user".to_string()))
+    );
+}